@@ -0,0 +1,165 @@
+//! Benchmarks for the database-backed wallet queries.
+//!
+//! A wallet is populated with a synthetic transfer history (see [`populate`]) and the timing of
+//! the main read paths is reported. The number of transfers per asset can be set with the
+//! `RGB_LIB_BENCH_TRANSFERS` environment variable.
+//!
+//! Run with `cargo bench --bench database`.
+
+use futures::executor::block_on;
+use rgb_lib::wallet::{DatabaseType, WalletData};
+use rgb_lib::{generate_keys, AssetSchema, BitcoinNetwork, ColoringType, TransferStatus, Wallet};
+use sea_orm::{ConnectionTrait, Database};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const BENCH_DATA_DIR: &str = "./target/bench-data";
+const NUM_ASSETS: usize = 10;
+const DEFAULT_TRANSFERS_PER_ASSET: usize = 1000;
+const ITERATIONS: u32 = 10;
+const INSERT_BATCH: usize = 500;
+
+fn insert(db_path: &Path, statements: Vec<String>) {
+    let connection_string = format!("sqlite://{}?mode=rwc", db_path.display());
+    let connection = block_on(Database::connect(connection_string)).unwrap();
+    for chunk in statements.chunks(INSERT_BATCH) {
+        let sql = format!("BEGIN;\n{}\nCOMMIT;", chunk.join("\n"));
+        block_on(connection.execute_unprepared(&sql)).unwrap();
+    }
+}
+
+/// Fill the wallet DB with `transfers` issuances and receives for each of `NUM_ASSETS` assets.
+/// One in ten receives is left pending and one in ten is spent by a settled send, so that every
+/// branch of the balance computation is exercised.
+fn populate(db_path: &Path, transfers: usize) {
+    let mut statements = vec![];
+    let mut batch_idx = 0;
+    let mut txo_idx = 0;
+    for a in 0..NUM_ASSETS {
+        let asset_id = format!("rgb:bench-asset-{a}");
+        statements.push(format!(
+            "INSERT INTO asset (asset_id, schema, added_at, issued_supply, name, precision, \
+             ticker, timestamp) VALUES ('{asset_id}', {}, 0, '{}', 'bench', 0, 'BNCH{a}', 0);",
+            AssetSchema::Nia as u16,
+            transfers * 10,
+        ));
+        for t in 0..transfers {
+            let (coloring_type, status, spent) = match t % 10 {
+                0 => (ColoringType::Issue, TransferStatus::Settled, false),
+                1 => (
+                    ColoringType::Receive,
+                    TransferStatus::WaitingCounterparty,
+                    false,
+                ),
+                2 => (ColoringType::Receive, TransferStatus::Settled, true),
+                _ => (ColoringType::Receive, TransferStatus::Settled, false),
+            };
+            let (coloring_type, status) = (coloring_type as u16, status as u16);
+            batch_idx += 1;
+            txo_idx += 1;
+            statements.push(format!(
+                "INSERT INTO batch_transfer (idx, status, created_at, updated_at, \
+                 min_confirmations) VALUES ({batch_idx}, {status}, 0, 0, 1);"
+            ));
+            statements.push(format!(
+                "INSERT INTO asset_transfer (idx, user_driven, batch_transfer_idx, asset_id) \
+                 VALUES ({batch_idx}, 1, {batch_idx}, '{asset_id}');"
+            ));
+            statements.push(format!(
+                "INSERT INTO txo (idx, txid, vout, btc_amount, spent) \
+                 VALUES ({txo_idx}, '{txo_idx:064x}', 0, '1000', {});",
+                spent as u8
+            ));
+            statements.push(format!(
+                "INSERT INTO coloring (txo_idx, asset_transfer_idx, coloring_type, amount) \
+                 VALUES ({txo_idx}, {batch_idx}, {coloring_type}, '10');"
+            ));
+            if spent {
+                batch_idx += 1;
+                statements.push(format!(
+                    "INSERT INTO batch_transfer (idx, status, created_at, updated_at, \
+                     min_confirmations) VALUES ({batch_idx}, {}, 0, 0, 1);",
+                    TransferStatus::Settled as u16,
+                ));
+                statements.push(format!(
+                    "INSERT INTO asset_transfer (idx, user_driven, batch_transfer_idx, asset_id) \
+                     VALUES ({batch_idx}, 1, {batch_idx}, '{asset_id}');"
+                ));
+                statements.push(format!(
+                    "INSERT INTO coloring (txo_idx, asset_transfer_idx, coloring_type, amount) \
+                     VALUES ({txo_idx}, {batch_idx}, {}, '10');",
+                    ColoringType::Input as u16,
+                ));
+            }
+        }
+    }
+    insert(db_path, statements);
+}
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        std::hint::black_box(f());
+        let elapsed = start.elapsed();
+        total += elapsed;
+        min = min.min(elapsed);
+    }
+    println!(
+        "{name:<24} avg {:>10.3?}  min {:>10.3?}",
+        total / ITERATIONS,
+        min
+    );
+}
+
+fn main() {
+    let transfers = std::env::var("RGB_LIB_BENCH_TRANSFERS")
+        .ok()
+        .map(|t| t.parse::<usize>().expect("invalid number of transfers"))
+        .unwrap_or(DEFAULT_TRANSFERS_PER_ASSET);
+
+    let _ = fs::remove_dir_all(BENCH_DATA_DIR);
+    fs::create_dir_all(BENCH_DATA_DIR).unwrap();
+    let keys = generate_keys(BitcoinNetwork::Regtest);
+    let wallet = Wallet::new(WalletData {
+        data_dir: BENCH_DATA_DIR.to_string(),
+        bitcoin_network: BitcoinNetwork::Regtest,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: 5,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
+    })
+    .unwrap();
+
+    let start = Instant::now();
+    populate(&wallet.get_wallet_dir().join("rgb_db"), transfers);
+    println!(
+        "populated {NUM_ASSETS} assets x {transfers} transfers in {:.3?}",
+        start.elapsed()
+    );
+
+    let asset_id = "rgb:bench-asset-0".to_string();
+    let balance = wallet.get_asset_balance(asset_id.clone()).unwrap();
+    println!("balance of {asset_id}: {balance:?}");
+
+    bench("get_asset_balance", || {
+        wallet.get_asset_balance(asset_id.clone()).unwrap()
+    });
+    bench("list_assets", || wallet.list_assets(vec![]).unwrap());
+    bench("list_unspents", || {
+        wallet.list_unspents(None, false).unwrap()
+    });
+    bench("list_unspents settled", || {
+        wallet.list_unspents(None, true).unwrap()
+    });
+
+    fs::remove_dir_all(BENCH_DATA_DIR).unwrap();
+}
@@ -1,12 +1,28 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20230608_071249_init_db;
+mod m20231020_090000_prepared_psbt;
+mod m20231025_100000_backup_info;
+mod m20231101_100000_query_indexes;
+mod m20231110_100000_transport_endpoint_protocol;
+mod m20231115_100000_transport_endpoint_health;
+mod m20231120_100000_transfer_transport_endpoint_posted;
+mod m20231125_100000_wallet_config;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20230608_071249_init_db::Migration)]
+        vec![
+            Box::new(m20230608_071249_init_db::Migration),
+            Box::new(m20231020_090000_prepared_psbt::Migration),
+            Box::new(m20231025_100000_backup_info::Migration),
+            Box::new(m20231101_100000_query_indexes::Migration),
+            Box::new(m20231110_100000_transport_endpoint_protocol::Migration),
+            Box::new(m20231115_100000_transport_endpoint_health::Migration),
+            Box::new(m20231120_100000_transfer_transport_endpoint_posted::Migration),
+            Box::new(m20231125_100000_wallet_config::Migration),
+        ]
     }
 }
@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PreparedPsbt::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PreparedPsbt::Idx)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PreparedPsbt::Txid).string().not_null())
+                    .col(
+                        ColumnDef::new(PreparedPsbt::Type)
+                            .tiny_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PreparedPsbt::CreatedAt)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-preparedpsbt-txid-preparedpsbttype")
+                    .table(PreparedPsbt::Table)
+                    .col(PreparedPsbt::Txid)
+                    .col(PreparedPsbt::Type)
+                    .unique()
+                    .clone(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PreparedPsbt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PreparedPsbt {
+    Table,
+    Idx,
+    Txid,
+    #[sea_orm(iden = "prepared_psbt_type")]
+    Type,
+    CreatedAt,
+}
@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BackupInfo::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BackupInfo::Idx)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(BackupInfo::StateVersion)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(BackupInfo::LastBackupStateVersion).big_unsigned())
+                    .col(ColumnDef::new(BackupInfo::LastBackupTimestamp).big_unsigned())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BackupInfo::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BackupInfo {
+    Table,
+    Idx,
+    StateVersion,
+    LastBackupStateVersion,
+    LastBackupTimestamp,
+}
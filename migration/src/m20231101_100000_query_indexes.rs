@@ -0,0 +1,122 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-txo-spent")
+                    .table(Txo::Table)
+                    .col(Txo::Spent)
+                    .clone(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-batchtransfer-status")
+                    .table(BatchTransfer::Table)
+                    .col(BatchTransfer::Status)
+                    .clone(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-assettransfer-assetid")
+                    .table(AssetTransfer::Table)
+                    .col(AssetTransfer::AssetId)
+                    .clone(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-assettransfer-batchtransferidx")
+                    .table(AssetTransfer::Table)
+                    .col(AssetTransfer::BatchTransferIdx)
+                    .clone(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-coloring-txoidx")
+                    .table(Coloring::Table)
+                    .col(Coloring::TxoIdx)
+                    .clone(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                sea_query::Index::create()
+                    .name("idx-transfer-assettransferidx")
+                    .table(Transfer::Table)
+                    .col(Transfer::AssetTransferIdx)
+                    .clone(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table) in [
+            ("idx-transfer-assettransferidx", Transfer::Table.into_iden()),
+            ("idx-coloring-txoidx", Coloring::Table.into_iden()),
+            (
+                "idx-assettransfer-batchtransferidx",
+                AssetTransfer::Table.into_iden(),
+            ),
+            (
+                "idx-assettransfer-assetid",
+                AssetTransfer::Table.into_iden(),
+            ),
+            ("idx-batchtransfer-status", BatchTransfer::Table.into_iden()),
+            ("idx-txo-spent", Txo::Table.into_iden()),
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).table(table).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Txo {
+    Table,
+    Spent,
+}
+
+#[derive(DeriveIden)]
+enum BatchTransfer {
+    Table,
+    Status,
+}
+
+#[derive(DeriveIden)]
+enum AssetTransfer {
+    Table,
+    AssetId,
+    BatchTransferIdx,
+}
+
+#[derive(DeriveIden)]
+enum Coloring {
+    Table,
+    TxoIdx,
+}
+
+#[derive(DeriveIden)]
+enum Transfer {
+    Table,
+    AssetTransferIdx,
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .add_column(ColumnDef::new(TransportEndpoint::ProtocolVersion).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .add_column(ColumnDef::new(TransportEndpoint::ProtocolCheckedAt).big_integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .drop_column(TransportEndpoint::ProtocolCheckedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .drop_column(TransportEndpoint::ProtocolVersion)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransportEndpoint {
+    Table,
+    ProtocolVersion,
+    ProtocolCheckedAt,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .add_column(ColumnDef::new(TransportEndpoint::LastSuccessAt).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .add_column(
+                        ColumnDef::new(TransportEndpoint::ConsecutiveFailures)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransportEndpoint::Table)
+                    .add_column(ColumnDef::new(TransportEndpoint::LatencyMs).big_integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            TransportEndpoint::LatencyMs,
+            TransportEndpoint::ConsecutiveFailures,
+            TransportEndpoint::LastSuccessAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(TransportEndpoint::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransportEndpoint {
+    Table,
+    LastSuccessAt,
+    ConsecutiveFailures,
+    LatencyMs,
+}
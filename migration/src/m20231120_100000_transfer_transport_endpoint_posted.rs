@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransferTransportEndpoint::Table)
+                    .add_column(
+                        ColumnDef::new(TransferTransportEndpoint::Posted)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // outgoing transfers have been posted to the endpoint they use
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(TransferTransportEndpoint::Table)
+                    .value(
+                        TransferTransportEndpoint::Posted,
+                        Expr::col(TransferTransportEndpoint::Used),
+                    )
+                    .and_where(
+                        Expr::col(TransferTransportEndpoint::TransferIdx).in_subquery(
+                            Query::select()
+                                .column(Transfer::Idx)
+                                .from(Transfer::Table)
+                                .and_where(Expr::col(Transfer::Incoming).eq(false))
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransferTransportEndpoint::Table)
+                    .drop_column(TransferTransportEndpoint::Posted)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransferTransportEndpoint {
+    Table,
+    TransferIdx,
+    Used,
+    Posted,
+}
+
+#[derive(DeriveIden)]
+enum Transfer {
+    Table,
+    Idx,
+    Incoming,
+}
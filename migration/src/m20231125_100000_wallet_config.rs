@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WalletConfig::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WalletConfig::Idx)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::MinBtcRequired)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WalletConfig::UtxoSize).unsigned().not_null())
+                    .col(
+                        ColumnDef::new(WalletConfig::UtxoNum)
+                            .tiny_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::MaxTransportEndpoints)
+                            .tiny_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WalletConfig::MinFeeRate).float().not_null())
+                    .col(ColumnDef::new(WalletConfig::MaxFeeRate).float().not_null())
+                    .col(
+                        ColumnDef::new(WalletConfig::DurationSendTransfer)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::DurationRcvTransfer)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::ElectrumTimeout)
+                            .tiny_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::ProxyTimeout)
+                            .tiny_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::ElectrumStopGap)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WalletConfig::DurationProxyProtocolCache)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WalletConfig::DurationPreparedPsbt).unsigned())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WalletConfig::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WalletConfig {
+    Table,
+    Idx,
+    MinBtcRequired,
+    UtxoSize,
+    UtxoNum,
+    MaxTransportEndpoints,
+    MinFeeRate,
+    MaxFeeRate,
+    DurationSendTransfer,
+    DurationRcvTransfer,
+    ElectrumTimeout,
+    ProxyTimeout,
+    ElectrumStopGap,
+    DurationProxyProtocolCache,
+    DurationPreparedPsbt,
+}
@@ -2,31 +2,54 @@
 
 use rgb_lib::{ScriptBuf, SecretSeal};
 use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
-use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::sync::{RwLock, RwLockReadGuard};
 
 uniffi::include_scaffolding!("rgb-lib");
 
+type AllocationDiscrepancy = rgb_lib::wallet::AllocationDiscrepancy;
 type AssetCFA = rgb_lib::wallet::AssetCFA;
 type AssetIface = rgb_lib::wallet::AssetIface;
 type AssetNIA = rgb_lib::wallet::AssetNIA;
 type AssetSchema = rgb_lib::AssetSchema;
 type Assets = rgb_lib::wallet::Assets;
+type AutoBackupConfig = rgb_lib::AutoBackupConfig;
+type BackupInfo = rgb_lib::BackupInfo;
 type Balance = rgb_lib::wallet::Balance;
 type BitcoinNetwork = rgb_lib::BitcoinNetwork;
 type BlockTime = rgb_lib::wallet::BlockTime;
 type BtcBalance = rgb_lib::wallet::BtcBalance;
+type ConsistencyReport = rgb_lib::wallet::ConsistencyReport;
 type DatabaseType = rgb_lib::wallet::DatabaseType;
+type InconsistentUtxo = rgb_lib::wallet::InconsistentUtxo;
 type InvoiceData = rgb_lib::wallet::InvoiceData;
 type Keys = rgb_lib::keys::Keys;
+type LogConfig = rgb_lib::LogConfig;
+type LogFormat = rgb_lib::LogFormat;
+type LogLevel = rgb_lib::LogLevel;
+type LogOutput = rgb_lib::LogOutput;
 type Media = rgb_lib::wallet::Media;
 type Metadata = rgb_lib::wallet::Metadata;
+type MissingAsset = rgb_lib::wallet::MissingAsset;
+type MnemonicLanguage = rgb_lib::keys::MnemonicLanguage;
+type MnemonicWordCount = rgb_lib::keys::MnemonicWordCount;
+type NetworkConfig = rgb_lib::wallet::NetworkConfig;
+type NetworkRoute = rgb_lib::wallet::NetworkRoute;
 type Online = rgb_lib::wallet::Online;
 type Outpoint = rgb_lib::wallet::Outpoint;
+type ProxyAuth = rgb_lib::wallet::ProxyAuth;
+type ProxyRetryConfig = rgb_lib::wallet::ProxyRetryConfig;
+type PsbtAllocation = rgb_lib::wallet::PsbtAllocation;
+type PsbtInput = rgb_lib::wallet::PsbtInput;
+type PsbtInspection = rgb_lib::wallet::PsbtInspection;
+type PsbtOutput = rgb_lib::wallet::PsbtOutput;
+type PsbtTransition = rgb_lib::wallet::PsbtTransition;
 type ReceiveData = rgb_lib::wallet::ReceiveData;
 type RecipientData = rgb_lib::wallet::RecipientData;
 type RefreshFilter = rgb_lib::wallet::RefreshFilter;
 type RefreshTransferStatus = rgb_lib::wallet::RefreshTransferStatus;
+type RepairAction = rgb_lib::wallet::RepairAction;
 type RgbAllocation = rgb_lib::wallet::RgbAllocation;
 type RgbLibBlindedUTXO = rgb_lib::wallet::BlindedUTXO;
 type RgbLibError = rgb_lib::Error;
@@ -34,6 +57,9 @@ type RgbLibInvoice = rgb_lib::wallet::Invoice;
 type RgbLibRecipient = rgb_lib::wallet::Recipient;
 type RgbLibTransportEndpoint = rgb_lib::wallet::TransportEndpoint;
 type RgbLibWallet = rgb_lib::wallet::Wallet;
+type ScryptParams = rgb_lib::ScryptParams;
+type SendOptions = rgb_lib::wallet::SendOptions;
+type Socks5Credentials = rgb_lib::wallet::Socks5Credentials;
 type Transaction = rgb_lib::wallet::Transaction;
 type TransactionType = rgb_lib::wallet::TransactionType;
 type Transfer = rgb_lib::wallet::Transfer;
@@ -43,6 +69,8 @@ type TransferTransportEndpoint = rgb_lib::wallet::TransferTransportEndpoint;
 type TransportType = rgb_lib::TransportType;
 type Unspent = rgb_lib::wallet::Unspent;
 type Utxo = rgb_lib::wallet::Utxo;
+type WalletBackupInfo = rgb_lib::WalletBackupInfo;
+type WalletConfig = rgb_lib::wallet::WalletConfig;
 type WalletData = rgb_lib::wallet::WalletData;
 
 pub struct Recipient {
@@ -65,12 +93,65 @@ pub struct ScriptData {
     blinding: Option<u64>,
 }
 
+/// A PSBT signer implemented in the foreign language
+pub trait Signer: Send + Sync + fmt::Debug {
+    fn sign_psbt(&self, unsigned_psbt: String) -> Result<String, SignerError>;
+}
+
+#[derive(Debug)]
+pub enum SignerError {
+    Failed { details: String },
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignerError::Failed { details } => write!(f, "Signer failed: {details}"),
+        }
+    }
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for SignerError {
+    fn from(e: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        SignerError::Failed { details: e.reason }
+    }
+}
+
+struct SignerAdapter {
+    signer: Box<dyn Signer>,
+}
+
+impl rgb_lib::wallet::Signer for SignerAdapter {
+    fn sign_psbt(&self, unsigned_psbt: String) -> Result<String, RgbLibError> {
+        self.signer.sign_psbt(unsigned_psbt).map_err(|e| match e {
+            SignerError::Failed { details } => RgbLibError::Signer { details },
+        })
+    }
+}
+
 fn generate_keys(bitcoin_network: BitcoinNetwork) -> Keys {
     rgb_lib::generate_keys(bitcoin_network)
 }
 
-fn restore_keys(bitcoin_network: BitcoinNetwork, mnemonic: String) -> Result<Keys, RgbLibError> {
-    rgb_lib::restore_keys(bitcoin_network, mnemonic)
+fn generate_keys_with_options(
+    bitcoin_network: BitcoinNetwork,
+    word_count: MnemonicWordCount,
+    language: MnemonicLanguage,
+    passphrase: Option<String>,
+) -> Keys {
+    rgb_lib::generate_keys_with_options(bitcoin_network, word_count, language, passphrase)
+}
+
+fn restore_keys(
+    bitcoin_network: BitcoinNetwork,
+    mnemonic: String,
+    passphrase: Option<String>,
+) -> Result<Keys, RgbLibError> {
+    rgb_lib::restore_keys(bitcoin_network, mnemonic, passphrase)
+}
+
+fn backup_info(backup_path: String) -> Result<BackupInfo, RgbLibError> {
+    rgb_lib::backup_info(&backup_path)
 }
 
 fn restore_backup(
@@ -81,6 +162,20 @@ fn restore_backup(
     rgb_lib::restore_backup(&backup_path, &password, &data_dir)
 }
 
+fn restore_backup_chain(
+    base_backup_path: String,
+    incremental_backup_paths: Vec<String>,
+    password: String,
+    data_dir: String,
+) -> Result<(), RgbLibError> {
+    rgb_lib::restore_backup_chain(
+        &base_backup_path,
+        incremental_backup_paths,
+        &password,
+        &data_dir,
+    )
+}
+
 struct BlindedUTXO {
     _blinded_utxo: RwLock<RgbLibBlindedUTXO>,
 }
@@ -144,24 +239,48 @@ impl Invoice {
 }
 
 struct Wallet {
-    wallet_mutex: Mutex<RgbLibWallet>,
+    wallet: RgbLibWallet,
 }
 
 impl Wallet {
     fn new(wallet_data: WalletData) -> Result<Self, RgbLibError> {
         Ok(Wallet {
-            wallet_mutex: Mutex::new(RgbLibWallet::new(wallet_data)?),
+            wallet: RgbLibWallet::new(wallet_data)?,
         })
     }
 
-    fn _get_wallet(&self) -> MutexGuard<RgbLibWallet> {
-        self.wallet_mutex.lock().expect("wallet")
+    fn _get_wallet(&self) -> &RgbLibWallet {
+        &self.wallet
     }
 
     fn backup(&self, backup_path: String, password: String) -> Result<(), RgbLibError> {
         self._get_wallet().backup(&backup_path, &password)
     }
 
+    fn backup_incremental(
+        &self,
+        parent_backup_path: String,
+        backup_path: String,
+        password: String,
+    ) -> Result<(), RgbLibError> {
+        self._get_wallet()
+            .backup_incremental(&parent_backup_path, &backup_path, &password)
+    }
+
+    fn backup_info(&self) -> Result<WalletBackupInfo, RgbLibError> {
+        self._get_wallet().backup_info()
+    }
+
+    fn backup_customize(
+        &self,
+        backup_path: String,
+        password: String,
+        scrypt_params: Option<ScryptParams>,
+    ) -> Result<(), RgbLibError> {
+        self._get_wallet()
+            .backup_customize(&backup_path, &password, scrypt_params)
+    }
+
     fn blind_receive(
         &self,
         asset_id: Option<String>,
@@ -200,6 +319,30 @@ impl Wallet {
         self._get_wallet().sign_psbt(unsigned_psbt)
     }
 
+    fn inspect_psbt(&self, psbt: String) -> Result<PsbtInspection, RgbLibError> {
+        self._get_wallet().inspect_psbt(psbt)
+    }
+
+    fn set_signer(&self, signer: Option<Box<dyn Signer>>) {
+        self._get_wallet().set_signer(
+            signer.map(|signer| {
+                Box::new(SignerAdapter { signer }) as Box<dyn rgb_lib::wallet::Signer>
+            }),
+        )
+    }
+
+    fn set_auto_backup(&self, auto_backup: Option<AutoBackupConfig>) -> Result<(), RgbLibError> {
+        self._get_wallet().set_auto_backup(auto_backup)
+    }
+
+    fn get_config(&self) -> WalletConfig {
+        self._get_wallet().get_config()
+    }
+
+    fn set_config(&self, config: WalletConfig) -> Result<(), RgbLibError> {
+        self._get_wallet().set_config(config)
+    }
+
     fn create_utxos(
         &self,
         online: Online,
@@ -300,6 +443,25 @@ impl Wallet {
             .go_online(skip_consistency_check, electrum_url)
     }
 
+    fn diagnose(&self, online: Online) -> Result<ConsistencyReport, RgbLibError> {
+        self._get_wallet().diagnose(online)
+    }
+
+    fn repair(
+        &self,
+        online: Online,
+        actions: Vec<RepairAction>,
+    ) -> Result<ConsistencyReport, RgbLibError> {
+        self._get_wallet().repair(online, actions)
+    }
+
+    fn audit_allocations(
+        &self,
+        online: Option<Online>,
+    ) -> Result<Vec<AllocationDiscrepancy>, RgbLibError> {
+        self._get_wallet().audit_allocations(online)
+    }
+
     fn issue_asset_nia(
         &self,
         online: Online,
@@ -371,6 +533,25 @@ impl Wallet {
         )
     }
 
+    fn send_with_options(
+        &self,
+        online: Online,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        donation: bool,
+        fee_rate: f32,
+        min_confirmations: u8,
+        options: SendOptions,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet().send_with_options(
+            online,
+            _convert_recipient_map(recipient_map)?,
+            donation,
+            fee_rate,
+            min_confirmations,
+            options,
+        )
+    }
+
     fn send_begin(
         &self,
         online: Online,
@@ -388,6 +569,25 @@ impl Wallet {
         )
     }
 
+    fn send_begin_with_options(
+        &self,
+        online: Online,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        donation: bool,
+        fee_rate: f32,
+        min_confirmations: u8,
+        options: SendOptions,
+    ) -> Result<String, RgbLibError> {
+        self._get_wallet().send_begin_with_options(
+            online,
+            _convert_recipient_map(recipient_map)?,
+            donation,
+            fee_rate,
+            min_confirmations,
+            options,
+        )
+    }
+
     fn send_end(&self, online: Online, signed_psbt: String) -> Result<String, RgbLibError> {
         self._get_wallet().send_end(online, signed_psbt)
     }
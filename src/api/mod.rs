@@ -1,2 +1,3 @@
 pub(crate) mod proxy;
 pub use proxy::Proxy;
+pub(crate) use proxy::{ProxyClient, ProxyProtocolVersion};
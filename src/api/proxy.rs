@@ -1,11 +1,15 @@
 use amplify::s;
-use reqwest::blocking::{multipart, Client};
+use reqwest::blocking::{multipart, Client, RequestBuilder, Response};
 use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::error::{Error, InternalError};
+use crate::wallet::ProxyAuth;
 
 const JSON: &str = "application/json";
 
@@ -30,6 +34,30 @@ pub struct JsonRpcResponse<R> {
     pub(crate) error: Option<JsonRpcError>,
 }
 
+/// Proxy protocol versions supported by this library
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ProxyProtocolVersion {
+    /// Previous version, consignments are posted without the recipient vout
+    V0_1,
+    /// Current version
+    V0_2,
+}
+
+impl ProxyProtocolVersion {
+    pub(crate) fn parse(version: &str) -> Option<Self> {
+        match version {
+            "0.1" => Some(ProxyProtocolVersion::V0_1),
+            "0.2" => Some(ProxyProtocolVersion::V0_2),
+            _ => None,
+        }
+    }
+
+    /// Whether consignments can be posted along with the vout of a witness recipient
+    pub(crate) fn supports_vout(&self) -> bool {
+        *self >= ProxyProtocolVersion::V0_2
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct NullRequest;
 
@@ -83,6 +111,69 @@ pub struct AttachmentIdParam {
     attachment_id: String,
 }
 
+/// HTTP client for a proxy server, sending the authentication configured for it
+pub(crate) struct ProxyClient {
+    client: Client,
+    auth: Option<ProxyAuth>,
+    timeout: Option<Duration>,
+}
+
+impl ProxyClient {
+    pub(crate) fn new(client: Client, auth: Option<ProxyAuth>) -> Self {
+        ProxyClient {
+            client,
+            auth,
+            timeout: None,
+        }
+    }
+
+    /// Override the timeout of the client for the requests made by this proxy client
+    pub(crate) fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn post(&self, url: &str) -> RequestBuilder {
+        let mut request = self.client.post(url);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(auth) = &self.auth {
+            if let Some(token) = &auth.bearer_token {
+                request = request.bearer_auth(token);
+            }
+            for (name, value) in &auth.headers {
+                request = request.header(name, value);
+            }
+        }
+        request
+    }
+}
+
+fn _parse_response<R: DeserializeOwned>(
+    url: &str,
+    response: Response,
+) -> Result<JsonRpcResponse<R>, Error> {
+    if matches!(
+        response.status(),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    ) {
+        return Err(Error::ProxyUnauthorized {
+            endpoint: url.to_string(),
+        });
+    }
+    // a proxy that is temporarily unavailable or rate limiting is worth retrying
+    let status = response.status();
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::Proxy {
+            details: format!("{url} returned status {status}"),
+        });
+    }
+    Ok(response
+        .json::<JsonRpcResponse<R>>()
+        .map_err(InternalError::from)?)
+}
+
 pub trait Proxy {
     fn get_info(self, url: &str) -> Result<JsonRpcResponse<ServerInfoResponse>, Error>;
 
@@ -120,7 +211,7 @@ pub trait Proxy {
     ) -> Result<JsonRpcResponse<bool>, Error>;
 }
 
-impl Proxy for Client {
+impl Proxy for ProxyClient {
     fn get_info(self, url: &str) -> Result<JsonRpcResponse<ServerInfoResponse>, Error> {
         let body: JsonRpcRequest<NullRequest> = JsonRpcRequest {
             method: s!("server.info"),
@@ -128,13 +219,12 @@ impl Proxy for Client {
             id: None,
             params: None,
         };
-        Ok(self
+        let response = self
             .post(url)
             .header(CONTENT_TYPE, JSON)
             .json(&body)
-            .send()?
-            .json::<JsonRpcResponse<ServerInfoResponse>>()
-            .map_err(InternalError::from)?)
+            .send()?;
+        _parse_response(url, response)
     }
 
     fn get_ack(self, url: &str, recipient_id: String) -> Result<JsonRpcResponse<bool>, Error> {
@@ -144,13 +234,12 @@ impl Proxy for Client {
             id: None,
             params: Some(RecipientIDParam { recipient_id }),
         };
-        Ok(self
+        let response = self
             .post(url)
             .header(CONTENT_TYPE, JSON)
             .json(&body)
-            .send()?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(InternalError::from)?)
+            .send()?;
+        _parse_response(url, response)
     }
 
     fn get_consignment(
@@ -164,13 +253,12 @@ impl Proxy for Client {
             id: None,
             params: Some(RecipientIDParam { recipient_id }),
         };
-        Ok(self
+        let response = self
             .post(url)
             .header(CONTENT_TYPE, JSON)
             .json(&body)
-            .send()?
-            .json::<JsonRpcResponse<GetConsignmentResponse>>()
-            .map_err(InternalError::from)?)
+            .send()?;
+        _parse_response(url, response)
     }
 
     fn get_media(self, url: &str, attachment_id: String) -> Result<JsonRpcResponse<String>, Error> {
@@ -180,13 +268,12 @@ impl Proxy for Client {
             id: None,
             params: Some(AttachmentIdParam { attachment_id }),
         };
-        Ok(self
+        let response = self
             .post(url)
             .header(CONTENT_TYPE, JSON)
             .json(&body)
-            .send()?
-            .json::<JsonRpcResponse<String>>()
-            .map_err(InternalError::from)?)
+            .send()?;
+        _parse_response(url, response)
     }
 
     fn post_ack(
@@ -201,13 +288,12 @@ impl Proxy for Client {
             id: None,
             params: Some(PostAckParams { recipient_id, ack }),
         };
-        Ok(self
+        let response = self
             .post(url)
             .header(CONTENT_TYPE, JSON)
             .json(&body)
-            .send()?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(InternalError::from)?)
+            .send()?;
+        _parse_response(url, response)
     }
 
     fn post_consignment(
@@ -235,12 +321,8 @@ impl Proxy for Client {
             .text("id", "1")
             .text("params", params)
             .file("file", consignment_path)?;
-        Ok(self
-            .post(url)
-            .multipart(form)
-            .send()?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(InternalError::from)?)
+        let response = self.post(url).multipart(form).send()?;
+        _parse_response(url, response)
     }
 
     fn post_media(
@@ -259,11 +341,7 @@ impl Proxy for Client {
                     .map_err(InternalError::from)?,
             )
             .file("file", media_path)?;
-        Ok(self
-            .post(url)
-            .multipart(form)
-            .send()?
-            .json::<JsonRpcResponse<bool>>()
-            .map_err(InternalError::from)?)
+        let response = self.post(url).multipart(form).send()?;
+        _parse_response(url, response)
     }
 }
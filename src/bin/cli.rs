@@ -0,0 +1,831 @@
+//! Command-line RGB wallet
+//!
+//! Usage: `rgb-lib-cli [<options>] <command> [<args>]`, see [`USAGE`] for the available commands.
+//!
+//! The data directory, Bitcoin network and Electrum URL are read from a JSON config file
+//! (`rgb-lib-cli.json` in the current directory by default) and can be overridden with the
+//! corresponding options. The wallet created by the `new` command is stored in the data
+//! directory, so that the following commands can operate on it. All results are printed to stdout
+//! as JSON, errors are printed to stderr as JSON.
+//!
+//! Secrets are never accepted as arguments, so that they don't end up in the process list or in
+//! the shell history. They're read from environment variables or, when not set, one per line from
+//! stdin. The mnemonic is stored in the wallet file only when requested with `--store-mnemonic`,
+//! otherwise it needs to be provided again to the commands that sign.
+
+use bdk::bitcoin::util::bip32::ExtendedPubKey;
+use rgb_lib::keys::{MnemonicLanguage, MnemonicWordCount};
+use rgb_lib::wallet::{
+    DatabaseType, Online, Recipient, RecipientData, SendOptions, Signer, SoftwareSigner, WalletData,
+};
+use rgb_lib::{
+    backup_info, generate_keys_with_options, restore_backup, restore_keys, AssetSchema,
+    BitcoinNetwork, Error, ScriptBuf, SecretSeal, Wallet,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::str::FromStr;
+
+const USAGE: &str = "usage: rgb-lib-cli [<options>] <command> [<args>]
+
+options:
+    --config <path>          JSON config file (default: rgb-lib-cli.json)
+    --data-dir <path>        data directory (default: current directory)
+    --network <network>      bitcoin network (default: regtest)
+    --electrum-url <url>     Electrum server URL
+    --skip-consistency-check skip the consistency check when going online
+
+commands:
+    keys generate [--passphrase]
+    keys restore [--passphrase]
+    new --pubkey <xpub> [--mnemonic] [--passphrase] [--store-mnemonic]
+        [--max-allocations-per-utxo <num>]
+    go-online
+    address
+    balance btc
+    balance asset <asset_id>
+    metadata <asset_id>
+    create-utxos [--up-to] [--num <num>] [--size <sats>] [--fee-rate <rate>]
+    issue nia <ticker> <name> <precision> <amount>...
+    issue cfa <name> <precision> <amount>... [--description <text>] [--file <path>]
+    receive blind|witness [--asset-id <asset_id>] [--amount <amount>]
+        [--duration-seconds <secs>] [--min-confirmations <num>] --transport-endpoint <endpoint>...
+    send <asset_id> <recipient_id> <amount> --transport-endpoint <endpoint>...
+        [--amount-sat <sats>] [--donation] [--fee-rate <rate>] [--min-confirmations <num>]
+        [--redundant-posting]
+    send-btc <address> <amount> [--fee-rate <rate>]
+    drain-to <address> [--destroy-assets] [--fee-rate <rate>]
+    refresh [--asset-id <asset_id>]
+    fail-transfers [--recipient-id <recipient_id>] [--txid <txid>] [--no-asset-only]
+    list assets [--schema nia|cfa]...
+    list transfers <asset_id>
+    list unspents [--settled-only]
+    list transactions
+    backup <path>
+    restore <path> <same options as new>
+    psbt send-begin <same args as send>
+    psbt sign <psbt>
+    psbt send-end <psbt>
+    psbt inspect <psbt>
+
+secrets:
+    the mnemonic (with 'keys restore' and the --mnemonic switch), the passphrase (with the
+    --passphrase switch) and the backup password (with 'backup' and 'restore') are read, in this
+    order, from the RGB_LIB_CLI_MNEMONIC, RGB_LIB_CLI_PASSPHRASE and RGB_LIB_CLI_PASSWORD
+    environment variables or, when not set, one per line from stdin. Commands that sign read the
+    mnemonic and passphrase from the environment variables if the wallet file doesn't store them";
+
+const CONFIG_FILE: &str = "rgb-lib-cli.json";
+const WALLET_FILE: &str = "rgb-lib-cli-wallet.json";
+
+const MNEMONIC_ENV: &str = "RGB_LIB_CLI_MNEMONIC";
+const PASSPHRASE_ENV: &str = "RGB_LIB_CLI_PASSPHRASE";
+const PASSWORD_ENV: &str = "RGB_LIB_CLI_PASSWORD";
+
+const DEFAULT_FEE_RATE: f32 = 1.5;
+const DEFAULT_MAX_ALLOCATIONS_PER_UTXO: u32 = 5;
+const DEFAULT_MIN_CONFIRMATIONS: u8 = 1;
+
+// options that take no value
+const SWITCHES: [&str; 10] = [
+    "destroy-assets",
+    "donation",
+    "mnemonic",
+    "no-asset-only",
+    "passphrase",
+    "redundant-posting",
+    "settled-only",
+    "skip-consistency-check",
+    "store-mnemonic",
+    "up-to",
+];
+
+enum CliError {
+    Usage(String),
+    Wallet(Error),
+}
+
+impl From<Error> for CliError {
+    fn from(e: Error) -> Self {
+        CliError::Wallet(e)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        CliError::Wallet(e.into())
+    }
+}
+
+type CliResult<T> = Result<T, CliError>;
+
+fn usage_error<T>(details: impl Into<String>) -> CliResult<T> {
+    Err(CliError::Usage(details.into()))
+}
+
+// read a secret from the given environment variable or, if not set, from a line of stdin
+fn read_secret(name: &str, env_var: &str) -> CliResult<String> {
+    if let Ok(secret) = std::env::var(env_var) {
+        return Ok(secret);
+    }
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return usage_error(format!(
+            "missing {name}, set {env_var} or provide it on stdin"
+        ));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// write the wallet file, readable and writable only by the current user
+fn write_wallet_file(path: &Path, wallet_data: &WalletData) -> CliResult<()> {
+    let serialized = serde_json::to_string_pretty(wallet_data).expect("serializable");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(serialized.as_bytes())?;
+    Ok(())
+}
+
+/// Config file contents, all fields can be overridden from the command line
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    data_dir: Option<String>,
+    network: Option<String>,
+    electrum_url: Option<String>,
+}
+
+/// Command-line arguments, split into positional arguments and options
+struct Args {
+    positional: VecDeque<String>,
+    options: HashMap<String, Vec<String>>,
+    consumed: HashSet<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> CliResult<Self> {
+        let mut positional = VecDeque::new();
+        let mut options: HashMap<String, Vec<String>> = HashMap::new();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push_back(arg);
+                continue;
+            };
+            let values = options.entry(name.to_string()).or_default();
+            if SWITCHES.contains(&name) {
+                continue;
+            }
+            match args.next() {
+                Some(value) => values.push(value),
+                None => return usage_error(format!("missing value for option --{name}")),
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+            consumed: HashSet::new(),
+        })
+    }
+
+    fn arg(&mut self, name: &str) -> CliResult<String> {
+        match self.positional.pop_front() {
+            Some(arg) => Ok(arg),
+            None => usage_error(format!("missing argument <{name}>")),
+        }
+    }
+
+    fn parse_arg<T: FromStr>(&mut self, name: &str) -> CliResult<T> {
+        let arg = self.arg(name)?;
+        arg.parse()
+            .or_else(|_| usage_error(format!("invalid value '{arg}' for <{name}>")))
+    }
+
+    fn rest(&mut self) -> Vec<String> {
+        self.positional.drain(..).collect()
+    }
+
+    fn options(&mut self, name: &str) -> Vec<String> {
+        self.consumed.insert(name.to_string());
+        self.options.get(name).cloned().unwrap_or_default()
+    }
+
+    fn option(&mut self, name: &str) -> Option<String> {
+        self.options(name).pop()
+    }
+
+    fn parse_option<T: FromStr>(&mut self, name: &str) -> CliResult<Option<T>> {
+        match self.option(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .or_else(|_| usage_error(format!("invalid value '{value}' for option --{name}"))),
+            None => Ok(None),
+        }
+    }
+
+    fn switch(&mut self, name: &str) -> bool {
+        self.consumed.insert(name.to_string());
+        self.options.contains_key(name)
+    }
+
+    // make sure all arguments have been used, so that typos don't go unnoticed
+    fn finish(&self) -> CliResult<()> {
+        if let Some(arg) = self.positional.front() {
+            return usage_error(format!("unexpected argument '{arg}'"));
+        }
+        let mut unknown: Vec<&String> = self
+            .options
+            .keys()
+            .filter(|o| !self.consumed.contains(*o))
+            .collect();
+        unknown.sort();
+        if let Some(option) = unknown.first() {
+            return usage_error(format!("unexpected option --{option}"));
+        }
+        Ok(())
+    }
+}
+
+struct Cli {
+    args: Args,
+    data_dir: String,
+    network: BitcoinNetwork,
+    electrum_url: Option<String>,
+    skip_consistency_check: bool,
+}
+
+impl Cli {
+    fn new(mut args: Args) -> CliResult<Self> {
+        let config_path = args.option("config");
+        let config = match &config_path {
+            Some(path) => Cli::_read_config(Path::new(path))?,
+            None if Path::new(CONFIG_FILE).exists() => Cli::_read_config(Path::new(CONFIG_FILE))?,
+            None => Config::default(),
+        };
+        let data_dir = args
+            .option("data-dir")
+            .or(config.data_dir)
+            .unwrap_or(".".to_string());
+        let network = args
+            .option("network")
+            .or(config.network)
+            .unwrap_or("regtest".to_string());
+        let network = BitcoinNetwork::from_str(&network)?;
+        let electrum_url = args.option("electrum-url").or(config.electrum_url);
+        let skip_consistency_check = args.switch("skip-consistency-check");
+        Ok(Cli {
+            args,
+            data_dir,
+            network,
+            electrum_url,
+            skip_consistency_check,
+        })
+    }
+
+    fn _read_config(path: &Path) -> CliResult<Config> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).or_else(|e| {
+            usage_error(format!(
+                "invalid config file {}: {e}",
+                path.to_string_lossy()
+            ))
+        })
+    }
+
+    fn _wallet_file(&self) -> PathBuf {
+        Path::new(&self.data_dir).join(WALLET_FILE)
+    }
+
+    // open the wallet, with a signer if the mnemonic is stored or provided in the environment
+    fn _wallet(&self) -> CliResult<Wallet> {
+        let (wallet_data, signer) = self._wallet_data()?;
+        let wallet = Wallet::new(wallet_data)?;
+        if let Some(signer) = signer {
+            wallet.set_signer(Some(Box::new(signer)));
+        }
+        Ok(wallet)
+    }
+
+    // the wallet is always opened without private keys, which are held by a separate signer, so
+    // that it can be opened both with and without the mnemonic
+    fn _wallet_data(&self) -> CliResult<(WalletData, Option<SoftwareSigner>)> {
+        let wallet_file = self._wallet_file();
+        if !wallet_file.exists() {
+            return usage_error(format!(
+                "no wallet found in {}, create one with the 'new' command",
+                self.data_dir
+            ));
+        }
+        let contents = fs::read_to_string(&wallet_file)?;
+        let mut wallet_data: WalletData = serde_json::from_str(&contents).or_else(|e| {
+            usage_error(format!(
+                "invalid wallet file {}: {e}",
+                wallet_file.to_string_lossy()
+            ))
+        })?;
+        // the mnemonic is not stored by default, commands that sign get it from the environment
+        let mnemonic = wallet_data.mnemonic.take();
+        let passphrase = wallet_data.passphrase.take();
+        let secrets = match mnemonic {
+            Some(mnemonic) => Some((mnemonic, passphrase)),
+            None => std::env::var(MNEMONIC_ENV)
+                .ok()
+                .map(|m| (m, std::env::var(PASSPHRASE_ENV).ok())),
+        };
+        let signer = match secrets {
+            Some((mnemonic, passphrase)) => {
+                Some(self._signer(&wallet_data, mnemonic, passphrase)?)
+            }
+            None => None,
+        };
+        Ok((wallet_data, signer))
+    }
+
+    fn _signer(
+        &self,
+        wallet_data: &WalletData,
+        mnemonic: String,
+        passphrase: Option<String>,
+    ) -> CliResult<SoftwareSigner> {
+        Ok(SoftwareSigner::new(
+            self.network,
+            wallet_data.pubkey.clone(),
+            mnemonic,
+            passphrase,
+        )?)
+    }
+
+    fn _online(&self, wallet: &Wallet) -> CliResult<Online> {
+        let Some(electrum_url) = self.electrum_url.clone() else {
+            return usage_error(
+                "missing Electrum URL, set it in the config or with --electrum-url",
+            );
+        };
+        Ok(wallet.go_online(self.skip_consistency_check, electrum_url)?)
+    }
+
+    fn _fee_rate(&mut self) -> CliResult<f32> {
+        Ok(self
+            .args
+            .parse_option("fee-rate")?
+            .unwrap_or(DEFAULT_FEE_RATE))
+    }
+
+    fn _min_confirmations(&mut self) -> CliResult<u8> {
+        Ok(self
+            .args
+            .parse_option("min-confirmations")?
+            .unwrap_or(DEFAULT_MIN_CONFIRMATIONS))
+    }
+
+    fn run(&mut self) -> CliResult<Value> {
+        let command = self.args.arg("command")?;
+        let result = match command.as_str() {
+            "keys" => self.keys()?,
+            "new" => self.new_wallet()?,
+            "go-online" => {
+                let wallet = self._wallet()?;
+                json!(self._online(&wallet)?)
+            }
+            "address" => json!({"address": self._wallet()?.get_address()}),
+            "balance" => self.balance()?,
+            "metadata" => {
+                let asset_id = self.args.arg("asset_id")?;
+                json!(self._wallet()?.get_asset_metadata(asset_id)?)
+            }
+            "create-utxos" => {
+                let wallet = self._wallet()?;
+                let online = self._online(&wallet)?;
+                let up_to = self.args.switch("up-to");
+                let num = self.args.parse_option("num")?;
+                let size = self.args.parse_option("size")?;
+                let fee_rate = self._fee_rate()?;
+                let created = wallet.create_utxos(online, up_to, num, size, fee_rate)?;
+                json!({ "created": created })
+            }
+            "issue" => self.issue()?,
+            "receive" => self.receive()?,
+            "send" => {
+                let wallet = self._wallet()?;
+                let online = self._online(&wallet)?;
+                let (recipient_map, donation, fee_rate, min_confirmations, options) =
+                    self._send_args()?;
+                let txid = wallet.send_with_options(
+                    online,
+                    recipient_map,
+                    donation,
+                    fee_rate,
+                    min_confirmations,
+                    options,
+                )?;
+                json!({ "txid": txid })
+            }
+            "send-btc" => {
+                let address = self.args.arg("address")?;
+                let amount = self.args.parse_arg("amount")?;
+                let wallet = self._wallet()?;
+                let online = self._online(&wallet)?;
+                let fee_rate = self._fee_rate()?;
+                json!({ "txid": wallet.send_btc(online, address, amount, fee_rate)? })
+            }
+            "drain-to" => {
+                let address = self.args.arg("address")?;
+                let wallet = self._wallet()?;
+                let online = self._online(&wallet)?;
+                let destroy_assets = self.args.switch("destroy-assets");
+                let fee_rate = self._fee_rate()?;
+                json!({ "txid": wallet.drain_to(online, address, destroy_assets, fee_rate)? })
+            }
+            "refresh" => {
+                let wallet = self._wallet()?;
+                let online = self._online(&wallet)?;
+                let asset_id = self.args.option("asset-id");
+                json!({ "changed": wallet.refresh(online, asset_id, vec![])? })
+            }
+            "fail-transfers" => {
+                let wallet = self._wallet()?;
+                let online = self._online(&wallet)?;
+                let recipient_id = self.args.option("recipient-id");
+                let txid = self.args.option("txid");
+                let no_asset_only = self.args.switch("no-asset-only");
+                let changed = wallet.fail_transfers(online, recipient_id, txid, no_asset_only)?;
+                json!({ "changed": changed })
+            }
+            "list" => self.list()?,
+            "backup" => {
+                let path = self.args.arg("path")?;
+                let wallet = self._wallet()?;
+                let password = read_secret("password", PASSWORD_ENV)?;
+                wallet.backup(&path, &password)?;
+                json!({ "backup": path })
+            }
+            "restore" => self.restore()?,
+            "psbt" => self.psbt()?,
+            _ => return usage_error(format!("unknown command '{command}'")),
+        };
+        self.args.finish()?;
+        Ok(result)
+    }
+
+    fn keys(&mut self) -> CliResult<Value> {
+        let subcommand = self.args.arg("subcommand")?;
+        let keys = match subcommand.as_str() {
+            "generate" => {
+                let passphrase = self._passphrase()?;
+                generate_keys_with_options(
+                    self.network,
+                    MnemonicWordCount::default(),
+                    MnemonicLanguage::default(),
+                    passphrase,
+                )
+            }
+            "restore" => {
+                let mnemonic = read_secret("mnemonic", MNEMONIC_ENV)?;
+                let passphrase = self._passphrase()?;
+                restore_keys(self.network, mnemonic, passphrase)?
+            }
+            _ => return usage_error(format!("unknown keys subcommand '{subcommand}'")),
+        };
+        Ok(json!(keys))
+    }
+
+    fn _passphrase(&mut self) -> CliResult<Option<String>> {
+        if !self.args.switch("passphrase") {
+            return Ok(None);
+        }
+        Ok(Some(read_secret("passphrase", PASSPHRASE_ENV)?))
+    }
+
+    // get the data of the wallet to be created from the options shared by new and restore
+    fn _new_wallet_data(&mut self) -> CliResult<WalletData> {
+        let Some(pubkey) = self.args.option("pubkey") else {
+            return usage_error("missing option --pubkey");
+        };
+        let wallet_file = self._wallet_file();
+        if wallet_file.exists() {
+            return usage_error(format!(
+                "a wallet already exists in {}",
+                wallet_file.to_string_lossy()
+            ));
+        }
+        let mnemonic = if self.args.switch("mnemonic") {
+            Some(read_secret("mnemonic", MNEMONIC_ENV)?)
+        } else {
+            None
+        };
+        Ok(WalletData {
+            data_dir: self.data_dir.clone(),
+            bitcoin_network: self.network,
+            database_type: DatabaseType::Sqlite,
+            max_allocations_per_utxo: self
+                .args
+                .parse_option("max-allocations-per-utxo")?
+                .unwrap_or(DEFAULT_MAX_ALLOCATIONS_PER_UTXO),
+            pubkey,
+            mnemonic,
+            passphrase: self._passphrase()?,
+            log_config: None,
+            proxy_auth: HashMap::new(),
+            network_config: None,
+            proxy_retry_config: None,
+        })
+    }
+
+    // open the wallet and save its wallet file, storing the secrets only if requested
+    fn _create_wallet(&mut self, wallet_data: WalletData) -> CliResult<Value> {
+        let store_mnemonic = self.args.switch("store-mnemonic");
+        let watch_only = wallet_data.mnemonic.is_none();
+        if let Some(mnemonic) = &wallet_data.mnemonic {
+            self._signer(
+                &wallet_data,
+                mnemonic.clone(),
+                wallet_data.passphrase.clone(),
+            )?;
+        }
+        let stored_wallet_data = if store_mnemonic {
+            wallet_data.clone()
+        } else {
+            WalletData {
+                mnemonic: None,
+                passphrase: None,
+                ..wallet_data.clone()
+            }
+        };
+        fs::create_dir_all(&self.data_dir)?;
+        let wallet = Wallet::new(WalletData {
+            mnemonic: None,
+            passphrase: None,
+            ..wallet_data
+        })?;
+        write_wallet_file(&self._wallet_file(), &stored_wallet_data)?;
+        Ok(json!({
+            "wallet_dir": wallet.get_wallet_dir(),
+            "watch_only": watch_only,
+            "mnemonic_stored": stored_wallet_data.mnemonic.is_some(),
+        }))
+    }
+
+    fn new_wallet(&mut self) -> CliResult<Value> {
+        let wallet_data = self._new_wallet_data()?;
+        self._create_wallet(wallet_data)
+    }
+
+    fn restore(&mut self) -> CliResult<Value> {
+        let path = self.args.arg("path")?;
+        let wallet_data = self._new_wallet_data()?;
+        let info = backup_info(&path)?;
+        let fingerprint = ExtendedPubKey::from_str(&wallet_data.pubkey)
+            .map_err(|e| Error::InvalidPubkey {
+                details: e.to_string(),
+            })?
+            .fingerprint()
+            .to_string();
+        if info.fingerprint != fingerprint {
+            return usage_error(format!(
+                "the backup is for wallet {}, not for the provided pubkey",
+                info.fingerprint
+            ));
+        }
+        if info.bitcoin_network != self.network {
+            return usage_error(format!(
+                "the backup is for network {:?}, not {:?}",
+                info.bitcoin_network, self.network
+            ));
+        }
+        let password = read_secret("password", PASSWORD_ENV)?;
+        restore_backup(&path, &password, &self.data_dir)?;
+        self._create_wallet(wallet_data)
+    }
+
+    fn balance(&mut self) -> CliResult<Value> {
+        let subcommand = self.args.arg("subcommand")?;
+        let wallet = self._wallet()?;
+        Ok(match subcommand.as_str() {
+            "btc" => {
+                let online = self._online(&wallet)?;
+                json!(wallet.get_btc_balance(online)?)
+            }
+            "asset" => json!(wallet.get_asset_balance(self.args.arg("asset_id")?)?),
+            _ => return usage_error(format!("unknown balance subcommand '{subcommand}'")),
+        })
+    }
+
+    fn _amounts(&mut self) -> CliResult<Vec<u64>> {
+        let amounts = self.args.rest();
+        if amounts.is_empty() {
+            return usage_error("missing argument <amount>");
+        }
+        amounts
+            .iter()
+            .map(|a| {
+                a.parse()
+                    .or_else(|_| usage_error(format!("invalid value '{a}' for <amount>")))
+            })
+            .collect()
+    }
+
+    fn issue(&mut self) -> CliResult<Value> {
+        let schema = self.args.arg("schema")?;
+        let wallet = self._wallet()?;
+        Ok(match schema.as_str() {
+            "nia" => {
+                let ticker = self.args.arg("ticker")?;
+                let name = self.args.arg("name")?;
+                let precision = self.args.parse_arg("precision")?;
+                let amounts = self._amounts()?;
+                let online = self._online(&wallet)?;
+                json!(wallet.issue_asset_nia(online, ticker, name, precision, amounts)?)
+            }
+            "cfa" => {
+                let name = self.args.arg("name")?;
+                let precision = self.args.parse_arg("precision")?;
+                let amounts = self._amounts()?;
+                let description = self.args.option("description");
+                let file_path = self.args.option("file");
+                let online = self._online(&wallet)?;
+                json!(wallet.issue_asset_cfa(
+                    online,
+                    name,
+                    description,
+                    precision,
+                    amounts,
+                    file_path
+                )?)
+            }
+            _ => return usage_error(format!("unknown asset schema '{schema}'")),
+        })
+    }
+
+    fn receive(&mut self) -> CliResult<Value> {
+        let kind = self.args.arg("kind")?;
+        let wallet = self._wallet()?;
+        let asset_id = self.args.option("asset-id");
+        let amount = self.args.parse_option("amount")?;
+        let duration_seconds = self.args.parse_option("duration-seconds")?;
+        let transport_endpoints = self.args.options("transport-endpoint");
+        let min_confirmations = self._min_confirmations()?;
+        let receive_data = match kind.as_str() {
+            "blind" => wallet.blind_receive(
+                asset_id,
+                amount,
+                duration_seconds,
+                transport_endpoints,
+                min_confirmations,
+            )?,
+            "witness" => wallet.witness_receive(
+                asset_id,
+                amount,
+                duration_seconds,
+                transport_endpoints,
+                min_confirmations,
+            )?,
+            _ => return usage_error(format!("unknown receive kind '{kind}'")),
+        };
+        Ok(json!(receive_data))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn _send_args(
+        &mut self,
+    ) -> CliResult<(HashMap<String, Vec<Recipient>>, bool, f32, u8, SendOptions)> {
+        let asset_id = self.args.arg("asset_id")?;
+        let recipient_id = self.args.arg("recipient_id")?;
+        let amount = self.args.parse_arg("amount")?;
+        // a recipient receiving to a witness TX needs to be sent some bitcoins
+        let recipient_data =
+            match self.args.parse_option("amount-sat")? {
+                Some(amount_sat) => RecipientData::WitnessData {
+                    script_buf: ScriptBuf::from_hex(&recipient_id).map_err(|e| {
+                        Error::InvalidScript {
+                            details: e.to_string(),
+                        }
+                    })?,
+                    amount_sat,
+                    blinding: None,
+                },
+                None => RecipientData::BlindedUTXO(SecretSeal::from_str(&recipient_id).map_err(
+                    |e| Error::InvalidBlindedUTXO {
+                        details: e.to_string(),
+                    },
+                )?),
+            };
+        let recipient_map = HashMap::from([(
+            asset_id,
+            vec![Recipient {
+                recipient_data,
+                amount,
+                transport_endpoints: self.args.options("transport-endpoint"),
+            }],
+        )]);
+        Ok((
+            recipient_map,
+            self.args.switch("donation"),
+            self._fee_rate()?,
+            self._min_confirmations()?,
+            SendOptions {
+                redundant_posting: self.args.switch("redundant-posting"),
+            },
+        ))
+    }
+
+    fn list(&mut self) -> CliResult<Value> {
+        let subcommand = self.args.arg("subcommand")?;
+        let wallet = self._wallet()?;
+        Ok(match subcommand.as_str() {
+            "assets" => {
+                let schemas = self
+                    .args
+                    .options("schema")
+                    .iter()
+                    .map(|s| match s.to_lowercase().as_str() {
+                        "nia" => Ok(AssetSchema::Nia),
+                        "cfa" => Ok(AssetSchema::Cfa),
+                        _ => usage_error(format!("unknown asset schema '{s}'")),
+                    })
+                    .collect::<CliResult<Vec<AssetSchema>>>()?;
+                json!(wallet.list_assets(schemas)?)
+            }
+            "transfers" => json!(wallet.list_transfers(self.args.arg("asset_id")?)?),
+            "unspents" => {
+                let online = self._online(&wallet)?;
+                let settled_only = self.args.switch("settled-only");
+                json!(wallet.list_unspents(Some(online), settled_only)?)
+            }
+            "transactions" => {
+                let online = self._online(&wallet)?;
+                json!(wallet.list_transactions(Some(online))?)
+            }
+            _ => return usage_error(format!("unknown list subcommand '{subcommand}'")),
+        })
+    }
+
+    fn psbt(&mut self) -> CliResult<Value> {
+        let subcommand = self.args.arg("subcommand")?;
+        let wallet = self._wallet()?;
+        Ok(match subcommand.as_str() {
+            "send-begin" => {
+                let online = self._online(&wallet)?;
+                let (recipient_map, donation, fee_rate, min_confirmations, options) =
+                    self._send_args()?;
+                let psbt = wallet.send_begin_with_options(
+                    online,
+                    recipient_map,
+                    donation,
+                    fee_rate,
+                    min_confirmations,
+                    options,
+                )?;
+                json!({ "psbt": psbt })
+            }
+            "sign" => {
+                let psbt = self.args.arg("psbt")?;
+                let Some(signer) = self._wallet_data()?.1 else {
+                    return Err(Error::WatchOnly.into());
+                };
+                json!({ "psbt": signer.sign_psbt(psbt)? })
+            }
+            "send-end" => {
+                let psbt = self.args.arg("psbt")?;
+                let online = self._online(&wallet)?;
+                json!({ "txid": wallet.send_end(online, psbt)? })
+            }
+            "inspect" => json!(wallet.inspect_psbt(self.args.arg("psbt")?)?),
+            _ => return usage_error(format!("unknown psbt subcommand '{subcommand}'")),
+        })
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || ["help", "--help", "-h"].contains(&args[0].as_str()) {
+        println!("{USAGE}");
+        return;
+    }
+    let result = Args::parse(args.into_iter())
+        .and_then(Cli::new)
+        .and_then(|mut cli| cli.run());
+    match result {
+        Ok(result) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&result).expect("valid JSON")
+            );
+        }
+        Err(CliError::Usage(details)) => {
+            eprintln!("{}", json!({ "error": details }));
+            eprintln!("run 'rgb-lib-cli help' for usage");
+            exit(2);
+        }
+        Err(CliError::Wallet(e)) => {
+            eprintln!("{}", json!({ "error": e.to_string() }));
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Wallet daemon
+//!
+//! Usage: `rgb-lib-daemon --config <path>`
+//!
+//! The config file is the JSON serialization of a [`DaemonConfig`]. The bearer token clients need
+//! to provide can be overridden with the `RGB_LIB_DAEMON_BEARER_TOKEN` environment variable, so
+//! that it doesn't need to be stored in the config file.
+
+use rgb_lib::daemon::{Daemon, DaemonConfig};
+use std::process::exit;
+
+const USAGE: &str = "usage: rgb-lib-daemon --config <path>";
+
+fn main() {
+    let mut config_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--config", Some(path)) => config_path = Some(path),
+            _ => {
+                eprintln!("{USAGE}");
+                exit(2);
+            }
+        }
+    }
+    let Some(config_path) = config_path else {
+        eprintln!("{USAGE}");
+        exit(2);
+    };
+
+    let mut config: DaemonConfig = match std::fs::read_to_string(&config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("invalid config file {config_path}: {e}");
+            exit(2);
+        }
+    };
+    if let Ok(bearer_token) = std::env::var("RGB_LIB_DAEMON_BEARER_TOKEN") {
+        config.bearer_token = bearer_token;
+    }
+    if config.bearer_token.is_empty() {
+        eprintln!("a bearer token is required");
+        exit(2);
+    }
+
+    let daemon = match Daemon::start(config) {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            eprintln!("cannot start the daemon: {e}");
+            exit(1);
+        }
+    };
+    println!(
+        "RGB wallet daemon listening on {}, hosting wallets: {}",
+        daemon.url(),
+        daemon.wallet_ids().join(", ")
+    );
+    daemon.join();
+}
@@ -0,0 +1,41 @@
+//! Reference RGB proxy server
+//!
+//! Usage: `rgb-proxy-server [--listen <address>] [--db <path>]`
+//!
+//! The optional bearer token clients need to provide is read from the `RGB_PROXY_BEARER_TOKEN`
+//! environment variable.
+
+use rgb_lib::proxy_server::{ProxyServer, ProxyServerConfig};
+use std::path::PathBuf;
+use std::process::exit;
+
+const USAGE: &str = "usage: rgb-proxy-server [--listen <address>] [--db <path>]";
+
+fn main() {
+    let mut config = ProxyServerConfig {
+        listen_address: "127.0.0.1:3000".to_string(),
+        db_path: PathBuf::from("rgb-proxy-server.sqlite"),
+        bearer_token: std::env::var("RGB_PROXY_BEARER_TOKEN").ok(),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--listen", Some(address)) => config.listen_address = address,
+            ("--db", Some(path)) => config.db_path = PathBuf::from(path),
+            _ => {
+                eprintln!("{USAGE}");
+                exit(2);
+            }
+        }
+    }
+
+    let server = match ProxyServer::start(config) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("cannot start the proxy server: {e}");
+            exit(1);
+        }
+    };
+    println!("RGB proxy server listening on {}", server.url());
+    server.join();
+}
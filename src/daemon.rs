@@ -0,0 +1,1083 @@
+//! Wallet daemon
+//!
+//! This module defines a daemon hosting one or more [`Wallet`]s and exposing their operations over
+//! an authenticated HTTP JSON-RPC API, so that the library can be used as a wallet service from any
+//! language.
+//!
+//! The daemon periodically refreshes the hosted wallets in a background thread and records every
+//! transfer status change as a [`TransferEvent`]. Clients can receive them by long-polling the
+//! `events.poll` method.
+//!
+//! Requests are served by a small pool of worker threads. Only some of them can be held by
+//! long-polls at the same time, polls beyond that limit return the available events right away so
+//! that wallet calls are never starved. Wallet methods take the ID of the target
+//! wallet (its xPub fingerprint) as the `wallet_id` param, along with the parameters of the
+//! corresponding [`Wallet`] method.
+
+use base64::{engine::general_purpose, Engine as _};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::ScriptBuf;
+use rgb_core::SecretSeal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use tempfile::NamedTempFile;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::database::enums::{AssetSchema, TransferStatus};
+use crate::error::Error;
+use crate::wallet::{
+    Online, Recipient, RecipientData, SendOptions, TransferStatusInfo, Wallet, WalletData,
+};
+
+const JSON_RPC_PATH: &str = "/json-rpc";
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+const WORKER_THREADS: usize = 4;
+const MAX_EVENTS: usize = 10_000;
+const MAX_POLL_TIMEOUT_SECS: u64 = 60;
+// long-polls that can wait at the same time, leaving the other workers to wallet calls
+const MAX_WAITING_POLLS: usize = WORKER_THREADS / 2;
+
+// wallet methods that can create transfers or change their status
+const TRANSFER_METHODS: [&str; 7] = [
+    "blind_receive",
+    "witness_receive",
+    "send",
+    "refresh",
+    "fail_transfers",
+    "issue_asset_nia",
+    "issue_asset_cfa",
+];
+
+const ERR_PARSE: i64 = -32700;
+const ERR_INVALID_REQUEST: i64 = -32600;
+const ERR_METHOD_NOT_FOUND: i64 = -32601;
+const ERR_INVALID_PARAMS: i64 = -32602;
+const ERR_INTERNAL: i64 = -32603;
+const ERR_WALLET_NOT_FOUND: i64 = -400;
+
+/// Configuration of a wallet hosted by a [`Daemon`]
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DaemonWalletConfig {
+    /// Data of the wallet
+    pub wallet_data: WalletData,
+    /// URL of the Electrum server the wallet connects to
+    pub electrum_url: String,
+    /// Whether to skip the consistency check when going online
+    #[serde(default)]
+    pub skip_consistency_check: bool,
+}
+
+/// Configuration of a [`Daemon`]
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DaemonConfig {
+    /// Address to listen on (e.g. `127.0.0.1:3100`, port 0 picks a free one)
+    pub listen_address: String,
+    /// Bearer token clients need to provide
+    pub bearer_token: String,
+    /// Interval between the automatic refreshes of the hosted wallets, in seconds
+    pub refresh_interval_secs: u64,
+    /// Wallets to host
+    pub wallets: Vec<DaemonWalletConfig>,
+}
+
+/// A transfer status change detected by a [`Daemon`]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TransferEvent {
+    /// Sequence number of the event, increasing across all hosted wallets
+    pub seq: u64,
+    /// ID of the wallet the transfer belongs to
+    pub wallet_id: String,
+    /// ID of the transfer
+    pub transfer_idx: i32,
+    /// ID of the transferred asset, if known
+    pub asset_id: Option<String>,
+    /// Txid of the transfer, if known
+    pub txid: Option<String>,
+    /// Recipient ID of the transfer
+    pub recipient_id: Option<String>,
+    /// Whether the transfer is incoming
+    pub incoming: bool,
+    /// New status of the transfer
+    pub status: TransferStatus,
+}
+
+/// A running wallet daemon
+///
+/// The daemon is stopped when dropped.
+pub struct Daemon {
+    address: SocketAddr,
+    server: Arc<Server>,
+    state: Arc<DaemonState>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Daemon {
+    /// Open the configured wallets, take them online and start serving requests and refreshing
+    /// the wallets in background threads
+    pub fn start(config: DaemonConfig) -> Result<Self, Error> {
+        let mut wallets = HashMap::new();
+        for wallet_config in config.wallets {
+            let wallet = Wallet::new(wallet_config.wallet_data)?;
+            let online = wallet.go_online(
+                wallet_config.skip_consistency_check,
+                wallet_config.electrum_url,
+            )?;
+            let wallet_id = wallet
+                .get_wallet_dir()
+                .file_name()
+                .expect("valid wallet directory")
+                .to_string_lossy()
+                .to_string();
+            let statuses = _status_map(wallet._transfer_statuses()?);
+            wallets.insert(
+                wallet_id,
+                HostedWallet {
+                    wallet,
+                    online,
+                    statuses: Mutex::new(statuses),
+                    last_refresh_error: Mutex::new(None),
+                },
+            );
+        }
+        let state = Arc::new(DaemonState {
+            wallets,
+            bearer_token: config.bearer_token,
+            events: Mutex::new(EventLog::default()),
+            events_changed: Condvar::new(),
+            waiting_polls: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+            stop_requested: Mutex::new(false),
+            stop_changed: Condvar::new(),
+        });
+
+        let server = Server::http(&config.listen_address).map_err(|e| Error::IO {
+            details: e.to_string(),
+        })?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .expect("server should listen on an IP address");
+        let server = Arc::new(server);
+        let mut handles = vec![];
+        for _ in 0..WORKER_THREADS {
+            let (requests, state) = (server.clone(), state.clone());
+            handles.push(thread::spawn(move || {
+                for request in requests.incoming_requests() {
+                    if state.stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    state.handle(request);
+                }
+            }));
+        }
+        let refresh_interval = Duration::from_secs(config.refresh_interval_secs);
+        let refresh_state = state.clone();
+        handles.push(thread::spawn(move || {
+            refresh_state.refresh_loop(refresh_interval)
+        }));
+
+        Ok(Daemon {
+            address,
+            server,
+            state,
+            handles,
+        })
+    }
+
+    /// Return the address the daemon is listening on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Return the URL of the JSON-RPC endpoint
+    pub fn url(&self) -> String {
+        format!("http://{}{JSON_RPC_PATH}", self.address)
+    }
+
+    /// Return the IDs of the hosted wallets
+    pub fn wallet_ids(&self) -> Vec<String> {
+        let mut wallet_ids: Vec<String> = self.state.wallets.keys().cloned().collect();
+        wallet_ids.sort();
+        wallet_ids
+    }
+
+    /// Block until the daemon stops
+    pub fn join(mut self) {
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+
+    /// Stop serving requests and wait for the background threads to exit
+    pub fn stop(mut self) {
+        self._stop();
+    }
+
+    fn _stop(&mut self) {
+        self.state.stopped.store(true, Ordering::SeqCst);
+        *self.state.stop_requested.lock().unwrap() = true;
+        self.state.stop_changed.notify_all();
+        self.state.events_changed.notify_all();
+        for _ in 0..WORKER_THREADS {
+            self.server.unblock();
+        }
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        self._stop();
+    }
+}
+
+fn _status_map(statuses: Vec<TransferStatusInfo>) -> HashMap<i32, TransferStatusInfo> {
+    statuses.into_iter().map(|s| (s.idx, s)).collect()
+}
+
+struct HostedWallet {
+    wallet: Wallet,
+    online: Online,
+    statuses: Mutex<HashMap<i32, TransferStatusInfo>>,
+    last_refresh_error: Mutex<Option<String>>,
+}
+
+// a slot among the long-polls allowed to wait, released on drop
+struct WaitingPoll<'a>(&'a AtomicUsize);
+
+impl<'a> WaitingPoll<'a> {
+    fn new(waiting_polls: &'a AtomicUsize) -> Option<Self> {
+        waiting_polls
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < MAX_WAITING_POLLS).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| WaitingPoll(waiting_polls))
+    }
+}
+
+impl Drop for WaitingPoll<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct EventLog {
+    events: VecDeque<TransferEvent>,
+    last_seq: u64,
+}
+
+struct DaemonState {
+    wallets: HashMap<String, HostedWallet>,
+    bearer_token: String,
+    events: Mutex<EventLog>,
+    events_changed: Condvar,
+    waiting_polls: AtomicUsize,
+    stopped: AtomicBool,
+    stop_requested: Mutex<bool>,
+    stop_changed: Condvar,
+}
+
+impl DaemonState {
+    fn refresh_loop(&self, refresh_interval: Duration) {
+        loop {
+            let stop_requested = self.stop_requested.lock().unwrap();
+            let (stop_requested, _) = self
+                .stop_changed
+                .wait_timeout_while(stop_requested, refresh_interval, |stop| !*stop)
+                .unwrap();
+            if *stop_requested {
+                return;
+            }
+            drop(stop_requested);
+            for (wallet_id, hosted) in &self.wallets {
+                let result = hosted
+                    .wallet
+                    .refresh(hosted.online.clone(), None, vec![])
+                    .and_then(|_| self._record_changes(wallet_id, hosted));
+                *hosted.last_refresh_error.lock().unwrap() = result.err().map(|e| e.to_string());
+            }
+        }
+    }
+
+    // compare the current transfer statuses with the last known ones, logging the differences
+    fn _record_changes(&self, wallet_id: &str, hosted: &HostedWallet) -> Result<(), Error> {
+        let mut known_statuses = hosted.statuses.lock().unwrap();
+        let statuses = _status_map(hosted.wallet._transfer_statuses()?);
+        let mut changed: Vec<&TransferStatusInfo> = statuses
+            .values()
+            .filter(|s| known_statuses.get(&s.idx).map(|k| &k.status) != Some(&s.status))
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        changed.sort_by_key(|s| s.idx);
+        let mut event_log = self.events.lock().unwrap();
+        for status in changed {
+            event_log.last_seq += 1;
+            let seq = event_log.last_seq;
+            event_log.events.push_back(TransferEvent {
+                seq,
+                wallet_id: wallet_id.to_string(),
+                transfer_idx: status.idx,
+                asset_id: status.asset_id.clone(),
+                txid: status.txid.clone(),
+                recipient_id: status.recipient_id.clone(),
+                incoming: status.incoming,
+                status: status.status,
+            });
+            if event_log.events.len() > MAX_EVENTS {
+                event_log.events.pop_front();
+            }
+        }
+        *known_statuses = statuses;
+        self.events_changed.notify_all();
+        Ok(())
+    }
+
+    fn handle(&self, mut request: Request) {
+        let response = if *request.method() != Method::Post
+            || request.url().split('?').next() != Some(JSON_RPC_PATH)
+        {
+            Response::from_string("").with_status_code(404)
+        } else if !self._authorized(&request) {
+            Response::from_string("").with_status_code(401)
+        } else {
+            let mut body = vec![];
+            let read = request
+                .as_reader()
+                .take(MAX_BODY_SIZE + 1)
+                .read_to_end(&mut body);
+            if read.is_err() || body.len() as u64 > MAX_BODY_SIZE {
+                Response::from_string("").with_status_code(413)
+            } else {
+                let content = self._process(&body).to_string();
+                Response::from_string(content).with_header(
+                    Header::from_bytes("Content-Type", "application/json").expect("valid header"),
+                )
+            }
+        };
+        let _ = request.respond(response);
+    }
+
+    fn _authorized(&self, request: &Request) -> bool {
+        // digests are compared in constant time, so the time taken doesn't leak the token
+        let digest = |value: &str| sha256::Hash::hash(value.as_bytes()).to_byte_array();
+        let expected = digest(&format!("Bearer {}", self.bearer_token));
+        request.headers().iter().any(|h| {
+            h.field.equiv("Authorization") && bool::from(digest(h.value.as_str()).ct_eq(&expected))
+        })
+    }
+
+    fn _process(&self, body: &[u8]) -> Value {
+        let rpc_request: RpcRequest = match serde_json::from_slice::<Value>(body) {
+            Ok(value) => match serde_json::from_value(value) {
+                Ok(rpc_request) => rpc_request,
+                Err(_) => {
+                    let error = RpcError::new(ERR_INVALID_REQUEST, "invalid request");
+                    return _response(Value::Null, Err(error));
+                }
+            },
+            Err(_) => return _response(Value::Null, Err(RpcError::new(ERR_PARSE, "parse error"))),
+        };
+        let params = rpc_request.params;
+        let result = match rpc_request.method.as_str() {
+            "wallets.list" => Ok(self._list_wallets()),
+            "events.poll" => self._poll_events(&params),
+            method => match method.strip_prefix("wallet.") {
+                Some(wallet_method) => self._call_wallet(wallet_method, &params),
+                None => Err(RpcError::new(ERR_METHOD_NOT_FOUND, "method not found")),
+            },
+        };
+        _response(rpc_request.id, result)
+    }
+
+    fn _list_wallets(&self) -> Value {
+        let mut wallets: Vec<Value> = self
+            .wallets
+            .iter()
+            .map(|(wallet_id, hosted)| {
+                json!({
+                    "wallet_id": wallet_id,
+                    "bitcoin_network": hosted.wallet.get_wallet_data().bitcoin_network,
+                    "watch_only": hosted.wallet.get_wallet_data().mnemonic.is_none(),
+                    "last_refresh_error": hosted.last_refresh_error.lock().unwrap().clone(),
+                })
+            })
+            .collect();
+        wallets.sort_by_key(|w| w["wallet_id"].as_str().unwrap_or_default().to_string());
+        Value::Array(wallets)
+    }
+
+    fn _poll_events(&self, params: &Value) -> Result<Value, RpcError> {
+        let params: PollParams = _params(params)?;
+        let mut timeout = Duration::from_secs(params.timeout_secs.min(MAX_POLL_TIMEOUT_SECS));
+        if let Some(wallet_id) = &params.wallet_id {
+            self._hosted(wallet_id)?;
+        }
+        let waiting = WaitingPoll::new(&self.waiting_polls);
+        if waiting.is_none() {
+            timeout = Duration::ZERO;
+        }
+        let deadline = Instant::now() + timeout;
+        let mut event_log = self.events.lock().unwrap();
+        loop {
+            let events: Vec<&TransferEvent> = event_log
+                .events
+                .iter()
+                .filter(|e| e.seq > params.since)
+                .filter(|e| {
+                    params
+                        .wallet_id
+                        .as_ref()
+                        .map_or(true, |w| *w == e.wallet_id)
+                })
+                .collect();
+            let now = Instant::now();
+            if !events.is_empty() || now >= deadline || self.stopped.load(Ordering::SeqCst) {
+                return Ok(json!({
+                    "events": events,
+                    "last_seq": event_log.last_seq,
+                }));
+            }
+            event_log = self
+                .events_changed
+                .wait_timeout(event_log, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+
+    fn _hosted(&self, wallet_id: &str) -> Result<&HostedWallet, RpcError> {
+        self.wallets
+            .get(wallet_id)
+            .ok_or_else(|| RpcError::new(ERR_WALLET_NOT_FOUND, "wallet not found"))
+    }
+
+    fn _call_wallet(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        let wallet_params: WalletParams = _params(params)?;
+        let wallet_id = wallet_params.wallet_id;
+        let hosted = self._hosted(&wallet_id)?;
+        let (wallet, online) = (&hosted.wallet, hosted.online.clone());
+        let result = match method {
+            "address" => json!(wallet.get_address()),
+            "btc_balance" => json!(wallet.get_btc_balance(online)?),
+            "asset_balance" => {
+                let params: AssetIdParams = _params(params)?;
+                json!(wallet.get_asset_balance(params.asset_id)?)
+            }
+            "asset_metadata" => {
+                let params: AssetIdParams = _params(params)?;
+                json!(wallet.get_asset_metadata(params.asset_id)?)
+            }
+            "list_assets" => {
+                let params: ListAssetsParams = _params(params)?;
+                json!(wallet.list_assets(params.filter_asset_schemas)?)
+            }
+            "list_transfers" => {
+                let params: AssetIdParams = _params(params)?;
+                json!(wallet.list_transfers(params.asset_id)?)
+            }
+            "list_unspents" => {
+                let params: ListUnspentsParams = _params(params)?;
+                json!(wallet.list_unspents(Some(online), params.settled_only)?)
+            }
+            "list_transactions" => json!(wallet.list_transactions(Some(online))?),
+            "create_utxos" => {
+                let params: CreateUtxosParams = _params(params)?;
+                json!(wallet.create_utxos(
+                    online,
+                    params.up_to,
+                    params.num,
+                    params.size,
+                    params.fee_rate,
+                )?)
+            }
+            "issue_asset_nia" => {
+                let params: IssueAssetNiaParams = _params(params)?;
+                json!(wallet.issue_asset_nia(
+                    online,
+                    params.ticker,
+                    params.name,
+                    params.precision,
+                    params.amounts,
+                )?)
+            }
+            "issue_asset_cfa" => {
+                let params: IssueAssetCfaParams = _params(params)?;
+                // the media is sent by the client, files on the daemon's host are never read
+                let media_file = params.media.map(_media_file).transpose()?;
+                json!(wallet.issue_asset_cfa(
+                    online,
+                    params.name,
+                    params.description,
+                    params.precision,
+                    params.amounts,
+                    media_file
+                        .as_ref()
+                        .map(|f| f.path().to_string_lossy().to_string()),
+                )?)
+            }
+            "blind_receive" | "witness_receive" => {
+                let params: ReceiveParams = _params(params)?;
+                let receive = if method == "blind_receive" {
+                    Wallet::blind_receive
+                } else {
+                    Wallet::witness_receive
+                };
+                json!(receive(
+                    wallet,
+                    params.asset_id,
+                    params.amount,
+                    params.duration_seconds,
+                    params.transport_endpoints,
+                    params.min_confirmations,
+                )?)
+            }
+            "send" => {
+                let params: SendParams = _params(params)?;
+                let mut recipient_map = HashMap::new();
+                for (asset_id, recipients) in params.recipient_map {
+                    let recipients = recipients
+                        .into_iter()
+                        .map(RecipientParams::into_recipient)
+                        .collect::<Result<Vec<Recipient>, Error>>()?;
+                    recipient_map.insert(asset_id, recipients);
+                }
+                json!(wallet.send_with_options(
+                    online,
+                    recipient_map,
+                    params.donation,
+                    params.fee_rate,
+                    params.min_confirmations,
+                    SendOptions {
+                        redundant_posting: params.redundant_posting,
+                    },
+                )?)
+            }
+            "send_btc" => {
+                let params: SendBtcParams = _params(params)?;
+                json!(wallet.send_btc(online, params.address, params.amount, params.fee_rate)?)
+            }
+            "drain_to" => {
+                let params: DrainToParams = _params(params)?;
+                json!(wallet.drain_to(
+                    online,
+                    params.address,
+                    params.destroy_assets,
+                    params.fee_rate,
+                )?)
+            }
+            "refresh" => {
+                let params: RefreshParams = _params(params)?;
+                json!(wallet.refresh(online, params.asset_id, vec![])?)
+            }
+            "fail_transfers" => {
+                let params: FailTransfersParams = _params(params)?;
+                json!(wallet.fail_transfers(
+                    online,
+                    params.recipient_id,
+                    params.txid,
+                    params.no_asset_only,
+                )?)
+            }
+            _ => return Err(RpcError::new(ERR_METHOD_NOT_FOUND, "method not found")),
+        };
+        // transfer changes made by a call are reflected in the events right away
+        if TRANSFER_METHODS.contains(&method) {
+            self._record_changes(&wallet_id, hosted)?;
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    method: String,
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: &str) -> Self {
+        RpcError {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(e: Error) -> Self {
+        RpcError::new(ERR_INTERNAL, &e.to_string())
+    }
+}
+
+fn _params<T: DeserializeOwned>(params: &Value) -> Result<T, RpcError> {
+    let params = if params.is_null() {
+        json!({})
+    } else {
+        params.clone()
+    };
+    serde_json::from_value(params)
+        .map_err(|e| RpcError::new(ERR_INVALID_PARAMS, &format!("invalid params: {e}")))
+}
+
+fn _media_file(media: String) -> Result<NamedTempFile, RpcError> {
+    let content = general_purpose::STANDARD
+        .decode(media)
+        .map_err(|e| RpcError::new(ERR_INVALID_PARAMS, &format!("invalid media: {e}")))?;
+    let mut media_file = NamedTempFile::new().map_err(Error::from)?;
+    media_file.write_all(&content).map_err(Error::from)?;
+    Ok(media_file)
+}
+
+fn _response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": e.code, "message": e.message},
+        }),
+    }
+}
+
+#[derive(Deserialize)]
+struct PollParams {
+    #[serde(default)]
+    since: u64,
+    #[serde(default)]
+    timeout_secs: u64,
+    wallet_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WalletParams {
+    wallet_id: String,
+}
+
+#[derive(Deserialize)]
+struct AssetIdParams {
+    asset_id: String,
+}
+
+#[derive(Deserialize)]
+struct ListAssetsParams {
+    #[serde(default)]
+    filter_asset_schemas: Vec<AssetSchema>,
+}
+
+#[derive(Deserialize)]
+struct ListUnspentsParams {
+    #[serde(default)]
+    settled_only: bool,
+}
+
+#[derive(Deserialize)]
+struct CreateUtxosParams {
+    #[serde(default)]
+    up_to: bool,
+    num: Option<u8>,
+    size: Option<u32>,
+    fee_rate: f32,
+}
+
+#[derive(Deserialize)]
+struct IssueAssetNiaParams {
+    ticker: String,
+    name: String,
+    precision: u8,
+    amounts: Vec<u64>,
+}
+
+#[derive(Deserialize)]
+struct IssueAssetCfaParams {
+    name: String,
+    description: Option<String>,
+    precision: u8,
+    amounts: Vec<u64>,
+    // base64-encoded content of the media to attach
+    media: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReceiveParams {
+    asset_id: Option<String>,
+    amount: Option<u64>,
+    duration_seconds: Option<u32>,
+    transport_endpoints: Vec<String>,
+    min_confirmations: u8,
+}
+
+// recipients are identified by the recipient ID returned by the receive methods, the bitcoin
+// amount is required for witness recipients
+#[derive(Deserialize)]
+struct RecipientParams {
+    recipient_id: String,
+    amount: u64,
+    amount_sat: Option<u64>,
+    blinding: Option<u64>,
+    transport_endpoints: Vec<String>,
+}
+
+impl RecipientParams {
+    fn into_recipient(self) -> Result<Recipient, Error> {
+        let recipient_data = match self.amount_sat {
+            Some(amount_sat) => RecipientData::WitnessData {
+                script_buf: ScriptBuf::from_hex(&self.recipient_id).map_err(|e| {
+                    Error::InvalidScript {
+                        details: e.to_string(),
+                    }
+                })?,
+                amount_sat,
+                blinding: self.blinding,
+            },
+            None => RecipientData::BlindedUTXO(SecretSeal::from_str(&self.recipient_id).map_err(
+                |e| Error::InvalidBlindedUTXO {
+                    details: e.to_string(),
+                },
+            )?),
+        };
+        Ok(Recipient {
+            recipient_data,
+            amount: self.amount,
+            transport_endpoints: self.transport_endpoints,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SendParams {
+    recipient_map: HashMap<String, Vec<RecipientParams>>,
+    #[serde(default)]
+    donation: bool,
+    fee_rate: f32,
+    min_confirmations: u8,
+    #[serde(default)]
+    redundant_posting: bool,
+}
+
+#[derive(Deserialize)]
+struct SendBtcParams {
+    address: String,
+    amount: u64,
+    fee_rate: f32,
+}
+
+#[derive(Deserialize)]
+struct DrainToParams {
+    address: String,
+    #[serde(default)]
+    destroy_assets: bool,
+    fee_rate: f32,
+}
+
+#[derive(Deserialize)]
+struct RefreshParams {
+    asset_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FailTransfersParams {
+    recipient_id: Option<String>,
+    txid: Option<String>,
+    #[serde(default)]
+    no_asset_only: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate_keys;
+    use crate::utils::BitcoinNetwork;
+    use crate::wallet::test::harness::RegtestHarness;
+    use crate::wallet::DatabaseType;
+    use amplify::s;
+    use reqwest::blocking::Client;
+    use tempfile::TempDir;
+
+    const BEARER_TOKEN: &str = "token";
+
+    fn wallet_config(data_dir: &TempDir, electrum_url: String) -> DaemonWalletConfig {
+        let keys = generate_keys(BitcoinNetwork::Regtest);
+        DaemonWalletConfig {
+            wallet_data: WalletData {
+                data_dir: data_dir.path().to_string_lossy().to_string(),
+                bitcoin_network: BitcoinNetwork::Regtest,
+                database_type: DatabaseType::Sqlite,
+                max_allocations_per_utxo: 5,
+                pubkey: keys.xpub,
+                mnemonic: Some(keys.mnemonic),
+                passphrase: None,
+                log_config: None,
+                proxy_auth: HashMap::new(),
+                network_config: None,
+                proxy_retry_config: None,
+            },
+            electrum_url,
+            skip_consistency_check: true,
+        }
+    }
+
+    fn start_daemon(wallets: Vec<DaemonWalletConfig>) -> Daemon {
+        Daemon::start(DaemonConfig {
+            listen_address: s!("127.0.0.1:0"),
+            bearer_token: BEARER_TOKEN.to_string(),
+            refresh_interval_secs: 1,
+            wallets,
+        })
+        .unwrap()
+    }
+
+    fn call(daemon: &Daemon, method: &str, params: Value) -> Value {
+        Client::new()
+            .post(daemon.url())
+            .bearer_auth(BEARER_TOKEN)
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    fn call_ok(daemon: &Daemon, method: &str, params: Value) -> Value {
+        let response = call(daemon, method, params);
+        assert!(response.get("error").is_none(), "{response}");
+        response["result"].clone()
+    }
+
+    #[test]
+    fn success() {
+        let harness = RegtestHarness::start();
+        let (snd_dir, rcv_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let daemon = start_daemon(vec![
+            wallet_config(&snd_dir, harness.electrum_url()),
+            wallet_config(&rcv_dir, harness.electrum_url()),
+        ]);
+        let wallets = call_ok(&daemon, "wallets.list", Value::Null);
+        assert_eq!(wallets.as_array().unwrap().len(), 2);
+        let (snd_id, rcv_id) = (
+            daemon.wallet_ids()[0].clone(),
+            daemon.wallet_ids()[1].clone(),
+        );
+
+        // fund the wallets
+        for wallet_id in [&snd_id, &rcv_id] {
+            let address = call_ok(&daemon, "wallet.address", json!({"wallet_id": wallet_id}));
+            harness.indexer.fund(address.as_str().unwrap(), 100_000_000);
+        }
+        harness.mine(1);
+        for wallet_id in [&snd_id, &rcv_id] {
+            let params = json!({"wallet_id": wallet_id, "fee_rate": 1.5});
+            call_ok(&daemon, "wallet.create_utxos", params);
+        }
+        harness.mine(1);
+
+        // issue and send
+        let params = json!({
+            "wallet_id": snd_id,
+            "ticker": "TICKER",
+            "name": "asset name",
+            "precision": 0,
+            "amounts": [1000],
+        });
+        let asset = call_ok(&daemon, "wallet.issue_asset_nia", params);
+        let asset_id = asset["asset_id"].as_str().unwrap().to_string();
+        let params = json!({
+            "wallet_id": rcv_id,
+            "transport_endpoints": harness.transport_endpoints(),
+            "min_confirmations": 1,
+        });
+        let receive_data = call_ok(&daemon, "wallet.blind_receive", params);
+        let recipient_id = receive_data["recipient_id"].as_str().unwrap().to_string();
+        let recipient = json!({
+            "recipient_id": recipient_id,
+            "amount": 66,
+            "transport_endpoints": harness.transport_endpoints(),
+        });
+        let params = json!({
+            "wallet_id": snd_id,
+            "recipient_map": {asset_id.clone(): [recipient]},
+            "fee_rate": 1.5,
+            "min_confirmations": 1,
+        });
+        let txid = call_ok(&daemon, "wallet.send", params);
+
+        // the transfers created by the calls are reported right away
+        let params = json!({"wallet_id": rcv_id});
+        let poll = call_ok(&daemon, "events.poll", params);
+        let events: Vec<TransferEvent> = serde_json::from_value(poll["events"].clone()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].recipient_id, Some(recipient_id.clone()));
+        assert_eq!(events[0].status, TransferStatus::WaitingCounterparty);
+
+        // the automatic refreshes take the transfers to settled
+        let mut since = 0;
+        let mut statuses: HashMap<(String, i32), TransferEvent> = HashMap::new();
+        let mut mined = false;
+        let start = Instant::now();
+        let settled = |statuses: &HashMap<(String, i32), TransferEvent>, wallet_id: &str| {
+            statuses.values().any(|e| {
+                e.wallet_id == wallet_id
+                    && e.txid.as_deref() == txid.as_str()
+                    && e.status == TransferStatus::Settled
+            })
+        };
+        while !settled(&statuses, &snd_id) || !settled(&statuses, &rcv_id) {
+            assert!(start.elapsed() < Duration::from_secs(120), "{statuses:?}");
+            let poll = call_ok(
+                &daemon,
+                "events.poll",
+                json!({"since": since, "timeout_secs": 5}),
+            );
+            since = poll["last_seq"].as_u64().unwrap();
+            let events: Vec<TransferEvent> =
+                serde_json::from_value(poll["events"].clone()).unwrap();
+            for event in events {
+                statuses.insert((event.wallet_id.clone(), event.transfer_idx), event);
+            }
+            if !mined
+                && harness
+                    .indexer
+                    .mempool()
+                    .contains(&txid.as_str().unwrap().to_string())
+            {
+                harness.mine(1);
+                mined = true;
+            }
+        }
+        let balance = call_ok(
+            &daemon,
+            "wallet.asset_balance",
+            json!({"wallet_id": rcv_id, "asset_id": asset_id}),
+        );
+        assert_eq!(balance["settled"], 66);
+        let wallets = call_ok(&daemon, "wallets.list", Value::Null);
+        assert!(wallets[0]["last_refresh_error"].is_null());
+    }
+
+    #[test]
+    fn fail() {
+        let daemon = start_daemon(vec![]);
+
+        // unauthorized
+        let response = Client::new()
+            .post(daemon.url())
+            .bearer_auth("wrong")
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "wallets.list"}))
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), 401);
+        let response = Client::new()
+            .post(daemon.url())
+            .bearer_auth(format!("{BEARER_TOKEN}_"))
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "wallets.list"}))
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), 401);
+
+        // wrong path
+        let url = daemon.url().replace(JSON_RPC_PATH, "/other");
+        let response = Client::new()
+            .post(url)
+            .bearer_auth(BEARER_TOKEN)
+            .send()
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        // parse error
+        let response: Value = Client::new()
+            .post(daemon.url())
+            .bearer_auth(BEARER_TOKEN)
+            .body("{")
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(response["error"]["code"], ERR_PARSE);
+
+        // unknown method
+        let response = call(&daemon, "wallet.unknown", json!({"wallet_id": "id"}));
+        assert_eq!(response["error"]["code"], ERR_WALLET_NOT_FOUND);
+        let response = call(&daemon, "unknown", Value::Null);
+        assert_eq!(response["error"]["code"], ERR_METHOD_NOT_FOUND);
+
+        // invalid params
+        let response = call(&daemon, "wallet.address", Value::Null);
+        assert_eq!(response["error"]["code"], ERR_INVALID_PARAMS);
+        let response = call(&daemon, "events.poll", json!({"since": "0"}));
+        assert_eq!(response["error"]["code"], ERR_INVALID_PARAMS);
+
+        // no events
+        let poll = call_ok(&daemon, "events.poll", json!({"timeout_secs": 1}));
+        assert_eq!(poll, json!({"events": [], "last_seq": 0}));
+
+        // stopped daemon
+        let url = daemon.url();
+        daemon.stop();
+        let result = Client::new()
+            .post(url)
+            .bearer_auth(BEARER_TOKEN)
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "wallets.list"}))
+            .send();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn busy_polls() {
+        let daemon = start_daemon(vec![]);
+
+        // fill the workers with long-polls
+        let pollers: Vec<JoinHandle<()>> = (0..WORKER_THREADS)
+            .map(|_| {
+                let url = daemon.url();
+                thread::spawn(move || {
+                    let _ = Client::new()
+                        .post(url)
+                        .bearer_auth(BEARER_TOKEN)
+                        .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "events.poll",
+                            "params": {"timeout_secs": 10}}))
+                        .send();
+                })
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(
+            daemon.state.waiting_polls.load(Ordering::SeqCst),
+            MAX_WAITING_POLLS
+        );
+
+        // other requests are still served and polls beyond the limit don't wait
+        let start = Instant::now();
+        call_ok(&daemon, "wallets.list", Value::Null);
+        let poll = call_ok(&daemon, "events.poll", json!({"timeout_secs": 10}));
+        assert_eq!(poll, json!({"events": [], "last_seq": 0}));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        drop(daemon);
+        for poller in pollers {
+            poller.join().unwrap();
+        }
+    }
+}
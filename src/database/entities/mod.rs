@@ -4,10 +4,13 @@ pub mod prelude;
 
 pub mod asset;
 pub mod asset_transfer;
+pub mod backup_info;
 pub mod batch_transfer;
 pub mod coloring;
+pub mod prepared_psbt;
 pub mod transfer;
 pub mod transfer_transport_endpoint;
 pub mod transport_endpoint;
 pub mod txo;
+pub mod wallet_config;
 pub mod wallet_transaction;
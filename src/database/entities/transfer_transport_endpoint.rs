@@ -17,6 +17,7 @@ pub struct Model {
     pub transfer_idx: i32,
     pub transport_endpoint_idx: i32,
     pub used: bool,
+    pub posted: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -25,6 +26,7 @@ pub enum Column {
     TransferIdx,
     TransportEndpointIdx,
     Used,
+    Posted,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -53,6 +55,7 @@ impl ColumnTrait for Column {
             Self::TransferIdx => ColumnType::Integer.def(),
             Self::TransportEndpointIdx => ColumnType::Integer.def(),
             Self::Used => ColumnType::Boolean.def(),
+            Self::Posted => ColumnType::Boolean.def(),
         }
     }
 }
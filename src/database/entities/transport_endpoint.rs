@@ -18,6 +18,11 @@ pub struct Model {
     pub idx: i32,
     pub transport_type: TransportType,
     pub endpoint: String,
+    pub protocol_version: Option<String>,
+    pub protocol_checked_at: Option<i64>,
+    pub last_success_at: Option<i64>,
+    pub consecutive_failures: i32,
+    pub latency_ms: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -25,6 +30,11 @@ pub enum Column {
     Idx,
     TransportType,
     Endpoint,
+    ProtocolVersion,
+    ProtocolCheckedAt,
+    LastSuccessAt,
+    ConsecutiveFailures,
+    LatencyMs,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -51,6 +61,11 @@ impl ColumnTrait for Column {
             Self::Idx => ColumnType::Integer.def(),
             Self::TransportType => ColumnType::SmallInteger.def(),
             Self::Endpoint => ColumnType::String(None).def(),
+            Self::ProtocolVersion => ColumnType::String(None).def().null(),
+            Self::ProtocolCheckedAt => ColumnType::BigInteger.def().null(),
+            Self::LastSuccessAt => ColumnType::BigInteger.def().null(),
+            Self::ConsecutiveFailures => ColumnType::Integer.def(),
+            Self::LatencyMs => ColumnType::BigInteger.def().null(),
         }
     }
 }
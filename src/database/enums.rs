@@ -33,19 +33,35 @@ impl AssetSchema {
     }
 }
 
+/// The role of an RGB allocation in the transfer that colors a UTXO
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "u16", db_type = "Integer")]
 pub enum ColoringType {
+    /// Allocation received from another wallet
     #[sea_orm(num_value = 1)]
     Receive = 1,
+    /// Allocation created by an asset issuance
     #[sea_orm(num_value = 2)]
     Issue = 2,
+    /// Allocation spent by an outgoing transfer
     #[sea_orm(num_value = 3)]
     Input = 3,
+    /// Allocation returned as change by an outgoing transfer
     #[sea_orm(num_value = 4)]
     Change = 4,
 }
 
+impl ColoringType {
+    pub(crate) fn incoming(&self) -> bool {
+        [
+            ColoringType::Receive,
+            ColoringType::Change,
+            ColoringType::Issue,
+        ]
+        .contains(self)
+    }
+}
+
 impl IntoActiveValue<ColoringType> for ColoringType {
     fn into_active_value(self) -> ActiveValue<ColoringType> {
         ActiveValue::Set(self)
@@ -126,6 +142,17 @@ impl TransferStatus {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "u16", db_type = "Integer")]
+pub enum PreparedPsbtType {
+    #[sea_orm(num_value = 1)]
+    CreateUtxos = 1,
+    #[sea_orm(num_value = 2)]
+    Drain = 2,
+    #[sea_orm(num_value = 3)]
+    Send = 3,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "u16", db_type = "Integer")]
 pub enum WalletTransactionType {
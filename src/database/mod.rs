@@ -1,12 +1,19 @@
 use bdk::bitcoin::OutPoint as BdkOutPoint;
 use bdk::LocalUtxo;
 use futures::executor::block_on;
-use sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::{
+    ActiveValue, ColumnTrait, Condition, DatabaseConnection, EntityTrait, FromQueryResult,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 
 use crate::error::InternalError;
 use crate::utils::now;
+#[cfg(feature = "daemon")]
+use crate::wallet::TransferStatusInfo;
 use crate::wallet::{Balance, Outpoint, RecipientData, TransferKind};
 use crate::Error;
 
@@ -19,7 +26,9 @@ use crate::database::entities::batch_transfer::{
     ActiveModel as DbBatchTransferActMod, Model as DbBatchTransfer,
 };
 use entities::asset::{ActiveModel as DbAssetActMod, Model as DbAsset};
+use entities::backup_info::{ActiveModel as DbBackupInfoActMod, Model as DbBackupInfo};
 use entities::coloring::{ActiveModel as DbColoringActMod, Model as DbColoring};
+use entities::prepared_psbt::{ActiveModel as DbPreparedPsbtActMod, Model as DbPreparedPsbt};
 use entities::transfer::{ActiveModel as DbTransferActMod, Model as DbTransfer};
 use entities::transfer_transport_endpoint::{
     ActiveModel as DbTransferTransportEndpointActMod, Model as DbTransferTransportEndpoint,
@@ -28,15 +37,44 @@ use entities::transport_endpoint::{
     ActiveModel as DbTransportEndpointActMod, Model as DbTransportEndpoint,
 };
 use entities::txo::{ActiveModel as DbTxoActMod, Model as DbTxo};
+use entities::wallet_config::{ActiveModel as DbWalletConfigActMod, Model as DbWalletConfig};
 use entities::wallet_transaction::{
     ActiveModel as DbWalletTransactionActMod, Model as DbWalletTransaction,
 };
 use entities::{
-    asset, asset_transfer, batch_transfer, coloring, transfer, transfer_transport_endpoint,
-    transport_endpoint, txo, wallet_transaction,
+    asset, asset_transfer, backup_info, batch_transfer, coloring, prepared_psbt, transfer,
+    transfer_transport_endpoint, transport_endpoint, txo, wallet_config, wallet_transaction,
 };
 
-use self::enums::{ColoringType, RecipientType, TransferStatus, TransportType};
+use self::enums::{ColoringType, PreparedPsbtType, RecipientType, TransferStatus, TransportType};
+
+// SQLite limits the number of bound parameters per statement
+const MAX_QUERY_PARAMS: usize = 500;
+
+#[derive(Debug, FromQueryResult)]
+struct DbAllocation {
+    txo_idx: i32,
+    txo_spent: bool,
+    asset_id: Option<String>,
+    amount: String,
+    coloring_type: ColoringType,
+    status: TransferStatus,
+}
+
+impl From<DbAllocation> for LocalRgbAllocation {
+    fn from(x: DbAllocation) -> LocalRgbAllocation {
+        LocalRgbAllocation {
+            asset_id: x.asset_id,
+            amount: x
+                .amount
+                .parse::<u64>()
+                .expect("DB should contain a valid u64 value"),
+            status: x.status,
+            incoming: x.coloring_type.incoming(),
+            txo_spent: x.txo_spent,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub(crate) struct DbAssetTransferData {
@@ -119,12 +157,7 @@ pub(crate) struct DbBatchTransferData {
 
 impl DbColoring {
     pub(crate) fn incoming(&self) -> bool {
-        [
-            ColoringType::Receive,
-            ColoringType::Change,
-            ColoringType::Issue,
-        ]
-        .contains(&self.coloring_type)
+        self.coloring_type.incoming()
     }
 }
 
@@ -189,6 +222,8 @@ pub(crate) struct LocalTransportEndpoint {
     pub endpoint: String,
     pub used: bool,
     pub usable: bool,
+    #[serde(default)]
+    pub posted: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -278,6 +313,14 @@ impl RgbLibDatabase {
         Ok(res.last_insert_id)
     }
 
+    pub(crate) fn set_backup_info(
+        &self,
+        backup_info: DbBackupInfoActMod,
+    ) -> Result<i32, InternalError> {
+        let res = block_on(backup_info::Entity::insert(backup_info).exec(self.get_connection()))?;
+        Ok(res.last_insert_id)
+    }
+
     pub(crate) fn set_batch_transfer(
         &self,
         batch_transfer: DbBatchTransferActMod,
@@ -294,6 +337,15 @@ impl RgbLibDatabase {
         Ok(res.last_insert_id)
     }
 
+    pub(crate) fn set_prepared_psbt(
+        &self,
+        prepared_psbt: DbPreparedPsbtActMod,
+    ) -> Result<i32, InternalError> {
+        let res =
+            block_on(prepared_psbt::Entity::insert(prepared_psbt).exec(self.get_connection()))?;
+        Ok(res.last_insert_id)
+    }
+
     pub(crate) fn set_transport_endpoint(
         &self,
         transport_endpoint: DbTransportEndpointActMod,
@@ -325,6 +377,15 @@ impl RgbLibDatabase {
         Ok(res.last_insert_id)
     }
 
+    pub(crate) fn set_wallet_config(
+        &self,
+        wallet_config: DbWalletConfigActMod,
+    ) -> Result<i32, InternalError> {
+        let res =
+            block_on(wallet_config::Entity::insert(wallet_config).exec(self.get_connection()))?;
+        Ok(res.last_insert_id)
+    }
+
     pub(crate) fn set_wallet_transaction(
         &self,
         wallet_transaction: DbWalletTransactionActMod,
@@ -358,6 +419,15 @@ impl RgbLibDatabase {
         )?)
     }
 
+    pub(crate) fn update_backup_info(
+        &self,
+        backup_info: DbBackupInfoActMod,
+    ) -> Result<DbBackupInfo, InternalError> {
+        Ok(block_on(
+            backup_info::Entity::update(backup_info).exec(self.get_connection()),
+        )?)
+    }
+
     pub(crate) fn update_batch_transfer(
         &self,
         batch_transfer: &mut DbBatchTransferActMod,
@@ -379,11 +449,30 @@ impl RgbLibDatabase {
         )?)
     }
 
+    pub(crate) fn update_transport_endpoint(
+        &self,
+        transport_endpoint: DbTransportEndpointActMod,
+    ) -> Result<(), InternalError> {
+        block_on(
+            transport_endpoint::Entity::update(transport_endpoint).exec(self.get_connection()),
+        )?;
+        Ok(())
+    }
+
     pub(crate) fn update_txo(&self, txo: DbTxoActMod) -> Result<(), InternalError> {
         block_on(txo::Entity::update(txo).exec(self.get_connection()))?;
         Ok(())
     }
 
+    pub(crate) fn update_wallet_config(
+        &self,
+        wallet_config: DbWalletConfigActMod,
+    ) -> Result<DbWalletConfig, InternalError> {
+        Ok(block_on(
+            wallet_config::Entity::update(wallet_config).exec(self.get_connection()),
+        )?)
+    }
+
     pub(crate) fn del_batch_transfer(
         &self,
         batch_transfer: &DbBatchTransfer,
@@ -401,6 +490,47 @@ impl RgbLibDatabase {
         Ok(())
     }
 
+    pub(crate) fn del_prepared_psbt(
+        &self,
+        prepared_psbt: &DbPreparedPsbt,
+    ) -> Result<(), InternalError> {
+        block_on(
+            prepared_psbt::Entity::delete_by_id(prepared_psbt.idx).exec(self.get_connection()),
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn del_prepared_psbts_created_before(
+        &self,
+        created_at: i64,
+    ) -> Result<(), InternalError> {
+        block_on(
+            prepared_psbt::Entity::delete_many()
+                .filter(prepared_psbt::Column::CreatedAt.lt(created_at))
+                .exec(self.get_connection()),
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn get_backup_info(&self) -> Result<Option<DbBackupInfo>, InternalError> {
+        Ok(block_on(
+            backup_info::Entity::find().one(self.get_connection()),
+        )?)
+    }
+
+    pub(crate) fn get_prepared_psbt(
+        &self,
+        txid: String,
+        prepared_psbt_type: PreparedPsbtType,
+    ) -> Result<Option<DbPreparedPsbt>, InternalError> {
+        Ok(block_on(
+            prepared_psbt::Entity::find()
+                .filter(prepared_psbt::Column::Txid.eq(txid))
+                .filter(prepared_psbt::Column::PreparedPsbtType.eq(prepared_psbt_type))
+                .one(self.get_connection()),
+        )?)
+    }
+
     pub(crate) fn get_transport_endpoint(
         &self,
         endpoint: String,
@@ -412,6 +542,12 @@ impl RgbLibDatabase {
         )?)
     }
 
+    pub(crate) fn get_wallet_config(&self) -> Result<Option<DbWalletConfig>, InternalError> {
+        Ok(block_on(
+            wallet_config::Entity::find().one(self.get_connection()),
+        )?)
+    }
+
     pub(crate) fn get_txo(&self, outpoint: Outpoint) -> Result<Option<DbTxo>, InternalError> {
         Ok(block_on(
             txo::Entity::find()
@@ -508,72 +644,134 @@ impl RgbLibDatabase {
     }
 
     pub(crate) fn get_unspent_txos(&self, txos: Vec<DbTxo>) -> Result<Vec<DbTxo>, InternalError> {
-        let txos = if txos.is_empty() {
-            self.iter_txos()?
-        } else {
-            txos
-        };
+        if txos.is_empty() {
+            return Ok(block_on(
+                txo::Entity::find()
+                    .filter(txo::Column::Spent.eq(false))
+                    .all(self.get_connection()),
+            )?);
+        }
         Ok(txos.into_iter().filter(|t| !t.spent).collect())
     }
 
-    pub(crate) fn get_asset_balance(
+    pub(crate) fn iter_pending_batch_transfers(
         &self,
-        asset_id: String,
-        asset_transfers: Option<Vec<DbAssetTransfer>>,
-        batch_transfers: Option<Vec<DbBatchTransfer>>,
-        colorings: Option<Vec<DbColoring>>,
-        txos: Option<Vec<DbTxo>>,
-    ) -> Result<Balance, Error> {
-        let batch_transfers = if let Some(bt) = batch_transfers {
-            bt
-        } else {
-            self.iter_batch_transfers()?
-        };
-        let asset_transfers = if let Some(at) = asset_transfers {
-            at
-        } else {
-            self.iter_asset_transfers()?
-        };
-        let colorings = if let Some(cs) = colorings {
-            cs
-        } else {
-            self.iter_colorings()?
-        };
-        let txos = if let Some(t) = txos {
-            t
-        } else {
-            self.iter_txos()?
-        };
+        asset_id: Option<String>,
+    ) -> Result<Vec<DbBatchTransfer>, InternalError> {
+        let mut query =
+            batch_transfer::Entity::find().filter(batch_transfer::Column::Status.is_in([
+                TransferStatus::WaitingCounterparty,
+                TransferStatus::WaitingConfirmations,
+            ]));
+        if let Some(aid) = asset_id {
+            query = query.filter(
+                batch_transfer::Column::Idx.in_subquery(
+                    Query::select()
+                        .column(asset_transfer::Column::BatchTransferIdx)
+                        .from(asset_transfer::Entity)
+                        .and_where(Expr::col(asset_transfer::Column::AssetId).eq(aid))
+                        .to_owned(),
+                ),
+            );
+        }
+        Ok(block_on(
+            query
+                .order_by_asc(batch_transfer::Column::Idx)
+                .all(self.get_connection()),
+        )?)
+    }
 
-        let txos_allocations = self.get_rgb_allocations(
-            txos,
-            Some(colorings),
-            Some(batch_transfers),
-            Some(asset_transfers),
-        )?;
+    fn _get_allocations(
+        &self,
+        condition: Condition,
+    ) -> Result<Vec<(i32, LocalRgbAllocation)>, InternalError> {
+        Ok(block_on(
+            coloring::Entity::find()
+                .select_only()
+                .column_as(coloring::Column::TxoIdx, "txo_idx")
+                .column_as(txo::Column::Spent, "txo_spent")
+                .column_as(asset_transfer::Column::AssetId, "asset_id")
+                .column_as(coloring::Column::Amount, "amount")
+                .column_as(coloring::Column::ColoringType, "coloring_type")
+                .column_as(batch_transfer::Column::Status, "status")
+                .join(JoinType::InnerJoin, coloring::Relation::Txo.def())
+                .join(JoinType::InnerJoin, coloring::Relation::AssetTransfer.def())
+                .join(
+                    JoinType::InnerJoin,
+                    asset_transfer::Relation::BatchTransfer.def(),
+                )
+                .filter(condition)
+                .order_by_asc(coloring::Column::Idx)
+                .into_model::<DbAllocation>()
+                .all(self.get_connection()),
+        )?
+        .into_iter()
+        .map(|a| (a.txo_idx, a.into()))
+        .collect())
+    }
 
-        let mut allocations: Vec<LocalRgbAllocation> = vec![];
-        txos_allocations
-            .iter()
-            .for_each(|u| allocations.extend(u.rgb_allocations.clone()));
-        let ass_allocations: Vec<LocalRgbAllocation> = allocations
-            .into_iter()
-            .filter(|a| a.asset_id == Some(asset_id.clone()))
-            .collect();
+    fn _get_txos_allocations(
+        &self,
+        txo_idxs: &[i32],
+    ) -> Result<BTreeMap<i32, Vec<LocalRgbAllocation>>, InternalError> {
+        let mut txos_allocations: BTreeMap<i32, Vec<LocalRgbAllocation>> = BTreeMap::new();
+        for chunk in txo_idxs.chunks(MAX_QUERY_PARAMS) {
+            let condition =
+                Condition::all().add(coloring::Column::TxoIdx.is_in(chunk.iter().copied()));
+            for (txo_idx, allocation) in self._get_allocations(condition)? {
+                txos_allocations
+                    .entry(txo_idx)
+                    .or_default()
+                    .push(allocation);
+            }
+        }
+        Ok(txos_allocations)
+    }
+
+    #[cfg(feature = "daemon")]
+    pub(crate) fn get_transfer_statuses(&self) -> Result<Vec<TransferStatusInfo>, InternalError> {
+        Ok(block_on(
+            transfer::Entity::find()
+                .select_only()
+                .column_as(transfer::Column::Idx, "idx")
+                .column_as(asset_transfer::Column::AssetId, "asset_id")
+                .column_as(batch_transfer::Column::Txid, "txid")
+                .column_as(transfer::Column::RecipientId, "recipient_id")
+                .column_as(transfer::Column::Incoming, "incoming")
+                .column_as(batch_transfer::Column::Status, "status")
+                .join(JoinType::InnerJoin, transfer::Relation::AssetTransfer.def())
+                .join(
+                    JoinType::InnerJoin,
+                    asset_transfer::Relation::BatchTransfer.def(),
+                )
+                .filter(asset_transfer::Column::UserDriven.eq(true))
+                .order_by_asc(transfer::Column::Idx)
+                .into_model::<TransferStatusInfo>()
+                .all(self.get_connection()),
+        )?)
+    }
+
+    pub(crate) fn get_asset_balance(&self, asset_id: String) -> Result<Balance, Error> {
+        let ass_allocations: Vec<(i32, LocalRgbAllocation)> = self._get_allocations(
+            Condition::all().add(asset_transfer::Column::AssetId.eq(asset_id.clone())),
+        )?;
 
         let settled: u64 = ass_allocations
             .iter()
+            .map(|(_, a)| a)
             .filter(|a| a.settled())
             .map(|a| a.amount)
             .sum();
 
         let ass_pending_incoming: u64 = ass_allocations
             .iter()
+            .map(|(_, a)| a)
             .filter(|a| !a.txo_spent && a.incoming && a.status.pending())
             .map(|a| a.amount)
             .sum();
         let ass_pending_outgoing: u64 = ass_allocations
             .iter()
+            .map(|(_, a)| a)
             .filter(|a| !a.incoming && a.status.pending())
             .map(|a| a.amount)
             .sum();
@@ -581,22 +779,27 @@ impl RgbLibDatabase {
 
         let future = settled as i128 + ass_pending;
 
-        let unspendable: u64 = txos_allocations
+        // only TXOs holding settled allocations of the asset can be unspendable
+        let settled_txo_idxs: Vec<i32> = ass_allocations
+            .iter()
+            .filter(|(_, a)| a.settled())
+            .map(|(i, _)| *i)
+            .collect::<BTreeSet<i32>>()
             .into_iter()
-            .filter(|u| {
-                (!u.utxo.spent
-                    && u.rgb_allocations.iter().any(|a| {
-                        (!a.incoming && !a.status.failed()) || (a.incoming && a.status.pending())
-                    }))
-                    || (u.utxo.spent
-                        && u.rgb_allocations
-                            .iter()
-                            .any(|a| !a.incoming && a.status.waiting_confirmations()))
+            .collect();
+        let unspendable: u64 = self
+            ._get_txos_allocations(&settled_txo_idxs)?
+            .into_values()
+            .filter(|allocations| {
+                allocations.iter().any(|a| {
+                    (!a.txo_spent
+                        && ((!a.incoming && !a.status.failed())
+                            || (a.incoming && a.status.pending())))
+                        || (a.txo_spent && !a.incoming && a.status.waiting_confirmations())
+                })
             })
-            .collect::<Vec<LocalUnspent>>()
-            .iter()
-            .map(|u| {
-                u.rgb_allocations
+            .map(|allocations| {
+                allocations
                     .iter()
                     .filter(|a| a.asset_id == Some(asset_id.clone()) && a.settled())
                     .map(|a| a.amount)
@@ -784,81 +987,19 @@ impl RgbLibDatabase {
         })
     }
 
-    fn _get_utxo_allocations(
-        &self,
-        utxo: &DbTxo,
-        colorings: Vec<DbColoring>,
-        asset_transfers: Vec<DbAssetTransfer>,
-        batch_transfers: Vec<DbBatchTransfer>,
-    ) -> Result<Vec<LocalRgbAllocation>, Error> {
-        let utxo_colorings: Vec<&DbColoring> =
-            colorings.iter().filter(|c| c.txo_idx == utxo.idx).collect();
-
-        let mut allocations: Vec<LocalRgbAllocation> = vec![];
-        utxo_colorings.iter().for_each(|c| {
-            let asset_transfer: &DbAssetTransfer = asset_transfers
-                .iter()
-                .find(|t| t.idx == c.asset_transfer_idx)
-                .expect("coloring should be connected to an asset transfer");
-            let batch_transfer: &DbBatchTransfer = batch_transfers
-                .iter()
-                .find(|t| asset_transfer.batch_transfer_idx == t.idx)
-                .expect("asset transfer should be connected to a batch transfer");
-
-            let coloring_amount = c
-                .amount
-                .parse::<u64>()
-                .expect("DB should contain a valid u64 value");
-
-            allocations.push(LocalRgbAllocation {
-                asset_id: asset_transfer.asset_id.clone(),
-                amount: coloring_amount,
-                status: batch_transfer.status,
-                incoming: c.incoming(),
-                txo_spent: utxo.spent,
-            });
-        });
-
-        Ok(allocations)
-    }
-
     pub(crate) fn get_rgb_allocations(
         &self,
         utxos: Vec<DbTxo>,
-        colorings: Option<Vec<DbColoring>>,
-        batch_transfers: Option<Vec<DbBatchTransfer>>,
-        asset_transfers: Option<Vec<DbAssetTransfer>>,
     ) -> Result<Vec<LocalUnspent>, Error> {
-        let batch_transfers = if let Some(bt) = batch_transfers {
-            bt
-        } else {
-            self.iter_batch_transfers()?
-        };
-        let asset_transfers = if let Some(at) = asset_transfers {
-            at
-        } else {
-            self.iter_asset_transfers()?
-        };
-        let colorings = if let Some(cs) = colorings {
-            cs
-        } else {
-            self.iter_colorings()?
-        };
-
-        utxos
-            .iter()
-            .map(|t| {
-                Ok(LocalUnspent {
-                    utxo: t.clone(),
-                    rgb_allocations: self._get_utxo_allocations(
-                        t,
-                        colorings.clone(),
-                        asset_transfers.clone(),
-                        batch_transfers.clone(),
-                    )?,
-                })
+        let txo_idxs: Vec<i32> = utxos.iter().map(|t| t.idx).collect();
+        let mut txos_allocations = self._get_txos_allocations(&txo_idxs)?;
+        Ok(utxos
+            .into_iter()
+            .map(|t| LocalUnspent {
+                rgb_allocations: txos_allocations.remove(&t.idx).unwrap_or_default(),
+                utxo: t,
             })
-            .collect()
+            .collect())
     }
 }
 
@@ -138,6 +138,20 @@ pub enum Error {
         asset_id: String,
     },
 
+    /// The provided automatic backup configuration is invalid
+    #[error("Invalid automatic backup configuration: {details}")]
+    InvalidAutoBackupConfig {
+        /// Error details
+        details: String,
+    },
+
+    /// The provided backups don't form a valid chain or don't match their manifest
+    #[error("Invalid backup chain: {details}")]
+    InvalidBackupChain {
+        /// Error details
+        details: String,
+    },
+
     /// Keys derived from the provided data do not match
     #[error("Invalid bitcoin keys")]
     InvalidBitcoinKeys,
@@ -230,6 +244,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided network configuration is invalid
+    #[error("Invalid network config: {details}")]
+    InvalidNetworkConfig {
+        /// Error details
+        details: String,
+    },
+
     /// The provided asset precision is invalid
     #[error("Invalid precision: {details}")]
     InvalidPrecision {
@@ -258,6 +279,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided scrypt parameters are invalid
+    #[error("Invalid scrypt parameters: {details}")]
+    InvalidScryptParams {
+        /// Error details
+        details: String,
+    },
+
     /// The provided asset ticker is invalid
     #[error("Invalid ticker: {details}")]
     InvalidTicker {
@@ -265,6 +293,13 @@ pub enum Error {
         details: String,
     },
 
+    /// The provided wallet configuration is invalid
+    #[error("Invalid wallet config: {details}")]
+    InvalidWalletConfig {
+        /// Error details
+        details: String,
+    },
+
     /// Cannot issue an asset without knowing the amounts
     #[error("Issuance request with no provided amounts")]
     NoIssuanceAmounts,
@@ -281,6 +316,20 @@ pub enum Error {
     #[error("Output below the dust limit")]
     OutputBelowDustLimit,
 
+    /// The PSBT matching the provided signed one has been prepared too long ago
+    #[error("Prepared PSBT with TXID {txid} has expired")]
+    PreparedPsbtExpired {
+        /// Transaction ID
+        txid: String,
+    },
+
+    /// The provided signed PSBT doesn't match any PSBT prepared by this wallet
+    #[error("Signed PSBT with TXID {txid} doesn't match any prepared PSBT")]
+    PsbtMismatch {
+        /// Transaction ID
+        txid: String,
+    },
+
     /// Error contacting the RGB proxy
     #[error("Proxy error: {details}")]
     Proxy {
@@ -288,10 +337,31 @@ pub enum Error {
         details: String,
     },
 
+    /// The RGB proxy has refused the provided credentials (or their absence)
+    #[error("Proxy at {endpoint} refused the request as unauthorized")]
+    ProxyUnauthorized {
+        /// Proxy endpoint
+        endpoint: String,
+    },
+
     /// Provided recipient ID has already been used for another transfer
     #[error("Recipient ID already used")]
     RecipientIDAlreadyUsed,
 
+    /// The RGB runtime is locked by another wallet instance
+    #[error("RGB runtime is locked: {details}")]
+    RgbRuntimeLocked {
+        /// Error details
+        details: String,
+    },
+
+    /// The registered signer has failed to sign the PSBT
+    #[error("Signer error: {details}")]
+    Signer {
+        /// Error details
+        details: String,
+    },
+
     /// Trying to issue too many assets
     #[error("Trying to issue too many assets")]
     TooHighIssuanceAmounts,
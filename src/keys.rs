@@ -4,70 +4,175 @@
 
 use bdk::bitcoin::secp256k1::Secp256k1;
 use bdk::bitcoin::Network as BdkNetwork;
+use bdk::descriptor::Segwitv0;
 use bdk::keys::bip39::{Language, Mnemonic, WordCount};
-use bdk::keys::{DerivableKey, ExtendedKey, GeneratableKey};
+use bdk::keys::{DerivableKey, ExtendedKey, GeneratableKey, GeneratedKey};
 use serde::{Deserialize, Serialize};
 
 use crate::{BitcoinNetwork, Error};
 
+/// Number of words of a BIP39 mnemonic phrase
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MnemonicWordCount {
+    /// 12 words (128 bits of entropy)
+    #[default]
+    Words12,
+    /// 15 words (160 bits of entropy)
+    Words15,
+    /// 18 words (192 bits of entropy)
+    Words18,
+    /// 21 words (224 bits of entropy)
+    Words21,
+    /// 24 words (256 bits of entropy)
+    Words24,
+}
+
+impl From<MnemonicWordCount> for WordCount {
+    fn from(x: MnemonicWordCount) -> WordCount {
+        match x {
+            MnemonicWordCount::Words12 => WordCount::Words12,
+            MnemonicWordCount::Words15 => WordCount::Words15,
+            MnemonicWordCount::Words18 => WordCount::Words18,
+            MnemonicWordCount::Words21 => WordCount::Words21,
+            MnemonicWordCount::Words24 => WordCount::Words24,
+        }
+    }
+}
+
+/// Language of a BIP39 mnemonic phrase
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MnemonicLanguage {
+    /// English
+    #[default]
+    English,
+    /// Simplified Chinese
+    SimplifiedChinese,
+    /// Traditional Chinese
+    TraditionalChinese,
+    /// Czech
+    Czech,
+    /// French
+    French,
+    /// Italian
+    Italian,
+    /// Japanese
+    Japanese,
+    /// Korean
+    Korean,
+    /// Spanish
+    Spanish,
+}
+
+impl From<MnemonicLanguage> for Language {
+    fn from(x: MnemonicLanguage) -> Language {
+        match x {
+            MnemonicLanguage::English => Language::English,
+            MnemonicLanguage::SimplifiedChinese => Language::SimplifiedChinese,
+            MnemonicLanguage::TraditionalChinese => Language::TraditionalChinese,
+            MnemonicLanguage::Czech => Language::Czech,
+            MnemonicLanguage::French => Language::French,
+            MnemonicLanguage::Italian => Language::Italian,
+            MnemonicLanguage::Japanese => Language::Japanese,
+            MnemonicLanguage::Korean => Language::Korean,
+            MnemonicLanguage::Spanish => Language::Spanish,
+        }
+    }
+}
+
 /// A set of Bitcoin keys used by the RGB wallet
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Keys {
     /// Mnemonic phrase
     pub mnemonic: String,
-    /// xPub corresponding to the mnemonic phrase
+    /// Optional BIP39 passphrase
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// xPub corresponding to the mnemonic phrase and passphrase
     pub xpub: String,
     /// Fingerprint of the xPub
     pub xpub_fingerprint: String,
 }
 
-/// Generate a set of [`Keys`] for the given Bitcoin network
-pub fn generate_keys(bitcoin_network: BitcoinNetwork) -> Keys {
-    let bdk_network = BdkNetwork::from(bitcoin_network);
-    let mnemonic = Mnemonic::generate((WordCount::Words12, Language::English))
-        .expect("to be able to generate a new mnemonic");
-    let xkey: ExtendedKey = mnemonic
-        .clone()
+pub(crate) fn get_extended_key(mnemonic: Mnemonic, passphrase: Option<String>) -> ExtendedKey {
+    (mnemonic, passphrase)
         .into_extended_key()
-        .expect("a valid key should have been provided");
+        .expect("a valid key should have been provided")
+}
+
+fn _get_keys(
+    bitcoin_network: BitcoinNetwork,
+    mnemonic: Mnemonic,
+    passphrase: Option<String>,
+) -> Keys {
+    let bdk_network = BdkNetwork::from(bitcoin_network);
+    let xkey = get_extended_key(mnemonic.clone(), passphrase.clone());
     let xpub = &xkey.into_xpub(bdk_network, &Secp256k1::new());
     Keys {
         mnemonic: mnemonic.to_string(),
+        passphrase,
         xpub: xpub.clone().to_string(),
         xpub_fingerprint: xpub.fingerprint().to_string(),
     }
 }
 
-/// Recreate a set of [`Keys`] from a given mnemonic phrase
-pub fn restore_keys(bitcoin_network: BitcoinNetwork, mnemonic: String) -> Result<Keys, Error> {
-    let bdk_network = BdkNetwork::from(bitcoin_network);
-    let mnemonic = Mnemonic::parse_in(Language::English, mnemonic)?;
-    let xkey: ExtendedKey = mnemonic
-        .clone()
-        .into_extended_key()
-        .expect("a valid key should have been provided");
-    let xpub = &xkey.into_xpub(bdk_network, &Secp256k1::new());
-    Ok(Keys {
-        mnemonic: mnemonic.to_string(),
-        xpub: xpub.clone().to_string(),
-        xpub_fingerprint: xpub.fingerprint().to_string(),
-    })
+/// Generate a set of [`Keys`] for the given Bitcoin network
+///
+/// The mnemonic phrase has 12 English words and no passphrase is used. See
+/// [`generate_keys_with_options`] to customize the generated keys.
+pub fn generate_keys(bitcoin_network: BitcoinNetwork) -> Keys {
+    generate_keys_with_options(
+        bitcoin_network,
+        MnemonicWordCount::default(),
+        MnemonicLanguage::default(),
+        None,
+    )
+}
+
+/// Generate a set of [`Keys`] for the given Bitcoin network, with a mnemonic phrase of the
+/// requested word count and language, optionally protected by a BIP39 passphrase
+pub fn generate_keys_with_options(
+    bitcoin_network: BitcoinNetwork,
+    word_count: MnemonicWordCount,
+    language: MnemonicLanguage,
+    passphrase: Option<String>,
+) -> Keys {
+    let mnemonic: GeneratedKey<_, Segwitv0> =
+        Mnemonic::generate((WordCount::from(word_count), Language::from(language)))
+            .expect("to be able to generate a new mnemonic");
+    let mnemonic = mnemonic.into_key();
+    _get_keys(bitcoin_network, mnemonic, passphrase)
+}
+
+/// Recreate a set of [`Keys`] from a given mnemonic phrase and optional BIP39 passphrase
+///
+/// The mnemonic phrase language is detected automatically.
+pub fn restore_keys(
+    bitcoin_network: BitcoinNetwork,
+    mnemonic: String,
+    passphrase: Option<String>,
+) -> Result<Keys, Error> {
+    let mnemonic = Mnemonic::parse(mnemonic)?;
+    Ok(_get_keys(bitcoin_network, mnemonic, passphrase))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use bitcoin::bip32::ExtendedPubKey;
+    use amplify::s;
+    use bitcoin::bip32::{ExtendedPrivKey, ExtendedPubKey};
     use std::str::FromStr;
 
     #[test]
     fn generate_success() {
         let Keys {
             mnemonic,
+            passphrase,
             xpub,
             xpub_fingerprint,
         } = generate_keys(BitcoinNetwork::Regtest);
 
+        assert!(passphrase.is_none());
+
         assert!(Mnemonic::from_str(&mnemonic).is_ok());
         let pubkey = ExtendedPubKey::from_str(&xpub);
         assert!(pubkey.is_ok());
@@ -81,10 +186,59 @@ mod test {
             mnemonic,
             xpub,
             xpub_fingerprint,
+            ..
         } = generate_keys(network);
 
-        let keys = restore_keys(network, mnemonic).unwrap();
+        let keys = restore_keys(network, mnemonic, None).unwrap();
         assert_eq!(keys.xpub, xpub);
         assert_eq!(keys.xpub_fingerprint, xpub_fingerprint);
     }
+
+    #[test]
+    fn generate_with_options_success() {
+        let network = BitcoinNetwork::Regtest;
+        let passphrase = Some(s!("passphrase"));
+        let keys = generate_keys_with_options(
+            network,
+            MnemonicWordCount::Words24,
+            MnemonicLanguage::Japanese,
+            passphrase.clone(),
+        );
+        let mnemonic = Mnemonic::parse(&keys.mnemonic).unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+        assert_eq!(mnemonic.language(), Language::Japanese);
+        assert_eq!(keys.passphrase, passphrase);
+
+        let restored = restore_keys(network, keys.mnemonic.clone(), passphrase).unwrap();
+        assert_eq!(restored.xpub, keys.xpub);
+        assert_eq!(restored.xpub_fingerprint, keys.xpub_fingerprint);
+        let restored_no_passphrase = restore_keys(network, keys.mnemonic, None).unwrap();
+        assert_ne!(restored_no_passphrase.xpub, keys.xpub);
+    }
+
+    #[test]
+    fn restore_bip39_vectors() {
+        // test vectors from https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+        let keys = restore_keys(
+            BitcoinNetwork::Mainnet,
+            s!("legal winner thank year wave sausage worth useful legal winner thank yellow"),
+            Some(s!("TREZOR")),
+        )
+        .unwrap();
+        let xprv = ExtendedPrivKey::from_str("xprv9s21ZrQH143K2gA81bYFHqU68xz1cX2APaSq5tt6MFSLeXnCKV1RVUJt9FWNTbrrryem4ZckN8k4Ls1H6nwdvDTvnV7zEXs2HgPezuVccsq").unwrap();
+        let xpub = ExtendedPubKey::from_priv(&bitcoin::secp256k1::Secp256k1::new(), &xprv);
+        assert_eq!(keys.xpub, xpub.to_string());
+        assert_eq!(keys.xpub_fingerprint, xpub.fingerprint().to_string());
+
+        let keys = restore_keys(
+            BitcoinNetwork::Mainnet,
+            s!("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+            Some(s!("TREZOR")),
+        )
+        .unwrap();
+        let xprv = ExtendedPrivKey::from_str("xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF").unwrap();
+        let xpub = ExtendedPubKey::from_priv(&bitcoin::secp256k1::Secp256k1::new(), &xprv);
+        assert_eq!(keys.xpub, xpub.to_string());
+        assert_eq!(keys.xpub_fingerprint, xpub.fingerprint().to_string());
+    }
 }
@@ -39,6 +39,7 @@
 //! ### Create an RGB wallet
 //! ```
 //! use rgb_lib::wallet::{DatabaseType, Wallet, WalletData};
+//! use std::collections::HashMap;
 //! use rgb_lib::{generate_keys, BitcoinNetwork};
 //!
 //! fn main() -> Result<(), rgb_lib::Error> {
@@ -51,6 +52,11 @@
 //!         max_allocations_per_utxo: 5,
 //!         pubkey: keys.xpub,
 //!         mnemonic: Some(keys.mnemonic),
+//!         passphrase: None,
+//!         log_config: None,
+//!         proxy_auth: HashMap::new(),
+//!         network_config: None,
+//!         proxy_retry_config: None,
 //!     };
 //!     let wallet = Wallet::new(wallet_data)?;
 //!
@@ -62,18 +68,26 @@
 extern crate slog;
 
 pub(crate) mod api;
+#[cfg(feature = "daemon")]
+pub mod daemon;
 pub(crate) mod database;
 pub(crate) mod error;
 pub mod keys;
+#[cfg(any(test, feature = "proxy-server"))]
+pub mod proxy_server;
 pub mod utils;
 pub mod wallet;
 
-pub use crate::database::enums::{AssetSchema, TransferStatus, TransportType};
+pub use crate::database::enums::{AssetSchema, ColoringType, TransferStatus, TransportType};
 pub use crate::error::Error;
 pub use crate::keys::generate_keys;
+pub use crate::keys::generate_keys_with_options;
 pub use crate::keys::restore_keys;
-pub use crate::utils::BitcoinNetwork;
-pub use crate::wallet::backup::restore_backup;
-pub use crate::wallet::{TransactionType, TransferKind, Wallet};
+pub use crate::utils::{BitcoinNetwork, CustomLogger, LogConfig, LogFormat, LogLevel, LogOutput};
+pub use crate::wallet::backup::{
+    backup_info, restore_backup, restore_backup_chain, restore_from_reader, AutoBackupConfig,
+    BackupInfo, ScryptParams, WalletBackupInfo,
+};
+pub use crate::wallet::{TransactionType, TransferKind, Wallet, WalletManager};
 pub use bitcoin::ScriptBuf;
 pub use rgb_core::SecretSeal;
@@ -0,0 +1,688 @@
+//! RGB proxy server
+//!
+//! This module defines a reference JSON-RPC server implementing the RGB proxy protocol (version
+//! 0.2) spoken by the library, storing consignments, media and ACKs in a SQLite database.
+//!
+//! It is meant for local testing and small self-hosted deployments: requests are served one at a
+//! time by a single background thread.
+
+use base64::{engine::general_purpose, Engine as _};
+use futures::executor::block_on;
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, QueryResult,
+    Statement, Value as DbValue,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::error::{Error, InternalError};
+
+const PROTOCOL_VERSION: &str = "0.2";
+const JSON_RPC_PATH: &str = "/json-rpc";
+const MAX_BODY_SIZE: u64 = 64 * 1024 * 1024;
+
+const ERR_PARSE: i64 = -32700;
+const ERR_INVALID_REQUEST: i64 = -32600;
+const ERR_METHOD_NOT_FOUND: i64 = -32601;
+const ERR_INVALID_PARAMS: i64 = -32602;
+const ERR_CANNOT_CHANGE_UPLOADED_FILE: i64 = -101;
+const ERR_CANNOT_CHANGE_ACK: i64 = -102;
+const ERR_NOT_FOUND: i64 = -400;
+
+const CREATE_TABLES: [&str; 2] = [
+    "CREATE TABLE IF NOT EXISTS consignment (
+        recipient_id TEXT PRIMARY KEY NOT NULL,
+        txid TEXT NOT NULL,
+        vout INTEGER,
+        consignment BLOB NOT NULL,
+        ack BOOLEAN
+    )",
+    "CREATE TABLE IF NOT EXISTS media (
+        attachment_id TEXT PRIMARY KEY NOT NULL,
+        media BLOB NOT NULL
+    )",
+];
+
+/// Configuration of a [`ProxyServer`]
+#[derive(Clone, Debug)]
+pub struct ProxyServerConfig {
+    /// Address to listen on (e.g. `127.0.0.1:3000`, port 0 picks a free one)
+    pub listen_address: String,
+    /// Path of the SQLite database file, created if missing
+    pub db_path: PathBuf,
+    /// Optional bearer token clients need to provide
+    pub bearer_token: Option<String>,
+}
+
+/// A running RGB proxy server
+///
+/// The server is stopped when dropped.
+pub struct ProxyServer {
+    address: SocketAddr,
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ProxyServer {
+    /// Open the database and start serving requests in a background thread
+    pub fn start(config: ProxyServerConfig) -> Result<Self, Error> {
+        let connection_string = format!("sqlite://{}?mode=rwc", config.db_path.display());
+        let mut opt = ConnectOptions::new(connection_string);
+        opt.max_connections(1)
+            .min_connections(0)
+            .connect_timeout(Duration::from_secs(8));
+        let db = block_on(Database::connect(opt)).map_err(InternalError::from)?;
+        for statement in CREATE_TABLES {
+            block_on(db.execute(Statement::from_string(
+                DbBackend::Sqlite,
+                statement.to_string(),
+            )))
+            .map_err(InternalError::from)?;
+        }
+
+        let server = Server::http(&config.listen_address).map_err(|e| Error::IO {
+            details: e.to_string(),
+        })?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .expect("server should listen on an IP address");
+        let server = Arc::new(server);
+        let handler = RequestHandler {
+            db,
+            bearer_token: config.bearer_token,
+            started_at: Instant::now(),
+        };
+        let requests = server.clone();
+        let handle = thread::spawn(move || {
+            for request in requests.incoming_requests() {
+                handler.handle(request);
+            }
+        });
+
+        Ok(ProxyServer {
+            address,
+            server,
+            handle: Some(handle),
+        })
+    }
+
+    /// Return the address the server is listening on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Return the URL of the JSON-RPC endpoint, as used in transport endpoints
+    pub fn url(&self) -> String {
+        format!("http://{}{JSON_RPC_PATH}", self.address)
+    }
+
+    /// Block until the server stops
+    pub fn join(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Stop serving requests and wait for the background thread to exit
+    pub fn stop(mut self) {
+        self._stop();
+    }
+
+    fn _stop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ProxyServer {
+    fn drop(&mut self) {
+        self._stop();
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    method: String,
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: &str) -> Self {
+        RpcError {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    fn invalid_params(details: &str) -> Self {
+        RpcError::new(ERR_INVALID_PARAMS, &format!("invalid params: {details}"))
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(e: Error) -> Self {
+        RpcError::new(-32603, &e.to_string())
+    }
+}
+
+impl From<sea_orm::DbErr> for RpcError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        Error::from(InternalError::from(e)).into()
+    }
+}
+
+struct RequestHandler {
+    db: DatabaseConnection,
+    bearer_token: Option<String>,
+    started_at: Instant,
+}
+
+impl RequestHandler {
+    fn handle(&self, mut request: Request) {
+        let response = if *request.method() != Method::Post
+            || request.url().split('?').next() != Some(JSON_RPC_PATH)
+        {
+            Response::from_string("").with_status_code(404)
+        } else if !self._authorized(&request) {
+            Response::from_string("").with_status_code(401)
+        } else {
+            let content_type = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Content-Type"))
+                .map(|h| h.value.as_str().to_string())
+                .unwrap_or_default();
+            let mut body = vec![];
+            let read = request
+                .as_reader()
+                .take(MAX_BODY_SIZE + 1)
+                .read_to_end(&mut body);
+            if read.is_err() || body.len() as u64 > MAX_BODY_SIZE {
+                Response::from_string("").with_status_code(413)
+            } else {
+                let content = self._process(&content_type, &body).to_string();
+                Response::from_string(content).with_header(
+                    Header::from_bytes("Content-Type", "application/json").expect("valid header"),
+                )
+            }
+        };
+        let _ = request.respond(response);
+    }
+
+    fn _authorized(&self, request: &Request) -> bool {
+        let Some(token) = &self.bearer_token else {
+            return true;
+        };
+        let expected = format!("Bearer {token}");
+        request
+            .headers()
+            .iter()
+            .any(|h| h.field.equiv("Authorization") && h.value.as_str() == expected)
+    }
+
+    fn _process(&self, content_type: &str, body: &[u8]) -> Value {
+        let (rpc_request, file) = match _parse_request(content_type, body) {
+            Ok(parsed) => parsed,
+            Err(e) => return _response(Value::Null, Err(e)),
+        };
+        let result = match rpc_request.method.as_str() {
+            "server.info" => Ok(self._server_info()),
+            "consignment.get" => self._get_consignment(&rpc_request.params),
+            "consignment.post" => self._post_consignment(&rpc_request.params, file),
+            "media.get" => self._get_media(&rpc_request.params),
+            "media.post" => self._post_media(&rpc_request.params, file),
+            "ack.get" => self._get_ack(&rpc_request.params),
+            "ack.post" => self._post_ack(&rpc_request.params),
+            _ => Err(RpcError::new(ERR_METHOD_NOT_FOUND, "method not found")),
+        };
+        _response(rpc_request.id, result)
+    }
+
+    fn _execute(&self, sql: &str, values: Vec<DbValue>) -> Result<(), RpcError> {
+        block_on(self.db.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            sql,
+            values,
+        )))?;
+        Ok(())
+    }
+
+    fn _query_one(&self, sql: &str, values: Vec<DbValue>) -> Result<Option<QueryResult>, RpcError> {
+        Ok(block_on(self.db.query_one(
+            Statement::from_sql_and_values(DbBackend::Sqlite, sql, values),
+        ))?)
+    }
+
+    fn _server_info(&self) -> Value {
+        json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime": self.started_at.elapsed().as_secs(),
+        })
+    }
+
+    fn _get_consignment(&self, params: &Value) -> Result<Value, RpcError> {
+        let recipient_id = _str_param(params, "recipient_id")?;
+        let row = self
+            ._query_one(
+                "SELECT txid, vout, consignment FROM consignment WHERE recipient_id = ?",
+                vec![recipient_id.into()],
+            )?
+            .ok_or_else(|| RpcError::new(ERR_NOT_FOUND, "consignment not found"))?;
+        let txid: String = row.try_get("", "txid")?;
+        let vout: Option<i64> = row.try_get("", "vout")?;
+        let consignment: Vec<u8> = row.try_get("", "consignment")?;
+        Ok(json!({
+            "consignment": general_purpose::STANDARD.encode(consignment),
+            "txid": txid,
+            "vout": vout,
+        }))
+    }
+
+    fn _post_consignment(&self, params: &Value, file: Option<Vec<u8>>) -> Result<Value, RpcError> {
+        let recipient_id = _str_param(params, "recipient_id")?;
+        let txid = _str_param(params, "txid")?;
+        let vout = match params.get("vout") {
+            None | Some(Value::Null) => None,
+            Some(vout) => Some(
+                vout.as_u64()
+                    .filter(|v| *v <= u32::MAX as u64)
+                    .ok_or_else(|| RpcError::invalid_params("vout"))? as i64,
+            ),
+        };
+        let file = file.ok_or_else(|| RpcError::invalid_params("file"))?;
+        if let Some(row) = self._query_one(
+            "SELECT consignment FROM consignment WHERE recipient_id = ?",
+            vec![recipient_id.clone().into()],
+        )? {
+            let consignment: Vec<u8> = row.try_get("", "consignment")?;
+            if consignment != file {
+                return Err(RpcError::new(
+                    ERR_CANNOT_CHANGE_UPLOADED_FILE,
+                    "cannot change uploaded file",
+                ));
+            }
+            return Ok(Value::Bool(true));
+        }
+        self._execute(
+            "INSERT INTO consignment (recipient_id, txid, vout, consignment) VALUES (?, ?, ?, ?)",
+            vec![recipient_id.into(), txid.into(), vout.into(), file.into()],
+        )?;
+        Ok(Value::Bool(true))
+    }
+
+    fn _get_media(&self, params: &Value) -> Result<Value, RpcError> {
+        let attachment_id = _str_param(params, "attachment_id")?;
+        let row = self
+            ._query_one(
+                "SELECT media FROM media WHERE attachment_id = ?",
+                vec![attachment_id.into()],
+            )?
+            .ok_or_else(|| RpcError::new(ERR_NOT_FOUND, "media not found"))?;
+        let media: Vec<u8> = row.try_get("", "media")?;
+        Ok(Value::String(general_purpose::STANDARD.encode(media)))
+    }
+
+    fn _post_media(&self, params: &Value, file: Option<Vec<u8>>) -> Result<Value, RpcError> {
+        let attachment_id = _str_param(params, "attachment_id")?;
+        let file = file.ok_or_else(|| RpcError::invalid_params("file"))?;
+        if let Some(row) = self._query_one(
+            "SELECT media FROM media WHERE attachment_id = ?",
+            vec![attachment_id.clone().into()],
+        )? {
+            let media: Vec<u8> = row.try_get("", "media")?;
+            if media != file {
+                return Err(RpcError::new(
+                    ERR_CANNOT_CHANGE_UPLOADED_FILE,
+                    "cannot change uploaded file",
+                ));
+            }
+            return Ok(Value::Bool(true));
+        }
+        self._execute(
+            "INSERT INTO media (attachment_id, media) VALUES (?, ?)",
+            vec![attachment_id.into(), file.into()],
+        )?;
+        Ok(Value::Bool(true))
+    }
+
+    fn _get_ack(&self, params: &Value) -> Result<Value, RpcError> {
+        let recipient_id = _str_param(params, "recipient_id")?;
+        let row = self
+            ._query_one(
+                "SELECT ack FROM consignment WHERE recipient_id = ?",
+                vec![recipient_id.into()],
+            )?
+            .ok_or_else(|| RpcError::new(ERR_NOT_FOUND, "consignment not found"))?;
+        let ack: Option<bool> = row.try_get("", "ack")?;
+        Ok(ack.map_or(Value::Null, Value::Bool))
+    }
+
+    fn _post_ack(&self, params: &Value) -> Result<Value, RpcError> {
+        let recipient_id = _str_param(params, "recipient_id")?;
+        let ack = params
+            .get("ack")
+            .and_then(Value::as_bool)
+            .ok_or_else(|| RpcError::invalid_params("ack"))?;
+        let row = self
+            ._query_one(
+                "SELECT ack FROM consignment WHERE recipient_id = ?",
+                vec![recipient_id.clone().into()],
+            )?
+            .ok_or_else(|| RpcError::new(ERR_NOT_FOUND, "consignment not found"))?;
+        let prev_ack: Option<bool> = row.try_get("", "ack")?;
+        match prev_ack {
+            Some(prev_ack) if prev_ack == ack => {}
+            Some(_) => return Err(RpcError::new(ERR_CANNOT_CHANGE_ACK, "cannot change ACK")),
+            None => self._execute(
+                "UPDATE consignment SET ack = ? WHERE recipient_id = ?",
+                vec![ack.into(), recipient_id.into()],
+            )?,
+        }
+        Ok(Value::Bool(true))
+    }
+}
+
+fn _response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": e.code, "message": e.message},
+        }),
+    }
+}
+
+fn _str_param(params: &Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+        .ok_or_else(|| RpcError::invalid_params(name))
+}
+
+/// Parse a JSON or multipart/form-data request, returning it along with the optional uploaded file
+fn _parse_request(
+    content_type: &str,
+    body: &[u8],
+) -> Result<(RpcRequest, Option<Vec<u8>>), RpcError> {
+    let Some(boundary) = content_type
+        .split(';')
+        .map(str::trim)
+        .find_map(|p| p.strip_prefix("boundary="))
+    else {
+        let request: RpcRequest =
+            serde_json::from_slice(body).map_err(|_| RpcError::new(ERR_PARSE, "parse error"))?;
+        return Ok((request, None));
+    };
+    let mut fields = _parse_multipart(body, boundary.trim_matches('"'))
+        .ok_or_else(|| RpcError::new(ERR_PARSE, "parse error"))?;
+    let text_field = |name: &str| {
+        fields
+            .get(name)
+            .map(|v| String::from_utf8_lossy(v).to_string())
+    };
+    let method = text_field("method")
+        .ok_or_else(|| RpcError::new(ERR_INVALID_REQUEST, "invalid request"))?;
+    let id = text_field("id").map_or(Value::Null, Value::String);
+    let params = match text_field("params") {
+        Some(params) => {
+            serde_json::from_str(&params).map_err(|_| RpcError::new(ERR_PARSE, "parse error"))?
+        }
+        None => Value::Null,
+    };
+    let file = fields.remove("file");
+    Ok((RpcRequest { method, id, params }, file))
+}
+
+/// Split a multipart/form-data body into its named parts
+fn _parse_multipart(body: &[u8], boundary: &str) -> Option<HashMap<String, Vec<u8>>> {
+    let delimiter = format!("--{boundary}").into_bytes();
+    let mut fields = HashMap::new();
+    let mut rest = &body[_find(body, &delimiter)? + delimiter.len()..];
+    while !rest.starts_with(b"--") {
+        rest = rest.strip_prefix(b"\r\n")?;
+        let headers_end = _find(rest, b"\r\n\r\n")?;
+        let headers = String::from_utf8_lossy(&rest[..headers_end]).to_string();
+        rest = &rest[headers_end + 4..];
+        let part_end = _find(rest, &[b"\r\n", &delimiter[..]].concat())?;
+        let name = headers
+            .lines()
+            .find(|l| l.to_lowercase().starts_with("content-disposition:"))?
+            .split(';')
+            .map(str::trim)
+            .find_map(|p| p.strip_prefix("name="))?
+            .trim_matches('"')
+            .to_string();
+        fields.insert(name, rest[..part_end].to_vec());
+        rest = &rest[part_end + 2 + delimiter.len()..];
+    }
+    Some(fields)
+}
+
+fn _find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::{Proxy, ProxyClient};
+    use crate::wallet::ProxyAuth;
+    use amplify::s;
+    use reqwest::blocking::Client;
+    use tempfile::TempDir;
+
+    fn start_server(bearer_token: Option<String>) -> (ProxyServer, TempDir) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let server = ProxyServer::start(ProxyServerConfig {
+            listen_address: s!("127.0.0.1:0"),
+            db_path: tmp_dir.path().join("proxy.sqlite"),
+            bearer_token,
+        })
+        .unwrap();
+        (server, tmp_dir)
+    }
+
+    fn client() -> ProxyClient {
+        ProxyClient::new(Client::new(), None)
+    }
+
+    #[test]
+    fn success() {
+        let (server, tmp_dir) = start_server(None);
+        let url = server.url();
+
+        // server info
+        let info = client().get_info(&url).unwrap().result.unwrap();
+        assert_eq!(info.protocol_version, PROTOCOL_VERSION);
+
+        // consignment
+        let consignment_path = tmp_dir.path().join("consignment.rgb");
+        std::fs::write(&consignment_path, b"consignment").unwrap();
+        let res = client()
+            .post_consignment(
+                &url,
+                s!("recipient"),
+                consignment_path.clone(),
+                s!("txid"),
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(res.result, Some(true));
+        // posting the same consignment again is allowed
+        let res = client()
+            .post_consignment(&url, s!("recipient"), consignment_path, s!("txid"), Some(1))
+            .unwrap();
+        assert_eq!(res.result, Some(true));
+        let consignment = client()
+            .get_consignment(&url, s!("recipient"))
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(
+            general_purpose::STANDARD
+                .decode(consignment.consignment)
+                .unwrap(),
+            b"consignment"
+        );
+        assert_eq!(consignment.txid, "txid");
+        assert_eq!(consignment.vout, Some(1));
+
+        // ACK
+        let res = client().get_ack(&url, s!("recipient")).unwrap();
+        assert!(res.error.is_none());
+        assert_eq!(res.result, None);
+        let res = client().post_ack(&url, s!("recipient"), true).unwrap();
+        assert_eq!(res.result, Some(true));
+        let res = client().get_ack(&url, s!("recipient")).unwrap();
+        assert_eq!(res.result, Some(true));
+
+        // media
+        let media_path = tmp_dir.path().join("media");
+        std::fs::write(&media_path, b"media").unwrap();
+        let res = client()
+            .post_media(&url, s!("attachment"), media_path)
+            .unwrap();
+        assert_eq!(res.result, Some(true));
+        let media = client()
+            .get_media(&url, s!("attachment"))
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(general_purpose::STANDARD.decode(media).unwrap(), b"media");
+
+        // data survives a restart
+        let db_path = tmp_dir.path().join("proxy.sqlite");
+        server.stop();
+        let server = ProxyServer::start(ProxyServerConfig {
+            listen_address: s!("127.0.0.1:0"),
+            db_path,
+            bearer_token: None,
+        })
+        .unwrap();
+        let res = client().get_ack(&server.url(), s!("recipient")).unwrap();
+        assert_eq!(res.result, Some(true));
+
+        // authentication
+        let (server, _tmp_dir) = start_server(Some(s!("token")));
+        let client = ProxyClient::new(
+            Client::new(),
+            Some(ProxyAuth {
+                bearer_token: Some(s!("token")),
+                headers: HashMap::new(),
+            }),
+        );
+        assert!(client.get_info(&server.url()).unwrap().result.is_some());
+    }
+
+    #[test]
+    fn fail() {
+        let (server, tmp_dir) = start_server(Some(s!("token")));
+        let url = server.url();
+        let auth_client = || {
+            ProxyClient::new(
+                Client::new(),
+                Some(ProxyAuth {
+                    bearer_token: Some(s!("token")),
+                    headers: HashMap::new(),
+                }),
+            )
+        };
+
+        // missing credentials
+        let result = client().get_info(&url);
+        assert!(matches!(result, Err(Error::ProxyUnauthorized { endpoint: e }) if e == url));
+
+        // not found
+        let res = auth_client()
+            .get_consignment(&url, s!("recipient"))
+            .unwrap();
+        assert_eq!(res.error.unwrap().code, ERR_NOT_FOUND);
+        let res = auth_client().get_ack(&url, s!("recipient")).unwrap();
+        assert_eq!(res.error.unwrap().code, ERR_NOT_FOUND);
+        let res = auth_client().post_ack(&url, s!("recipient"), true).unwrap();
+        assert_eq!(res.error.unwrap().code, ERR_NOT_FOUND);
+        let res = auth_client().get_media(&url, s!("attachment")).unwrap();
+        assert_eq!(res.error.unwrap().code, ERR_NOT_FOUND);
+
+        // uploaded files cannot be changed
+        let consignment_path = tmp_dir.path().join("consignment.rgb");
+        std::fs::write(&consignment_path, b"consignment").unwrap();
+        auth_client()
+            .post_consignment(
+                &url,
+                s!("recipient"),
+                consignment_path.clone(),
+                s!("txid"),
+                None,
+            )
+            .unwrap();
+        std::fs::write(&consignment_path, b"other").unwrap();
+        let res = auth_client()
+            .post_consignment(&url, s!("recipient"), consignment_path, s!("txid"), None)
+            .unwrap();
+        assert_eq!(res.error.unwrap().code, ERR_CANNOT_CHANGE_UPLOADED_FILE);
+
+        // ACK cannot be changed
+        auth_client()
+            .post_ack(&url, s!("recipient"), false)
+            .unwrap();
+        let res = auth_client().post_ack(&url, s!("recipient"), true).unwrap();
+        assert_eq!(res.error.unwrap().code, ERR_CANNOT_CHANGE_ACK);
+
+        // malformed requests
+        let res: Value = Client::new()
+            .post(&url)
+            .bearer_auth("token")
+            .body("not json")
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(res["error"]["code"], ERR_PARSE);
+        let res: Value = Client::new()
+            .post(&url)
+            .bearer_auth("token")
+            .json(&json!({"jsonrpc": "2.0", "method": "unknown"}))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(res["error"]["code"], ERR_METHOD_NOT_FOUND);
+        let res: Value = Client::new()
+            .post(&url)
+            .bearer_auth("token")
+            .json(&json!({"jsonrpc": "2.0", "method": "ack.post", "params": {}}))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(res["error"]["code"], ERR_INVALID_PARAMS);
+
+        // stopped server
+        server.stop();
+        assert!(auth_client().get_info(&url).is_err());
+    }
+}
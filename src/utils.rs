@@ -12,7 +12,8 @@ use bdk::keys::DescriptorKey::Public;
 use bdk::keys::{DerivableKey, DescriptorKey};
 use bp::{Outpoint, Txid};
 use commit_verify::mpc::MerkleBlock;
-use rgb::Runtime;
+use fs2::FileExt;
+use rgb::{Runtime, RuntimeError, StockFs};
 use rgb_core::validation::Status;
 use rgb_core::{
     Anchor, ContractId, Genesis, GenesisSeal, GraphSeal, Opout, SchemaId, SubSchema,
@@ -20,18 +21,24 @@ use rgb_core::{
 };
 use rgbstd::containers::{Bindle, BuilderSeal, Contract, Transfer};
 use rgbstd::interface::{ContractIface, Iface, IfaceId, IfaceImpl, TransitionBuilder, TypedState};
-use rgbstd::persistence::{Inventory, Stash};
+use rgbstd::persistence::{Inventory, Stash, Stock};
 use rgbstd::resolvers::ResolveHeight;
 use rgbstd::Chain as RgbNetwork;
 use serde::{Deserialize, Serialize};
-use slog::{Drain, Logger};
+use slog::{
+    BorrowedKV, Drain, Key, Level, Logger, OwnedKVList, Record, RecordLocation, RecordStatic,
+    Serializer, KV,
+};
 use slog_term::{FullFormat, PlainDecorator};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
-use std::io;
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::{fs::OpenOptions, path::PathBuf};
+use std::sync::{Arc, Mutex, Once, PoisonError};
+use std::time::{Duration, Instant};
 use strict_encoding::TypeName;
 use time::OffsetDateTime;
 
@@ -42,9 +49,16 @@ const TIMESTAMP_FORMAT: &[time::format_description::FormatItem] = time::macros::
     "[year]-[month]-[day]T[hour repr:24]:[minute]:[second].[subsecond digits:3]+00"
 );
 
-const RGB_RUNTIME_LOCK_FILE: &str = "rgb_runtime.lock";
+pub(crate) const RGB_RUNTIME_LOCK_FILE: &str = "rgb_runtime.lock";
+const RGB_RUNTIME_LOCK_TIMEOUT: Duration = Duration::from_secs(120);
+const RGB_RUNTIME_LOCK_RETRY: Duration = Duration::from_millis(100);
 
 pub(crate) const LOG_FILE: &str = "log";
+const LOG_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const LOG_MAX_ROTATED_FILES: u32 = 5;
+const LOG_REDACTED: &str = "<redacted>";
+// shorter secrets are not replaced in the log messages, as they would redact unrelated text
+const LOG_MIN_REDACTED_LEN: usize = 8;
 
 /// Supported Bitcoin networks
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
@@ -150,6 +164,14 @@ pub(crate) fn get_txid(bitcoin_network: BitcoinNetwork) -> String {
     }
 }
 
+/// Whether the host of the provided URL (e.g. `ssl://<host>:50002` or `https://<host>/json-rpc`)
+/// is an onion address
+pub(crate) fn is_onion_url(url: &str) -> bool {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = without_scheme.split(['/', ':']).next().unwrap_or_default();
+    host.to_ascii_lowercase().ends_with(".onion")
+}
+
 pub(crate) fn _get_derivation_path(
     watch_only: bool,
     bitcoin_network: BitcoinNetwork,
@@ -207,37 +229,605 @@ fn log_timestamp(io: &mut dyn io::Write) -> io::Result<()> {
     )
 }
 
+/// Minimum level of the messages to log
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LogLevel {
+    /// Critical errors only
+    Critical,
+    /// Errors
+    Error,
+    /// Warnings
+    Warning,
+    /// Informational messages
+    Info,
+    /// Debug messages
+    #[default]
+    Debug,
+    /// Trace messages
+    Trace,
+}
+
+impl From<LogLevel> for Level {
+    fn from(x: LogLevel) -> Level {
+        match x {
+            LogLevel::Critical => Level::Critical,
+            LogLevel::Error => Level::Error,
+            LogLevel::Warning => Level::Warning,
+            LogLevel::Info => Level::Info,
+            LogLevel::Debug => Level::Debug,
+            LogLevel::Trace => Level::Trace,
+        }
+    }
+}
+
+/// Destination of the log
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LogOutput {
+    /// The `log` file in the wallet directory
+    #[default]
+    File,
+    /// The standard error
+    Stderr,
+}
+
+/// Format of the log messages
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LogFormat {
+    /// Plain text, one message per line
+    #[default]
+    Plain,
+    /// One JSON object per line
+    Json,
+}
+
+/// Logging configuration of a wallet
+///
+/// The log file is never truncated: new messages are appended and, when `max_file_size` is set,
+/// the file is rotated once it exceeds that size, keeping the `max_rotated_files` most recent
+/// files (`log.1` being the most recent).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Minimum level of the messages to log
+    pub level: LogLevel,
+    /// Destination of the log
+    pub output: LogOutput,
+    /// Format of the log messages
+    pub format: LogFormat,
+    /// Size in bytes after which the log file is rotated, if any
+    pub max_file_size: Option<u64>,
+    /// Number of rotated log files to keep
+    pub max_rotated_files: u32,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: LogLevel::default(),
+            output: LogOutput::default(),
+            format: LogFormat::default(),
+            max_file_size: Some(LOG_MAX_FILE_SIZE),
+            max_rotated_files: LOG_MAX_ROTATED_FILES,
+        }
+    }
+}
+
+/// A logger provided by the caller, receiving the wallet log messages in place of the
+/// configured [`LogOutput`]
+///
+/// The level of [`LogConfig`] still applies, as does the redaction of secrets.
+#[derive(Clone)]
+pub enum CustomLogger {
+    /// A `slog` logger
+    Slog(Logger),
+    /// A `tracing` dispatcher wrapping the caller's subscriber
+    #[cfg(feature = "tracing")]
+    Tracing(tracing::Dispatch),
+}
+
+/// Whether the provided file name is the one of a wallet log file, including rotated ones
+pub(crate) fn is_log_file(file_name: &str) -> bool {
+    file_name == LOG_FILE
+        || file_name
+            .strip_prefix(LOG_FILE)
+            .and_then(|s| s.strip_prefix('.'))
+            .map_or(false, |n| n.parse::<u32>().is_ok())
+}
+
+/// A log file that is rotated when it exceeds a maximum size.
+///
+/// The size is only checked on flush, which the formatters call after each record, so a record
+/// is never split between two files.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: Option<u64>,
+    max_rotated_files: u32,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_size: Option<u64>, max_rotated_files: u32) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path,
+            file,
+            size,
+            max_size,
+            max_rotated_files,
+        })
+    }
+
+    fn rotated_path(&self, num: u32) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(format!(".{num}"));
+        self.path.with_file_name(file_name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_rotated_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let oldest = self.rotated_path(self.max_rotated_files);
+            if oldest.exists() {
+                fs::remove_file(oldest)?;
+            }
+            for num in (1..self.max_rotated_files).rev() {
+                let rotated = self.rotated_path(num);
+                if rotated.exists() {
+                    fs::rename(rotated, self.rotated_path(num + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if matches!(self.max_size, Some(max_size) if self.size >= max_size) {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+}
+
+fn _redact(secrets: &[String], text: String) -> String {
+    if !secrets.iter().any(|s| text.contains(s)) {
+        return text;
+    }
+    secrets
+        .iter()
+        .fold(text, |text, s| text.replace(s, LOG_REDACTED))
+}
+
+/// Replaces the given secrets in the log messages and key-value pairs before passing them on to
+/// the inner drain.
+///
+/// This is only a fallback for secrets that end up in a message by mistake, types holding secrets
+/// redact them in their `Debug` implementation.
+struct RedactingDrain<D> {
+    drain: D,
+    secrets: Vec<String>,
+}
+
+impl<D: Drain> Drain for RedactingDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        if self.secrets.is_empty() {
+            return self.drain.log(record, values);
+        }
+        let redacted = _redact(&self.secrets, record.msg().to_string());
+        let kv = RedactingKV {
+            kv: record.kv(),
+            secrets: &self.secrets,
+        };
+        let location = RecordLocation {
+            file: record.file(),
+            line: record.line(),
+            column: record.column(),
+            function: record.function(),
+            module: record.module(),
+        };
+        let record_static = RecordStatic {
+            location: &location,
+            tag: record.tag(),
+            level: record.level(),
+        };
+        self.drain.log(
+            &Record::new(&record_static, &format_args!("{redacted}"), BorrowedKV(&kv)),
+            values,
+        )
+    }
+}
+
+/// Key-value pairs of a log record with the given secrets redacted from their values.
+struct RedactingKV<'a> {
+    kv: BorrowedKV<'a>,
+    secrets: &'a [String],
+}
+
+impl KV for RedactingKV<'_> {
+    fn serialize(&self, record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        self.kv.serialize(
+            record,
+            &mut RedactingSerializer {
+                serializer,
+                secrets: self.secrets,
+            },
+        )
+    }
+}
+
+struct RedactingSerializer<'a> {
+    serializer: &'a mut dyn Serializer,
+    secrets: &'a [String],
+}
+
+macro_rules! forward_emit {
+    ($($emit:ident: $ty:ty),*) => {
+        $(fn $emit(&mut self, key: Key, val: $ty) -> slog::Result {
+            self.serializer.$emit(key, val)
+        })*
+    };
+}
+
+impl Serializer for RedactingSerializer<'_> {
+    forward_emit!(
+        emit_usize: usize, emit_isize: isize, emit_bool: bool, emit_u8: u8, emit_i8: i8,
+        emit_u16: u16, emit_i16: i16, emit_u32: u32, emit_i32: i32, emit_f32: f32,
+        emit_u64: u64, emit_i64: i64, emit_f64: f64
+    );
+
+    fn emit_unit(&mut self, key: Key) -> slog::Result {
+        self.serializer.emit_unit(key)
+    }
+
+    fn emit_none(&mut self, key: Key) -> slog::Result {
+        self.serializer.emit_none(key)
+    }
+
+    fn emit_str(&mut self, key: Key, val: &str) -> slog::Result {
+        let redacted = _redact(self.secrets, val.to_string());
+        self.serializer.emit_str(key, &redacted)
+    }
+
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> slog::Result {
+        let redacted = _redact(self.secrets, val.to_string());
+        self.serializer
+            .emit_arguments(key, &format_args!("{redacted}"))
+    }
+}
+
+/// Forwards the log messages to a `tracing` subscriber.
+#[cfg(feature = "tracing")]
+struct TracingDrain {
+    // the subscriber is only used to emit events, a panic can't leave it in a broken state
+    dispatch: panic::AssertUnwindSafe<tracing::Dispatch>,
+}
+
+#[cfg(feature = "tracing")]
+impl Drain for TracingDrain {
+    type Ok = ();
+    type Err = slog::Never;
+
+    fn log(&self, record: &Record, _values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let module = record.module();
+        let msg = record.msg();
+        tracing::dispatcher::with_default(&self.dispatch.0, || match record.level() {
+            Level::Critical | Level::Error => tracing::error!(module, "{}", msg),
+            Level::Warning => tracing::warn!(module, "{}", msg),
+            Level::Info => tracing::info!(module, "{}", msg),
+            Level::Debug => tracing::debug!(module, "{}", msg),
+            Level::Trace => tracing::trace!(module, "{}", msg),
+        });
+        Ok(())
+    }
+}
+
+fn _build_drain<W: Write + Send + 'static>(io: W, format: LogFormat) -> slog_async::Async {
+    match format {
+        LogFormat::Plain => {
+            let drain = FullFormat::new(PlainDecorator::new(io))
+                .use_custom_timestamp(log_timestamp)
+                .use_file_location()
+                .build()
+                .fuse();
+            slog_async::Async::new(drain).build()
+        }
+        LogFormat::Json => {
+            let drain = slog_json::Json::new(io)
+                .add_default_keys()
+                .set_flush(true)
+                .build()
+                .fuse();
+            slog_async::Async::new(drain).build()
+        }
+    }
+}
+
 pub(crate) fn setup_logger(log_path: PathBuf, log_name: Option<&str>) -> Result<Logger, Error> {
-    let log_file = log_name.unwrap_or(LOG_FILE);
-    let log_filepath = log_path.join(log_file);
-    let file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(log_filepath)?;
+    setup_logger_with_config(log_path, log_name, &LogConfig::default(), None, vec![])
+}
+
+/// Set up a logger following the provided [`LogConfig`], or forwarding to the provided
+/// [`CustomLogger`], that redacts the provided secrets from every message.
+///
+/// Secrets shorter than 8 characters are not redacted.
+pub(crate) fn setup_logger_with_config(
+    log_path: PathBuf,
+    log_name: Option<&str>,
+    config: &LogConfig,
+    custom_logger: Option<CustomLogger>,
+    secrets: Vec<String>,
+) -> Result<Logger, Error> {
+    let secrets: Vec<String> = secrets
+        .into_iter()
+        .filter(|s| s.chars().count() >= LOG_MIN_REDACTED_LEN)
+        .collect();
+    let level = Level::from(config.level);
+    let logger = match custom_logger {
+        Some(CustomLogger::Slog(logger)) => {
+            let drain = logger.filter_level(level).ignore_res();
+            Logger::root(RedactingDrain { drain, secrets }, o!())
+        }
+        #[cfg(feature = "tracing")]
+        Some(CustomLogger::Tracing(dispatch)) => {
+            let drain = TracingDrain {
+                dispatch: panic::AssertUnwindSafe(dispatch),
+            }
+            .filter_level(level)
+            .ignore_res();
+            Logger::root(RedactingDrain { drain, secrets }, o!())
+        }
+        None => {
+            let drain = match config.output {
+                LogOutput::File => {
+                    let log_filepath = log_path.join(log_name.unwrap_or(LOG_FILE));
+                    let file = RotatingFile::open(
+                        log_filepath,
+                        config.max_file_size,
+                        config.max_rotated_files,
+                    )?;
+                    _build_drain(file, config.format)
+                }
+                LogOutput::Stderr => _build_drain(io::stderr(), config.format),
+            };
+            let drain = drain.filter_level(level).ignore_res();
+            Logger::root(RedactingDrain { drain, secrets }, o!())
+        }
+    };
+    Ok(logger)
+}
 
-    let decorator = PlainDecorator::new(file);
-    let drain = FullFormat::new(decorator)
-        .use_custom_timestamp(log_timestamp)
-        .use_file_location();
-    let drain = slog_async::Async::new(drain.build().fuse()).build().fuse();
+static PANIC_HOOK: Once = Once::new();
+static PANIC_LOGGERS: Mutex<BTreeMap<PathBuf, Logger>> = Mutex::new(BTreeMap::new());
 
-    Ok(Logger::root(drain, o!()))
+/// Log panics to the given logger until [`unregister_panic_logger`] is called with the same path.
+///
+/// The panic hook is installed only once per process and chains the previously installed one.
+pub(crate) fn register_panic_logger(path: PathBuf, logger: Logger) {
+    PANIC_HOOK.call_once(|| {
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // a panic while the registry is locked must not deadlock the hook
+            if let Ok(loggers) = PANIC_LOGGERS.try_lock() {
+                for logger in loggers.values() {
+                    error!(logger, "PANIC: {:?}", info);
+                }
+            }
+            prev_hook(info);
+        }));
+    });
+    PANIC_LOGGERS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(path, logger);
+}
+
+pub(crate) fn unregister_panic_logger(path: &Path) {
+    PANIC_LOGGERS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(path);
 }
 
 pub(crate) fn now() -> OffsetDateTime {
     OffsetDateTime::now_utc()
 }
 
-/// Wrapper for the RGB runtime. Needed to handle its lockfile.
+/// Exclusive OS advisory lock on the RGB runtime of a wallet directory.
+///
+/// The OS releases the lock when the holding process exits, so a lock file left behind by a
+/// crashed process is stale and gets reused instead of blocking the wallet. The file contents
+/// record the runtime generation, bumped each time the runtime is persisted, and the PID of the
+/// last holder.
+pub(crate) struct RgbRuntimeLock {
+    file: File,
+    generation: u64,
+}
+
+impl RgbRuntimeLock {
+    pub(crate) fn acquire(wallet_dir: &Path, timeout: Duration) -> Result<Self, Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(wallet_dir.join(RGB_RUNTIME_LOCK_FILE))?;
+        let start = Instant::now();
+        loop {
+            match FileExt::try_lock_exclusive(&file) {
+                Ok(()) => break,
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                    if start.elapsed() >= timeout {
+                        let (_, holder) = Self::_read(&mut file)?;
+                        return Err(Error::RgbRuntimeLocked {
+                            details: format!(
+                                "lock held by process {} for more than {}s",
+                                holder.map_or(s!("unknown"), |p| p.to_string()),
+                                timeout.as_secs()
+                            ),
+                        });
+                    }
+                    std::thread::sleep(RGB_RUNTIME_LOCK_RETRY);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let (generation, _) = Self::_read(&mut file)?;
+        let mut lock = Self { file, generation };
+        lock._write()?;
+        Ok(lock)
+    }
+
+    fn _read(file: &mut File) -> Result<(u64, Option<u32>), Error> {
+        let mut content = String::new();
+        file.rewind()?;
+        file.read_to_string(&mut content)?;
+        // a legacy or truncated lock file counts as generation 0
+        let mut fields = content.split_whitespace().map(|f| f.parse().ok());
+        let generation = fields.next().flatten().unwrap_or(0);
+        let holder = fields.next().flatten().map(|p: u64| p as u32);
+        Ok((generation, holder))
+    }
+
+    fn _write(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.rewind()?;
+        write!(self.file, "{} {}", self.generation, std::process::id())?;
+        self.file.sync_data()
+    }
+
+    fn bump(&mut self) -> io::Result<()> {
+        self.generation += 1;
+        self._write()
+    }
+}
+
+impl Drop for RgbRuntimeLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// RGB runtime kept loaded between operations on the same wallet.
+pub(crate) struct RgbRuntimeCache {
+    wallet_dir: PathBuf,
+    runtime: Option<Runtime>,
+    // generation of the persisted runtime matching the cached one, if any
+    generation: Option<u64>,
+}
+
+impl RgbRuntimeCache {
+    pub(crate) fn new(wallet_dir: PathBuf) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self {
+            wallet_dir,
+            runtime: None,
+            generation: None,
+        }))
+    }
+}
+
+impl Drop for RgbRuntimeCache {
+    fn drop(&mut self) {
+        // a `Runtime` stores its stock when dropped, so it's dropped holding the runtime lock and
+        // once its stock matches the persisted one, which is then rewritten unchanged
+        if let Some(mut runtime) = self.runtime.take() {
+            match RgbRuntimeLock::acquire(&self.wallet_dir, RGB_RUNTIME_LOCK_TIMEOUT) {
+                Ok(lock)
+                    if self.generation == Some(lock.generation)
+                        || _reload_stock(&mut runtime).is_ok() =>
+                {
+                    drop(runtime);
+                }
+                _ => {
+                    // the stock can't be written safely, so as a last resort its memory is freed
+                    // and only the empty runtime is leaked
+                    drop(std::mem::take(&mut *runtime));
+                    std::mem::forget(runtime);
+                }
+            }
+        }
+    }
+}
+
+/// Atomically persist the stock of a runtime, writing it to a temporary file that then replaces
+/// the current one, so a crash never leaves a partially written stock behind.
+fn _persist_runtime(runtime: &Runtime) -> Result<(), InternalError> {
+    let stock_path = runtime.stock_path();
+    let tmp_path = stock_path.with_extension("dat.tmp");
+    runtime.store(&tmp_path).map_err(RuntimeError::from)?;
+    File::open(&tmp_path)
+        .and_then(|f| f.sync_all())
+        .and_then(|_| fs::rename(&tmp_path, stock_path))
+        .map_err(RuntimeError::from)?;
+    Ok(())
+}
+
+/// Replace the stock of a runtime with the persisted one.
+fn _reload_stock(runtime: &mut Runtime) -> Result<(), InternalError> {
+    let stock = Stock::load(runtime.stock_path()).map_err(RuntimeError::from)?;
+    **runtime = stock;
+    Ok(())
+}
+
+/// Wrapper for the RGB runtime. Holds the runtime lock until dropped.
+///
+/// Changes need to be saved with `persist`, as the ones left unpersisted are discarded when the
+/// runtime is dropped.
 pub struct RgbRuntime {
-    /// The RGB runtime
-    pub runtime: Runtime,
-    /// The wallet directory containing the lockfile for the runtime
-    pub wallet_dir: PathBuf,
+    runtime: Option<Runtime>,
+    // dropped before the cache, which may need to lock the runtime itself
+    lock: RgbRuntimeLock,
+    cache: Arc<Mutex<RgbRuntimeCache>>,
+    changed: bool,
 }
 
 impl RgbRuntime {
+    /// Atomically persist the changes made to the runtime, if any, bumping its generation.
+    pub(crate) fn persist(&mut self) -> Result<(), InternalError> {
+        if !self.changed {
+            return Ok(());
+        }
+        // bumped first, so a failed persist only makes other processes reload the same stock
+        self.lock.bump().map_err(RuntimeError::from)?;
+        _persist_runtime(self.runtime())?;
+        self.changed = false;
+        Ok(())
+    }
+
+    fn runtime(&self) -> &Runtime {
+        self.runtime.as_ref().expect("runtime should be loaded")
+    }
+
+    // some stock reads need mutable access, without changing anything
+    fn runtime_mut(&mut self) -> &mut Runtime {
+        self.runtime.as_mut().expect("runtime should be loaded")
+    }
+
+    // any other mutable access may change the stock, so it marks the runtime as changed
+    fn changed_runtime(&mut self) -> &mut Runtime {
+        self.changed = true;
+        self.runtime.as_mut().expect("runtime should be loaded")
+    }
+
     pub(crate) fn accept_transfer<R: ResolveHeight>(
         &mut self,
         transfer: Transfer,
@@ -247,7 +837,7 @@ impl RgbRuntime {
     where
         R::Error: 'static,
     {
-        self.runtime
+        self.changed_runtime()
             .accept_transfer(transfer, resolver, force)
             .map_err(InternalError::from)
     }
@@ -257,20 +847,20 @@ impl RgbRuntime {
         contract_id: ContractId,
         iface: impl Into<TypeName>,
     ) -> Result<TransitionBuilder, InternalError> {
-        self.runtime
+        self.changed_runtime()
             .blank_builder(contract_id, iface)
             .map_err(InternalError::from)
     }
 
     pub(crate) fn chain(&self) -> RgbNetwork {
-        self.runtime.chain()
+        self.runtime().chain()
     }
 
     pub(crate) fn consume_anchor(
         &mut self,
         anchor: Anchor<MerkleBlock>,
     ) -> Result<(), InternalError> {
-        self.runtime
+        self.changed_runtime()
             .consume_anchor(anchor)
             .map_err(InternalError::from)
     }
@@ -281,13 +871,13 @@ impl RgbRuntime {
         bundle: TransitionBundle,
         witness_txid: Txid,
     ) -> Result<(), InternalError> {
-        self.runtime
+        self.changed_runtime()
             .consume_bundle(contract_id, bundle, witness_txid)
             .map_err(InternalError::from)
     }
 
     pub(crate) fn contract_ids(&self) -> Result<BTreeSet<ContractId>, InternalError> {
-        self.runtime.contract_ids().map_err(InternalError::from)
+        self.runtime().contract_ids().map_err(InternalError::from)
     }
 
     pub(crate) fn contract_iface(
@@ -295,7 +885,7 @@ impl RgbRuntime {
         contract_id: ContractId,
         iface_id: IfaceId,
     ) -> Result<ContractIface, InternalError> {
-        self.runtime
+        self.runtime_mut()
             .contract_iface(contract_id, iface_id)
             .map_err(InternalError::from)
     }
@@ -304,19 +894,19 @@ impl RgbRuntime {
         &mut self,
         outpoints: impl IntoIterator<Item = impl Into<Outpoint>>,
     ) -> Result<BTreeSet<ContractId>, InternalError> {
-        self.runtime
+        self.runtime_mut()
             .contracts_by_outpoints(outpoints)
             .map_err(InternalError::from)
     }
 
     pub(crate) fn genesis(&self, contract_id: ContractId) -> Result<&Genesis, InternalError> {
-        self.runtime
+        self.runtime()
             .genesis(contract_id)
             .map_err(InternalError::from)
     }
 
     pub(crate) fn iface_by_name(&self, name: &TypeName) -> Result<&Iface, InternalError> {
-        self.runtime
+        self.runtime()
             .iface_by_name(name)
             .map_err(InternalError::from)
     }
@@ -329,7 +919,7 @@ impl RgbRuntime {
     where
         R::Error: 'static,
     {
-        self.runtime
+        self.changed_runtime()
             .import_contract(contract, resolver)
             .map_err(InternalError::from)
     }
@@ -338,7 +928,7 @@ impl RgbRuntime {
         &mut self,
         iface: impl Into<Bindle<Iface>>,
     ) -> Result<Status, InternalError> {
-        self.runtime
+        self.changed_runtime()
             .import_iface(iface)
             .map_err(InternalError::from)
     }
@@ -347,7 +937,7 @@ impl RgbRuntime {
         &mut self,
         iimpl: impl Into<Bindle<IfaceImpl>>,
     ) -> Result<Status, InternalError> {
-        self.runtime
+        self.changed_runtime()
             .import_iface_impl(iimpl)
             .map_err(InternalError::from)
     }
@@ -356,13 +946,13 @@ impl RgbRuntime {
         &mut self,
         schema: impl Into<Bindle<SubSchema>>,
     ) -> Result<Status, InternalError> {
-        self.runtime
+        self.changed_runtime()
             .import_schema(schema)
             .map_err(InternalError::from)
     }
 
     pub(crate) fn schema_ids(&self) -> Result<BTreeSet<SchemaId>, InternalError> {
-        self.runtime.schema_ids().map_err(InternalError::from)
+        self.runtime().schema_ids().map_err(InternalError::from)
     }
 
     pub(crate) fn state_for_outpoints(
@@ -370,13 +960,13 @@ impl RgbRuntime {
         contract_id: ContractId,
         outpoints: impl IntoIterator<Item = impl Into<Outpoint>>,
     ) -> Result<BTreeMap<Opout, TypedState>, InternalError> {
-        self.runtime
+        self.runtime_mut()
             .state_for_outpoints(contract_id, outpoints)
             .map_err(InternalError::from)
     }
 
     pub(crate) fn store_seal_secret(&mut self, seal: GraphSeal) -> Result<(), InternalError> {
-        self.runtime
+        self.changed_runtime()
             .store_seal_secret(seal)
             .map_err(InternalError::from)
     }
@@ -386,7 +976,7 @@ impl RgbRuntime {
         contract_id: ContractId,
         seals: impl IntoIterator<Item = impl Into<BuilderSeal<GenesisSeal>>>,
     ) -> Result<Bindle<Transfer>, InternalError> {
-        self.runtime
+        self.changed_runtime()
             .transfer(contract_id, seals)
             .map_err(InternalError::from)
     }
@@ -397,7 +987,7 @@ impl RgbRuntime {
         iface: impl Into<TypeName>,
         transition_name: Option<impl Into<TypeName>>,
     ) -> Result<TransitionBuilder, InternalError> {
-        self.runtime
+        self.changed_runtime()
             .transition_builder(contract_id, iface, transition_name)
             .map_err(InternalError::from)
     }
@@ -405,35 +995,63 @@ impl RgbRuntime {
 
 impl Drop for RgbRuntime {
     fn drop(&mut self) {
-        std::fs::remove_file(self.wallet_dir.join(RGB_RUNTIME_LOCK_FILE))
-            .expect("should be able to drop lockfile")
+        let runtime = self.runtime.take().expect("runtime should be loaded");
+        let mut cache = self
+            .cache
+            .lock()
+            .expect("cache mutex should not be poisoned");
+        cache.runtime = Some(runtime);
+        // a runtime with unpersisted changes doesn't match any persisted one
+        cache.generation = (!self.changed).then_some(self.lock.generation);
     }
 }
 
-fn _write_rgb_runtime_lockfile(wallet_dir: &Path) {
-    let lock_file_path = wallet_dir.join(RGB_RUNTIME_LOCK_FILE);
-    loop {
-        match OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(lock_file_path.clone())
-        {
-            Ok(_) => break,
-            Err(_) => std::thread::sleep(std::time::Duration::from_millis(400)),
+/// Lock the RGB runtime of the given wallet directory and return it, reusing the runtime cached
+/// in `cache` if no other process has changed it since it was last persisted
+pub(crate) fn load_cached_rgb_runtime(
+    cache: Arc<Mutex<RgbRuntimeCache>>,
+    bitcoin_network: BitcoinNetwork,
+) -> Result<RgbRuntime, Error> {
+    let wallet_dir = cache
+        .lock()
+        .expect("cache mutex should not be poisoned")
+        .wallet_dir
+        .clone();
+    let lock = RgbRuntimeLock::acquire(&wallet_dir, RGB_RUNTIME_LOCK_TIMEOUT)?;
+    let cached = {
+        let mut cache = cache.lock().expect("cache mutex should not be poisoned");
+        match cache.runtime.take() {
+            Some(runtime) if cache.generation == Some(lock.generation) => Some(runtime),
+            Some(mut runtime) => {
+                // changed by another process or discarding unpersisted changes: the stock is
+                // reloaded in place, as dropping a stale runtime would overwrite the persisted one
+                if let Err(e) = _reload_stock(&mut runtime) {
+                    cache.runtime = Some(runtime);
+                    cache.generation = None;
+                    return Err(e.into());
+                }
+                Some(runtime)
+            }
+            None => None,
         }
-    }
+    };
+    let runtime = match cached {
+        Some(runtime) => runtime,
+        None => Runtime::load(wallet_dir, RgbNetwork::from(bitcoin_network))
+            .map_err(InternalError::from)?,
+    };
+    Ok(RgbRuntime {
+        runtime: Some(runtime),
+        lock,
+        cache,
+        changed: false,
+    })
 }
 
-/// Write the lock file for write access to the RGB runtime and load the runtime
+/// Lock the RGB runtime of the given wallet directory and load it
 pub fn load_rgb_runtime(
     wallet_dir: PathBuf,
     bitcoin_network: BitcoinNetwork,
 ) -> Result<RgbRuntime, Error> {
-    _write_rgb_runtime_lockfile(&wallet_dir);
-    let runtime = Runtime::load(wallet_dir.clone(), RgbNetwork::from(bitcoin_network))
-        .map_err(InternalError::from)?;
-    Ok(RgbRuntime {
-        runtime,
-        wallet_dir,
-    })
+    load_cached_rgb_runtime(RgbRuntimeCache::new(wallet_dir), bitcoin_network)
 }
@@ -1,35 +1,233 @@
-use chacha20poly1305::aead::{generic_array::GenericArray, stream};
+use amplify::s;
+use bitcoin::hashes::{sha256, Hash as Sha256Hash, HashEngine};
+use chacha20poly1305::aead::{generic_array::GenericArray, stream, Payload};
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use rand::{distributions::Alphanumeric, Rng};
 use scrypt::password_hash::{PasswordHasher, Salt};
-use scrypt::Scrypt;
+use scrypt::{Params, Scrypt};
+use serde::{Deserialize, Serialize};
 use slog::Logger;
-use tempfile::TempDir;
 use typenum::consts::U32;
 use walkdir::WalkDir;
+use zip::read::{read_zipfile_from_stream, ZipFile};
 use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
 
-use std::fs::{create_dir_all, read_to_string, remove_file, write, File};
-use std::io::{Read, Write};
+use sea_orm::ActiveValue;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{create_dir_all, read_dir, remove_file, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use crate::utils::now;
-use crate::wallet::{setup_logger, InternalError, LOG_FILE};
+use crate::database::entities::backup_info::{
+    ActiveModel as DbBackupInfoActMod, Model as DbBackupInfo,
+};
+use crate::database::RgbLibDatabase;
+use crate::utils::{is_log_file, RGB_RUNTIME_LOCK_FILE};
+use crate::utils::{now, BitcoinNetwork};
+use crate::wallet::{setup_logger, InternalError};
 use crate::{Error, Wallet};
 
 const BACKUP_BUFFER_LEN_ENCRYPT: usize = 239; // 255 max, leaving 16 for the checksum
 const BACKUP_BUFFER_LEN_DECRYPT: usize = BACKUP_BUFFER_LEN_ENCRYPT + 16;
 const BACKUP_KEY_LENGTH: usize = 32;
 const BACKUP_NONCE_LENGTH: usize = 19;
-const BACKUP_VERSION: u8 = 1;
+const BACKUP_VERSION: u8 = 2;
+const BACKUP_VERSION_V1: u8 = 1;
+
+// upper bounds on the scrypt parameters, as they're read from the unauthenticated header on restore
+const MAX_SCRYPT_MEMORY: u128 = 1 << 30; // 1 GiB
+const MAX_SCRYPT_P: u32 = 16;
+
+// automatic backups wait this long after a state change, to include the following ones
+const AUTO_BACKUP_DELAY: Duration = Duration::from_secs(2);
+
+const BACKUP_FILE_EXTENSION: &str = "rgb-lib_backup";
+
+const BACKUP_ENCRYPTED_ENTRY: &str = "backup.enc";
+const BACKUP_HEADER_ENTRY: &str = "backup.header";
+const BACKUP_MANIFEST_ENTRY: &str = "backup.manifest";
+const BACKUP_NONCE_ENTRY: &str = "backup.nonce";
+const BACKUP_SALT_ENTRY: &str = "backup.salt";
+const BACKUP_VERSION_ENTRY: &str = "backup.version";
+
+/// Scrypt parameters used to derive the backup encryption key from the password
+///
+/// Parameters requiring more than 1 GiB of memory (128 * `r` * 2^`log_n` bytes) or with `p`
+/// above 16 are rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScryptParams {
+    /// Log2 of the CPU/memory cost parameter N
+    pub log_n: u8,
+    /// Block size parameter r
+    pub r: u32,
+    /// Parallelization parameter p
+    pub p: u32,
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        ScryptParams {
+            log_n: Params::RECOMMENDED_LOG_N,
+            r: Params::RECOMMENDED_R,
+            p: Params::RECOMMENDED_P,
+        }
+    }
+}
+
+impl TryFrom<ScryptParams> for Params {
+    type Error = Error;
+
+    fn try_from(scrypt_params: ScryptParams) -> Result<Self, Self::Error> {
+        // scrypt uses 128 * r * N bytes of memory
+        if scrypt_params.log_n >= 64
+            || (128 * scrypt_params.r as u128) << scrypt_params.log_n > MAX_SCRYPT_MEMORY
+        {
+            return Err(Error::InvalidScryptParams {
+                details: format!("memory cost above the maximum of {MAX_SCRYPT_MEMORY} bytes"),
+            });
+        }
+        if scrypt_params.p > MAX_SCRYPT_P {
+            return Err(Error::InvalidScryptParams {
+                details: format!("p above the maximum of {MAX_SCRYPT_P}"),
+            });
+        }
+        Params::new(
+            scrypt_params.log_n,
+            scrypt_params.r,
+            scrypt_params.p,
+            BACKUP_KEY_LENGTH,
+        )
+        .map_err(|e| Error::InvalidScryptParams {
+            details: e.to_string(),
+        })
+    }
+}
+
+/// Information on a backup, available without decrypting it
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BackupInfo {
+    /// Backup format version
+    pub version: u8,
+    /// Fingerprint of the backed up wallet
+    pub fingerprint: String,
+    /// Bitcoin network of the backed up wallet
+    pub bitcoin_network: BitcoinNetwork,
+    /// Timestamp of the backup creation
+    pub created_at: i64,
+    /// Version of rgb-lib that created the backup
+    pub rgb_lib_version: String,
+    /// Scrypt parameters used to derive the encryption key
+    pub scrypt_params: ScryptParams,
+    /// Identifier of the backup, committing to its parent and to the hashes of the wallet files
+    pub backup_id: String,
+    /// Identifier of the parent backup, set only for incremental backups
+    pub parent_backup_id: Option<String>,
+}
+
+/// Backup status of a wallet
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WalletBackupInfo {
+    /// Whether the wallet state has changed since the last backup
+    pub backup_needed: bool,
+    /// Current version of the wallet state, increased by every state-changing operation
+    pub state_version: u64,
+    /// Version of the wallet state at the time of the last backup
+    pub last_backup_state_version: Option<u64>,
+    /// Timestamp of the last backup
+    pub last_backup_timestamp: Option<i64>,
+}
+
+/// Configuration of the automatic backups
+///
+/// When set, an encrypted backup of the wallet is written to `backup_dir` after the operations
+/// that change the wallet state. Backups run on a background thread, shortly after a state change
+/// so that close changes are included in a single backup, and a pending backup is completed when
+/// the wallet is dropped. Only the most recent `retention` backups are kept.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AutoBackupConfig {
+    /// Directory where backups are written
+    pub backup_dir: String,
+    /// Password used to encrypt backups
+    pub password: String,
+    /// Number of backups to keep
+    pub retention: u8,
+    /// Optional scrypt parameters, the recommended ones are used if not provided
+    pub scrypt_params: Option<ScryptParams>,
+}
+
+impl fmt::Debug for AutoBackupConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoBackupConfig")
+            .field("backup_dir", &self.backup_dir)
+            .field("password", &"<redacted>")
+            .field("retention", &self.retention)
+            .field("scrypt_params", &self.scrypt_params)
+            .finish()
+    }
+}
 
-struct BackupPaths {
-    encrypted: PathBuf,
-    nonce: PathBuf,
-    salt: PathBuf,
-    tempdir: TempDir,
-    version: PathBuf,
-    zip: PathBuf,
+#[derive(Deserialize, Serialize)]
+struct BackupHeader {
+    #[serde(flatten)]
+    info: BackupInfo,
+    salt: String,
+    nonce: String,
+    /// Associated data authenticating the header, set when reading it
+    #[serde(skip)]
+    aad: Vec<u8>,
+}
+
+impl BackupHeader {
+    /// Associated data binding the serialized header to the encrypted wallet archive
+    fn _get_aad(serialized_header: &str) -> Vec<u8> {
+        let header_hash: sha256::Hash = Sha256Hash::hash(serialized_header.as_bytes());
+        header_hash.to_byte_array().to_vec()
+    }
+}
+
+/// Wallet files, by archive name, mapped to the hex-encoded SHA256 hash of their contents
+type BackupManifest = BTreeMap<String, String>;
+
+struct BackupParent {
+    backup_id: String,
+    manifest: BackupManifest,
+}
+
+/// Wallet data needed to create backups, shared with the automatic backup thread
+pub(crate) struct BackupContext {
+    database: Arc<RgbLibDatabase>,
+    wallet_dir: PathBuf,
+    bitcoin_network: BitcoinNetwork,
+    logger: Logger,
+    write_lock: Arc<Mutex<()>>,
+    auto_backup: Arc<RwLock<Option<AutoBackupConfig>>>,
+}
+
+/// Secrets used to encrypt a backup, derived before locking the wallet as scrypt is slow
+struct BackupSecrets {
+    salt: String,
+    nonce: String,
+    scrypt_params: ScryptParams,
+    cypher_secrets: CypherSecrets,
+}
+
+enum AutoBackupRequest {
+    StateChanged,
+    #[cfg(test)]
+    Flush(Sender<()>),
+}
+
+/// Background thread running the automatic backups of a wallet, stopped when dropped
+pub(crate) struct AutoBackupWorker {
+    sender: Option<Sender<AutoBackupRequest>>,
+    handle: Option<JoinHandle<()>>,
 }
 
 struct CypherSecrets {
@@ -37,158 +235,1099 @@ struct CypherSecrets {
     nonce: [u8; BACKUP_NONCE_LENGTH],
 }
 
+/// Writer encrypting the data written to it in chunks with the XChaCha20Poly1305 stream cipher
+///
+/// [`finish`](EncryptingWriter::finish) needs to be called to encrypt the last chunk.
+struct EncryptingWriter<W: Write> {
+    inner: W,
+    encryptor: stream::EncryptorBE32<XChaCha20Poly1305>,
+    buffer: Vec<u8>,
+    aad: Vec<u8>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    fn new(inner: W, cypher_secrets: CypherSecrets, aad: Vec<u8>) -> Self {
+        // - XChacha20Poly1305 is fast, requires no special hardware and supports stream operation
+        // - stream mode required as data to encrypt may be big, so avoiding a memory buffer
+        let aead = XChaCha20Poly1305::new(&cypher_secrets.key);
+        let nonce = GenericArray::from_slice(&cypher_secrets.nonce);
+        Self {
+            inner,
+            encryptor: stream::EncryptorBE32::from_aead(aead, nonce),
+            buffer: Vec::with_capacity(BACKUP_BUFFER_LEN_ENCRYPT),
+            aad,
+        }
+    }
+
+    fn finish(self) -> Result<W, Error> {
+        let EncryptingWriter {
+            mut inner,
+            encryptor,
+            buffer,
+            aad,
+        } = self;
+        let ciphertext = encryptor
+            .encrypt_last(Payload {
+                msg: &buffer,
+                aad: &aad,
+            })
+            .map_err(|e| InternalError::AeadError(e.to_string()))?;
+        inner.write_all(&ciphertext)?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = buf.len().min(BACKUP_BUFFER_LEN_ENCRYPT - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..count]);
+        if self.buffer.len() == BACKUP_BUFFER_LEN_ENCRYPT {
+            let ciphertext = self
+                .encryptor
+                .encrypt_next(Payload {
+                    msg: &self.buffer,
+                    aad: &self.aad,
+                })
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            self.inner.write_all(&ciphertext)?;
+            self.buffer.clear();
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader decrypting the data read from `inner` in chunks with the XChaCha20Poly1305 stream
+/// cipher
+///
+/// A failed chunk authentication is recorded in `failed`, so that it can be told apart from other
+/// errors once the zip library has wrapped it.
+struct DecryptingReader<R: Read> {
+    inner: R,
+    decryptor: Option<stream::DecryptorBE32<XChaCha20Poly1305>>,
+    buffer: Vec<u8>,
+    position: usize,
+    aad: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    fn new(inner: R, cypher_secrets: CypherSecrets, aad: Vec<u8>) -> Self {
+        let aead = XChaCha20Poly1305::new(&cypher_secrets.key);
+        let nonce = GenericArray::from_slice(&cypher_secrets.nonce);
+        Self {
+            inner,
+            decryptor: Some(stream::DecryptorBE32::from_aead(aead, nonce)),
+            buffer: vec![],
+            position: 0,
+            aad,
+            failed: false,
+        }
+    }
+
+    fn _decrypt_chunk(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; BACKUP_BUFFER_LEN_DECRYPT];
+        let read_count = _read_chunk(&mut self.inner, &mut chunk)?;
+        // the last chunk is always shorter than a full one, a missing one fails authentication
+        let payload = Payload {
+            msg: &chunk[..read_count],
+            aad: &self.aad,
+        };
+        let cleartext = if read_count == BACKUP_BUFFER_LEN_DECRYPT {
+            self.decryptor
+                .as_mut()
+                .expect("decryptor is only taken for the last chunk")
+                .decrypt_next(payload)
+        } else {
+            self.decryptor
+                .take()
+                .expect("decryptor is only taken for the last chunk")
+                .decrypt_last(payload)
+        };
+        self.buffer = cleartext.map_err(|_| {
+            self.failed = true;
+            io::Error::new(io::ErrorKind::InvalidData, "backup decryption failed")
+        })?;
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.buffer.len() {
+            if self.decryptor.is_none() {
+                return Ok(0);
+            }
+            self._decrypt_chunk()?;
+        }
+        let count = buf.len().min(self.buffer.len() - self.position);
+        buf[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+/// Seekable writer for a [`ZipWriter`], forwarding the archive data to `inner` as soon as it
+/// can't be rewritten anymore
+///
+/// The zip writer only seeks back to update the local header of the entry it has just finished
+/// and then returns to the end of the archive, so only the data of the entry being written is kept
+/// in memory. [`finish`](ZipStreamWriter::finish) needs to be called to forward the remaining
+/// data.
+struct ZipStreamWriter<W: Write> {
+    inner: W,
+    forwarded: u64,
+    buffer: Vec<u8>,
+    position: u64,
+}
+
+impl<W: Write> ZipStreamWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            forwarded: 0,
+            buffer: vec![],
+            position: 0,
+        }
+    }
+
+    fn _end(&self) -> u64 {
+        self.forwarded + self.buffer.len() as u64
+    }
+
+    fn finish(mut self) -> Result<W, Error> {
+        self.inner.write_all(&self.buffer)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for ZipStreamWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let offset = (self.position - self.forwarded) as usize;
+        let overlap = buf.len().min(self.buffer.len() - offset);
+        self.buffer[offset..offset + overlap].copy_from_slice(&buf[..overlap]);
+        self.buffer.extend_from_slice(&buf[overlap..]);
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Seek for ZipStreamWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let end = self._end();
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+            SeekFrom::End(n) => end.checked_add_signed(n),
+        };
+        let target = match target {
+            Some(t) if t >= self.forwarded && t <= end => t,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "seek outside the rewritable zip data",
+                ))
+            }
+        };
+        // returning to the end after an update means the last entry is complete
+        if target == end && self.position < end {
+            self.inner.write_all(&self.buffer)?;
+            self.forwarded = end;
+            self.buffer.clear();
+        }
+        self.position = target;
+        Ok(target)
+    }
+}
+
 impl Wallet {
     /// Create a backup of the wallet as a file with the provided name and encrypted with the
     /// provided password.
     ///
     /// Scrypt is used for hashing and xchacha20poly1305 is used for encryption. A random salt for
     /// hashing and a random nonce for encrypting are randomly generated and included in the final
-    /// backup file, along with the backup version
+    /// backup file, along with a cleartext header (see [`backup_info`](crate::backup_info)). The
+    /// header is authenticated by the encryption, so it can't be modified without failing the
+    /// restore.
     pub fn backup(&self, backup_path: &str, password: &str) -> Result<(), Error> {
-        // setup
+        self.backup_customize(backup_path, password, None)
+    }
+
+    /// Create a backup of the wallet as a file with the provided name and encrypted with the
+    /// provided password, deriving the encryption key with the provided scrypt parameters.
+    ///
+    /// If `scrypt_params` is not provided, the recommended ones are used. See the
+    /// [`backup`](Wallet::backup) function for details.
+    pub fn backup_customize(
+        &self,
+        backup_path: &str,
+        password: &str,
+        scrypt_params: Option<ScryptParams>,
+    ) -> Result<(), Error> {
         info!(self.logger, "starting backup...");
+        let secrets = _new_backup_secrets(password, scrypt_params)?;
+        let _write_lock = self._write_lock();
+        self._backup_context()
+            ._backup_to_file(backup_path, secrets, None)?;
+        info!(self.logger, "backup completed");
+        Ok(())
+    }
+
+    /// Create an incremental backup of the wallet as a file with the provided name and encrypted
+    /// with the provided password, including only the wallet files that changed since the backup
+    /// at `parent_backup_path`.
+    ///
+    /// The parent backup can be a full or an incremental one, it needs to belong to this wallet and
+    /// to be encrypted with the same password. Its scrypt parameters are reused.
+    ///
+    /// Changes are detected at the file level by comparing hashes with the parent manifest: a
+    /// changed file is included in full, there are no row-level or block-level deltas. Since
+    /// nearly every wallet operation updates the wallet database and the RGB stock, both files are
+    /// usually included in their entirety, so incremental backups mainly save the space taken by
+    /// the unchanged files (e.g. media and the BDK data when no BTC activity happened).
+    ///
+    /// Incremental backups can be restored with
+    /// [`restore_backup_chain`](crate::restore_backup_chain).
+    pub fn backup_incremental(
+        &self,
+        parent_backup_path: &str,
+        backup_path: &str,
+        password: &str,
+    ) -> Result<(), Error> {
+        info!(self.logger, "starting incremental backup...");
+        let backup_context = self._backup_context();
+
+        // check the parent backup belongs to this wallet
+        let mut parent_archive =
+            ZipArchive::new(File::open(parent_backup_path)?).map_err(InternalError::from)?;
+        let parent_header = match _read_any_backup_header(&mut parent_archive)? {
+            Some(header) => header,
+            None => {
+                return Err(Error::UnsupportedBackupVersion {
+                    version: BACKUP_VERSION_V1.to_string(),
+                })
+            }
+        };
+        debug!(self.logger, "parent header info: {:?}", &parent_header.info);
+        if parent_header.info.fingerprint != backup_context._get_fingerprint()? {
+            return Err(Error::InvalidBackupChain {
+                details: s!("parent backup belongs to a different wallet"),
+            });
+        }
+
+        // retrieve the parent manifest
+        let parent_manifest = _read_manifest(&mut parent_archive, &Some(&parent_header), password)?;
+        _verify_backup_id(&parent_header.info, &parent_manifest)?;
+
+        let secrets = _new_backup_secrets(password, Some(parent_header.info.scrypt_params))?;
+        let _write_lock = self._write_lock();
+        backup_context._backup_to_file(
+            backup_path,
+            secrets,
+            Some(BackupParent {
+                backup_id: parent_header.info.backup_id,
+                manifest: parent_manifest,
+            }),
+        )?;
+        info!(self.logger, "incremental backup completed");
+        Ok(())
+    }
+
+    /// Create a backup of the wallet, encrypted with the provided password, and write it to the
+    /// provided `writer`.
+    ///
+    /// The produced data has the same format as the one of backup files (see
+    /// [`backup`](Wallet::backup)) and can be restored with
+    /// [`restore_from_reader`](crate::restore_from_reader) or, once saved to a file, with
+    /// [`restore_backup`](crate::restore_backup).
+    ///
+    /// No plaintext data is written to disk, the wallet archive is encrypted while it's being
+    /// created. As the backup format requires seeking, the encrypted backup is written to an
+    /// anonymous temporary file before being copied to `writer`.
+    pub fn backup_to_writer(&self, writer: &mut impl Write, password: &str) -> Result<(), Error> {
+        info!(self.logger, "starting backup to writer...");
+        let secrets = _new_backup_secrets(password, None)?;
+        let _write_lock = self._write_lock();
+        let backup_context = self._backup_context();
+        backup_context._tracked_backup(|| {
+            let mut spool = tempfile::tempfile()?;
+            backup_context._backup_to_writer(&mut spool, secrets, None)?;
+            spool.rewind()?;
+            io::copy(&mut spool, writer)?;
+            writer.flush()?;
+            Ok(())
+        })?;
+        info!(self.logger, "backup completed");
+        Ok(())
+    }
+
+    /// Return the backup status of the wallet, including whether a backup is needed.
+    ///
+    /// A backup is needed if the wallet state has changed since the last backup was completed.
+    pub fn backup_info(&self) -> Result<WalletBackupInfo, Error> {
+        let backup_info = self._backup_context()._get_backup_info()?;
+        Ok(WalletBackupInfo {
+            backup_needed: _backup_needed(&backup_info),
+            state_version: backup_info.state_version as u64,
+            last_backup_state_version: backup_info.last_backup_state_version.map(|v| v as u64),
+            last_backup_timestamp: backup_info.last_backup_timestamp,
+        })
+    }
+
+    /// Set or unset the configuration for automatic backups.
+    ///
+    /// The configured backup directory is created if it doesn't exist. See [`AutoBackupConfig`]
+    /// for details.
+    pub fn set_auto_backup(&self, auto_backup: Option<AutoBackupConfig>) -> Result<(), Error> {
+        if let Some(config) = &auto_backup {
+            if config.retention == 0 {
+                return Err(Error::InvalidAutoBackupConfig {
+                    details: s!("retention must be greater than 0"),
+                });
+            }
+            Params::try_from(config.scrypt_params.unwrap_or_default())?;
+            create_dir_all(&config.backup_dir)?;
+        }
+        let enabled = auto_backup.is_some();
+        *self
+            .auto_backup
+            .write()
+            .expect("auto backup lock should not be poisoned") = auto_backup;
+        let mut auto_backup_worker = self
+            .auto_backup_worker
+            .lock()
+            .expect("auto backup worker mutex should not be poisoned");
+        if enabled && auto_backup_worker.is_none() {
+            *auto_backup_worker = Some(AutoBackupWorker::start(self._backup_context())?);
+        }
+        Ok(())
+    }
+
+    fn _backup_context(&self) -> BackupContext {
+        BackupContext {
+            database: self.database.clone(),
+            wallet_dir: self.wallet_dir.clone(),
+            bitcoin_network: self.wallet_data.bitcoin_network,
+            logger: self.logger.clone(),
+            write_lock: self.write_lock.clone(),
+            auto_backup: self.auto_backup.clone(),
+        }
+    }
+
+    /// Bump the wallet state version and, if configured, schedule an automatic backup
+    ///
+    /// The automatic backup runs on a background thread, so it doesn't delay the operation that
+    /// changed the wallet state, which holds the wallet write lock.
+    pub(crate) fn _state_changed(&self) -> Result<(), Error> {
+        let backup_context = self._backup_context();
+        let backup_info = backup_context._get_backup_info()?;
+        let state_version = backup_info.state_version + 1;
+        let mut updated_backup_info: DbBackupInfoActMod = backup_info.into();
+        updated_backup_info.state_version = ActiveValue::Set(state_version);
+        self.database.update_backup_info(updated_backup_info)?;
+        debug!(self.logger, "wallet state version: {}", state_version);
+
+        if let Some(worker) = self
+            .auto_backup_worker
+            .lock()
+            .expect("auto backup worker mutex should not be poisoned")
+            .as_ref()
+        {
+            worker.send(AutoBackupRequest::StateChanged);
+        }
+
+        Ok(())
+    }
+
+    /// Run the pending automatic backup, if any, and wait for it to complete
+    #[cfg(test)]
+    pub(crate) fn _flush_auto_backup(&self) {
+        let (sender, receiver) = mpsc::channel();
+        if let Some(worker) = self
+            .auto_backup_worker
+            .lock()
+            .expect("auto backup worker mutex should not be poisoned")
+            .as_ref()
+        {
+            worker.send(AutoBackupRequest::Flush(sender));
+        }
+        // returns right away if there's no worker, as the sender has been dropped
+        receiver.recv().unwrap_or_default();
+    }
+}
+
+impl BackupContext {
+    fn _write_lock(&self) -> MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .expect("wallet write mutex should not be poisoned")
+    }
+
+    fn _get_backup_info(&self) -> Result<DbBackupInfo, Error> {
+        if let Some(backup_info) = self.database.get_backup_info()? {
+            return Ok(backup_info);
+        }
+        let idx = self.database.set_backup_info(DbBackupInfoActMod {
+            state_version: ActiveValue::Set(0),
+            ..Default::default()
+        })?;
+        Ok(DbBackupInfo {
+            idx,
+            state_version: 0,
+            last_backup_state_version: None,
+            last_backup_timestamp: None,
+        })
+    }
+
+    fn _set_last_backup(
+        &self,
+        backup_info: &DbBackupInfo,
+        last_backup_state_version: Option<i64>,
+        last_backup_timestamp: Option<i64>,
+    ) -> Result<(), Error> {
+        let mut updated_backup_info: DbBackupInfoActMod = backup_info.clone().into();
+        updated_backup_info.last_backup_state_version = ActiveValue::Set(last_backup_state_version);
+        updated_backup_info.last_backup_timestamp = ActiveValue::Set(last_backup_timestamp);
+        self.database.update_backup_info(updated_backup_info)?;
+        Ok(())
+    }
+
+    fn _tracked_backup<F>(&self, backup: F) -> Result<(), Error>
+    where
+        F: FnOnce() -> Result<(), Error>,
+    {
+        // the last backup is recorded before archiving the wallet data, so that the backup
+        // includes it, and reverted if the backup fails
+        let backup_info = self._get_backup_info()?;
+        self._set_last_backup(
+            &backup_info,
+            Some(backup_info.state_version),
+            Some(now().unix_timestamp()),
+        )?;
+        if let Err(e) = backup() {
+            self._set_last_backup(
+                &backup_info,
+                backup_info.last_backup_state_version,
+                backup_info.last_backup_timestamp,
+            )?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn _auto_backup(&self) -> Result<(), Error> {
+        let config = match self
+            .auto_backup
+            .read()
+            .expect("auto backup lock should not be poisoned")
+            .clone()
+        {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        if !_backup_needed(&self._get_backup_info()?) {
+            return Ok(());
+        }
+
+        // the key derivation is the slowest part, so it's done before locking the wallet
+        let secrets = _new_backup_secrets(&config.password, config.scrypt_params)?;
+        let _write_lock = self._write_lock();
+        let state_version = self._get_backup_info()?.state_version;
+        let backup_dir = PathBuf::from(&config.backup_dir);
+        let prefix = format!("{}_", self._get_fingerprint()?);
+        let backup_path = backup_dir.join(format!(
+            "{prefix}{state_version:020}.{BACKUP_FILE_EXTENSION}"
+        ));
+        info!(self.logger, "automatic backup to {:?}", backup_path);
+        self._backup_to_file(
+            backup_path
+                .to_str()
+                .ok_or_else(|| InternalError::Unexpected)?,
+            secrets,
+            None,
+        )?;
+
+        // only keep the most recent backups, names sort by state version
+        let mut backups: Vec<PathBuf> = read_dir(&backup_dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension().and_then(|e| e.to_str()) == Some(BACKUP_FILE_EXTENSION)
+                    && p.file_name()
+                        .and_then(|n| n.to_str())
+                        .map_or(false, |n| n.starts_with(&prefix))
+            })
+            .collect();
+        backups.sort();
+        let to_remove = backups.len().saturating_sub(config.retention as usize);
+        for old_backup in backups.into_iter().take(to_remove) {
+            debug!(self.logger, "removing old backup {:?}", old_backup);
+            remove_file(old_backup)?;
+        }
+
+        Ok(())
+    }
+
+    fn _get_fingerprint(&self) -> Result<String, Error> {
+        Ok(self
+            .wallet_dir
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| InternalError::Unexpected)?
+            .to_string())
+    }
+
+    fn _backup_to_file(
+        &self,
+        backup_path: &str,
+        secrets: BackupSecrets,
+        parent: Option<BackupParent>,
+    ) -> Result<(), Error> {
         let backup_file = PathBuf::from(&backup_path);
         if backup_file.exists() {
             return Err(Error::FileAlreadyExists {
                 path: backup_path.to_string(),
             })?;
         }
-        let tmp_base_path = _get_parent_path(&backup_file)?;
-        let files = _get_backup_paths(&tmp_base_path)?;
-        let salt: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(BACKUP_KEY_LENGTH)
-            .map(char::from)
-            .collect();
-        debug!(self.logger, "using generated salt: {}", &salt);
-        let nonce: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(BACKUP_NONCE_LENGTH)
-            .map(char::from)
-            .collect();
-        debug!(self.logger, "using generated nonce: {}", &nonce);
 
-        // create zip archive of wallet data
-        debug!(
-            self.logger,
-            "\nzipping {:?} to {:?}", &self.wallet_dir, &files.zip
-        );
-        _zip_dir(&self.wallet_dir, &files.zip, true, &self.logger)?;
+        // write the backup, removing the incomplete file on failure
+        self._tracked_backup(|| {
+            let mut file = File::create(&backup_file)?;
+            if let Err(e) = self._backup_to_writer(&mut file, secrets, parent) {
+                drop(file);
+                remove_file(&backup_file).unwrap_or_default();
+                return Err(e);
+            }
+            file.flush()?;
+            file.sync_all()?;
+            Ok(())
+        })
+    }
 
-        // encrypt the backup file
-        debug!(
-            self.logger,
-            "\nencrypting {:?} to {:?}", &files.zip, &files.encrypted
-        );
-        _encrypt_file(&files.zip, &files.encrypted, password, &salt, &nonce)?;
+    fn _backup_to_writer(
+        &self,
+        writer: &mut (impl Write + Seek),
+        secrets: BackupSecrets,
+        parent: Option<BackupParent>,
+    ) -> Result<(), Error> {
+        // compute the manifest of the wallet files first, as the backup ID is part of the header,
+        // which needs to be known before encrypting
+        let entries = _archive_entries(&self.wallet_dir, true)?;
+        let manifest = _get_manifest(&entries)?;
+        let parent_backup_id = parent.as_ref().map(|p| p.backup_id.clone());
+        let backup_id = _get_backup_id(&parent_backup_id, &manifest)?;
+        let header = BackupHeader {
+            info: BackupInfo {
+                version: BACKUP_VERSION,
+                fingerprint: self._get_fingerprint()?,
+                bitcoin_network: self.bitcoin_network,
+                created_at: now().unix_timestamp(),
+                rgb_lib_version: env!("CARGO_PKG_VERSION").to_string(),
+                scrypt_params: secrets.scrypt_params,
+                backup_id,
+                parent_backup_id,
+            },
+            salt: secrets.salt,
+            nonce: secrets.nonce,
+            aad: vec![],
+        };
+        let serialized_header = serde_json::to_string(&header).map_err(InternalError::from)?;
+        debug!(self.logger, "using header: {}", &serialized_header);
 
-        // add backup nonce + salt + version to final zip file
-        write(files.nonce, nonce)?;
-        write(files.salt, salt)?;
-        write(files.version, BACKUP_VERSION.to_string())?;
+        // create zip archive of wallet data, only including changed files if there's a parent, and
+        // encrypt it into the backup one while it's being created, authenticating the header
         debug!(
             self.logger,
-            "\nzipping {:?} to {:?}", &files.tempdir, &backup_file
+            "\nzipping and encrypting {:?}", &self.wallet_dir
+        );
+        let mut backup_zip = ZipWriter::new(writer);
+        let options = _zip_file_options();
+        backup_zip
+            .start_file(BACKUP_ENCRYPTED_ENTRY, options)
+            .map_err(InternalError::from)?;
+        let encrypting_writer = EncryptingWriter::new(
+            &mut backup_zip,
+            secrets.cypher_secrets,
+            BackupHeader::_get_aad(&serialized_header),
         );
         _zip_dir(
-            &PathBuf::from(files.tempdir.path()),
-            &backup_file,
-            false,
+            &entries,
+            ZipStreamWriter::new(encrypting_writer),
+            &manifest,
+            parent.as_ref().map(|p| &p.manifest),
             &self.logger,
-        )?;
+        )?
+        .finish()?
+        .finish()?;
+
+        // add header + version to the backup archive
+        for (name, contents) in [
+            (BACKUP_HEADER_ENTRY, serialized_header),
+            (BACKUP_VERSION_ENTRY, BACKUP_VERSION.to_string()),
+        ] {
+            backup_zip
+                .start_file(name, options)
+                .map_err(InternalError::from)?;
+            backup_zip.write_all(contents.as_bytes())?;
+        }
+        let writer = backup_zip.finish().map_err(InternalError::from)?;
+        writer.flush()?;
 
-        info!(self.logger, "backup completed");
         Ok(())
     }
 }
 
+impl AutoBackupWorker {
+    fn start(backup_context: BackupContext) -> Result<Self, Error> {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name(s!("rgb-lib-auto-backup"))
+            .spawn(move || _run_auto_backups(backup_context, receiver))?;
+        Ok(Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    fn send(&self, request: AutoBackupRequest) {
+        if let Some(sender) = &self.sender {
+            // the thread only stops when the worker is dropped
+            sender.send(request).unwrap_or_default();
+        }
+    }
+}
+
+impl Drop for AutoBackupWorker {
+    fn drop(&mut self) {
+        // disconnecting makes the thread run the pending backup, if any, and exit
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            handle.join().unwrap_or_default();
+        }
+    }
+}
+
+/// Run the automatic backups requested on `receiver`, coalescing the state changes notified within
+/// [`AUTO_BACKUP_DELAY`] from the first one into a single backup
+fn _run_auto_backups(backup_context: BackupContext, receiver: Receiver<AutoBackupRequest>) {
+    let mut pending_since: Option<Instant> = None;
+    let mut flushes: Vec<Sender<()>> = vec![];
+    loop {
+        let request = match pending_since {
+            Some(since) => receiver.recv_timeout(
+                (since + AUTO_BACKUP_DELAY).saturating_duration_since(Instant::now()),
+            ),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let stop = match request {
+            Ok(AutoBackupRequest::StateChanged) => {
+                pending_since.get_or_insert_with(Instant::now);
+                continue;
+            }
+            #[cfg(test)]
+            Ok(AutoBackupRequest::Flush(ack)) => {
+                flushes.push(ack);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if pending_since.take().is_some() {
+            // a failed automatic backup doesn't fail the operation that triggered it, as the
+            // wallet state has already changed and a backup will be reported as needed
+            if let Err(e) = backup_context._auto_backup() {
+                error!(backup_context.logger, "automatic backup failed: {}", e);
+            }
+        }
+        for ack in flushes.drain(..) {
+            ack.send(()).unwrap_or_default();
+        }
+        if stop {
+            break;
+        }
+    }
+}
+
+fn _backup_needed(backup_info: &DbBackupInfo) -> bool {
+    match backup_info.last_backup_state_version {
+        Some(v) => backup_info.state_version > v,
+        None => backup_info.state_version > 0,
+    }
+}
+
+fn _new_backup_secrets(
+    password: &str,
+    scrypt_params: Option<ScryptParams>,
+) -> Result<BackupSecrets, Error> {
+    let scrypt_params = scrypt_params.unwrap_or_default();
+    let salt: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(BACKUP_KEY_LENGTH)
+        .map(char::from)
+        .collect();
+    let nonce: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(BACKUP_NONCE_LENGTH)
+        .map(char::from)
+        .collect();
+    let cypher_secrets = _get_cypher_secrets(password, &salt, &nonce, scrypt_params)?;
+    Ok(BackupSecrets {
+        salt,
+        nonce,
+        scrypt_params,
+        cypher_secrets,
+    })
+}
+
+/// Return the information on the backup at the provided path, reading its cleartext header.
+///
+/// No password is required. Backups created with a format version older than 2 have no header and
+/// cause an [`Error::UnsupportedBackupVersion`] to be returned.
+pub fn backup_info(backup_path: &str) -> Result<BackupInfo, Error> {
+    let backup_file = File::open(backup_path)?;
+    let mut archive = ZipArchive::new(backup_file).map_err(InternalError::from)?;
+    match _read_any_backup_header(&mut archive)? {
+        Some(header) => Ok(header.info),
+        None => Err(Error::UnsupportedBackupVersion {
+            version: BACKUP_VERSION_V1.to_string(),
+        }),
+    }
+}
+
 /// Restore a backup from the given file and password to the provided target directory.
+///
+/// Backups created with any supported format version can be restored. Incremental backups need to
+/// be restored along with their base with [`restore_backup_chain`].
+///
+/// An [`Error::WrongPassword`] is returned if the decryption fails, which also happens when the
+/// header of a backup has been tampered with.
 pub fn restore_backup(backup_path: &str, password: &str, target_dir: &str) -> Result<(), Error> {
+    restore_backup_chain(backup_path, vec![], password, target_dir)
+}
+
+/// Restore a full backup, followed by the provided ordered chain of incremental backups, from the
+/// given files and password to the provided target directory.
+///
+/// Each incremental backup needs to have the previous backup in the chain as its parent. The chain
+/// is checked using the cleartext headers before restoring anything, then the hash of each
+/// restored wallet file is verified against the manifest of the backup that restored it.
+pub fn restore_backup_chain(
+    base_backup_path: &str,
+    incremental_backup_paths: Vec<String>,
+    password: &str,
+    target_dir: &str,
+) -> Result<(), Error> {
+    let mut backups = vec![File::open(base_backup_path)?];
+    for backup_path in incremental_backup_paths {
+        backups.push(File::open(backup_path)?);
+    }
+    _restore(backups, password, target_dir)
+}
+
+/// Restore a backup read from the given `reader` and password to the provided target directory.
+///
+/// The backup data, as produced by [`backup_to_writer`](crate::wallet::Wallet::backup_to_writer)
+/// or [`backup`](crate::wallet::Wallet::backup), is decrypted in chunks without writing plaintext
+/// data to disk, except for the restored wallet files. As the backup format requires seeking, the
+/// encrypted data is first copied to an anonymous temporary file.
+pub fn restore_from_reader(
+    mut reader: impl Read,
+    password: &str,
+    target_dir: &str,
+) -> Result<(), Error> {
+    let mut spool = tempfile::tempfile()?;
+    io::copy(&mut reader, &mut spool)?;
+    spool.rewind()?;
+    _restore(vec![spool], password, target_dir)
+}
+
+fn _restore<R: Read + Seek>(
+    backups: Vec<R>,
+    password: &str,
+    target_dir: &str,
+) -> Result<(), Error> {
     // setup
     create_dir_all(target_dir)?;
     let log_dir = Path::new(&target_dir);
     let log_name = format!("restore_{}", now().unix_timestamp());
     let logger = setup_logger(log_dir.to_path_buf(), Some(&log_name))?;
     info!(logger, "starting restore...");
-    let backup_file = PathBuf::from(backup_path);
-    let tmp_base_path = _get_parent_path(&backup_file)?;
-    let files = _get_backup_paths(&tmp_base_path)?;
     let target_dir_path = PathBuf::from(&target_dir);
 
-    // unpack given zip file and retrieve backup data
-    info!(logger, "unzipping {:?}", backup_file);
-    _unzip(&backup_file, &PathBuf::from(files.tempdir.path()), &logger)?;
-    let nonce = read_to_string(files.nonce)?;
-    debug!(logger, "using retrieved nonce: {}", &nonce);
-    let salt = read_to_string(files.salt)?;
-    debug!(logger, "using retrieved salt: {}", &salt);
-    let version = read_to_string(files.version)?
-        .parse::<u8>()
-        .map_err(|_| InternalError::Unexpected)?;
-    debug!(logger, "retrieved version: {}", &version);
-    if version != BACKUP_VERSION {
-        return Err(Error::UnsupportedBackupVersion {
-            version: version.to_string(),
-        });
+    // open given zip archives and check the chain from their headers
+    let mut archives = vec![];
+    for backup in backups {
+        let mut archive = ZipArchive::new(backup).map_err(InternalError::from)?;
+        let header = _read_any_backup_header(&mut archive)?;
+        if let Some(header) = &header {
+            debug!(logger, "retrieved header info: {:?}", &header.info);
+        }
+        archives.push((archive, header));
     }
+    let headers: Vec<Option<&BackupHeader>> = archives.iter().map(|(_, h)| h.as_ref()).collect();
+    _check_backup_chain(&headers)?;
 
-    // decrypt backup and restore files
-    info!(
-        logger.clone(),
-        "decrypting {:?} to {:?}", files.encrypted, files.zip
-    );
-    _decrypt_file(&files.encrypted, &files.zip, password, &salt, &nonce)?;
-    info!(
-        logger.clone(),
-        "unzipping {:?} to {:?}", &files.zip, &target_dir_path
-    );
-    _unzip(&files.zip, &target_dir_path, &logger)?;
+    // decrypt backups and restore files, in chain order
+    let mut prev_manifest: Option<BackupManifest> = None;
+    for (mut archive, header) in archives {
+        info!(
+            logger.clone(),
+            "decrypting and unzipping wallet archive to {:?}", &target_dir_path
+        );
+        let mut archive_manifest = None;
+        _read_wallet_archive(&mut archive, &header.as_ref(), password, |file| {
+            if file.name() == BACKUP_MANIFEST_ENTRY {
+                archive_manifest = Some(_parse_manifest(file)?);
+                return Ok(());
+            }
+            _unzip_entry(file, &target_dir_path, &logger)
+        })?;
+        let manifest = match &header {
+            Some(header) => {
+                let manifest = archive_manifest.ok_or_else(|| InternalError::Unexpected)?;
+                _verify_backup_id(&header.info, &manifest)?;
+                Some(manifest)
+            }
+            None => None,
+        };
+        if let (Some(prev_manifest), Some(manifest)) = (&prev_manifest, &manifest) {
+            for name in prev_manifest.keys().filter(|n| !manifest.contains_key(*n)) {
+                let removed_path = target_dir_path.join(name);
+                if removed_path.exists() {
+                    debug!(logger, "removing file {}", removed_path.display());
+                    remove_file(removed_path)?;
+                }
+            }
+        }
+        if let Some(manifest) = &manifest {
+            _verify_manifest(manifest, &target_dir_path)?;
+        }
+        prev_manifest = manifest;
+    }
 
     info!(logger, "restore completed");
     Ok(())
 }
 
-fn _get_backup_paths(tmp_base_path: &Path) -> Result<BackupPaths, Error> {
-    create_dir_all(tmp_base_path)?;
-    let tempdir = tempfile::tempdir_in(tmp_base_path)?;
-    let encrypted = tempdir.path().join("backup.enc");
-    let nonce = tempdir.path().join("backup.nonce");
-    let salt = tempdir.path().join("backup.salt");
-    let version = tempdir.path().join("backup.version");
-    let zip = tempdir.path().join("backup.zip");
-    Ok(BackupPaths {
-        encrypted,
-        nonce,
-        salt,
-        tempdir,
-        version,
-        zip,
-    })
+fn _check_backup_chain(headers: &[Option<&BackupHeader>]) -> Result<(), Error> {
+    let (base, incrementals) = headers
+        .split_first()
+        .ok_or_else(|| InternalError::Unexpected)?;
+    if let Some(base) = base {
+        if base.info.parent_backup_id.is_some() {
+            return Err(Error::InvalidBackupChain {
+                details: s!("the base backup is an incremental one"),
+            });
+        }
+    }
+    let mut prev = *base;
+    for header in incrementals {
+        let (prev_header, header) = match (prev, header) {
+            (Some(prev_header), Some(header)) => (prev_header, header),
+            _ => {
+                return Err(Error::InvalidBackupChain {
+                    details: s!("backups with format version 1 can't be part of a chain"),
+                })
+            }
+        };
+        if header.info.fingerprint != prev_header.info.fingerprint {
+            return Err(Error::InvalidBackupChain {
+                details: format!(
+                    "backup {} belongs to a different wallet",
+                    header.info.backup_id
+                ),
+            });
+        }
+        if header.info.parent_backup_id.as_ref() != Some(&prev_header.info.backup_id) {
+            return Err(Error::InvalidBackupChain {
+                details: format!(
+                    "backup {} is not a child of backup {}",
+                    header.info.backup_id, prev_header.info.backup_id
+                ),
+            });
+        }
+        prev = Some(header);
+    }
+    Ok(())
 }
 
-fn _get_parent_path(file: &Path) -> Result<PathBuf, Error> {
-    if let Some(parent) = file.parent() {
-        Ok(parent.to_path_buf())
-    } else {
-        Err(Error::IO {
-            details: "provided file path has no parent".to_string(),
-        })
+fn _get_backup_id(
+    parent_backup_id: &Option<String>,
+    manifest: &BackupManifest,
+) -> Result<String, Error> {
+    let mut id_data = parent_backup_id.clone().unwrap_or_default().into_bytes();
+    id_data.extend(serde_json::to_vec(manifest).map_err(InternalError::from)?);
+    let backup_id: sha256::Hash = Sha256Hash::hash(&id_data[..]);
+    Ok(backup_id.to_string())
+}
+
+fn _verify_backup_id(info: &BackupInfo, manifest: &BackupManifest) -> Result<(), Error> {
+    if _get_backup_id(&info.parent_backup_id, manifest)? != info.backup_id {
+        return Err(Error::InvalidBackupChain {
+            details: format!("manifest of backup {} doesn't match its ID", info.backup_id),
+        });
     }
+    Ok(())
 }
 
-fn _zip_dir(
-    path_in: &PathBuf,
-    path_out: &PathBuf,
-    keep_last_path_component: bool,
-    logger: &Logger,
-) -> Result<(), Error> {
-    // setup
-    let writer = File::create(path_out)?;
-    let mut zip = zip::ZipWriter::new(writer);
-    let options = FileOptions::default().compression_method(zip::CompressionMethod::Zstd);
+fn _verify_manifest(manifest: &BackupManifest, path_out: &Path) -> Result<(), Error> {
     let mut buffer = [0u8; 4096];
+    for (name, expected_hash) in manifest {
+        if &_hash_file(&path_out.join(name), &mut buffer)? != expected_hash {
+            return Err(Error::InvalidBackupChain {
+                details: format!("hash mismatch for restored file {name}"),
+            });
+        }
+    }
+    Ok(())
+}
 
-    // archive
+/// Return the hex-encoded SHA256 hash of the file at `path`, reading it in chunks of the size of
+/// `buffer`
+fn _hash_file(path: &Path, buffer: &mut [u8]) -> Result<String, Error> {
+    let mut engine = sha256::Hash::engine();
+    let mut f = File::open(path)?;
+    loop {
+        let read_count = f.read(buffer)?;
+        if read_count != 0 {
+            engine.input(&buffer[..read_count]);
+        } else {
+            break;
+        }
+    }
+    Ok(sha256::Hash::from_engine(engine).to_string())
+}
+
+fn _parse_manifest(file: &mut ZipFile) -> Result<BackupManifest, Error> {
+    let mut serialized_manifest = String::new();
+    file.read_to_string(&mut serialized_manifest)?;
+    Ok(serde_json::from_str(&serialized_manifest).map_err(InternalError::from)?)
+}
+
+fn _read_manifest<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    header: &Option<&BackupHeader>,
+    password: &str,
+) -> Result<BackupManifest, Error> {
+    let mut manifest = None;
+    _read_wallet_archive(archive, header, password, |file| {
+        if file.name() == BACKUP_MANIFEST_ENTRY {
+            manifest = Some(_parse_manifest(file)?);
+        }
+        Ok(())
+    })?;
+    Ok(manifest.ok_or_else(|| InternalError::Unexpected)?)
+}
+
+/// Decrypt the wallet archive of the given backup in chunks, passing each of its entries to
+/// `handle_entry` as soon as it's read
+fn _read_wallet_archive<R, F>(
+    archive: &mut ZipArchive<R>,
+    header: &Option<&BackupHeader>,
+    password: &str,
+    handle_entry: F,
+) -> Result<(), Error>
+where
+    R: Read + Seek,
+    F: FnMut(&mut ZipFile) -> Result<(), Error>,
+{
+    let (salt, nonce, scrypt_params) = match header {
+        Some(header) => (
+            header.salt.clone(),
+            header.nonce.clone(),
+            header.info.scrypt_params,
+        ),
+        None => {
+            let nonce = _read_zip_entry(archive, BACKUP_NONCE_ENTRY)?;
+            let salt = _read_zip_entry(archive, BACKUP_SALT_ENTRY)?;
+            (salt, nonce, ScryptParams::default())
+        }
+    };
+    let cypher_secrets = _get_cypher_secrets(password, &salt, &nonce, scrypt_params)?;
+    let encrypted = archive
+        .by_name(BACKUP_ENCRYPTED_ENTRY)
+        .map_err(InternalError::from)?;
+    let aad = header.map(|h| h.aad.clone()).unwrap_or_default();
+    let mut reader = DecryptingReader::new(encrypted, cypher_secrets, aad);
+    let result = _read_zip_stream(&mut reader, handle_entry);
+    if reader.failed {
+        return Err(Error::WrongPassword);
+    }
+    result
+}
+
+fn _read_zip_stream<R, F>(reader: &mut R, mut handle_entry: F) -> Result<(), Error>
+where
+    R: Read,
+    F: FnMut(&mut ZipFile) -> Result<(), Error>,
+{
+    while let Some(mut file) = read_zipfile_from_stream(reader).map_err(InternalError::from)? {
+        handle_entry(&mut file)?;
+    }
+    // read the central directory as well, so the last chunk gets authenticated
+    io::copy(reader, &mut io::sink())?;
+    Ok(())
+}
+
+/// Read the cleartext header of the given backup archive, which is missing in v1 backups
+fn _read_any_backup_header<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<BackupHeader>, Error> {
+    match _read_backup_version(archive)? {
+        BACKUP_VERSION => Ok(Some(_read_backup_header(archive)?)),
+        BACKUP_VERSION_V1 => Ok(None),
+        version => Err(Error::UnsupportedBackupVersion {
+            version: version.to_string(),
+        }),
+    }
+}
+
+fn _read_zip_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<String, Error> {
+    let mut contents = String::new();
+    archive
+        .by_name(name)
+        .map_err(InternalError::from)?
+        .read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+fn _read_backup_version<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<u8, Error> {
+    _read_zip_entry(archive, BACKUP_VERSION_ENTRY)?
+        .parse::<u8>()
+        .map_err(|_| InternalError::Unexpected.into())
+}
+
+/// Read the cleartext header of the given backup archive, setting the associated data that
+/// authenticates it
+fn _read_backup_header<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<BackupHeader, Error> {
+    let serialized_header = _read_zip_entry(archive, BACKUP_HEADER_ENTRY)?;
+    let mut header: BackupHeader =
+        serde_json::from_str(&serialized_header).map_err(InternalError::from)?;
+    header.aad = BackupHeader::_get_aad(&serialized_header);
+    Ok(header)
+}
+
+fn _zip_file_options() -> FileOptions {
+    FileOptions::default().compression_method(zip::CompressionMethod::Zstd)
+}
+
+/// Return the paths of the files and directories to archive from `path_in`, along with their
+/// names in the archive
+fn _archive_entries(
+    path_in: &PathBuf,
+    keep_last_path_component: bool,
+) -> Result<Vec<(PathBuf, String)>, Error> {
     let prefix = if keep_last_path_component {
         if let Some(parent) = path_in.parent() {
             parent
@@ -200,18 +1339,58 @@ fn _zip_dir(
     } else {
         path_in
     };
+    let mut entries = vec![];
     let entry_iterator = WalkDir::new(path_in).into_iter().filter_map(|e| e.ok());
     for entry in entry_iterator {
         let path = entry.path();
         let name = path.strip_prefix(prefix).map_err(InternalError::from)?;
+        if name.as_os_str().is_empty() {
+            continue;
+        }
+        if path.is_file() {
+            let file_name = entry.file_name().to_string_lossy();
+            if is_log_file(&file_name) || file_name == RGB_RUNTIME_LOCK_FILE {
+                continue;
+            }; // skip log and runtime lock files
+        }
         let name_str = name.to_str().ok_or_else(|| InternalError::Unexpected)?;
+        entries.push((path.to_path_buf(), name_str.to_string()));
+    }
+    Ok(entries)
+}
+
+/// Return the manifest of the files in the given archive entries
+fn _get_manifest(entries: &[(PathBuf, String)]) -> Result<BackupManifest, Error> {
+    let mut manifest = BackupManifest::new();
+    let mut buffer = [0u8; 4096];
+    for (path, name) in entries.iter().filter(|(p, _)| p.is_file()) {
+        manifest.insert(name.clone(), _hash_file(path, &mut buffer)?);
+    }
+    Ok(manifest)
+}
+
+fn _zip_dir<W: Write + Seek>(
+    entries: &[(PathBuf, String)],
+    writer: W,
+    manifest: &BackupManifest,
+    parent_manifest: Option<&BackupManifest>,
+    logger: &Logger,
+) -> Result<W, Error> {
+    // setup
+    let mut zip = ZipWriter::new(writer);
+    let options = _zip_file_options();
+    let mut buffer = [0u8; 4096];
+
+    // archive
+    for (path, name) in entries {
         if path.is_file() {
-            if path.ends_with(LOG_FILE) {
+            let unchanged = parent_manifest.map_or(false, |m| m.get(name) == manifest.get(name));
+            if unchanged {
+                debug!(logger, "skipping unchanged file {path:?}");
                 continue;
-            }; // skip log file
+            }
             debug!(logger, "adding file {path:?} as {name:?}");
-            zip.start_file(name_str, options)
-                .map_err(InternalError::from)?;
+            zip.start_file(name, options).map_err(InternalError::from)?;
             let mut f = File::open(path)?;
             loop {
                 let read_count = f.read(&mut buffer)?;
@@ -221,67 +1400,81 @@ fn _zip_dir(
                     break;
                 }
             }
-        } else if !name.as_os_str().is_empty() {
+        } else {
             debug!(logger, "adding directory {path:?} as {name:?}");
-            zip.add_directory(name_str, options)
+            zip.add_directory(name, options)
                 .map_err(InternalError::from)?;
         }
     }
 
-    // finalize
-    let mut file = zip.finish().map_err(InternalError::from)?;
-    file.flush()?;
-    file.sync_all()?;
+    // add the manifest of all wallet files, including unchanged ones
+    zip.start_file(BACKUP_MANIFEST_ENTRY, options)
+        .map_err(InternalError::from)?;
+    zip.write_all(&serde_json::to_vec(manifest).map_err(InternalError::from)?)?;
 
-    Ok(())
+    // finalize
+    Ok(zip.finish().map_err(InternalError::from)?)
 }
 
-fn _unzip(zip_path: &PathBuf, path_out: &Path, logger: &Logger) -> Result<(), Error> {
-    // setup
-    let file = File::open(zip_path).map_err(InternalError::from)?;
-    let mut archive = zip::ZipArchive::new(file).map_err(InternalError::from)?;
-
-    // extract
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i).map_err(InternalError::from)?;
-        let outpath = match file.enclosed_name() {
-            Some(path) => path_out.join(path),
-            None => continue,
-        };
-        if file.name().ends_with('/') {
-            debug!(logger, "creating directory {i} as {}", outpath.display());
-            create_dir_all(&outpath)?;
-        } else {
-            debug!(
-                logger,
-                "extracting file {i} to {} ({} bytes)",
-                outpath.display(),
-                file.size()
-            );
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    debug!(logger, "creating parent dir {}", p.display());
-                    create_dir_all(p)?;
-                }
+fn _unzip_entry(file: &mut ZipFile, path_out: &Path, logger: &Logger) -> Result<(), Error> {
+    let outpath = match file.enclosed_name() {
+        Some(path) => path_out.join(path),
+        None => return Ok(()),
+    };
+    if file.name().ends_with('/') {
+        debug!(logger, "creating directory {}", outpath.display());
+        create_dir_all(&outpath)?;
+    } else {
+        debug!(
+            logger,
+            "extracting file {} to {} ({} bytes)",
+            file.name(),
+            outpath.display(),
+            file.size()
+        );
+        if let Some(p) = outpath.parent() {
+            if !p.exists() {
+                debug!(logger, "creating parent dir {}", p.display());
+                create_dir_all(p)?;
             }
-            let mut outfile = File::create(&outpath)?;
-            std::io::copy(&mut file, &mut outfile)?;
         }
+        let mut outfile = File::create(&outpath)?;
+        io::copy(file, &mut outfile)?;
     }
 
     Ok(())
 }
 
+/// Read from `reader` until `buffer` is full or the end of the stream is reached, returning the
+/// number of bytes read
+fn _read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read_count = 0;
+    while read_count < buffer.len() {
+        match reader.read(&mut buffer[read_count..])? {
+            0 => break,
+            n => read_count += n,
+        }
+    }
+    Ok(read_count)
+}
+
 fn _get_cypher_secrets(
     password: &str,
     salt_str: &str,
     nonce_str: &str,
+    scrypt_params: ScryptParams,
 ) -> Result<CypherSecrets, Error> {
-    // hash password using scrypt with the provided salt
+    // hash password using scrypt with the provided salt and parameters
     let password_bytes = password.as_bytes();
     let salt = Salt::from_b64(salt_str).map_err(InternalError::from)?;
     let password_hash = Scrypt
-        .hash_password(password_bytes, salt)
+        .hash_password_customized(
+            password_bytes,
+            None,
+            None,
+            Params::try_from(scrypt_params)?,
+            salt,
+        )
         .map_err(InternalError::from)?;
     let hash_output = password_hash
         .hash
@@ -299,85 +1492,3 @@ fn _get_cypher_secrets(
 
     Ok(CypherSecrets { key, nonce })
 }
-
-fn _encrypt_file(
-    path_cleartext: &PathBuf,
-    path_encrypted: &PathBuf,
-    password: &str,
-    salt_str: &str,
-    nonce_str: &str,
-) -> Result<(), Error> {
-    let cypher_secrets = _get_cypher_secrets(password, salt_str, nonce_str)?;
-
-    // - XChacha20Poly1305 is fast, requires no special hardware and supports stream operation
-    // - stream mode required as files to encrypt may be big, so avoiding a memory buffer
-
-    // setup
-    let aead = XChaCha20Poly1305::new(&cypher_secrets.key);
-    let nonce = GenericArray::from_slice(&cypher_secrets.nonce);
-    let mut stream_encryptor = stream::EncryptorBE32::from_aead(aead, nonce);
-    let mut buffer = [0u8; BACKUP_BUFFER_LEN_ENCRYPT];
-    let mut source_file = File::open(path_cleartext)?;
-    let mut destination_file = File::create(path_encrypted)?;
-
-    // encrypt file
-    loop {
-        let read_count = source_file.read(&mut buffer)?;
-        if read_count == BACKUP_BUFFER_LEN_ENCRYPT {
-            let ciphertext = stream_encryptor
-                .encrypt_next(buffer.as_slice())
-                .map_err(|e| InternalError::AeadError(e.to_string()))?;
-            destination_file.write_all(&ciphertext)?;
-        } else {
-            let ciphertext = stream_encryptor
-                .encrypt_last(&buffer[..read_count])
-                .map_err(|e| InternalError::AeadError(e.to_string()))?;
-            destination_file.write_all(&ciphertext)?;
-            break;
-        }
-    }
-
-    // remove cleartext source file
-    remove_file(path_cleartext)?;
-
-    Ok(())
-}
-
-fn _decrypt_file(
-    path_encrypted: &PathBuf,
-    path_cleartext: &PathBuf,
-    password: &str,
-    salt_str: &str,
-    nonce_str: &str,
-) -> Result<(), Error> {
-    let cypher_secrets = _get_cypher_secrets(password, salt_str, nonce_str)?;
-
-    // setup
-    let aead = XChaCha20Poly1305::new(&cypher_secrets.key);
-    let nonce = GenericArray::from_slice(&cypher_secrets.nonce);
-    let mut stream_decryptor = stream::DecryptorBE32::from_aead(aead, nonce);
-    let mut buffer = [0u8; BACKUP_BUFFER_LEN_DECRYPT];
-    let mut source_file = File::open(path_encrypted)?;
-    let mut destination_file = File::create(path_cleartext)?;
-
-    // decrypt file
-    loop {
-        let read_count = source_file.read(&mut buffer)?;
-        if read_count == BACKUP_BUFFER_LEN_DECRYPT {
-            let cleartext = stream_decryptor
-                .decrypt_next(buffer.as_slice())
-                .map_err(|_| Error::WrongPassword)?;
-            destination_file.write_all(&cleartext)?;
-        } else if read_count == 0 {
-            break;
-        } else {
-            let cleartext = stream_decryptor
-                .decrypt_last(&buffer[..read_count])
-                .map_err(|_| Error::WrongPassword)?;
-            destination_file.write_all(&cleartext)?;
-            break;
-        }
-    }
-
-    Ok(())
-}
@@ -0,0 +1,207 @@
+use bdk::bitcoin::util::bip32::ExtendedPubKey;
+use slog::Logger;
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use crate::utils::{register_panic_logger, setup_logger, unregister_panic_logger};
+use crate::wallet::{SharedResources, WalletData};
+use crate::{Error, Wallet};
+
+const WALLET_MANAGER_LOG_FILE: &str = "manager.log";
+
+#[derive(Default)]
+struct OpenWallets {
+    wallets: HashMap<String, Arc<Wallet>>,
+    // fingerprints of the open wallets, the most recently used last
+    lru: VecDeque<String>,
+    // every instance created by the manager, so that an evicted wallet still in use elsewhere
+    // is handed out again instead of being opened twice
+    instances: HashMap<String, Weak<Wallet>>,
+}
+
+impl OpenWallets {
+    fn touch(&mut self, fingerprint: &str) {
+        self.lru.retain(|f| f != fingerprint);
+        self.lru.push_back(fingerprint.to_string());
+    }
+
+    fn remove(&mut self, fingerprint: &str) -> Option<Arc<Wallet>> {
+        self.lru.retain(|f| f != fingerprint);
+        self.wallets.remove(fingerprint)
+    }
+
+    // the open instance of the wallet, or an evicted one still in use elsewhere
+    fn get(&self, fingerprint: &str) -> Option<Arc<Wallet>> {
+        self.wallets
+            .get(fingerprint)
+            .cloned()
+            .or_else(|| self.instances.get(fingerprint).and_then(Weak::upgrade))
+    }
+
+    fn insert(&mut self, fingerprint: &str, wallet: Arc<Wallet>) {
+        self.instances
+            .retain(|_, instance| instance.strong_count() > 0);
+        self.instances
+            .insert(fingerprint.to_string(), Arc::downgrade(&wallet));
+        self.wallets.insert(fingerprint.to_string(), wallet);
+        self.touch(fingerprint);
+    }
+}
+
+/// A manager for hosting many RGB wallets in one process
+///
+/// Wallets opened through a `WalletManager` share the indexer connections (one per electrum URL)
+/// and the proxy HTTP client, while each wallet still gets its own
+/// [`Online`](crate::wallet::Online) data. Indexer connections are closed once no wallet uses them
+/// and are replaced when a wallet going online finds them down.
+///
+/// At most `max_open_wallets` wallets are kept open: when the limit is reached, the least recently
+/// used wallet is closed, releasing its databases and connections once no other reference to it
+/// is alive.
+///
+/// Panics are logged to the manager log, in the manager data directory.
+pub struct WalletManager {
+    data_dir: PathBuf,
+    max_open_wallets: usize,
+    logger: Logger,
+    pub(super) shared: Arc<SharedResources>,
+    open_wallets: Mutex<OpenWallets>,
+    // locks serializing the concurrent openings of the same wallet, by fingerprint
+    pub(super) opening_wallets: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl WalletManager {
+    /// Create a new wallet manager for the wallets in `data_dir`
+    ///
+    /// A `max_open_wallets` of 0 is treated as 1.
+    pub fn new(data_dir: String, max_open_wallets: usize) -> Result<Self, Error> {
+        let data_dir = PathBuf::from(data_dir);
+        if !data_dir.exists() {
+            return Err(Error::InexistentDataDir);
+        }
+        let data_dir = fs::canonicalize(data_dir)?;
+        let logger = setup_logger(data_dir.clone(), Some(WALLET_MANAGER_LOG_FILE))?;
+        register_panic_logger(data_dir.clone(), logger.clone());
+        info!(logger, "New wallet manager in '{:?}'", data_dir);
+
+        Ok(WalletManager {
+            data_dir,
+            max_open_wallets: max_open_wallets.max(1),
+            logger,
+            shared: Arc::new(SharedResources::new()?),
+            open_wallets: Mutex::new(OpenWallets::default()),
+            opening_wallets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn _open_wallets(&self) -> MutexGuard<'_, OpenWallets> {
+        self.open_wallets
+            .lock()
+            .expect("open wallets mutex should not be poisoned")
+    }
+
+    fn _opening_wallets(&self) -> MutexGuard<'_, HashMap<String, Arc<Mutex<()>>>> {
+        self.opening_wallets
+            .lock()
+            .expect("opening wallets mutex should not be poisoned")
+    }
+
+    /// Open the wallet described by the provided [`WalletData`] and return it
+    ///
+    /// The wallet is opened in the manager data directory, the `data_dir` field of the provided
+    /// [`WalletData`] is ignored. If the wallet is already open, the open instance is returned.
+    pub fn open_wallet(&self, wallet_data: WalletData) -> Result<Arc<Wallet>, Error> {
+        let fingerprint = ExtendedPubKey::from_str(&wallet_data.pubkey)?
+            .fingerprint()
+            .to_string();
+        info!(self.logger, "Opening wallet {}...", fingerprint);
+
+        // wallets are opened without holding the open wallets mutex, so that opening a wallet
+        // doesn't block the other ones, only the openings of the same wallet are serialized
+        let opening = self
+            ._opening_wallets()
+            .entry(fingerprint.clone())
+            .or_default()
+            .clone();
+        let opening_guard = opening
+            .lock()
+            .expect("opening wallet mutex should not be poisoned");
+        let existing = self._open_wallets().get(&fingerprint);
+        let result = match existing {
+            Some(wallet) => Ok(wallet),
+            None => {
+                let wallet_data = WalletData {
+                    data_dir: self.data_dir.to_string_lossy().to_string(),
+                    ..wallet_data
+                };
+                Wallet::_new(wallet_data, Some(self.shared.clone()), None).map(Arc::new)
+            }
+        };
+        if let Ok(wallet) = &result {
+            let mut open_wallets = self._open_wallets();
+            open_wallets.insert(&fingerprint, wallet.clone());
+            while open_wallets.lru.len() > self.max_open_wallets {
+                let evicted = open_wallets
+                    .lru
+                    .pop_front()
+                    .expect("there are more open wallets than the limit");
+                debug!(
+                    self.logger,
+                    "Closing least recently used wallet {}", evicted
+                );
+                open_wallets.wallets.remove(&evicted);
+            }
+        }
+        drop(opening_guard);
+        let mut opening_wallets = self._opening_wallets();
+        // references to the lock are only taken and released with the map locked, so it's only
+        // referenced by the map when no other opening is in progress
+        drop(opening);
+        if opening_wallets
+            .get(&fingerprint)
+            .map_or(false, |o| Arc::strong_count(o) == 1)
+        {
+            opening_wallets.remove(&fingerprint);
+        }
+        drop(opening_wallets);
+        let wallet = result?;
+
+        info!(self.logger, "Open wallet completed");
+        Ok(wallet)
+    }
+
+    /// Return the open wallet with the provided fingerprint, if any
+    pub fn get_wallet(&self, fingerprint: &str) -> Option<Arc<Wallet>> {
+        let mut open_wallets = self._open_wallets();
+        let wallet = open_wallets.wallets.get(fingerprint).cloned();
+        if wallet.is_some() {
+            open_wallets.touch(fingerprint);
+        }
+        wallet
+    }
+
+    /// Close the wallet with the provided fingerprint, returning whether it was open
+    ///
+    /// The wallet resources are released once no other reference to it is alive.
+    pub fn close_wallet(&self, fingerprint: &str) -> bool {
+        info!(self.logger, "Closing wallet {}...", fingerprint);
+        let closed = self._open_wallets().remove(fingerprint).is_some();
+        info!(self.logger, "Close wallet completed");
+        closed
+    }
+
+    /// Return the fingerprints of the open wallets, the most recently used first
+    pub fn list_open_wallets(&self) -> Vec<String> {
+        self._open_wallets().lru.iter().rev().cloned().collect()
+    }
+}
+
+impl Drop for WalletManager {
+    fn drop(&mut self) {
+        unregister_panic_logger(&self.data_dir);
+    }
+}
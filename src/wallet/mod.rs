@@ -8,68 +8,73 @@ use bdk::bitcoin::secp256k1::Secp256k1;
 use bdk::bitcoin::util::bip32::ExtendedPubKey;
 use bdk::bitcoin::{
     psbt::Psbt as BdkPsbt, Address as BdkAddress, Network as BdkNetwork, OutPoint as BdkOutPoint,
-    Script as BdkScript, Transaction as BdkTransaction,
+    Script as BdkScript, Transaction as BdkTransaction, TxOut as BdkTxOut,
 };
-use bdk::blockchain::{
-    Blockchain, ConfigurableBlockchain, ElectrumBlockchain, ElectrumBlockchainConfig,
-};
-use bdk::database::any::SledDbConfiguration;
+use bdk::blockchain::electrum::ElectrumBlockchainConfig;
+use bdk::blockchain::{Blockchain, ConfigurableBlockchain, ElectrumBlockchain};
 use bdk::database::{
-    AnyDatabase, BatchDatabase, ConfigurableDatabase as BdkConfigurableDatabase, MemoryDatabase,
+    AnyDatabase, BatchDatabase, BatchOperations, Database as BdkDatabase, MemoryDatabase,
 };
 use bdk::descriptor::IntoWalletDescriptor;
-use bdk::keys::bip39::{Language, Mnemonic};
-use bdk::keys::{DerivableKey, ExtendedKey};
+use bdk::electrum_client::{
+    Client as BdkElectrumClient, ConfigBuilder as BdkElectrumConfigBuilder,
+    Error as BdkElectrumError, Socks5Config as BdkSocks5Config,
+};
+use bdk::keys::bip39::Mnemonic;
+use bdk::keys::ExtendedKey;
+use bdk::sled;
 use bdk::wallet::AddressIndex;
 pub use bdk::BlockTime;
-use bdk::{FeeRate, KeychainKind, LocalUtxo, SignOptions, SyncOptions, Wallet as BdkWallet};
+use bdk::{FeeRate, KeychainKind, LocalUtxo, SyncOptions, Wallet as BdkWallet};
 use bitcoin::hashes::{sha256, Hash as Sha256Hash};
 use bitcoin::psbt::PartiallySignedTransaction;
 use bitcoin::{Address, OutPoint};
 use bitcoin::{ScriptBuf, Txid};
 use bp::seals::txout::blind::ChainBlindSeal;
-use bp::seals::txout::{CloseMethod, ExplicitSeal};
+use bp::seals::txout::{CloseMethod, ExplicitSeal, TxPtr};
 use bp::Outpoint as RgbOutpoint;
 use bp::Txid as BpTxid;
-use electrum_client::{Client as ElectrumClient, ConfigBuilder, ElectrumApi, Param};
+use electrum_client::{Client as ElectrumClient, ConfigBuilder, ElectrumApi, Param, Socks5Config};
 use futures::executor::block_on;
 use reqwest::blocking::Client as RestClient;
+use reqwest::Url as RestUrl;
 use rgb::BlockchainResolver;
 use rgb_core::validation::Validity;
-use rgb_core::{Assign, Operation, Opout, SecretSeal, Transition};
+use rgb_core::{Assign, Operation, Opout, SecretSeal, Transition, BLANK_TRANSITION_ID};
 use rgb_lib_migration::{Migrator, MigratorTrait};
 use rgb_schemata::{cfa_rgb25, cfa_schema, nia_rgb20, nia_schema};
-use rgbstd::containers::{Bindle, BuilderSeal, Transfer as RgbTransfer};
-use rgbstd::contract::{ContractId, GenesisSeal, GraphSeal};
+use rgbstd::containers::{Bindle, BuilderSeal, Consignment, Transfer as RgbTransfer};
+use rgbstd::contract::{ContractId, GenesisSeal, GraphSeal, WitnessOrd};
 use rgbstd::interface::{rgb20, rgb25, ContractBuilder, ContractIface, Rgb20, Rgb25, TypedState};
+use rgbstd::resolvers::ResolveHeight;
 use rgbstd::stl::{
     Amount, AssetNaming, Attachment, ContractData, Details, DivisibleAssetSpec, MediaType, Name,
     Precision, RicardianContract, Ticker, Timestamp,
 };
-use rgbstd::validation::ConsignmentApi;
+use rgbstd::validation::{ConsignmentApi, TxResolverError};
 use rgbstd::Txid as RgbTxid;
 use rgbwallet::psbt::opret::OutputOpret;
 use rgbwallet::psbt::{PsbtDbc, RgbExt, RgbInExt};
 use rgbwallet::{Beneficiary, RgbInvoice, RgbTransport};
 use sea_orm::{ActiveValue, ConnectOptions, Database, TryIntoModel};
 use serde::{Deserialize, Serialize};
-use slog::{debug, error, info, Logger};
+use slog::{debug, error, info, warn, Logger};
 use std::cmp::min;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::panic;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, Weak};
+use std::time::{Duration, Instant};
 use strict_encoding::{tn, FieldName, TypeName};
 use strict_types::value::StrictNum;
 use strict_types::StrictVal;
 
-use crate::api::Proxy;
+use crate::api::proxy::JsonRpcResponse;
+use crate::api::{Proxy, ProxyClient, ProxyProtocolVersion};
 use crate::database::entities::asset::{ActiveModel as DbAssetActMod, Model as DbAsset};
 use crate::database::entities::asset_transfer::{
     ActiveModel as DbAssetTransferActMod, Model as DbAssetTransfer,
@@ -78,6 +83,9 @@ use crate::database::entities::batch_transfer::{
     ActiveModel as DbBatchTransferActMod, Model as DbBatchTransfer,
 };
 use crate::database::entities::coloring::{ActiveModel as DbColoringActMod, Model as DbColoring};
+use crate::database::entities::prepared_psbt::{
+    ActiveModel as DbPreparedPsbtActMod, Model as DbPreparedPsbt,
+};
 use crate::database::entities::transfer::{ActiveModel as DbTransferActMod, Model as DbTransfer};
 use crate::database::entities::transfer_transport_endpoint::{
     ActiveModel as DbTransferTransportEndpointActMod, Model as DbTransferTransportEndpoint,
@@ -86,22 +94,34 @@ use crate::database::entities::transport_endpoint::{
     ActiveModel as DbTransportEndpointActMod, Model as DbTransportEndpoint,
 };
 use crate::database::entities::txo::{ActiveModel as DbTxoActMod, Model as DbTxo};
+use crate::database::entities::wallet_config::{
+    ActiveModel as DbWalletConfigActMod, Model as DbWalletConfig,
+};
 use crate::database::entities::wallet_transaction::ActiveModel as DbWalletTransactionActMod;
 use crate::database::enums::{
-    AssetSchema, ColoringType, RecipientType, TransferStatus, TransportType, WalletTransactionType,
+    AssetSchema, ColoringType, PreparedPsbtType, RecipientType, TransferStatus, TransportType,
+    WalletTransactionType,
 };
 use crate::database::{
     DbData, LocalRecipient, LocalRgbAllocation, LocalTransportEndpoint, LocalUnspent,
     RgbLibDatabase, TransferData,
 };
 use crate::error::{Error, InternalError};
+use crate::keys::get_extended_key;
 use crate::utils::{
-    calculate_descriptor_from_xprv, calculate_descriptor_from_xpub, get_txid, load_rgb_runtime,
-    now, setup_logger, BitcoinNetwork, RgbRuntime, LOG_FILE,
+    calculate_descriptor_from_xprv, calculate_descriptor_from_xpub, get_txid, is_onion_url,
+    load_cached_rgb_runtime, now, register_panic_logger, setup_logger, setup_logger_with_config,
+    unregister_panic_logger, BitcoinNetwork, CustomLogger, LogConfig, RgbRuntime, RgbRuntimeCache,
 };
 
 const RGB_DB_NAME: &str = "rgb_db";
 const BDK_DB_NAME: &str = "bdk_db";
+const BDK_SYNC_TREE_NAME: &str = "bdk_sync";
+
+// key prefixes of the BDK sled database: scripts cached by path and by script pubkey
+const BDK_SCRIPT_KEY_PREFIXES: [&[u8]; 2] = [b"p", b"s"];
+// key prefixes of the BDK sled database: UTXOs, raw TXs, wallet TXs and last sync time
+const BDK_SYNC_KEY_PREFIXES: [&[u8]; 4] = [b"u", b"r", b"t", b"l"];
 
 const KEYCHAIN_RGB: u8 = 9;
 const KEYCHAIN_BTC: u8 = 1;
@@ -133,15 +153,32 @@ const DURATION_SEND_TRANSFER: i64 = 3600;
 const DURATION_RCV_TRANSFER: u32 = 86400;
 
 const ELECTRUM_TIMEOUT: u8 = 4;
+const ELECTRUM_STOP_GAP: u32 = 20;
 const PROXY_TIMEOUT: u8 = 90;
-
-const PROXY_PROTOCOL_VERSION: &str = "0.2";
+const DURATION_PROXY_PROTOCOL_CACHE: u32 = 3600;
+const DURATION_PREPARED_PSBT: u32 = 86400;
 
 pub(crate) const SCHEMA_ID_NIA: &str =
     "urn:lnp-bp:sc:BEiLYE-am9WhTW1-oK8cpvw4-FEMtzMrf-mKocuGZn-qWK6YF#ginger-parking-nirvana";
 pub(crate) const SCHEMA_ID_CFA: &str =
     "urn:lnp-bp:sc:4nfgJ2-jkeTRQuG-uTet6NSW-Fy1sFTU8-qqrN2uY2-j6S5rv#ravioli-justin-brave";
 
+/// A mismatch between the RGB allocations in the wallet DB and the state owned in the RGB runtime
+/// for an asset on a wallet UTXO, see [`Wallet::audit_allocations`]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AllocationDiscrepancy {
+    /// Outpoint of the UTXO
+    pub outpoint: Outpoint,
+    /// ID of the asset
+    pub asset_id: String,
+    /// Amount of the settled allocations in the wallet DB
+    pub settled_amount: u64,
+    /// Amount of the incoming allocations in the wallet DB that are not settled yet
+    pub unsettled_amount: u64,
+    /// Amount owned in the RGB runtime
+    pub runtime_amount: u64,
+}
+
 /// The interface of an asset
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum AssetIface {
@@ -161,10 +198,6 @@ impl AssetIface {
         wallet: &Wallet,
         asset: &DbAsset,
         assets_dir: PathBuf,
-        asset_transfers: Option<Vec<DbAssetTransfer>>,
-        batch_transfers: Option<Vec<DbBatchTransfer>>,
-        colorings: Option<Vec<DbColoring>>,
-        txos: Option<Vec<DbTxo>>,
     ) -> Result<AssetType, Error> {
         let mut data_paths = vec![];
         let asset_dir = assets_dir.join(asset.asset_id.clone());
@@ -176,13 +209,7 @@ impl AssetIface {
                 data_paths.push(Media { file_path, mime });
             }
         }
-        let balance = wallet.database.get_asset_balance(
-            asset.asset_id.clone(),
-            asset_transfers,
-            batch_transfers,
-            colorings,
-            txos,
-        )?;
+        let balance = wallet.database.get_asset_balance(asset.asset_id.clone())?;
         let issued_supply = asset.issued_supply.parse::<u64>().unwrap();
         Ok(match &self {
             AssetIface::RGB20 => AssetType::AssetNIA(AssetNIA {
@@ -266,20 +293,8 @@ impl AssetNIA {
         wallet: &Wallet,
         asset: &DbAsset,
         assets_dir: PathBuf,
-        asset_transfers: Option<Vec<DbAssetTransfer>>,
-        batch_transfers: Option<Vec<DbBatchTransfer>>,
-        colorings: Option<Vec<DbColoring>>,
-        txos: Option<Vec<DbTxo>>,
     ) -> Result<AssetNIA, Error> {
-        match AssetIface::RGB20.get_asset_details(
-            wallet,
-            asset,
-            assets_dir,
-            asset_transfers,
-            batch_transfers,
-            colorings,
-            txos,
-        )? {
+        match AssetIface::RGB20.get_asset_details(wallet, asset, assets_dir)? {
             AssetType::AssetNIA(asset) => Ok(asset),
             _ => unreachable!("impossible"),
         }
@@ -346,20 +361,8 @@ impl AssetCFA {
         wallet: &Wallet,
         asset: &DbAsset,
         assets_dir: PathBuf,
-        asset_transfers: Option<Vec<DbAssetTransfer>>,
-        batch_transfers: Option<Vec<DbBatchTransfer>>,
-        colorings: Option<Vec<DbColoring>>,
-        txos: Option<Vec<DbTxo>>,
     ) -> Result<AssetCFA, Error> {
-        match AssetIface::RGB25.get_asset_details(
-            wallet,
-            asset,
-            assets_dir,
-            asset_transfers,
-            batch_transfers,
-            colorings,
-            txos,
-        )? {
+        match AssetIface::RGB25.get_asset_details(wallet, asset, assets_dir)? {
             AssetType::AssetCFA(asset) => Ok(asset),
             _ => unreachable!("impossible"),
         }
@@ -411,7 +414,7 @@ pub struct Balance {
 }
 
 /// The bitcoin balances for the vanilla and colored wallets
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct BtcBalance {
     /// Extra funds that will never hold RGB assets
     pub vanilla: Balance,
@@ -419,6 +422,17 @@ pub struct BtcBalance {
     pub colored: Balance,
 }
 
+/// The result of a wallet consistency diagnosis, see [`Wallet::diagnose`]
+///
+/// The wallet is consistent if both lists are empty.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ConsistencyReport {
+    /// UTXOs that are unspent for the wallet DB but have been spent outside of the wallet
+    pub inconsistent_utxos: Vec<InconsistentUtxo>,
+    /// Assets known to the wallet DB but missing from the RGB runtime
+    pub missing_assets: Vec<MissingAsset>,
+}
+
 /// Data to receive an asset
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ReceiveData {
@@ -484,18 +498,31 @@ impl TryFrom<RgbTransport> for TransportEndpoint {
 }
 
 /// Supported database types
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum DatabaseType {
     /// A SQLite database
     Sqlite,
 }
 
+/// A UTXO that is unspent for the wallet DB but has been spent outside of the wallet
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InconsistentUtxo {
+    /// Outpoint of the UTXO
+    pub outpoint: Outpoint,
+    /// RGB allocations lost with the UTXO
+    pub rgb_allocations: Vec<RgbAllocation>,
+    /// IDs of the pending transfers involving the UTXO
+    pub pending_transfer_idxs: Vec<i32>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct InfoBatchTransfer {
     change_utxo_idx: i32,
     blank_allocations: HashMap<String, u64>,
     donation: bool,
     min_confirmations: u8,
+    #[serde(default)]
+    redundant_posting: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -649,13 +676,367 @@ pub struct Online {
     pub electrum_url: String,
 }
 
+/// Credentials sent to an RGB proxy server
+///
+/// Credentials are configured in [`WalletData::proxy_auth`] and are never included in invoices
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProxyAuth {
+    /// Optional bearer token, sent in the `Authorization` header
+    pub bearer_token: Option<String>,
+    /// Additional HTTP headers (e.g. an API key), mapped by header name
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+// credentials are secret, so they're never printed (e.g. in the logs)
+impl fmt::Debug for ProxyAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyAuth")
+            .field(
+                "bearer_token",
+                &self.bearer_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "headers",
+                &self
+                    .headers
+                    .keys()
+                    .map(|k| (k, "<redacted>"))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl ProxyAuth {
+    fn secrets(&self) -> Vec<String> {
+        self.bearer_token
+            .iter()
+            .chain(self.headers.values())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Traffic to be routed through the SOCKS5 proxy of a [`NetworkConfig`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum NetworkRoute {
+    /// Only the traffic to the electrum server
+    Indexer,
+    /// Only the traffic to the RGB proxy servers
+    Proxy,
+    /// All the traffic
+    #[default]
+    All,
+}
+
+/// Credentials for a SOCKS5 proxy
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Socks5Credentials {
+    /// SOCKS5 username
+    pub username: String,
+    /// SOCKS5 password
+    pub password: String,
+}
+
+// the password is a secret, so it's never printed (e.g. in the logs)
+impl fmt::Debug for Socks5Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Socks5Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Network configuration of a wallet, to route its traffic through a SOCKS5 proxy (e.g. Tor)
+///
+/// Host names are resolved by the SOCKS5 proxy, so onion addresses can be used as electrum URL
+/// and transport endpoints for the routed traffic
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NetworkConfig {
+    /// Address of the SOCKS5 proxy (e.g. `127.0.0.1:9050` for a local Tor daemon)
+    pub socks5_address: String,
+    /// Optional SOCKS5 credentials
+    pub socks5_credentials: Option<Socks5Credentials>,
+    /// Traffic to be routed through the SOCKS5 proxy
+    #[serde(default)]
+    pub route: NetworkRoute,
+}
+
+impl NetworkConfig {
+    fn routes_indexer(&self) -> bool {
+        matches!(self.route, NetworkRoute::Indexer | NetworkRoute::All)
+    }
+
+    fn routes_proxy(&self) -> bool {
+        matches!(self.route, NetworkRoute::Proxy | NetworkRoute::All)
+    }
+}
+
+/// Retry policy for the calls to the RGB proxy servers
+///
+/// Calls failing because the proxy cannot be reached are retried with an exponential backoff
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProxyRetryConfig {
+    /// Maximum number of retries after a failed call
+    pub max_retries: u8,
+    /// Delay before the first retry, in milliseconds, doubled at each following retry
+    pub initial_backoff_ms: u64,
+    /// Maximum delay between retries, in milliseconds
+    pub max_backoff_ms: u64,
+}
+
+impl Default for ProxyRetryConfig {
+    fn default() -> Self {
+        ProxyRetryConfig {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8000,
+        }
+    }
+}
+
+impl ProxyRetryConfig {
+    fn backoff(&self, retry: u8) -> Duration {
+        let backoff_ms = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(retry as u32));
+        Duration::from_millis(backoff_ms.min(self.max_backoff_ms))
+    }
+}
+
+/// Configurable parameters of a wallet
+///
+/// The configuration is persisted in the wallet DB, the default one is used until a different one
+/// is set with [`Wallet::set_config`]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct WalletConfig {
+    /// Minimum amount of bitcoins (in sats) needed to create UTXOs
+    pub min_btc_required: u64,
+    /// Default size (in sats) of the UTXOs created by [`Wallet::create_utxos`]
+    pub utxo_size: u32,
+    /// Default number of UTXOs created by [`Wallet::create_utxos`]
+    pub utxo_num: u8,
+    /// Max number of transport endpoints accepted for a transfer
+    pub max_transport_endpoints: u8,
+    /// Minimum accepted fee rate (in sat/vB)
+    pub min_fee_rate: f32,
+    /// Maximum accepted fee rate (in sat/vB)
+    pub max_fee_rate: f32,
+    /// Seconds after which a pending outgoing transfer expires
+    pub duration_send_transfer: i64,
+    /// Default seconds after which a pending incoming transfer expires
+    pub duration_rcv_transfer: u32,
+    /// Timeout (in seconds) of the requests to the electrum server
+    pub electrum_timeout: u8,
+    /// Timeout (in seconds) of the requests to the RGB proxy servers
+    pub proxy_timeout: u8,
+    /// Number of consecutive unused addresses after which the wallet stops looking for
+    /// transactions during a sync
+    pub electrum_stop_gap: u32,
+    /// Seconds the protocol version of a proxy server is cached before being checked again
+    pub duration_proxy_protocol_cache: u32,
+    /// Seconds after which a PSBT prepared by a `*_begin` call can no longer be completed by the
+    /// matching `*_end` one, `None` to never expire prepared PSBTs (e.g. for slow offline signing)
+    pub duration_prepared_psbt: Option<u32>,
+}
+
+impl Default for WalletConfig {
+    fn default() -> Self {
+        WalletConfig {
+            min_btc_required: MIN_BTC_REQUIRED,
+            utxo_size: UTXO_SIZE,
+            utxo_num: UTXO_NUM,
+            max_transport_endpoints: MAX_TRANSPORT_ENDPOINTS,
+            min_fee_rate: MIN_FEE_RATE,
+            max_fee_rate: MAX_FEE_RATE,
+            duration_send_transfer: DURATION_SEND_TRANSFER,
+            duration_rcv_transfer: DURATION_RCV_TRANSFER,
+            electrum_timeout: ELECTRUM_TIMEOUT,
+            proxy_timeout: PROXY_TIMEOUT,
+            electrum_stop_gap: ELECTRUM_STOP_GAP,
+            duration_proxy_protocol_cache: DURATION_PROXY_PROTOCOL_CACHE,
+            duration_prepared_psbt: Some(DURATION_PREPARED_PSBT),
+        }
+    }
+}
+
+impl From<DbWalletConfig> for WalletConfig {
+    fn from(x: DbWalletConfig) -> WalletConfig {
+        WalletConfig {
+            min_btc_required: x.min_btc_required as u64,
+            utxo_size: x.utxo_size,
+            utxo_num: x.utxo_num,
+            max_transport_endpoints: x.max_transport_endpoints,
+            min_fee_rate: x.min_fee_rate,
+            max_fee_rate: x.max_fee_rate,
+            duration_send_transfer: x.duration_send_transfer,
+            duration_rcv_transfer: x.duration_rcv_transfer,
+            electrum_timeout: x.electrum_timeout,
+            proxy_timeout: x.proxy_timeout,
+            electrum_stop_gap: x.electrum_stop_gap,
+            duration_proxy_protocol_cache: x.duration_proxy_protocol_cache,
+            duration_prepared_psbt: x.duration_prepared_psbt,
+        }
+    }
+}
+
+impl WalletConfig {
+    // electrum parameters, a change of which requires reconnecting to the electrum server
+    fn electrum_params(&self) -> (u8, u32) {
+        (self.electrum_timeout, self.electrum_stop_gap)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |details: &str| {
+            Err(Error::InvalidWalletConfig {
+                details: details.to_string(),
+            })
+        };
+        if self.utxo_size == 0 {
+            return invalid("utxo_size must be greater than 0");
+        }
+        if self.utxo_num == 0 {
+            return invalid("utxo_num must be greater than 0");
+        }
+        if self.max_transport_endpoints == 0 {
+            return invalid("max_transport_endpoints must be greater than 0");
+        }
+        if !self.min_fee_rate.is_finite() || self.min_fee_rate <= 0.0 {
+            return invalid("min_fee_rate must be greater than 0");
+        }
+        if !self.max_fee_rate.is_finite() || self.max_fee_rate < self.min_fee_rate {
+            return invalid("max_fee_rate must not be lower than min_fee_rate");
+        }
+        if self.duration_send_transfer <= 0 {
+            return invalid("duration_send_transfer must be greater than 0");
+        }
+        if self.electrum_timeout == 0 {
+            return invalid("electrum_timeout must be greater than 0");
+        }
+        if self.proxy_timeout == 0 {
+            return invalid("proxy_timeout must be greater than 0");
+        }
+        if self.electrum_stop_gap == 0 {
+            return invalid("electrum_stop_gap must be greater than 0");
+        }
+        if self.duration_prepared_psbt == Some(0) {
+            return invalid("duration_prepared_psbt must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+// connections to the indexer, possibly shared between wallets
 struct OnlineData {
-    id: u64,
     bdk_blockchain: ElectrumBlockchain,
     electrum_url: String,
     electrum_client: ElectrumClient,
 }
 
+/// Resources shared by the wallets opened through the same [`WalletManager`]
+pub(crate) struct SharedResources {
+    rest_client: RestClient,
+    // connections are only referenced weakly, so that they are closed once no wallet uses them
+    online_data: Mutex<HashMap<String, Weak<OnlineData>>>,
+}
+
+impl SharedResources {
+    pub(crate) fn new() -> Result<Self, Error> {
+        Ok(SharedResources {
+            rest_client: _build_rest_client(None)?,
+            online_data: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+// the timeout is set on each request, from the config of the wallet making it
+fn _build_rest_client(network_config: Option<&NetworkConfig>) -> Result<RestClient, Error> {
+    let mut builder = RestClient::builder();
+    if let Some(config) = network_config.filter(|c| c.routes_proxy()) {
+        let invalid_network_config = |e: reqwest::Error| Error::InvalidNetworkConfig {
+            details: e.to_string(),
+        };
+        // socks5h makes the SOCKS5 proxy resolve host names, which is required for onion addresses
+        let mut proxy = reqwest::Proxy::all(format!("socks5h://{}", config.socks5_address))
+            .map_err(invalid_network_config)?;
+        if let Some(credentials) = &config.socks5_credentials {
+            proxy = proxy.basic_auth(&credentials.username, &credentials.password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+// atomically copy the BDK database entries with the given key prefixes, replacing the existing
+// ones if requested
+fn _copy_bdk_entries(
+    from: &sled::Tree,
+    to: &sled::Tree,
+    prefixes: &[&[u8]],
+    replace: bool,
+) -> Result<(), Error> {
+    let mut batch = sled::Batch::default();
+    let mut fill_batch = || -> Result<(), sled::Error> {
+        for prefix in prefixes {
+            if replace {
+                for key in to.scan_prefix(prefix).keys() {
+                    batch.remove(key?);
+                }
+            }
+            for entry in from.scan_prefix(prefix) {
+                let (key, value) = entry?;
+                batch.insert(key, value);
+            }
+        }
+        Ok(())
+    };
+    fill_batch()
+        .and_then(|_| to.apply_batch(batch))
+        .map_err(|e| InternalError::from(bdk::Error::from(e)))?;
+    Ok(())
+}
+
+// return the output spent by the given input of a PSBT, if known, checking it exists
+fn _psbt_prev_output(psbt: &BdkPsbt, input_index: usize) -> Result<Option<BdkTxOut>, Error> {
+    let invalid_psbt = |details: String| Error::InvalidPsbt { details };
+    let txin = psbt
+        .unsigned_tx
+        .input
+        .get(input_index)
+        .ok_or_else(|| invalid_psbt(format!("missing input {input_index}")))?;
+    let Some(input) = psbt.inputs.get(input_index) else {
+        return Ok(None);
+    };
+    if let Some(witness_utxo) = &input.witness_utxo {
+        return Ok(Some(witness_utxo.clone()));
+    }
+    match &input.non_witness_utxo {
+        Some(prev_tx) => prev_tx
+            .output
+            .get(txin.previous_output.vout as usize)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| {
+                invalid_psbt(format!(
+                    "input {input_index} spends inexistent output {}",
+                    txin.previous_output
+                ))
+            }),
+        None => Ok(None),
+    }
+}
+
+/// An asset known to the wallet DB but missing from the RGB runtime
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MissingAsset {
+    /// ID of the asset
+    pub asset_id: String,
+    /// Whether a stored consignment is available to re-import the asset contract
+    pub consignment_available: bool,
+}
+
 /// Bitcoin transaction outpoint
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Outpoint {
@@ -716,6 +1097,78 @@ impl From<Outpoint> for RgbOutpoint {
     }
 }
 
+/// An RGB allocation involved in an inspected PSBT
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PsbtAllocation {
+    /// Outpoint of the allocation, if known
+    pub outpoint: Option<Outpoint>,
+    /// Blinded UTXO the allocation is assigned to, if the seal is concealed
+    pub recipient_id: Option<String>,
+    /// RGB amount
+    pub amount: u64,
+    /// Defines if the allocation belongs to the wallet
+    pub is_mine: bool,
+}
+
+/// A bitcoin input of an inspected PSBT
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PsbtInput {
+    /// Previous outpoint spent by the input
+    pub outpoint: Outpoint,
+    /// Amount held in satoshi, if the previous output is included in the PSBT
+    pub btc_amount: Option<u64>,
+    /// Defines if the input belongs to the wallet
+    pub is_mine: bool,
+}
+
+/// A bitcoin output of an inspected PSBT
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PsbtOutput {
+    /// Output index
+    pub vout: u32,
+    /// Output script, hex-encoded
+    pub script: String,
+    /// Output address, if the script has an address form
+    pub address: Option<String>,
+    /// Amount in satoshi
+    pub btc_amount: u64,
+    /// Defines if the output belongs to the wallet
+    pub is_mine: bool,
+    /// Defines if the output is an OP_RETURN one
+    pub is_op_return: bool,
+}
+
+/// An RGB state transition included in an inspected PSBT
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PsbtTransition {
+    /// Asset ID
+    pub asset_id: String,
+    /// Defines if the transition is a blank one, moving allocations of an asset that is not being
+    /// sent to the change UTXO
+    pub blank: bool,
+    /// Wallet RGB allocations spent by the transition
+    pub spent_allocations: Vec<PsbtAllocation>,
+    /// RGB allocations created by the transition
+    pub new_allocations: Vec<PsbtAllocation>,
+}
+
+/// The decoded content of a PSBT
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PsbtInspection {
+    /// ID of the unsigned transaction
+    pub txid: String,
+    /// Bitcoin inputs
+    pub inputs: Vec<PsbtInput>,
+    /// Bitcoin outputs
+    pub outputs: Vec<PsbtOutput>,
+    /// Fee in satoshi, if the amounts of all inputs are known
+    pub fee: Option<u64>,
+    /// Opret commitment to the RGB data, hex-encoded, if present
+    pub opret_commitment: Option<String>,
+    /// RGB state transitions
+    pub transitions: Vec<PsbtTransition>,
+}
+
 /// An RGB recipient
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct Recipient {
@@ -734,7 +1187,7 @@ impl Recipient {
 }
 
 /// The information needed to receive RGB assets
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum RecipientData {
     /// A blinded UTXO
     BlindedUTXO(SecretSeal),
@@ -749,6 +1202,27 @@ pub enum RecipientData {
     },
 }
 
+// the blinding is a secret, so it's never printed (e.g. in the logs)
+impl fmt::Debug for RecipientData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecipientData::BlindedUTXO(secret_seal) => {
+                f.debug_tuple("BlindedUTXO").field(secret_seal).finish()
+            }
+            RecipientData::WitnessData {
+                script_buf,
+                amount_sat,
+                blinding,
+            } => f
+                .debug_struct("WitnessData")
+                .field("script_buf", script_buf)
+                .field("amount_sat", amount_sat)
+                .field("blinding", &blinding.map(|_| "<redacted>"))
+                .finish(),
+        }
+    }
+}
+
 impl RecipientData {
     pub(crate) fn recipient_id(&self) -> String {
         match &self {
@@ -758,6 +1232,14 @@ impl RecipientData {
     }
 }
 
+/// Options for sending tokens
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SendOptions {
+    /// Post the consignment (and optional media) to all the usable transport endpoints of each
+    /// recipient, instead of stopping at the first one accepting it
+    pub redundant_posting: bool,
+}
+
 /// A transfer refresh filter
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct RefreshFilter {
@@ -788,6 +1270,17 @@ impl TryFrom<TransferStatus> for RefreshTransferStatus {
     }
 }
 
+/// An action to repair an inconsistent wallet, see [`Wallet::repair`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum RepairAction {
+    /// Mark the inconsistent UTXOs as spent
+    MarkUtxosSpent,
+    /// Fail the pending transfers involving inconsistent UTXOs
+    FailAffectedTransfers,
+    /// Re-import the contracts of missing assets from the stored consignments
+    ReimportContracts,
+}
+
 /// An RGB allocation
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
 pub struct RgbAllocation {
@@ -897,6 +1390,18 @@ impl Transfer {
     }
 }
 
+/// The current status of a user-driven transfer, used to detect status changes
+#[cfg(feature = "daemon")]
+#[derive(Clone, Debug, PartialEq, Eq, sea_orm::FromQueryResult)]
+pub(crate) struct TransferStatusInfo {
+    pub(crate) idx: i32,
+    pub(crate) asset_id: Option<String>,
+    pub(crate) txid: Option<String>,
+    pub(crate) recipient_id: Option<String>,
+    pub(crate) incoming: bool,
+    pub(crate) status: TransferStatus,
+}
+
 /// An RGB transfer transport endpoint
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransferTransportEndpoint {
@@ -906,6 +1411,8 @@ pub struct TransferTransportEndpoint {
     pub transport_type: TransportType,
     /// Whether the endpoint has been used
     pub used: bool,
+    /// Whether the consignment has been posted to the endpoint (outgoing transfers only)
+    pub posted: bool,
 }
 
 impl TransferTransportEndpoint {
@@ -917,6 +1424,7 @@ impl TransferTransportEndpoint {
             endpoint: ce.endpoint.clone(),
             transport_type: ce.transport_type,
             used: x.used,
+            posted: x.posted,
         }
     }
 }
@@ -1014,26 +1522,118 @@ pub struct WalletData {
     pub pubkey: String,
     /// Wallet mnemonic phrase
     pub mnemonic: Option<String>,
+    /// Optional BIP39 passphrase for the wallet mnemonic phrase
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Optional logging configuration, the default one is used if not provided
+    #[serde(default)]
+    pub log_config: Option<LogConfig>,
+    /// Credentials for the RGB proxy servers, mapped by endpoint host
+    ///
+    /// Keys are either `host:port` or `host`, the former taking precedence when both match
+    #[serde(default)]
+    pub proxy_auth: HashMap<String, ProxyAuth>,
+    /// Optional network configuration, to route the wallet traffic through a SOCKS5 proxy
+    #[serde(default)]
+    pub network_config: Option<NetworkConfig>,
+    /// Optional retry policy for the RGB proxy calls, the default one is used if not provided
+    #[serde(default)]
+    pub proxy_retry_config: Option<ProxyRetryConfig>,
+}
+
+impl fmt::Debug for WalletData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalletData")
+            .field("data_dir", &self.data_dir)
+            .field("bitcoin_network", &self.bitcoin_network)
+            .field("database_type", &self.database_type)
+            .field("max_allocations_per_utxo", &self.max_allocations_per_utxo)
+            .field("pubkey", &self.pubkey)
+            .field("mnemonic", &self.mnemonic.as_ref().map(|_| "<redacted>"))
+            .field(
+                "passphrase",
+                &self.passphrase.as_ref().map(|_| "<redacted>"),
+            )
+            .field("log_config", &self.log_config)
+            .field("proxy_auth", &self.proxy_auth)
+            .field("network_config", &self.network_config)
+            .field("proxy_retry_config", &self.proxy_retry_config)
+            .finish()
+    }
+}
+
+/// Proxy protocol version of a transport endpoint, as far as it could be retrieved
+enum ProxyProtocol {
+    Known(String),
+    /// The version could not be retrieved, for the given reason
+    Unknown(&'static str),
+}
+
+/// Witness heights resolved ahead of time, to accept a consignment while the RGB runtime is locked
+/// without making network calls.
+#[derive(Default)]
+struct ResolvedHeights(HashMap<RgbTxid, WitnessOrd>);
+
+impl ResolveHeight for ResolvedHeights {
+    type Error = TxResolverError;
+
+    fn resolve_height(&mut self, txid: RgbTxid) -> Result<WitnessOrd, Self::Error> {
+        self.0
+            .get(&txid)
+            .copied()
+            .ok_or(TxResolverError::Unknown(txid))
+    }
 }
 
 /// An RGB wallet
 ///
 /// A `Wallet` struct holds all the data required to operate it
+///
+/// A `Wallet` is `Send + Sync` and can be shared between threads (e.g. in an `Arc`). Read-only
+/// methods run concurrently, while methods that change the wallet state are serialized.
 pub struct Wallet {
     wallet_data: WalletData,
     logger: Logger,
     watch_only: bool,
     database: Arc<RgbLibDatabase>,
     wallet_dir: PathBuf,
-    bdk_wallet: BdkWallet<AnyDatabase>,
+    bdk_wallet: Mutex<BdkWallet<AnyDatabase>>,
+    bdk_sync_wallet: Mutex<BdkWallet<AnyDatabase>>,
+    bdk_database: sled::Tree,
+    bdk_sync_database: sled::Tree,
     rest_client: RestClient,
     max_allocations_per_utxo: u32,
-    online_data: Option<OnlineData>,
+    config: RwLock<WalletConfig>,
+    // the Online data given out by this wallet, with the connections it uses
+    online_data: RwLock<Option<(Online, Arc<OnlineData>)>>,
+    signer: RwLock<Option<Arc<dyn Signer>>>,
+    auto_backup: Arc<RwLock<Option<AutoBackupConfig>>>,
+    auto_backup_worker: Mutex<Option<AutoBackupWorker>>,
+    rgb_runtime: Arc<Mutex<RgbRuntimeCache>>,
+    write_lock: Arc<Mutex<()>>,
+    shared: Option<Arc<SharedResources>>,
 }
 
 impl Wallet {
     /// Create a new RGB wallet based on the provided [`WalletData`]
     pub fn new(wallet_data: WalletData) -> Result<Self, Error> {
+        Self::_new(wallet_data, None, None)
+    }
+
+    /// Create a new RGB wallet based on the provided [`WalletData`], sending its log messages to
+    /// the provided [`CustomLogger`]
+    pub fn new_with_logger(
+        wallet_data: WalletData,
+        custom_logger: CustomLogger,
+    ) -> Result<Self, Error> {
+        Self::_new(wallet_data, None, Some(custom_logger))
+    }
+
+    pub(crate) fn _new(
+        wallet_data: WalletData,
+        shared: Option<Arc<SharedResources>>,
+        custom_logger: Option<CustomLogger>,
+    ) -> Result<Self, Error> {
         let wdata = wallet_data.clone();
 
         // wallet directory and file logging setup
@@ -1051,40 +1651,47 @@ impl Wallet {
         if !wallet_dir.exists() {
             fs::create_dir(wallet_dir.clone())?;
         }
-        let logger = setup_logger(wallet_dir.clone(), None)?;
+        let secrets = [wdata.mnemonic.clone(), wdata.passphrase.clone()]
+            .into_iter()
+            .flatten()
+            .chain(wdata.proxy_auth.values().flat_map(ProxyAuth::secrets))
+            .chain(
+                wdata
+                    .network_config
+                    .iter()
+                    .filter_map(|c| c.socks5_credentials.as_ref())
+                    .map(|c| c.password.clone()),
+            )
+            .collect();
+        let logger = setup_logger_with_config(
+            wallet_dir.clone(),
+            None,
+            &wdata.log_config.clone().unwrap_or_default(),
+            custom_logger,
+            secrets,
+        )?;
         info!(logger.clone(), "New wallet in '{:?}'", wallet_dir);
-        let panic_logger = logger.clone();
-        let prev_hook = panic::take_hook();
-        panic::set_hook(Box::new(move |info| {
-            error!(panic_logger.clone(), "PANIC: {:?}", info);
-            prev_hook(info);
-        }));
 
         // BDK setup
         let bdk_db = wallet_dir.join(BDK_DB_NAME);
-        let bdk_config = SledDbConfiguration {
-            path: bdk_db
-                .into_os_string()
-                .into_string()
-                .expect("should be possible to convert path to a string"),
-            tree_name: BDK_DB_NAME.to_string(),
-        };
-        let bdk_database =
-            AnyDatabase::from_config(&bdk_config.into()).map_err(InternalError::from)?;
+        let (bdk_tree, bdk_sync_tree) = sled::open(bdk_db)
+            .and_then(|db| {
+                Ok((
+                    db.open_tree(BDK_DB_NAME)?,
+                    db.open_tree(BDK_SYNC_TREE_NAME)?,
+                ))
+            })
+            .map_err(|e| InternalError::from(bdk::Error::from(e)))?;
+        let bdk_database = AnyDatabase::Sled(bdk_tree.clone());
         let watch_only = wdata.mnemonic.is_none();
         let bdk_wallet = if let Some(mnemonic) = wdata.mnemonic {
-            let mnemonic = Mnemonic::parse_in(Language::English, mnemonic)?;
-            let xkey: ExtendedKey = mnemonic
-                .clone()
-                .into_extended_key()
-                .expect("a valid key should have been provided");
+            let mnemonic = Mnemonic::parse(mnemonic)?;
+            let xkey = get_extended_key(mnemonic.clone(), wdata.passphrase.clone());
             let xpub_from_mnemonic = &xkey.into_xpub(bdk_network, &Secp256k1::new());
             if *xpub_from_mnemonic != xpub {
                 return Err(Error::InvalidBitcoinKeys);
             }
-            let xkey: ExtendedKey = mnemonic
-                .into_extended_key()
-                .expect("a valid key should have been provided");
+            let xkey = get_extended_key(mnemonic, wdata.passphrase);
             let xprv = xkey
                 .into_xprv(bdk_network)
                 .expect("should be possible to get an extended private key");
@@ -1112,9 +1719,27 @@ impl Wallet {
             )
             .map_err(InternalError::from)?
         };
+        // syncs run on a second wallet with its own database, only needing public descriptors
+        let secp = Secp256k1::new();
+        let sync_descriptors = [KeychainKind::External, KeychainKind::Internal].map(|keychain| {
+            bdk_wallet
+                .get_descriptor_for_keychain(keychain)
+                .clone()
+                .into_wallet_descriptor(&secp, bdk_network)
+                .expect("wallet descriptor should be valid")
+        });
+        let [(sync_descriptor, _), (sync_change_descriptor, _)] = sync_descriptors;
+        let bdk_sync_wallet = BdkWallet::new(
+            sync_descriptor,
+            Some(sync_change_descriptor),
+            bdk_network,
+            AnyDatabase::Sled(bdk_sync_tree.clone()),
+        )
+        .map_err(InternalError::from)?;
 
         // RGB setup
-        let mut runtime = load_rgb_runtime(wallet_dir.clone(), wdata.bitcoin_network)?;
+        let rgb_runtime = RgbRuntimeCache::new(wallet_dir.clone());
+        let mut runtime = load_cached_rgb_runtime(rgb_runtime.clone(), wdata.bitcoin_network)?;
         if runtime.schema_ids()?.len() < NUM_KNOWN_SCHEMAS {
             runtime.import_iface(rgb20())?;
             runtime.import_schema(nia_schema())?;
@@ -1124,6 +1749,8 @@ impl Wallet {
             runtime.import_schema(cfa_schema())?;
             runtime.import_iface_impl(cfa_rgb25())?;
         }
+        runtime.persist()?;
+        drop(runtime);
 
         // RGB-LIB setup
         let db_path = wallet_dir.join(RGB_DB_NAME);
@@ -1138,9 +1765,23 @@ impl Wallet {
         let connection = db_cnn.map_err(InternalError::from)?;
         block_on(Migrator::up(&connection, None)).map_err(InternalError::from)?;
         let database = RgbLibDatabase::new(connection);
-        let rest_client = RestClient::builder()
-            .timeout(Duration::from_secs(PROXY_TIMEOUT as u64))
-            .build()?;
+        let config = database
+            .get_wallet_config()?
+            .map(WalletConfig::from)
+            .unwrap_or_default();
+        // wallets routing proxy traffic through SOCKS5 cannot use the shared client
+        let network_config = wdata.network_config.as_ref();
+        let rest_client = match &shared {
+            Some(shared) if !network_config.map_or(false, |c| c.routes_proxy()) => {
+                shared.rest_client.clone()
+            }
+            _ => _build_rest_client(network_config)?,
+        };
+
+        // wallets opened through a manager log panics to the manager log
+        if shared.is_none() {
+            register_panic_logger(wallet_dir.clone(), logger.clone());
+        }
 
         info!(logger, "New wallet completed");
         Ok(Wallet {
@@ -1149,39 +1790,96 @@ impl Wallet {
             watch_only,
             database: Arc::new(database),
             wallet_dir,
-            bdk_wallet,
+            bdk_wallet: Mutex::new(bdk_wallet),
+            bdk_sync_wallet: Mutex::new(bdk_sync_wallet),
+            bdk_database: bdk_tree,
+            bdk_sync_database: bdk_sync_tree,
             rest_client,
             max_allocations_per_utxo: wdata.max_allocations_per_utxo,
-            online_data: None,
+            config: RwLock::new(config),
+            online_data: RwLock::new(None),
+            signer: RwLock::new(None),
+            auto_backup: Arc::new(RwLock::new(None)),
+            auto_backup_worker: Mutex::new(None),
+            rgb_runtime,
+            write_lock: Arc::new(Mutex::new(())),
+            shared,
         })
     }
 
-    fn _bdk_blockchain(&self) -> Result<&ElectrumBlockchain, InternalError> {
-        match self.online_data {
-            Some(ref x) => Ok(&x.bdk_blockchain),
-            None => Err(InternalError::Unexpected),
-        }
+    fn _bdk_wallet(&self) -> MutexGuard<'_, BdkWallet<AnyDatabase>> {
+        self.bdk_wallet
+            .lock()
+            .expect("BDK wallet mutex should not be poisoned")
+    }
+
+    pub(crate) fn _proxy_client(&self, proxy_url: &str) -> ProxyClient {
+        let auth = RestUrl::parse(proxy_url).ok().and_then(|url| {
+            let host = url.host_str()?;
+            let proxy_auth = &self.wallet_data.proxy_auth;
+            url.port()
+                .and_then(|port| proxy_auth.get(&format!("{host}:{port}")))
+                .or_else(|| proxy_auth.get(host))
+                .cloned()
+        });
+        ProxyClient::new(self.rest_client.clone(), auth)
+            .with_timeout(Duration::from_secs(self._config().proxy_timeout as u64))
+    }
+
+    fn _config(&self) -> WalletConfig {
+        self.config
+            .read()
+            .expect("config lock should not be poisoned")
+            .clone()
     }
 
     fn _bitcoin_network(&self) -> BitcoinNetwork {
         self.wallet_data.bitcoin_network
     }
 
-    fn _electrum_client(&self) -> Result<&ElectrumClient, InternalError> {
-        match self.online_data {
-            Some(ref x) => Ok(&x.electrum_client),
-            None => Err(InternalError::Unexpected),
-        }
+    fn _online_data(&self) -> Result<Arc<OnlineData>, InternalError> {
+        self.online_data
+            .read()
+            .expect("online data lock should not be poisoned")
+            .as_ref()
+            .map(|(_, online_data)| online_data.clone())
+            .ok_or(InternalError::Unexpected)
     }
 
     fn _blockchain_resolver(&self) -> Result<BlockchainResolver, Error> {
         Ok(BlockchainResolver::with(
-            &self.online_data.as_ref().unwrap().electrum_url,
+            &self._online_data()?.electrum_url,
         )?)
     }
 
+    fn _resolve_witness_heights<const TYPE: bool>(
+        &self,
+        consignment: &Consignment<TYPE>,
+    ) -> Result<ResolvedHeights, Error> {
+        let mut resolver = self._blockchain_resolver()?;
+        let mut heights = ResolvedHeights::default();
+        for anchored_bundle in &consignment.bundles {
+            let txid = anchored_bundle.anchor.txid;
+            if let Entry::Vacant(entry) = heights.0.entry(txid) {
+                let height = resolver.resolve_height(txid).map_err(|e| Error::Internal {
+                    details: e.to_string(),
+                })?;
+                entry.insert(height);
+            }
+        }
+        Ok(heights)
+    }
+
+    /// Serialize the operations that change the wallet state. Read-only operations don't take
+    /// this lock, so they never wait behind a long operation such as a refresh.
+    fn _write_lock(&self) -> MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .expect("wallet write mutex should not be poisoned")
+    }
+
     fn _rgb_runtime(&self) -> Result<RgbRuntime, Error> {
-        load_rgb_runtime(self.wallet_dir.clone(), self._bitcoin_network())
+        load_cached_rgb_runtime(self.rgb_runtime.clone(), self._bitcoin_network())
     }
 
     fn _get_tx_details(
@@ -1189,10 +1887,12 @@ impl Wallet {
         txid: String,
         electrum_client: Option<&ElectrumClient>,
     ) -> Result<serde_json::Value, Error> {
+        let online_data;
         let electrum_client = if let Some(client) = electrum_client {
             client
         } else {
-            self._electrum_client()?
+            online_data = self._online_data()?;
+            &online_data.electrum_client
         };
         electrum_client
             .raw_call(
@@ -1204,43 +1904,284 @@ impl Wallet {
             })
     }
 
+    fn _routes_proxy(&self) -> bool {
+        self.wallet_data
+            .network_config
+            .as_ref()
+            .map_or(false, |c| c.routes_proxy())
+    }
+
+    fn _routes_indexer(&self) -> bool {
+        self.wallet_data
+            .network_config
+            .as_ref()
+            .map_or(false, |c| c.routes_indexer())
+    }
+
+    fn _check_transport_endpoint_reachable(&self, endpoint: &str) -> Result<(), Error> {
+        if is_onion_url(endpoint) && !self._routes_proxy() {
+            return Err(Error::InvalidTransportEndpoint {
+                details: s!("onion endpoints require routing proxy traffic through SOCKS5"),
+            });
+        }
+        Ok(())
+    }
+
     fn _check_transport_endpoints(&self, transport_endpoints: &Vec<String>) -> Result<(), Error> {
         if transport_endpoints.is_empty() {
             return Err(Error::InvalidTransportEndpoints {
                 details: s!("must provide at least a transport endpoint"),
             });
         }
-        if transport_endpoints.len() > MAX_TRANSPORT_ENDPOINTS as usize {
+        let max_transport_endpoints = self._config().max_transport_endpoints;
+        if transport_endpoints.len() > max_transport_endpoints as usize {
             return Err(Error::InvalidTransportEndpoints {
                 details: format!(
-                    "library supports at max {MAX_TRANSPORT_ENDPOINTS} transport endpoints"
+                    "library supports at max {max_transport_endpoints} transport endpoints"
                 ),
             });
         }
+        for transport_endpoint in transport_endpoints {
+            self._check_transport_endpoint_reachable(transport_endpoint)?;
+        }
 
         Ok(())
     }
 
-    fn _check_fee_rate(&self, fee_rate: f32) -> Result<(), Error> {
-        if fee_rate < MIN_FEE_RATE {
-            return Err(Error::InvalidFeeRate {
-                details: format!("value under minimum {MIN_FEE_RATE}"),
-            });
-        } else if fee_rate > MAX_FEE_RATE {
-            return Err(Error::InvalidFeeRate {
-                details: format!("value above maximum {MAX_FEE_RATE}"),
-            });
+    fn _get_or_create_transport_endpoint(
+        &self,
+        transport_endpoint: &TransportEndpoint,
+    ) -> Result<DbTransportEndpoint, Error> {
+        if let Some(db_transport_endpoint) = self
+            .database
+            .get_transport_endpoint(transport_endpoint.endpoint.clone())?
+        {
+            return Ok(db_transport_endpoint);
         }
-        Ok(())
+        let idx = self
+            .database
+            .set_transport_endpoint(DbTransportEndpointActMod {
+                transport_type: ActiveValue::Set(transport_endpoint.transport_type),
+                endpoint: ActiveValue::Set(transport_endpoint.endpoint.clone()),
+                ..Default::default()
+            })?;
+        Ok(DbTransportEndpoint {
+            idx,
+            transport_type: transport_endpoint.transport_type,
+            endpoint: transport_endpoint.endpoint.clone(),
+            protocol_version: None,
+            protocol_checked_at: None,
+            last_success_at: None,
+            consecutive_failures: 0,
+            latency_ms: None,
+        })
     }
 
-    fn _sync_wallet<D>(&self, wallet: &BdkWallet<D>) -> Result<(), Error>
-    where
-        D: BatchDatabase,
-    {
-        self._sync_wallet_with_blockchain(wallet, self._bdk_blockchain()?)?;
-        Ok(())
-    }
+    /// Call the proxy at the provided endpoint, retrying with an exponential backoff if it cannot
+    /// be reached, and record the endpoint health in the DB
+    fn _call_proxy<R>(
+        &self,
+        endpoint: &str,
+        call: impl Fn(ProxyClient) -> Result<JsonRpcResponse<R>, Error>,
+    ) -> Result<JsonRpcResponse<R>, Error> {
+        let retry_config = self
+            .wallet_data
+            .proxy_retry_config
+            .clone()
+            .unwrap_or_default();
+        let mut retry = 0;
+        loop {
+            let start = Instant::now();
+            let res = call(self._proxy_client(endpoint));
+            match &res {
+                Err(e @ Error::Proxy { .. }) if retry < retry_config.max_retries => {
+                    let backoff = retry_config.backoff(retry);
+                    debug!(
+                        self.logger,
+                        "Proxy call to {} failed ({}), retrying in {:?}", endpoint, e, backoff
+                    );
+                    std::thread::sleep(backoff);
+                    retry += 1;
+                }
+                Err(Error::Proxy { .. }) => {
+                    self._record_endpoint_health(endpoint, None)?;
+                    return res;
+                }
+                Ok(_) => {
+                    self._record_endpoint_health(endpoint, Some(start.elapsed()))?;
+                    return res;
+                }
+                Err(_) => return res,
+            }
+        }
+    }
+
+    /// Record the outcome of a proxy call on the transport endpoint DB row, if any, where a
+    /// `None` latency means the call has failed
+    fn _record_endpoint_health(
+        &self,
+        endpoint: &str,
+        latency: Option<Duration>,
+    ) -> Result<(), Error> {
+        let db_transport_endpoint =
+            match self.database.get_transport_endpoint(endpoint.to_string())? {
+                Some(db_transport_endpoint) => db_transport_endpoint,
+                None => return Ok(()),
+            };
+        let consecutive_failures = db_transport_endpoint.consecutive_failures;
+        let mut updated_transport_endpoint: DbTransportEndpointActMod =
+            db_transport_endpoint.into();
+        if let Some(latency) = latency {
+            updated_transport_endpoint.last_success_at =
+                ActiveValue::Set(Some(now().unix_timestamp()));
+            updated_transport_endpoint.consecutive_failures = ActiveValue::Set(0);
+            updated_transport_endpoint.latency_ms =
+                ActiveValue::Set(Some(latency.as_millis() as i64));
+        } else {
+            updated_transport_endpoint.consecutive_failures =
+                ActiveValue::Set(consecutive_failures.saturating_add(1));
+        }
+        self.database
+            .update_transport_endpoint(updated_transport_endpoint)?;
+        Ok(())
+    }
+
+    /// Return the proxy protocol version of the provided endpoint, from the cache on its DB row
+    /// when still valid or asking the proxy otherwise
+    fn _proxy_protocol(&self, endpoint: &str) -> Result<ProxyProtocol, Error> {
+        if let Some(db_transport_endpoint) =
+            self.database.get_transport_endpoint(endpoint.to_string())?
+        {
+            let now = now().unix_timestamp();
+            let cached = db_transport_endpoint
+                .protocol_checked_at
+                .map_or(false, |t| {
+                    now - t < self._config().duration_proxy_protocol_cache as i64
+                });
+            if let (true, Some(version)) = (cached, db_transport_endpoint.protocol_version) {
+                return Ok(ProxyProtocol::Known(version));
+            }
+        }
+        let server_info = match self._call_proxy(endpoint, |c| c.get_info(endpoint)) {
+            Ok(server_info) => server_info.result,
+            Err(e @ Error::ProxyUnauthorized { .. }) => return Err(e),
+            Err(e) => {
+                debug!(self.logger, "Server info GET response error: {:?}", e);
+                return Ok(ProxyProtocol::Unknown("unreachable"));
+            }
+        };
+        Ok(match server_info {
+            Some(info) => ProxyProtocol::Known(info.protocol_version),
+            None => ProxyProtocol::Unknown("invalid server info"),
+        })
+    }
+
+    /// Retrieve the proxy protocol versions of the provided endpoints, skipping invalid ones, so
+    /// that they can be checked without network calls while holding the write lock
+    fn _proxy_protocols(
+        &self,
+        transport_endpoints: &[String],
+    ) -> Result<HashMap<String, ProxyProtocol>, Error> {
+        let mut protocols = HashMap::new();
+        for endpoint_str in transport_endpoints {
+            if let Ok(transport_endpoint) = TransportEndpoint::new(endpoint_str.clone()) {
+                let protocol = self._proxy_protocol(&transport_endpoint.endpoint)?;
+                protocols.insert(transport_endpoint.endpoint, protocol);
+            }
+        }
+        Ok(protocols)
+    }
+
+    /// Check the proxy protocol version of the provided endpoint, returning the reason why it's
+    /// not supported if that's the case. Endpoints whose version can't be retrieved are
+    /// considered unsupported
+    fn _check_proxy_protocol(
+        &self,
+        transport_endpoint: &TransportEndpoint,
+        needs_vout: bool,
+    ) -> Result<Option<String>, Error> {
+        let protocol = self._proxy_protocol(&transport_endpoint.endpoint)?;
+        self._check_proxy_protocol_version(transport_endpoint, protocol, needs_vout)
+    }
+
+    /// Check the provided proxy protocol version, caching it on the transport endpoint DB row,
+    /// and return the reason why it's not supported if that's the case
+    fn _check_proxy_protocol_version(
+        &self,
+        transport_endpoint: &TransportEndpoint,
+        protocol: ProxyProtocol,
+        needs_vout: bool,
+    ) -> Result<Option<String>, Error> {
+        let db_transport_endpoint = self._get_or_create_transport_endpoint(transport_endpoint)?;
+        let version = match protocol {
+            ProxyProtocol::Known(version) => version,
+            ProxyProtocol::Unknown(reason) => return Ok(Some(reason.to_string())),
+        };
+        let now = now().unix_timestamp();
+        let cached = db_transport_endpoint
+            .protocol_checked_at
+            .map_or(false, |t| {
+                now - t < self._config().duration_proxy_protocol_cache as i64
+            });
+        if !cached || db_transport_endpoint.protocol_version.as_ref() != Some(&version) {
+            let mut updated_transport_endpoint: DbTransportEndpointActMod =
+                db_transport_endpoint.into();
+            updated_transport_endpoint.protocol_version = ActiveValue::Set(Some(version.clone()));
+            updated_transport_endpoint.protocol_checked_at = ActiveValue::Set(Some(now));
+            self.database
+                .update_transport_endpoint(updated_transport_endpoint)?;
+        }
+        Ok(match ProxyProtocolVersion::parse(&version) {
+            None => Some(format!("unsupported protocol version {version}")),
+            Some(v) if needs_vout && !v.supports_vout() => Some(format!(
+                "protocol version {version} doesn't support witness recipients"
+            )),
+            Some(_) => None,
+        })
+    }
+
+    /// Return the sorting key for the provided endpoint, which puts healthier endpoints first
+    /// and, among equally healthy ones, the ones supporting a newer proxy protocol version
+    fn _endpoint_priority(
+        &self,
+        endpoint: &str,
+    ) -> Result<(i32, std::cmp::Reverse<Option<ProxyProtocolVersion>>), Error> {
+        Ok(
+            match self.database.get_transport_endpoint(endpoint.to_string())? {
+                Some(db_transport_endpoint) => (
+                    db_transport_endpoint.consecutive_failures,
+                    std::cmp::Reverse(
+                        db_transport_endpoint
+                            .protocol_version
+                            .and_then(|v| ProxyProtocolVersion::parse(&v)),
+                    ),
+                ),
+                None => (0, std::cmp::Reverse(None)),
+            },
+        )
+    }
+
+    fn _check_fee_rate(&self, fee_rate: f32) -> Result<(), Error> {
+        let config = self._config();
+        if fee_rate < config.min_fee_rate {
+            return Err(Error::InvalidFeeRate {
+                details: format!("value under minimum {}", config.min_fee_rate),
+            });
+        } else if fee_rate > config.max_fee_rate {
+            return Err(Error::InvalidFeeRate {
+                details: format!("value above maximum {}", config.max_fee_rate),
+            });
+        }
+        Ok(())
+    }
+
+    fn _sync_wallet<D>(&self, wallet: &BdkWallet<D>) -> Result<(), Error>
+    where
+        D: BatchDatabase,
+    {
+        self._sync_wallet_with_blockchain(wallet, &self._online_data()?.bdk_blockchain)?;
+        Ok(())
+    }
 
     fn _sync_wallet_with_blockchain<D>(
         &self,
@@ -1258,12 +2199,66 @@ impl Wallet {
         Ok(())
     }
 
+    /// Sync the BDK wallet without blocking the operations using it.
+    ///
+    /// The sync runs on the dedicated sync wallet, whose database is first given the scripts
+    /// cached by the main one. The synced data then replaces the one in the main database, where
+    /// the last derivation indexes are only ever increased, so an address derived during the sync
+    /// is never handed out again.
+    fn _sync_bdk_wallet(&self, bdk_blockchain: &ElectrumBlockchain) -> Result<(), Error> {
+        let bdk_sync_wallet = self
+            .bdk_sync_wallet
+            .lock()
+            .expect("BDK sync wallet mutex should not be poisoned");
+        {
+            let _bdk_wallet = self._bdk_wallet();
+            _copy_bdk_entries(
+                &self.bdk_database,
+                &self.bdk_sync_database,
+                &BDK_SCRIPT_KEY_PREFIXES,
+                false,
+            )?;
+        }
+        self._sync_wallet_with_blockchain(&*bdk_sync_wallet, bdk_blockchain)?;
+
+        // no address can be derived while the synced data is copied
+        let _bdk_wallet = self._bdk_wallet();
+        _copy_bdk_entries(
+            &self.bdk_sync_database,
+            &self.bdk_database,
+            &BDK_SCRIPT_KEY_PREFIXES,
+            false,
+        )?;
+        _copy_bdk_entries(
+            &self.bdk_sync_database,
+            &self.bdk_database,
+            &BDK_SYNC_KEY_PREFIXES,
+            true,
+        )?;
+        let mut bdk_database = self.bdk_database.clone();
+        for keychain in [KeychainKind::External, KeychainKind::Internal] {
+            let synced_index = self
+                .bdk_sync_database
+                .get_last_index(keychain)
+                .map_err(InternalError::from)?;
+            let last_index = bdk_database
+                .get_last_index(keychain)
+                .map_err(InternalError::from)?;
+            if let Some(index) = synced_index.filter(|i| last_index.map_or(true, |l| l < *i)) {
+                bdk_database
+                    .set_last_index(keychain, index)
+                    .map_err(InternalError::from)?;
+            }
+        }
+        Ok(())
+    }
+
     fn _sync_db_txos_with_blockchain(
         &self,
         bdk_blockchain: &ElectrumBlockchain,
     ) -> Result<(), Error> {
         debug!(self.logger, "Syncing TXOs...");
-        self._sync_wallet_with_blockchain(&self.bdk_wallet, bdk_blockchain)?;
+        self._sync_bdk_wallet(bdk_blockchain)?;
 
         let db_outpoints: Vec<String> = self
             .database
@@ -1273,7 +2268,7 @@ impl Wallet {
             .map(|u| u.outpoint().to_string())
             .collect();
         let bdk_utxos: Vec<LocalUtxo> = self
-            .bdk_wallet
+            ._bdk_wallet()
             .list_unspent()
             .map_err(InternalError::from)?;
         let new_utxos: Vec<DbTxoActMod> = bdk_utxos
@@ -1290,22 +2285,80 @@ impl Wallet {
     }
 
     fn _sync_db_txos(&self) -> Result<(), Error> {
-        self._sync_db_txos_with_blockchain(self._bdk_blockchain()?)?;
+        self._sync_db_txos_with_blockchain(&self._online_data()?.bdk_blockchain)?;
         Ok(())
     }
 
     fn _internal_unspents(&self) -> Result<impl Iterator<Item = LocalUtxo>, Error> {
         Ok(self
-            .bdk_wallet
+            ._bdk_wallet()
             .list_unspent()
             .map_err(InternalError::from)?
             .into_iter()
             .filter(|u| u.keychain == KeychainKind::Internal))
     }
 
+    // creation time before which a prepared PSBT is expired, if they expire
+    fn _prepared_psbt_expiry(&self) -> Option<i64> {
+        self._config()
+            .duration_prepared_psbt
+            .map(|d| now().unix_timestamp() - d as i64)
+    }
+
+    // delete the PSBTs prepared by `*_begin` calls that have never been completed
+    fn _del_expired_prepared_psbts(&self) -> Result<(), Error> {
+        if let Some(expiry) = self._prepared_psbt_expiry() {
+            self.database.del_prepared_psbts_created_before(expiry)?;
+        }
+        Ok(())
+    }
+
+    fn _save_prepared_psbt(
+        &self,
+        txid: String,
+        prepared_psbt_type: PreparedPsbtType,
+    ) -> Result<(), Error> {
+        self._del_expired_prepared_psbts()?;
+        if self
+            .database
+            .get_prepared_psbt(txid.clone(), prepared_psbt_type)?
+            .is_none()
+        {
+            self.database.set_prepared_psbt(DbPreparedPsbtActMod {
+                txid: ActiveValue::Set(txid),
+                prepared_psbt_type: ActiveValue::Set(prepared_psbt_type),
+                created_at: ActiveValue::Set(now().unix_timestamp()),
+                ..Default::default()
+            })?;
+        }
+        Ok(())
+    }
+
+    fn _get_prepared_psbt(
+        &self,
+        signed_psbt: &BdkPsbt,
+        prepared_psbt_type: PreparedPsbtType,
+    ) -> Result<DbPreparedPsbt, Error> {
+        // the TXID of the unsigned transaction commits to all inputs and outputs, including the
+        // OP_RETURN one holding the RGB commitment, and is not affected by signing
+        let txid = signed_psbt.unsigned_tx.txid().to_string();
+        let prepared_psbt = self
+            .database
+            .get_prepared_psbt(txid.clone(), prepared_psbt_type)?
+            .ok_or(Error::PsbtMismatch { txid: txid.clone() })?;
+        if self
+            ._prepared_psbt_expiry()
+            .map_or(false, |expiry| prepared_psbt.created_at < expiry)
+        {
+            return Err(Error::PreparedPsbtExpired { txid });
+        }
+        Ok(prepared_psbt)
+    }
+
     fn _broadcast_psbt(&self, signed_psbt: BdkPsbt) -> Result<BdkTransaction, Error> {
         let tx = signed_psbt.extract_tx();
-        self._bdk_blockchain()?
+        self._online_data()?
+            .bdk_blockchain
             .broadcast(&tx)
             .map_err(|e| Error::FailedBroadcast {
                 details: e.to_string(),
@@ -1338,8 +2391,12 @@ impl Wallet {
     }
 
     fn _check_online(&self, online: Online) -> Result<(), Error> {
-        if let Some(online_data) = &self.online_data {
-            if online_data.id != online.id || online_data.electrum_url != online.electrum_url {
+        if let Some((current_online, _)) = &*self
+            .online_data
+            .read()
+            .expect("online data lock should not be poisoned")
+        {
+            if *current_online != online {
                 error!(self.logger, "Cannot change online object");
                 return Err(Error::CannotChangeOnline);
             }
@@ -1350,8 +2407,14 @@ impl Wallet {
         Ok(())
     }
 
-    fn _check_xprv(&self) -> Result<(), Error> {
-        if self.watch_only {
+    fn _check_signer(&self) -> Result<(), Error> {
+        if self.watch_only
+            && self
+                .signer
+                .read()
+                .expect("signer lock should not be poisoned")
+                .is_none()
+        {
             error!(self.logger, "Invalid operation for a watch only wallet");
             return Err(Error::WatchOnly);
         }
@@ -1362,7 +2425,7 @@ impl Wallet {
         Ok(self._internal_unspents()?.map(|u| u.txout.value).sum())
     }
 
-    fn _handle_expired_transfers(&mut self, db_data: &mut DbData) -> Result<(), Error> {
+    fn _handle_expired_transfers(&self, db_data: &mut DbData) -> Result<(), Error> {
         self._sync_db_txos()?;
         let now = now().unix_timestamp();
         let expired_transfers: Vec<DbBatchTransfer> = db_data
@@ -1410,9 +2473,10 @@ impl Wallet {
 
     fn _detect_btc_unspendable_err(&self) -> Result<Error, Error> {
         let available = self._get_uncolorable_btc_sum()?;
-        Ok(if available < MIN_BTC_REQUIRED {
+        let min_btc_required = self._config().min_btc_required;
+        Ok(if available < min_btc_required {
             Error::InsufficientBitcoins {
-                needed: MIN_BTC_REQUIRED,
+                needed: min_btc_required,
                 available,
             }
         } else {
@@ -1429,12 +2493,8 @@ impl Wallet {
         let unspents = if let Some(u) = unspents {
             u
         } else {
-            self.database.get_rgb_allocations(
-                self.database.get_unspent_txos(vec![])?,
-                None,
-                None,
-                None,
-            )?
+            self.database
+                .get_rgb_allocations(self.database.get_unspent_txos(vec![])?)?
         };
         let mut allocatable = self._get_available_allocations(unspents, exclude_utxos, None)?;
         allocatable.sort_by_key(|t| t.rgb_allocations.len());
@@ -1467,25 +2527,19 @@ impl Wallet {
         transfer_idx: i32,
         transport_endpoint: &LocalTransportEndpoint,
     ) -> Result<(), Error> {
-        let transport_endpoint_idx = match self
-            .database
-            .get_transport_endpoint(transport_endpoint.endpoint.clone())?
-        {
-            Some(ce) => ce.idx,
-            None => self
-                .database
-                .set_transport_endpoint(DbTransportEndpointActMod {
-                    transport_type: ActiveValue::Set(transport_endpoint.transport_type),
-                    endpoint: ActiveValue::Set(transport_endpoint.endpoint.clone()),
-                    ..Default::default()
-                })?,
-        };
+        let transport_endpoint_idx = self
+            ._get_or_create_transport_endpoint(&TransportEndpoint {
+                endpoint: transport_endpoint.endpoint.clone(),
+                transport_type: transport_endpoint.transport_type,
+            })?
+            .idx;
 
         self.database
             .set_transfer_transport_endpoint(DbTransferTransportEndpointActMod {
                 transfer_idx: ActiveValue::Set(transfer_idx),
                 transport_endpoint_idx: ActiveValue::Set(transport_endpoint_idx),
                 used: ActiveValue::Set(transport_endpoint.used),
+                posted: ActiveValue::Set(transport_endpoint.posted),
                 ..Default::default()
             })?;
 
@@ -1516,6 +2570,7 @@ impl Wallet {
         beneficiary: Beneficiary,
         recipient_type: RecipientType,
         recipient_id: String,
+        mut protocols: HashMap<String, ProxyProtocol>,
     ) -> Result<(String, Option<i64>, i32), Error> {
         let (iface, contract_id) = if let Some(aid) = asset_id.clone() {
             let asset = self.database.check_asset_exists(aid.clone())?;
@@ -1531,7 +2586,8 @@ impl Wallet {
         let expiry = if duration_seconds == Some(0) {
             None
         } else {
-            let duration_seconds = duration_seconds.unwrap_or(DURATION_RCV_TRANSFER) as i64;
+            let duration_seconds =
+                duration_seconds.unwrap_or(self._config().duration_rcv_transfer) as i64;
             let expiry = created_at + duration_seconds;
             Some(expiry)
         };
@@ -1564,6 +2620,38 @@ impl Wallet {
                 }
             }
         }
+        let needs_vout = recipient_type == RecipientType::Witness;
+        let mut unsupported = vec![];
+        for endpoint in &endpoints {
+            let transport_endpoint = TransportEndpoint {
+                endpoint: endpoint.clone(),
+                transport_type: TransportType::JsonRpc,
+            };
+            let protocol = protocols
+                .remove(endpoint)
+                .unwrap_or(ProxyProtocol::Unknown("not checked"));
+            if let ProxyProtocol::Unknown(reason) = protocol {
+                // the proxy could be temporarily down, only known unsupported ones are rejected
+                warn!(
+                    self.logger,
+                    "Cannot check the protocol version of transport endpoint {}: {}",
+                    endpoint,
+                    reason
+                );
+                self._get_or_create_transport_endpoint(&transport_endpoint)?;
+                continue;
+            }
+            if let Some(reason) =
+                self._check_proxy_protocol_version(&transport_endpoint, protocol, needs_vout)?
+            {
+                unsupported.push(format!("{endpoint} ({reason})"));
+            }
+        }
+        if unsupported.len() == endpoints.len() {
+            return Err(Error::InvalidTransportEndpoints {
+                details: format!("no valid transport endpoints: {}", unsupported.join(", ")),
+            });
+        }
 
         let owned_state = if let Some(value) = amount {
             TypedState::Amount(value)
@@ -1616,6 +2704,7 @@ impl Wallet {
                     transport_type: TransportType::JsonRpc,
                     used: false,
                     usable: true,
+                    posted: false,
                 },
             )?;
         }
@@ -1626,14 +2715,20 @@ impl Wallet {
     /// Blind an UTXO to receive RGB assets and return the resulting [`ReceiveData`]
     ///
     /// Optional Asset ID and duration (in seconds) can be specified
+    ///
+    /// The proxy protocol version of the transport endpoints is checked and at least one of them
+    /// needs to be supported. Endpoints that can't be reached are accepted, as their version is
+    /// unknown
     pub fn blind_receive(
-        &mut self,
+        &self,
         asset_id: Option<String>,
         amount: Option<u64>,
         duration_seconds: Option<u32>,
         transport_endpoints: Vec<String>,
         min_confirmations: u8,
     ) -> Result<ReceiveData, Error> {
+        let protocols = self._proxy_protocols(&transport_endpoints)?;
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Receiving via blinded UTXO for asset '{:?}' with duration '{:?}'...",
@@ -1641,12 +2736,9 @@ impl Wallet {
             duration_seconds
         );
 
-        let mut unspents: Vec<LocalUnspent> = self.database.get_rgb_allocations(
-            self.database.get_unspent_txos(vec![])?,
-            None,
-            None,
-            None,
-        )?;
+        let mut unspents: Vec<LocalUnspent> = self
+            .database
+            .get_rgb_allocations(self.database.get_unspent_txos(vec![])?)?;
         unspents.retain(|u| {
             !(u.rgb_allocations
                 .iter()
@@ -1677,10 +2769,12 @@ impl Wallet {
             concealed_seal.into(),
             RecipientType::Blind,
             blinded_utxo.clone(),
+            protocols,
         )?;
 
         let mut runtime = self._rgb_runtime()?;
         runtime.store_seal_secret(seal)?;
+        runtime.persist()?;
 
         let db_coloring = DbColoringActMod {
             txo_idx: ActiveValue::Set(utxo.idx),
@@ -1691,6 +2785,8 @@ impl Wallet {
         };
         self.database.set_coloring(db_coloring)?;
 
+        self._state_changed()?;
+
         info!(self.logger, "Blind receive completed");
         Ok(ReceiveData {
             invoice,
@@ -1702,14 +2798,20 @@ impl Wallet {
     /// Create an address to receive RGB assets and return the resulting [`ReceiveData`]
     ///
     /// Optional Asset ID and duration (in seconds) can be specified
+    ///
+    /// The proxy protocol version of the transport endpoints is checked and at least one of them
+    /// needs to be supported. Endpoints that can't be reached are accepted, as their version is
+    /// unknown
     pub fn witness_receive(
-        &mut self,
+        &self,
         asset_id: Option<String>,
         amount: Option<u64>,
         duration_seconds: Option<u32>,
         transport_endpoints: Vec<String>,
         min_confirmations: u8,
     ) -> Result<ReceiveData, Error> {
+        let protocols = self._proxy_protocols(&transport_endpoints)?;
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Receiving via witness TX for asset '{:?}' with duration '{:?}'...",
@@ -1731,8 +2833,11 @@ impl Wallet {
             Beneficiary::WitnessUtxo(address),
             RecipientType::Witness,
             script_buf_str.clone(),
+            protocols,
         )?;
 
+        self._state_changed()?;
+
         info!(self.logger, "Witness receive completed");
         Ok(ReceiveData {
             invoice,
@@ -1742,10 +2847,7 @@ impl Wallet {
     }
 
     fn _sign_psbt(&self, psbt: &mut BdkPsbt) -> Result<(), Error> {
-        self.bdk_wallet
-            .sign(psbt, SignOptions::default())
-            .map_err(InternalError::from)?;
-        Ok(())
+        sign_bdk_psbt(&self._bdk_wallet(), psbt)
     }
 
     /// Sign a PSBT
@@ -1755,6 +2857,244 @@ impl Wallet {
         Ok(psbt.to_string())
     }
 
+    /// Inspect a PSBT, such as the ones returned by the `*_begin` functions, decoding its bitcoin
+    /// and RGB content.
+    ///
+    /// The RGB content is read from the PSBT proprietary keys (state transitions and the inputs
+    /// they consume) and from the opret commitment. Allocations spent by the PSBT are looked up
+    /// in the wallet database for the outpoints of the PSBT inputs consuming RGB state, returning
+    /// an [`InvalidPsbt`](Error::InvalidPsbt) error if any of them is unknown to the wallet.
+    ///
+    /// This requires no private keys nor [`Online`] data, so that watch-only wallets and signers
+    /// can verify a PSBT before signing it.
+    pub fn inspect_psbt(&self, psbt: String) -> Result<PsbtInspection, Error> {
+        info!(self.logger, "Inspecting PSBT...");
+        let bdk_psbt = BdkPsbt::from_str(&psbt)?;
+        let rgb_psbt =
+            PartiallySignedTransaction::from_str(&psbt).map_err(|e| Error::InvalidPsbt {
+                details: e.to_string(),
+            })?;
+        let witness_txid = rgb_psbt.unsigned_tx.txid();
+
+        let inputs = bdk_psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .enumerate()
+            .map(|(i, txin)| {
+                let prev_output = _psbt_prev_output(&bdk_psbt, i)?;
+                Ok(PsbtInput {
+                    outpoint: Outpoint::from(txin.previous_output),
+                    btc_amount: prev_output.as_ref().map(|o| o.value),
+                    is_mine: match prev_output {
+                        Some(o) => self
+                            ._bdk_wallet()
+                            .is_mine(&o.script_pubkey)
+                            .map_err(InternalError::from)?,
+                        None => false,
+                    },
+                })
+            })
+            .collect::<Result<Vec<PsbtInput>, Error>>()?;
+
+        let bdk_network = self._bdk_wallet().network();
+        let outputs = bdk_psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .enumerate()
+            .map(|(vout, txout)| {
+                Ok(PsbtOutput {
+                    vout: vout as u32,
+                    script: hex::encode(txout.script_pubkey.as_bytes()),
+                    address: BdkAddress::from_script(&txout.script_pubkey, bdk_network)
+                        .ok()
+                        .map(|a| a.to_string()),
+                    btc_amount: txout.value,
+                    is_mine: self
+                        ._bdk_wallet()
+                        .is_mine(&txout.script_pubkey)
+                        .map_err(InternalError::from)?,
+                    is_op_return: txout.script_pubkey.is_op_return(),
+                })
+            })
+            .collect::<Result<Vec<PsbtOutput>, Error>>()?;
+
+        let opret_commitment = rgb_psbt
+            .outputs
+            .iter()
+            .find_map(|o| o.opret_commitment())
+            .map(|c| c.to_string());
+
+        let contract_ids = rgb_psbt.rgb_contract_ids();
+        let mut spent_txos = vec![];
+        for (input, txin) in rgb_psbt.inputs.iter().zip(&rgb_psbt.unsigned_tx.input) {
+            if contract_ids
+                .iter()
+                .all(|cid| input.rgb_consumer(*cid).is_none())
+            {
+                continue;
+            }
+            let outpoint = Outpoint::from(txin.previous_output);
+            match self.database.get_txo(outpoint.clone())? {
+                Some(txo) => spent_txos.push(txo),
+                None => {
+                    return Err(Error::InvalidPsbt {
+                        details: format!("input {outpoint} spends RGB state from an unknown UTXO"),
+                    })
+                }
+            }
+        }
+        let spent_unspents = self.database.get_rgb_allocations(spent_txos)?;
+
+        let mut transitions = vec![];
+        for contract_id in contract_ids {
+            let asset_id = contract_id.to_string();
+            for (opid, transition) in rgb_psbt.rgb_transitions(contract_id) {
+                let mut spent_allocations = vec![];
+                for (input, txin) in rgb_psbt.inputs.iter().zip(&rgb_psbt.unsigned_tx.input) {
+                    if input.rgb_consumer(contract_id) != Some(opid) {
+                        continue;
+                    }
+                    let outpoint = Outpoint::from(txin.previous_output);
+                    for unspent in spent_unspents
+                        .iter()
+                        .filter(|u| u.utxo.outpoint() == outpoint)
+                    {
+                        spent_allocations.extend(
+                            unspent
+                                .rgb_allocations
+                                .iter()
+                                .filter(|a| a.asset_id == Some(asset_id.clone()) && a.settled())
+                                .map(|a| PsbtAllocation {
+                                    outpoint: Some(outpoint.clone()),
+                                    recipient_id: None,
+                                    amount: a.amount,
+                                    is_mine: true,
+                                }),
+                        );
+                    }
+                }
+
+                let mut new_allocations = vec![];
+                for assignment in transition.assignments.values() {
+                    for fungible_assignment in assignment.as_fungible() {
+                        match fungible_assignment {
+                            Assign::Revealed { seal, state } => {
+                                let (txid, is_mine) = match seal.txid {
+                                    TxPtr::WitnessTx => {
+                                        let is_mine = outputs
+                                            .get(seal.vout.into_u32() as usize)
+                                            .map(|o| o.is_mine)
+                                            .unwrap_or(false);
+                                        (witness_txid.to_string(), is_mine)
+                                    }
+                                    TxPtr::Txid(txid) => {
+                                        let txid = txid.to_string();
+                                        let is_mine = self
+                                            .database
+                                            .get_txo(Outpoint {
+                                                txid: txid.clone(),
+                                                vout: seal.vout.into_u32(),
+                                            })?
+                                            .is_some();
+                                        (txid, is_mine)
+                                    }
+                                };
+                                new_allocations.push(PsbtAllocation {
+                                    outpoint: Some(Outpoint {
+                                        txid,
+                                        vout: seal.vout.into_u32(),
+                                    }),
+                                    recipient_id: None,
+                                    amount: state.value.as_u64(),
+                                    is_mine,
+                                });
+                            }
+                            Assign::ConfidentialSeal { seal, state } => {
+                                new_allocations.push(PsbtAllocation {
+                                    outpoint: None,
+                                    recipient_id: Some(seal.to_string()),
+                                    amount: state.value.as_u64(),
+                                    is_mine: false,
+                                });
+                            }
+                            Assign::Confidential { .. } | Assign::ConfidentialState { .. } => {}
+                        }
+                    }
+                }
+
+                transitions.push(PsbtTransition {
+                    asset_id: asset_id.clone(),
+                    blank: transition.transition_type == BLANK_TRANSITION_ID,
+                    spent_allocations,
+                    new_allocations,
+                });
+            }
+        }
+
+        // amounts come from an untrusted PSBT, so they're checked instead of trusting bdk helpers
+        let input_amounts: Option<Vec<u64>> = inputs.iter().map(|i| i.btc_amount).collect();
+        let fee = match input_amounts {
+            Some(input_amounts) => {
+                let invalid_amounts = || Error::InvalidPsbt {
+                    details: s!("outputs exceed inputs"),
+                };
+                let input_sum = input_amounts
+                    .into_iter()
+                    .try_fold(0u64, |sum, a| sum.checked_add(a))
+                    .ok_or_else(invalid_amounts)?;
+                let output_sum = outputs
+                    .iter()
+                    .try_fold(0u64, |sum, o| sum.checked_add(o.btc_amount))
+                    .ok_or_else(invalid_amounts)?;
+                Some(
+                    input_sum
+                        .checked_sub(output_sum)
+                        .ok_or_else(invalid_amounts)?,
+                )
+            }
+            None => None,
+        };
+
+        info!(self.logger, "Inspect PSBT completed");
+        Ok(PsbtInspection {
+            txid: witness_txid.to_string(),
+            inputs,
+            outputs,
+            fee,
+            opret_commitment,
+            transitions,
+        })
+    }
+
+    fn _sign_with_signer(&self, unsigned_psbt: String) -> Result<String, Error> {
+        let signer = self
+            .signer
+            .read()
+            .expect("signer lock should not be poisoned")
+            .clone();
+        if let Some(signer) = signer {
+            debug!(self.logger, "Signing PSBT with the registered signer");
+            signer.sign_psbt(unsigned_psbt)
+        } else {
+            self.sign_psbt(unsigned_psbt)
+        }
+    }
+
+    /// Register the [`Signer`] to be used by the full versions of the APIs that need to sign a
+    /// transaction, or unregister the current one by providing `None`.
+    ///
+    /// When no signer is registered, the wallet private keys are used, if available.
+    pub fn set_signer(&self, signer: Option<Box<dyn Signer>>) {
+        info!(self.logger, "Setting signer...");
+        *self
+            .signer
+            .write()
+            .expect("signer lock should not be poisoned") = signer.map(Arc::from);
+        info!(self.logger, "Set signer completed");
+    }
+
     fn _create_split_tx(
         &self,
         inputs: &[BdkOutPoint],
@@ -1762,13 +3102,17 @@ impl Wallet {
         size: u32,
         fee_rate: f32,
     ) -> Result<BdkPsbt, bdk::Error> {
-        let mut tx_builder = self.bdk_wallet.build_tx();
+        let addresses: Vec<BdkAddress> = (0..num_utxos_to_create)
+            .map(|_| self._get_new_address())
+            .collect();
+        let bdk_wallet = self._bdk_wallet();
+        let mut tx_builder = bdk_wallet.build_tx();
         tx_builder
             .add_utxos(inputs)?
             .manually_selected_only()
             .fee_rate(FeeRate::from_sat_per_vb(fee_rate));
-        for _i in 0..num_utxos_to_create {
-            tx_builder.add_recipient(self._get_new_address().script_pubkey(), size as u64);
+        for address in addresses {
+            tx_builder.add_recipient(address.script_pubkey(), size as u64);
         }
         Ok(tx_builder.finish()?.0)
     }
@@ -1778,7 +3122,7 @@ impl Wallet {
     ///
     /// This is the full version, requiring a wallet with private keys and [`Online`] data
     pub fn create_utxos(
-        &mut self,
+        &self,
         online: Online,
         up_to: bool,
         num: Option<u8>,
@@ -1786,11 +3130,11 @@ impl Wallet {
         fee_rate: f32,
     ) -> Result<u8, Error> {
         info!(self.logger, "Creating UTXOs...");
-        self._check_xprv()?;
+        self._check_signer()?;
 
         let unsigned_psbt = self.create_utxos_begin(online.clone(), up_to, num, size, fee_rate)?;
 
-        let psbt = self.sign_psbt(unsigned_psbt)?;
+        let psbt = self._sign_with_signer(unsigned_psbt)?;
 
         self.create_utxos_end(online, psbt)
     }
@@ -1802,10 +3146,10 @@ impl Wallet {
     /// an error if none need to be created.
     ///
     /// Providing the optional `num` parameter requests that many UTXOs, if it's not specified the
-    /// default number is used.
+    /// default number (see [`WalletConfig::utxo_num`]) is used.
     ///
     /// Providing the optional `size` parameter requests that UTXOs be created of that size, if it's
-    /// not specified the default one is used.
+    /// not specified the default one (see [`WalletConfig::utxo_size`]) is used.
     ///
     /// If not enough bitcoin funds are available to create the requested (or default) number of
     /// UTXOs, the number is decremented by one until it is possible to complete the operation. If
@@ -1817,13 +3161,14 @@ impl Wallet {
     ///
     /// Returns a PSBT ready to be signed
     pub fn create_utxos_begin(
-        &mut self,
+        &self,
         online: Online,
         up_to: bool,
         num: Option<u8>,
         size: Option<u32>,
         fee_rate: f32,
     ) -> Result<String, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Creating UTXOs (begin)...");
         self._check_online(online)?;
         self._check_fee_rate(fee_rate)?;
@@ -1831,11 +3176,10 @@ impl Wallet {
         self._sync_db_txos()?;
 
         let unspent_txos = self.database.get_unspent_txos(vec![])?;
-        let unspents = self
-            .database
-            .get_rgb_allocations(unspent_txos, None, None, None)?;
+        let unspents = self.database.get_rgb_allocations(unspent_txos)?;
 
-        let mut utxos_to_create = num.unwrap_or(UTXO_NUM);
+        let config = self._config();
+        let mut utxos_to_create = num.unwrap_or(config.utxo_num);
         if up_to {
             let allocatable = self
                 ._get_available_allocations(unspents, vec![], None)?
@@ -1851,7 +3195,7 @@ impl Wallet {
         let inputs: Vec<BdkOutPoint> = self._internal_unspents()?.map(|u| u.outpoint).collect();
         let inputs: &[BdkOutPoint] = &inputs;
         let new_btc_amount = self._get_uncolorable_btc_sum()?;
-        let utxo_size = size.unwrap_or(UTXO_SIZE);
+        let utxo_size = size.unwrap_or(config.utxo_size);
         let max_possible_utxos = new_btc_amount / utxo_size as u64;
         let mut btc_needed: u64 = utxo_size as u64 + 1000;
         let mut btc_available: u64 = 0;
@@ -1875,11 +3219,15 @@ impl Wallet {
                 available: btc_available,
             })
         } else {
-            info!(self.logger, "Create UTXOs completed");
-            Ok(self
+            let psbt = self
                 ._create_split_tx(inputs, num_try_creating, utxo_size, fee_rate)
-                .map_err(InternalError::from)?
-                .to_string())
+                .map_err(InternalError::from)?;
+            self._save_prepared_psbt(
+                psbt.unsigned_tx.txid().to_string(),
+                PreparedPsbtType::CreateUtxos,
+            )?;
+            info!(self.logger, "Create UTXOs completed");
+            Ok(psbt.to_string())
         }
     }
 
@@ -1887,14 +3235,19 @@ impl Wallet {
     ///
     /// This is the second half of the partial version, requiring [`Online`] data but no private keys.
     /// The provided PSBT, prepared with the [`create_utxos_begin`](Wallet::create_utxos_begin)
-    /// function, needs to have already been signed.
+    /// function, needs to have already been signed. If its transaction differs from the prepared
+    /// one a [`PsbtMismatch`](Error::PsbtMismatch) error is returned, if it has been prepared
+    /// longer ago than the configured `duration_prepared_psbt` a
+    /// [`PreparedPsbtExpired`](Error::PreparedPsbtExpired) one is, and nothing is broadcast.
     ///
     /// Returns the number of created UTXOs
     pub fn create_utxos_end(&self, online: Online, signed_psbt: String) -> Result<u8, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Creating UTXOs (end)...");
         self._check_online(online)?;
 
         let signed_psbt = BdkPsbt::from_str(&signed_psbt)?;
+        let prepared_psbt = self._get_prepared_psbt(&signed_psbt, PreparedPsbtType::CreateUtxos)?;
         let tx = self._broadcast_psbt(signed_psbt)?;
 
         self.database
@@ -1903,10 +3256,11 @@ impl Wallet {
                 wallet_transaction_type: ActiveValue::Set(WalletTransactionType::CreateUtxos),
                 ..Default::default()
             })?;
+        self.database.del_prepared_psbt(&prepared_psbt)?;
 
         let mut num_utxos_created = 0;
         let bdk_utxos: Vec<LocalUtxo> = self
-            .bdk_wallet
+            ._bdk_wallet()
             .list_unspent()
             .map_err(InternalError::from)?;
         let txid = tx.txid();
@@ -1916,6 +3270,8 @@ impl Wallet {
             }
         }
 
+        self._state_changed()?;
+
         info!(self.logger, "Create UTXOs completed");
         Ok(num_utxos_created)
     }
@@ -1948,6 +3304,7 @@ impl Wallet {
         txid: Option<String>,
         no_asset_only: bool,
     ) -> Result<bool, Error> {
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Deleting transfer with recipient ID {:?} and TXID {:?}...", recipient_id, txid
@@ -1958,7 +3315,7 @@ impl Wallet {
 
         if recipient_id.is_some() || txid.is_some() {
             let (batch_transfer, asset_transfers) = if let Some(recipient_id) = recipient_id {
-                let db_transfer = &mut self
+                let db_transfer = &self
                     .database
                     .get_transfer_or_fail(recipient_id, &db_data.transfers)?;
                 let (_, batch_transfer) = db_transfer
@@ -2022,6 +3379,10 @@ impl Wallet {
             }
         }
 
+        if transfers_changed {
+            self._state_changed()?;
+        }
+
         info!(self.logger, "Delete transfer completed");
         Ok(transfers_changed)
     }
@@ -2041,12 +3402,12 @@ impl Wallet {
             self.logger,
             "Draining to '{}' destroying asset '{}'...", address, destroy_assets
         );
-        self._check_xprv()?;
+        self._check_signer()?;
 
         let unsigned_psbt =
             self.drain_to_begin(online.clone(), address, destroy_assets, fee_rate)?;
 
-        let psbt = self.sign_psbt(unsigned_psbt)?;
+        let psbt = self._sign_with_signer(unsigned_psbt)?;
 
         self.drain_to_end(online, psbt)
     }
@@ -2078,6 +3439,7 @@ impl Wallet {
         destroy_assets: bool,
         fee_rate: f32,
     ) -> Result<String, Error> {
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Draining (begin) to '{}' destroying asset '{}'...", address, destroy_assets
@@ -2089,14 +3451,20 @@ impl Wallet {
 
         let address = BdkAddress::from_str(&address).map(|x| x.script_pubkey())?;
 
-        let mut tx_builder = self.bdk_wallet.build_tx();
+        let unspendable = if destroy_assets {
+            None
+        } else {
+            Some(self._get_unspendable_bdk_outpoints()?)
+        };
+
+        let bdk_wallet = self._bdk_wallet();
+        let mut tx_builder = bdk_wallet.build_tx();
         tx_builder
             .drain_wallet()
             .drain_to(address)
             .fee_rate(FeeRate::from_sat_per_vb(fee_rate));
 
-        if !destroy_assets {
-            let unspendable = self._get_unspendable_bdk_outpoints()?;
+        if let Some(unspendable) = unspendable {
             tx_builder.unspendable(unspendable);
         }
 
@@ -2108,25 +3476,31 @@ impl Wallet {
                 }
                 _ => Error::from(InternalError::from(e)),
             })?
-            .0
-            .to_string();
+            .0;
+        drop(bdk_wallet);
+        self._save_prepared_psbt(psbt.unsigned_tx.txid().to_string(), PreparedPsbtType::Drain)?;
 
         info!(self.logger, "Drain (begin) completed");
-        Ok(psbt)
+        Ok(psbt.to_string())
     }
 
     /// Broadcast the provided PSBT to send bitcoin funds.
     ///
     /// This is the second half of the partial version, requiring [`Online`] data but no private keys.
     /// The provided PSBT, prepared with the [`drain_to_begin`](Wallet::drain_to_begin) function,
-    /// needs to have already been signed.
+    /// needs to have already been signed. If its transaction differs from the prepared one a
+    /// [`PsbtMismatch`](Error::PsbtMismatch) error is returned, if it has been prepared longer ago
+    /// than the configured `duration_prepared_psbt` a
+    /// [`PreparedPsbtExpired`](Error::PreparedPsbtExpired) one is, and nothing is broadcast.
     ///
     /// Returns the TXID of the transaction that's been broadcast
     pub fn drain_to_end(&self, online: Online, signed_psbt: String) -> Result<String, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Draining (end)...");
         self._check_online(online)?;
 
         let signed_psbt = BdkPsbt::from_str(&signed_psbt)?;
+        let prepared_psbt = self._get_prepared_psbt(&signed_psbt, PreparedPsbtType::Drain)?;
         let tx = self._broadcast_psbt(signed_psbt)?;
 
         self.database
@@ -2135,6 +3509,9 @@ impl Wallet {
                 wallet_transaction_type: ActiveValue::Set(WalletTransactionType::Drain),
                 ..Default::default()
             })?;
+        self.database.del_prepared_psbt(&prepared_psbt)?;
+
+        self._state_changed()?;
 
         info!(self.logger, "Drain (end) completed");
         Ok(tx.txid().to_string())
@@ -2151,7 +3528,7 @@ impl Wallet {
     }
 
     fn _try_fail_batch_transfer(
-        &mut self,
+        &self,
         batch_transfer: &DbBatchTransfer,
         throw_err: bool,
         db_data: &mut DbData,
@@ -2182,12 +3559,13 @@ impl Wallet {
     /// after a `refresh` has been performed. If nor `recipient_id` not `txid` have been provided,
     /// only expired transfers will be failed.
     pub fn fail_transfers(
-        &mut self,
+        &self,
         online: Online,
         recipient_id: Option<String>,
         txid: Option<String>,
         no_asset_only: bool,
     ) -> Result<bool, Error> {
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Failing transfer with recipient ID {:?} and TXID {:?}...", recipient_id, txid
@@ -2199,7 +3577,7 @@ impl Wallet {
 
         if recipient_id.is_some() || txid.is_some() {
             let batch_transfer = if let Some(recipient_id) = recipient_id {
-                let db_transfer = &mut self
+                let db_transfer = &self
                     .database
                     .get_transfer_or_fail(recipient_id, &db_data.transfers)?;
                 let (_, batch_transfer) = db_transfer
@@ -2264,12 +3642,16 @@ impl Wallet {
             }
         }
 
+        if transfers_changed {
+            self._state_changed()?;
+        }
+
         info!(self.logger, "Fail transfers completed");
         Ok(transfers_changed)
     }
 
     fn _get_new_address(&self) -> BdkAddress {
-        self.bdk_wallet
+        self._bdk_wallet()
             .get_address(AddressIndex::New)
             .expect("to be able to get a new address")
             .address
@@ -2279,7 +3661,7 @@ impl Wallet {
     pub fn get_address(&self) -> String {
         info!(self.logger, "Getting address...");
         let address = self
-            .bdk_wallet
+            ._bdk_wallet()
             .get_internal_address(AddressIndex::New)
             .expect("to be able to get a new address")
             .address
@@ -2292,9 +3674,7 @@ impl Wallet {
     pub fn get_asset_balance(&self, asset_id: String) -> Result<Balance, Error> {
         info!(self.logger, "Getting balance for asset '{}'...", asset_id);
         self.database.check_asset_exists(asset_id.clone())?;
-        let balance = self
-            .database
-            .get_asset_balance(asset_id, None, None, None, None);
+        let balance = self.database.get_asset_balance(asset_id);
         info!(self.logger, "Get asset balance completed");
         balance
     }
@@ -2304,10 +3684,10 @@ impl Wallet {
         info!(self.logger, "Getting BTC balance...");
         self._check_online(online)?;
 
-        let bdk_network = self.bdk_wallet.network();
+        let bdk_network = self._bdk_wallet().network();
         let secp = Secp256k1::new();
         let (descriptor_keychain_1, _) = self
-            .bdk_wallet
+            ._bdk_wallet()
             .get_descriptor_for_keychain(KeychainKind::Internal)
             .clone()
             .into_wallet_descriptor(&secp, bdk_network)
@@ -2320,7 +3700,7 @@ impl Wallet {
         )
         .map_err(InternalError::from)?;
         let (descriptor_keychain_9, _) = self
-            .bdk_wallet
+            ._bdk_wallet()
             .get_descriptor_for_keychain(KeychainKind::External)
             .clone()
             .into_wallet_descriptor(&secp, bdk_network)
@@ -2396,7 +3776,7 @@ impl Wallet {
     }
 
     /// Return the [`Metadata`] for the requested asset
-    pub fn get_asset_metadata(&mut self, asset_id: String) -> Result<Metadata, Error> {
+    pub fn get_asset_metadata(&self, asset_id: String) -> Result<Metadata, Error> {
         info!(self.logger, "Getting metadata for asset '{}'...", asset_id);
         let asset = self.database.check_asset_exists(asset_id)?;
 
@@ -2429,54 +3809,496 @@ impl Wallet {
     ) -> Result<(), Error> {
         info!(self.logger, "Doing a consistency check...");
 
+        let report = self._diagnose(bdk_blockchain, runtime)?;
+        if !report.inconsistent_utxos.is_empty() {
+            return Err(Error::Inconsistency {
+                details: s!("spent bitcoins with another wallet"),
+            });
+        }
+        if !report.missing_assets.is_empty() {
+            return Err(Error::Inconsistency {
+                details: s!("DB assets do not match with ones stored in RGB"),
+            });
+        }
+
+        info!(self.logger, "Consistency check completed");
+        Ok(())
+    }
+
+    fn _diagnose(
+        &self,
+        bdk_blockchain: &ElectrumBlockchain,
+        runtime: &RgbRuntime,
+    ) -> Result<ConsistencyReport, Error> {
         self._sync_db_txos_with_blockchain(bdk_blockchain)?;
         let bdk_utxos: Vec<String> = self
-            .bdk_wallet
+            ._bdk_wallet()
             .list_unspent()
             .map_err(InternalError::from)?
             .into_iter()
             .map(|u| u.outpoint.to_string())
             .collect();
         let bdk_utxos: HashSet<String> = HashSet::from_iter(bdk_utxos);
-        let db_utxos: Vec<String> = self
+        let db_data = self.database.get_db_data(false)?;
+
+        let inconsistent_txos: Vec<DbTxo> = db_data
+            .txos
+            .clone()
+            .into_iter()
+            .filter(|t| !t.spent && !bdk_utxos.contains(&t.outpoint().to_string()))
+            .collect();
+        let inconsistent_utxos = self
             .database
-            .iter_txos()?
+            .get_rgb_allocations(inconsistent_txos)?
             .into_iter()
-            .filter(|t| !t.spent)
-            .map(|u| u.outpoint().to_string())
+            .map(|u| InconsistentUtxo {
+                outpoint: u.utxo.outpoint(),
+                rgb_allocations: u
+                    .rgb_allocations
+                    .into_iter()
+                    .filter(|a| !a.status.failed())
+                    .map(RgbAllocation::from)
+                    .collect(),
+                pending_transfer_idxs: self._get_pending_transfer_idxs(u.utxo.idx, &db_data),
+            })
             .collect();
-        let db_utxos: HashSet<String> = HashSet::from_iter(db_utxos);
-        if db_utxos.difference(&bdk_utxos).count() > 0 {
-            return Err(Error::Inconsistency {
-                details: s!("spent bitcoins with another wallet"),
-            });
-        }
 
         let asset_ids: Vec<String> = runtime
             .contract_ids()?
             .iter()
             .map(|id| id.to_string())
             .collect();
-        let db_asset_ids: Vec<String> = self.database.get_asset_ids()?;
-        if !db_asset_ids.iter().all(|i| asset_ids.contains(i)) {
-            return Err(Error::Inconsistency {
-                details: s!("DB assets do not match with ones stored in RGB"),
-            });
+        let missing_assets = self
+            .database
+            .get_asset_ids()?
+            .into_iter()
+            .filter(|i| !asset_ids.contains(i))
+            .map(|asset_id| MissingAsset {
+                consignment_available: !self
+                    ._get_asset_consignments(&asset_id, &db_data)
+                    .is_empty(),
+                asset_id,
+            })
+            .collect();
+
+        Ok(ConsistencyReport {
+            inconsistent_utxos,
+            missing_assets,
+        })
+    }
+
+    fn _get_pending_transfer_idxs(&self, txo_idx: i32, db_data: &DbData) -> Vec<i32> {
+        let asset_transfer_idxs: Vec<i32> = db_data
+            .colorings
+            .iter()
+            .filter(|c| c.txo_idx == txo_idx)
+            .map(|c| c.asset_transfer_idx)
+            .collect();
+        let pending_asset_transfer_idxs: Vec<i32> = db_data
+            .asset_transfers
+            .iter()
+            .filter(|at| asset_transfer_idxs.contains(&at.idx))
+            .filter(|at| {
+                db_data
+                    .batch_transfers
+                    .iter()
+                    .any(|bt| bt.idx == at.batch_transfer_idx && bt.pending())
+            })
+            .map(|at| at.idx)
+            .collect();
+        db_data
+            .transfers
+            .iter()
+            .filter(|t| pending_asset_transfer_idxs.contains(&t.asset_transfer_idx))
+            .map(|t| t.idx)
+            .collect()
+    }
+
+    /// Return the paths of the stored consignments for the given asset, along with whether each
+    /// one belongs to a settled incoming transfer. Consignments received from the proxy
+    /// (`CONSIGNMENT_RCV_FILE` in the recipient ID directory) come first, followed by the ones
+    /// sent by the wallet
+    fn _get_asset_consignments(&self, asset_id: &str, db_data: &DbData) -> Vec<(PathBuf, bool)> {
+        let transfers_dir = self.wallet_dir.join(TRANSFER_DIR);
+        let mut incoming_consignments = vec![];
+        let mut outgoing_consignments = vec![];
+        for asset_transfer in db_data
+            .asset_transfers
+            .iter()
+            .filter(|at| at.asset_id.as_deref() == Some(asset_id))
+        {
+            let batch_transfer = db_data
+                .batch_transfers
+                .iter()
+                .find(|bt| bt.idx == asset_transfer.batch_transfer_idx)
+                .expect("asset transfer should be connected to a batch transfer");
+            if let Some(txid) = &batch_transfer.txid {
+                let consignment_path = transfers_dir
+                    .join(txid)
+                    .join(asset_id)
+                    .join(CONSIGNMENT_FILE);
+                if consignment_path.exists() {
+                    outgoing_consignments.push((consignment_path, false));
+                }
+            }
+            for transfer in db_data
+                .transfers
+                .iter()
+                .filter(|t| t.asset_transfer_idx == asset_transfer.idx && t.incoming)
+            {
+                if let Some(recipient_id) = &transfer.recipient_id {
+                    let consignment_path =
+                        transfers_dir.join(recipient_id).join(CONSIGNMENT_RCV_FILE);
+                    if consignment_path.exists() {
+                        incoming_consignments
+                            .push((consignment_path, batch_transfer.status.settled()));
+                    }
+                }
+            }
+        }
+        incoming_consignments.extend(outgoing_consignments);
+        incoming_consignments
+    }
+
+    /// Diagnose the wallet consistency, returning the issues that would make the consistency
+    /// check in [`go_online`](Wallet::go_online) fail.
+    ///
+    /// This lists the UTXOs that have been spent outside of the wallet, along with the RGB
+    /// allocations lost on them, and the assets missing from the RGB runtime. An inconsistent
+    /// wallet can go online with `skip_consistency_check` set to true in order to run this and
+    /// then [`repair`](Wallet::repair) it.
+    pub fn diagnose(&self, online: Online) -> Result<ConsistencyReport, Error> {
+        info!(self.logger, "Diagnosing wallet...");
+        self._check_online(online)?;
+
+        let runtime = self._rgb_runtime()?;
+        let report = self._diagnose(&self._online_data()?.bdk_blockchain, &runtime)?;
+
+        info!(self.logger, "Diagnose completed");
+        Ok(report)
+    }
+
+    /// Repair the wallet consistency issues reported by [`diagnose`](Wallet::diagnose),
+    /// applying the requested `actions`, and return the updated diagnosis.
+    ///
+    /// Available actions are:
+    /// - [`RepairAction::MarkUtxosSpent`]: mark the inconsistent UTXOs as spent, dropping the
+    ///   allocations on them from the wallet balances
+    /// - [`RepairAction::FailAffectedTransfers`]: set the status of the pending transfers involving
+    ///   inconsistent UTXOs to [`TransferStatus::Failed`]
+    /// - [`RepairAction::ReimportContracts`]: re-import the contracts of missing assets into the
+    ///   RGB runtime from the stored consignments, accepting the ones of settled incoming
+    ///   transfers
+    ///
+    /// Every change is logged. Assets without any stored consignment (e.g. issued ones that have
+    /// never been sent) cannot be re-imported and are still reported as missing.
+    pub fn repair(
+        &self,
+        online: Online,
+        actions: Vec<RepairAction>,
+    ) -> Result<ConsistencyReport, Error> {
+        let _write_lock = self._write_lock();
+        info!(
+            self.logger,
+            "Repairing wallet with actions {:?}...", actions
+        );
+        self._check_online(online)?;
+
+        let mut runtime = self._rgb_runtime()?;
+        let report = self._diagnose(&self._online_data()?.bdk_blockchain, &runtime)?;
+        let db_data = self.database.get_db_data(false)?;
+        let mut changed = false;
+
+        if actions.contains(&RepairAction::FailAffectedTransfers) {
+            let transfer_idxs: Vec<i32> = report
+                .inconsistent_utxos
+                .iter()
+                .flat_map(|u| u.pending_transfer_idxs.clone())
+                .collect();
+            let asset_transfer_idxs: Vec<i32> = db_data
+                .transfers
+                .iter()
+                .filter(|t| transfer_idxs.contains(&t.idx))
+                .map(|t| t.asset_transfer_idx)
+                .collect();
+            for batch_transfer in db_data.batch_transfers.iter().filter(|bt| {
+                bt.pending()
+                    && db_data.asset_transfers.iter().any(|at| {
+                        at.batch_transfer_idx == bt.idx && asset_transfer_idxs.contains(&at.idx)
+                    })
+            }) {
+                info!(
+                    self.logger,
+                    "Failing batch transfer {} with TXID {:?}",
+                    batch_transfer.idx,
+                    batch_transfer.txid
+                );
+                self._fail_batch_transfer(batch_transfer)?;
+                changed = true;
+            }
+        }
+
+        if actions.contains(&RepairAction::MarkUtxosSpent) {
+            for inconsistent_utxo in &report.inconsistent_utxos {
+                let mut db_txo: DbTxoActMod = self
+                    .database
+                    .get_txo(inconsistent_utxo.outpoint.clone())?
+                    .expect("outpoint should be in the DB")
+                    .into();
+                db_txo.spent = ActiveValue::Set(true);
+                self.database.update_txo(db_txo)?;
+                info!(
+                    self.logger,
+                    "Marked UTXO {} as spent, lost allocations: {:?}",
+                    inconsistent_utxo.outpoint,
+                    inconsistent_utxo.rgb_allocations
+                );
+                changed = true;
+            }
+        }
+
+        if actions.contains(&RepairAction::ReimportContracts) {
+            for missing_asset in &report.missing_assets {
+                let consignments = self._get_asset_consignments(&missing_asset.asset_id, &db_data);
+                if consignments.is_empty() {
+                    warn!(
+                        self.logger,
+                        "No consignment available for asset {}", missing_asset.asset_id
+                    );
+                    continue;
+                }
+                self._reimport_contract(&mut runtime, consignments)?;
+                runtime.persist()?;
+                info!(
+                    self.logger,
+                    "Re-imported contract for asset {}", missing_asset.asset_id
+                );
+                changed = true;
+            }
+        }
+
+        if changed {
+            self._state_changed()?;
+        }
+
+        let report = self._diagnose(&self._online_data()?.bdk_blockchain, &runtime)?;
+        info!(self.logger, "Repair completed");
+        Ok(report)
+    }
+
+    /// Audit the RGB allocations tracked in the wallet DB against the state owned in the RGB
+    /// runtime, returning the differences found for each asset on each wallet UTXO.
+    ///
+    /// The runtime can already know about allocations that are not settled yet (e.g. the change of
+    /// a pending send), so a discrepancy is reported only when the runtime amount is lower than
+    /// the settled amount, which could cause a failure when spending, or higher than the settled
+    /// and unsettled incoming amounts combined, meaning the wallet DB is missing some allocations.
+    ///
+    /// If an `online` is provided, the wallet UTXOs are synced before running the audit.
+    pub fn audit_allocations(
+        &self,
+        online: Option<Online>,
+    ) -> Result<Vec<AllocationDiscrepancy>, Error> {
+        info!(self.logger, "Auditing allocations...");
+        self._sync_if_online(online)?;
+
+        let mut runtime = self._rgb_runtime()?;
+        let unspent_txos = self.database.get_unspent_txos(vec![])?;
+        let txos_allocations = self.database.get_rgb_allocations(unspent_txos)?;
+
+        let mut discrepancies = vec![];
+        for txo_allocations in txos_allocations {
+            let outpoint = txo_allocations.utxo.outpoint();
+
+            // (settled, unsettled incoming) amounts in the DB, by asset
+            let mut db_amounts: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+            for allocation in txo_allocations.rgb_allocations {
+                if let Some(asset_id) = &allocation.asset_id {
+                    let amounts = db_amounts.entry(asset_id.clone()).or_default();
+                    if allocation.settled() {
+                        amounts.0 += allocation.amount;
+                    } else if allocation.incoming {
+                        amounts.1 += allocation.amount;
+                    }
+                }
+            }
+
+            // amounts owned in the runtime, by asset
+            let rgb_outpoint = RgbOutpoint::from(outpoint.clone());
+            let mut runtime_amounts: BTreeMap<String, u64> = BTreeMap::new();
+            for contract_id in runtime.contracts_by_outpoints([rgb_outpoint])? {
+                let amount = runtime
+                    .state_for_outpoints(contract_id, [rgb_outpoint])?
+                    .values()
+                    .map(|state| match state {
+                        TypedState::Amount(amt) => *amt,
+                        _ => 0,
+                    })
+                    .sum();
+                runtime_amounts.insert(contract_id.to_string(), amount);
+            }
+
+            let asset_ids: BTreeSet<&String> =
+                db_amounts.keys().chain(runtime_amounts.keys()).collect();
+            for asset_id in asset_ids {
+                let (settled_amount, unsettled_amount) =
+                    db_amounts.get(asset_id).copied().unwrap_or_default();
+                let runtime_amount = runtime_amounts.get(asset_id).copied().unwrap_or_default();
+                if runtime_amount < settled_amount
+                    || runtime_amount > settled_amount + unsettled_amount
+                {
+                    warn!(
+                        self.logger,
+                        "Allocation discrepancy for asset {} on {}: DB {} (+{}), runtime {}",
+                        asset_id,
+                        outpoint,
+                        settled_amount,
+                        unsettled_amount,
+                        runtime_amount
+                    );
+                    discrepancies.push(AllocationDiscrepancy {
+                        outpoint: outpoint.clone(),
+                        asset_id: asset_id.clone(),
+                        settled_amount,
+                        unsettled_amount,
+                        runtime_amount,
+                    });
+                }
+            }
+        }
+
+        info!(self.logger, "Audit allocations completed");
+        Ok(discrepancies)
+    }
+
+    fn _reimport_contract(
+        &self,
+        runtime: &mut RgbRuntime,
+        consignments: Vec<(PathBuf, bool)>,
+    ) -> Result<(), Error> {
+        // register the contract from the first consignment, an incoming one if available as done
+        // when receiving an unknown asset, then accept the consignments of settled incoming
+        // transfers
+        let (first_path, _) = &consignments[0];
+        let bindle = Bindle::<RgbTransfer>::load(first_path).map_err(InternalError::from)?;
+        let consignment: RgbTransfer = bindle.unbindle();
+        let mut minimal_contract = consignment.into_contract();
+        minimal_contract.bundles = none!();
+        minimal_contract.terminals = none!();
+        let minimal_contract_validated = minimal_contract
+            .validate(&mut self._blockchain_resolver()?)
+            .unwrap_or_else(|c| c);
+        runtime.import_contract(
+            minimal_contract_validated,
+            &mut self._blockchain_resolver()?,
+        )?;
+        debug!(self.logger, "Contract registered from {:?}", first_path);
+
+        for (consignment_path, _) in consignments.iter().filter(|(_, accept)| *accept) {
+            let bindle =
+                Bindle::<RgbTransfer>::load(consignment_path).map_err(InternalError::from)?;
+            let consignment = bindle
+                .unbindle()
+                .validate(&mut self._blockchain_resolver()?)
+                .unwrap_or_else(|c| c);
+            let force = false;
+            let validation_status =
+                runtime.accept_transfer(consignment, &mut self._blockchain_resolver()?, force)?;
+            debug!(
+                self.logger,
+                "Accepted consignment {:?} with validity {:?}",
+                consignment_path,
+                validation_status.validity()
+            );
         }
 
-        info!(self.logger, "Consistency check completed");
         Ok(())
     }
 
-    fn _go_online(&self, electrum_url: String) -> Result<(Online, OnlineData), Error> {
-        let online_id = now().unix_timestamp_nanos() as u64;
-        let online = Online {
-            id: online_id,
-            electrum_url: electrum_url.clone(),
+    // return the connections to the indexer, shared with the other wallets of the same manager
+    // when possible
+    fn _go_online(&self, electrum_url: String) -> Result<Arc<OnlineData>, Error> {
+        let shared = match &self.shared {
+            Some(shared) if !self._routes_indexer() => shared,
+            _ => return self._connect_electrum(electrum_url, &self._config()),
         };
+        // connections are only shared between wallets using the same electrum parameters
+        let (timeout, stop_gap) = self._config().electrum_params();
+        let cache_key = format!("{electrum_url}|{timeout}|{stop_gap}");
+        let shared_online_data = shared
+            .online_data
+            .lock()
+            .expect("shared online data mutex should not be poisoned")
+            .get(&cache_key)
+            .and_then(Weak::upgrade);
+        if let Some(online_data) = shared_online_data {
+            // connections that went down are replaced by a new one
+            if online_data.electrum_client.ping().is_ok() {
+                debug!(self.logger, "Reusing shared connection to {}", electrum_url);
+                return Ok(online_data);
+            }
+            debug!(self.logger, "Shared connection to {} is down", electrum_url);
+        }
+        let online_data = self._connect_electrum(electrum_url, &self._config())?;
+        let mut shared_online_data = shared
+            .online_data
+            .lock()
+            .expect("shared online data mutex should not be poisoned");
+        shared_online_data.retain(|_, o| o.strong_count() > 0);
+        shared_online_data.insert(cache_key, Arc::downgrade(&online_data));
+        Ok(online_data)
+    }
+
+    // BDK only supports SOCKS5 credentials on electrum clients built directly, which use its
+    // default stop gap, while a custom one can only be set through its blockchain config
+    fn _check_electrum_stop_gap(&self, config: &WalletConfig) -> Result<(), Error> {
+        if config.electrum_stop_gap != ELECTRUM_STOP_GAP && self._indexer_socks5_credentials() {
+            return Err(Error::InvalidWalletConfig {
+                details: s!("a custom electrum_stop_gap is not supported with SOCKS5 credentials"),
+            });
+        }
+        Ok(())
+    }
+
+    fn _indexer_socks5_credentials(&self) -> bool {
+        self.wallet_data
+            .network_config
+            .as_ref()
+            .filter(|c| c.routes_indexer())
+            .map_or(false, |c| c.socks5_credentials.is_some())
+    }
+
+    fn _connect_electrum(
+        &self,
+        electrum_url: String,
+        config: &WalletConfig,
+    ) -> Result<Arc<OnlineData>, Error> {
+        self._check_electrum_stop_gap(config)?;
+
+        let network_config = self
+            .wallet_data
+            .network_config
+            .as_ref()
+            .filter(|c| c.routes_indexer());
+        if is_onion_url(&electrum_url) && network_config.is_none() {
+            return Err(Error::InvalidElectrum {
+                details: s!("onion URLs require routing indexer traffic through SOCKS5"),
+            });
+        }
 
         // create electrum client
-        let electrum_config = ConfigBuilder::new().timeout(Some(ELECTRUM_TIMEOUT)).build();
+        let socks5 = network_config.map(|c| match &c.socks5_credentials {
+            Some(creds) => Socks5Config::with_credentials(
+                &c.socks5_address,
+                creds.username.clone(),
+                creds.password.clone(),
+            ),
+            None => Socks5Config::new(&c.socks5_address),
+        });
+        let electrum_config = ConfigBuilder::new()
+            .timeout(Some(config.electrum_timeout))
+            .socks5(socks5)
+            .build();
         let electrum_client =
             ElectrumClient::from_config(&electrum_url, electrum_config).map_err(|e| {
                 Error::InvalidElectrum {
@@ -2485,74 +4307,183 @@ impl Wallet {
             })?;
 
         // BDK setup
-        let config = ElectrumBlockchainConfig {
-            url: electrum_url.clone(),
-            socks5: None,
-            retry: 3,
-            timeout: Some(5),
-            stop_gap: 20,
-            validate_domain: true,
+        let invalid_electrum = |e: BdkElectrumError| Error::InvalidElectrum {
+            details: e.to_string(),
+        };
+        let socks5_credentials = network_config.and_then(|c| {
+            c.socks5_credentials
+                .as_ref()
+                .map(|creds| (&c.socks5_address, creds))
+        });
+        let bdk_blockchain = if let Some((socks5_address, creds)) = socks5_credentials {
+            let bdk_socks5 = BdkSocks5Config::with_credentials(
+                socks5_address,
+                creds.username.clone(),
+                creds.password.clone(),
+            );
+            let bdk_electrum_config = BdkElectrumConfigBuilder::new()
+                .retry(3)
+                .timeout(Some(config.electrum_timeout))
+                .map_err(invalid_electrum)?
+                .socks5(Some(bdk_socks5))
+                .map_err(invalid_electrum)?
+                .validate_domain(true)
+                .build();
+            ElectrumBlockchain::from(
+                BdkElectrumClient::from_config(&electrum_url, bdk_electrum_config)
+                    .map_err(invalid_electrum)?,
+            )
+        } else {
+            ElectrumBlockchain::from_config(&ElectrumBlockchainConfig {
+                url: electrum_url.clone(),
+                socks5: network_config.map(|c| c.socks5_address.clone()),
+                retry: 3,
+                timeout: Some(config.electrum_timeout),
+                stop_gap: config.electrum_stop_gap as usize,
+                validate_domain: true,
+            })
+            .map_err(|e| Error::InvalidElectrum {
+                details: e.to_string(),
+            })?
         };
-        let bdk_blockchain =
-            ElectrumBlockchain::from_config(&config).map_err(|e| Error::InvalidElectrum {
-                details: e.to_string(),
-            })?;
 
         // check electrum server
         if self._bitcoin_network() != BitcoinNetwork::Regtest {
             self._get_tx_details(get_txid(self._bitcoin_network()), Some(&electrum_client))?;
         }
 
-        let online_data = OnlineData {
-            id: online.id,
+        Ok(Arc::new(OnlineData {
             bdk_blockchain,
             electrum_url,
             electrum_client,
-        };
-
-        Ok((online, online_data))
+        }))
     }
 
     /// Return the existing or freshly generated set of wallet [`Online`] data
     ///
     /// Setting `skip_consistency_check` to true bypasses the check and allows operating an
     /// inconsistent wallet. Warning: this is dangerous, only do this if you know what you're doing!
+    /// An inconsistent wallet can be inspected with [`diagnose`](Wallet::diagnose) and fixed with
+    /// [`repair`](Wallet::repair).
     pub fn go_online(
-        &mut self,
+        &self,
         skip_consistency_check: bool,
         electrum_url: String,
     ) -> Result<Online, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Going online...");
 
-        let online = if let Some(online_data) = &self.online_data {
-            let online = Online {
-                id: online_data.id,
-                electrum_url,
-            };
-            if online_data.electrum_url != online.electrum_url {
-                let (online, online_data) = self._go_online(online.electrum_url)?;
-                self.online_data = Some(online_data);
-                info!(self.logger, "Went online with new electrum URL");
-                online
-            } else {
-                self._check_online(online.clone())?;
+        let current_online = self
+            .online_data
+            .read()
+            .expect("online data lock should not be poisoned")
+            .as_ref()
+            .map(|(online, _)| online.clone());
+        let online = match current_online {
+            Some(online) if online.electrum_url == electrum_url => online,
+            current_online => {
+                // each wallet gets its own Online ID, even when sharing the connections
+                let online_data = self._go_online(electrum_url.clone())?;
+                let online = Online {
+                    id: now().unix_timestamp_nanos() as u64,
+                    electrum_url,
+                };
+                *self
+                    .online_data
+                    .write()
+                    .expect("online data lock should not be poisoned") =
+                    Some((online.clone(), online_data));
+                if current_online.is_some() {
+                    info!(self.logger, "Went online with new electrum URL");
+                }
                 online
             }
-        } else {
-            let (online, online_data) = self._go_online(electrum_url)?;
-            self.online_data = Some(online_data);
-            online
         };
 
         if !skip_consistency_check {
             let runtime = self._rgb_runtime()?;
-            self._check_consistency(self._bdk_blockchain()?, &runtime)?;
+            self._check_consistency(&self._online_data()?.bdk_blockchain, &runtime)?;
         }
 
         info!(self.logger, "Go online completed");
         Ok(online)
     }
 
+    /// Return the current [`WalletConfig`] of the wallet
+    pub fn get_config(&self) -> WalletConfig {
+        self._config()
+    }
+
+    /// Validate and set the provided [`WalletConfig`], persisting it in the wallet DB
+    ///
+    /// The new configuration applies to all subsequent operations. If the wallet is online and the
+    /// electrum parameters change, the wallet reconnects to the electrum server, keeping the same
+    /// [`Online`] data valid.
+    ///
+    /// A non-default `electrum_stop_gap` is not supported when the indexer traffic is routed
+    /// through a SOCKS5 proxy requiring credentials.
+    pub fn set_config(&self, config: WalletConfig) -> Result<(), Error> {
+        let _write_lock = self._write_lock();
+        info!(self.logger, "Setting config {:?}...", config);
+
+        config.validate()?;
+        self._check_electrum_stop_gap(&config)?;
+
+        // reconnect before persisting the config, so a failure leaves the current one in place
+        let current_online_data = self
+            .online_data
+            .read()
+            .expect("online data lock should not be poisoned")
+            .clone();
+        let new_online_data = match current_online_data {
+            Some((online, online_data))
+                if self._config().electrum_params() != config.electrum_params() =>
+            {
+                let online_data =
+                    self._connect_electrum(online_data.electrum_url.clone(), &config)?;
+                Some((online, online_data))
+            }
+            _ => None,
+        };
+
+        let mut db_config = DbWalletConfigActMod {
+            min_btc_required: ActiveValue::Set(config.min_btc_required as i64),
+            utxo_size: ActiveValue::Set(config.utxo_size),
+            utxo_num: ActiveValue::Set(config.utxo_num),
+            max_transport_endpoints: ActiveValue::Set(config.max_transport_endpoints),
+            min_fee_rate: ActiveValue::Set(config.min_fee_rate),
+            max_fee_rate: ActiveValue::Set(config.max_fee_rate),
+            duration_send_transfer: ActiveValue::Set(config.duration_send_transfer),
+            duration_rcv_transfer: ActiveValue::Set(config.duration_rcv_transfer),
+            electrum_timeout: ActiveValue::Set(config.electrum_timeout),
+            proxy_timeout: ActiveValue::Set(config.proxy_timeout),
+            electrum_stop_gap: ActiveValue::Set(config.electrum_stop_gap),
+            duration_proxy_protocol_cache: ActiveValue::Set(config.duration_proxy_protocol_cache),
+            duration_prepared_psbt: ActiveValue::Set(config.duration_prepared_psbt),
+            ..Default::default()
+        };
+        if let Some(DbWalletConfig { idx, .. }) = self.database.get_wallet_config()? {
+            db_config.idx = ActiveValue::Set(idx);
+            self.database.update_wallet_config(db_config)?;
+        } else {
+            self.database.set_wallet_config(db_config)?;
+        }
+
+        *self
+            .config
+            .write()
+            .expect("config lock should not be poisoned") = config;
+        if let Some(online_data) = new_online_data {
+            *self
+                .online_data
+                .write()
+                .expect("online data lock should not be poisoned") = Some(online_data);
+        }
+
+        info!(self.logger, "Set config completed");
+        Ok(())
+    }
+
     fn _check_name(&self, name: String) -> Result<Name, Error> {
         Name::try_from(name).map_err(|e| Error::InvalidName {
             details: e.to_string(),
@@ -2620,13 +4551,14 @@ impl Wallet {
 
     /// Issue a new RGB [`AssetNIA`] and return it
     pub fn issue_asset_nia(
-        &mut self,
+        &self,
         online: Online,
         ticker: String,
         name: String,
         precision: u8,
         amounts: Vec<u64>,
     ) -> Result<AssetNIA, Error> {
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Issuing RGB20 asset with ticker '{}' name '{}' precision '{}' amounts '{:?}'...",
@@ -2642,12 +4574,9 @@ impl Wallet {
         let mut db_data = self.database.get_db_data(false)?;
         self._handle_expired_transfers(&mut db_data)?;
 
-        let mut unspents: Vec<LocalUnspent> = self.database.get_rgb_allocations(
-            self.database.get_unspent_txos(db_data.txos)?,
-            None,
-            None,
-            None,
-        )?;
+        let mut unspents: Vec<LocalUnspent> = self
+            .database
+            .get_rgb_allocations(self.database.get_unspent_txos(db_data.txos)?)?;
         unspents.retain(|u| {
             !(u.rgb_allocations
                 .iter()
@@ -2709,6 +4638,7 @@ impl Wallet {
         runtime
             .import_contract(validated_contract, &mut self._blockchain_resolver()?)
             .expect("failure importing issued contract");
+        runtime.persist()?;
 
         let asset = self._add_asset_to_db(
             asset_id.clone(),
@@ -2754,15 +4684,9 @@ impl Wallet {
             self.database.set_coloring(db_coloring)?;
         }
 
-        let asset = AssetNIA::get_asset_details(
-            self,
-            &asset,
-            self.wallet_dir.join(ASSETS_DIR),
-            None,
-            None,
-            None,
-            None,
-        )?;
+        let asset = AssetNIA::get_asset_details(self, &asset, self.wallet_dir.join(ASSETS_DIR))?;
+
+        self._state_changed()?;
 
         info!(self.logger, "Issue asset RGB20 completed");
         Ok(asset)
@@ -2770,7 +4694,7 @@ impl Wallet {
 
     /// Issue a new RGB [`AssetCFA`] and return it
     pub fn issue_asset_cfa(
-        &mut self,
+        &self,
         online: Online,
         name: String,
         description: Option<String>,
@@ -2778,6 +4702,7 @@ impl Wallet {
         amounts: Vec<u64>,
         file_path: Option<String>,
     ) -> Result<AssetCFA, Error> {
+        let _write_lock = self._write_lock();
         info!(
             self.logger,
             "Issuing RGB25 asset with name '{}' precision '{}' amounts '{:?}'...",
@@ -2792,12 +4717,9 @@ impl Wallet {
         let mut db_data = self.database.get_db_data(false)?;
         self._handle_expired_transfers(&mut db_data)?;
 
-        let mut unspents: Vec<LocalUnspent> = self.database.get_rgb_allocations(
-            self.database.get_unspent_txos(db_data.txos)?,
-            None,
-            None,
-            None,
-        )?;
+        let mut unspents: Vec<LocalUnspent> = self
+            .database
+            .get_rgb_allocations(self.database.get_unspent_txos(db_data.txos)?)?;
         unspents.retain(|u| {
             !(u.rgb_allocations
                 .iter()
@@ -2892,6 +4814,7 @@ impl Wallet {
         runtime
             .import_contract(validated_contract, &mut self._blockchain_resolver()?)
             .expect("failure importing issued contract");
+        runtime.persist()?;
 
         if let Some(fp) = file_path {
             let attachment_id = hex::encode(media.unwrap().digest);
@@ -2951,35 +4874,21 @@ impl Wallet {
             self.database.set_coloring(db_coloring)?;
         }
 
-        let asset = AssetCFA::get_asset_details(
-            self,
-            &asset,
-            self.wallet_dir.join(ASSETS_DIR),
-            None,
-            None,
-            None,
-            None,
-        )?;
+        let asset = AssetCFA::get_asset_details(self, &asset, self.wallet_dir.join(ASSETS_DIR))?;
+
+        self._state_changed()?;
 
         info!(self.logger, "Issue asset RGB25 completed");
         Ok(asset)
     }
 
     /// List the assets known by the underlying RGB node
-    pub fn list_assets(
-        &mut self,
-        mut filter_asset_schemas: Vec<AssetSchema>,
-    ) -> Result<Assets, Error> {
+    pub fn list_assets(&self, mut filter_asset_schemas: Vec<AssetSchema>) -> Result<Assets, Error> {
         info!(self.logger, "Listing assets...");
         if filter_asset_schemas.is_empty() {
             filter_asset_schemas = vec![AssetSchema::Nia, AssetSchema::Cfa];
         }
 
-        let batch_transfers = Some(self.database.iter_batch_transfers()?);
-        let colorings = Some(self.database.iter_colorings()?);
-        let txos = Some(self.database.iter_txos()?);
-        let asset_transfers = Some(self.database.iter_asset_transfers()?);
-
         let assets = self.database.iter_assets()?;
         let mut nia = None;
         let mut cfa = None;
@@ -2995,10 +4904,6 @@ impl Wallet {
                                     self,
                                     a,
                                     self.wallet_dir.join(ASSETS_DIR),
-                                    asset_transfers.clone(),
-                                    batch_transfers.clone(),
-                                    colorings.clone(),
-                                    txos.clone(),
                                 )
                             })
                             .collect::<Result<Vec<AssetNIA>, Error>>()?,
@@ -3010,17 +4915,7 @@ impl Wallet {
                         assets
                             .iter()
                             .filter(|a| a.schema == schema)
-                            .map(|a| {
-                                AssetCFA::get_asset_details(
-                                    self,
-                                    a,
-                                    assets_dir.clone(),
-                                    asset_transfers.clone(),
-                                    batch_transfers.clone(),
-                                    colorings.clone(),
-                                    txos.clone(),
-                                )
-                            })
+                            .map(|a| AssetCFA::get_asset_details(self, a, assets_dir.clone()))
                             .collect::<Result<Vec<AssetCFA>, Error>>()?,
                     );
                 }
@@ -3034,7 +4929,7 @@ impl Wallet {
     fn _sync_if_online(&self, online: Option<Online>) -> Result<(), Error> {
         if let Some(online) = online {
             self._check_online(online)?;
-            self._sync_wallet(&self.bdk_wallet)?;
+            self._sync_bdk_wallet(&self._online_data()?.bdk_blockchain)?;
         }
         Ok(())
     }
@@ -3061,7 +4956,7 @@ impl Wallet {
             .filter_map(|t| t.txid)
             .collect();
         let transactions = self
-            .bdk_wallet
+            ._bdk_wallet()
             .list_transactions(false)
             .map_err(InternalError::from)?
             .into_iter()
@@ -3133,6 +5028,11 @@ impl Wallet {
         Ok(transfers)
     }
 
+    #[cfg(feature = "daemon")]
+    pub(crate) fn _transfer_statuses(&self) -> Result<Vec<TransferStatusInfo>, Error> {
+        Ok(self.database.get_transfer_statuses()?)
+    }
+
     /// List the [`Unspent`]s known to the RGB wallet,
     /// if `settled` is true only show settled allocations
     /// if `settled` is false also show pending allocations
@@ -3186,12 +5086,7 @@ impl Wallet {
             .collect();
         allocation_txos.append(&mut spent_txos);
 
-        let mut txos_allocations = self.database.get_rgb_allocations(
-            allocation_txos,
-            Some(db_data.colorings),
-            Some(db_data.batch_transfers),
-            Some(db_data.asset_transfers),
-        )?;
+        let mut txos_allocations = self.database.get_rgb_allocations(allocation_txos)?;
 
         txos_allocations
             .iter_mut()
@@ -3243,10 +5138,9 @@ impl Wallet {
         updated_batch_transfer: &mut DbBatchTransferActMod,
     ) -> Result<Option<DbBatchTransfer>, Error> {
         debug!(self.logger, "Consignment is invalid");
-        let nack_res = self
-            .rest_client
-            .clone()
-            .post_ack(&proxy_url, recipient_id, false)?;
+        let nack_res = self._call_proxy(&proxy_url, |c| {
+            c.post_ack(&proxy_url, recipient_id.clone(), false)
+        })?;
         debug!(self.logger, "Consignment NACK response: {:?}", nack_res);
         updated_batch_transfer.status = ActiveValue::Set(TransferStatus::Failed);
         Ok(Some(
@@ -3304,7 +5198,7 @@ impl Wallet {
     }
 
     fn _wait_consignment(
-        &mut self,
+        &self,
         batch_transfer: &DbBatchTransfer,
         db_data: &DbData,
     ) -> Result<Option<DbBatchTransfer>, Error> {
@@ -3326,12 +5220,17 @@ impl Wallet {
         if self._fail_batch_transfer_if_no_endpoints(batch_transfer, &tte_data)? {
             return Ok(None);
         }
+        // healthier endpoints are tried first
+        let mut tte_data = tte_data;
+        tte_data.sort_by_key(|(_, te)| te.consecutive_failures);
         let mut proxy_res = None;
         for (transfer_transport_endpoint, transport_endpoint) in tte_data {
-            let consignment_res = self
-                .rest_client
-                .clone()
-                .get_consignment(&transport_endpoint.endpoint, recipient_id.clone());
+            let consignment_res = self._call_proxy(&transport_endpoint.endpoint, |c| {
+                c.get_consignment(&transport_endpoint.endpoint, recipient_id.clone())
+            });
+            if let Err(Error::ProxyUnauthorized { .. }) = consignment_res {
+                return Err(consignment_res.unwrap_err());
+            }
             if consignment_res.is_err() {
                 debug!(
                     self.logger,
@@ -3385,7 +5284,6 @@ impl Wallet {
             .map_err(InternalError::from)?;
         fs::write(consignment_path.clone(), consignment_bytes).expect("Unable to write file");
 
-        let mut runtime = self._rgb_runtime()?;
         let bindle = Bindle::<RgbTransfer>::load(consignment_path).map_err(InternalError::from)?;
         let consignment: RgbTransfer = bindle.unbindle();
         let contract_id = consignment.contract_id();
@@ -3422,27 +5320,33 @@ impl Wallet {
         let schema_id = consignment.schema_id().to_string();
         let asset_schema = AssetSchema::from_schema_id(schema_id)?;
 
+        // validate the contract of an unknown asset before locking the runtime
+        let unknown_contract = if asset_transfer.asset_id.is_none()
+            && self.database.check_asset_exists(asset_id.clone()).is_err()
+        {
+            let mut minimal_contract = consignment.clone().into_contract();
+            minimal_contract.bundles = none!();
+            minimal_contract.terminals = none!();
+            Some(
+                match minimal_contract.validate(&mut self._blockchain_resolver()?) {
+                    Ok(consignment) => consignment,
+                    Err(consignment) => consignment,
+                },
+            )
+        } else {
+            None
+        };
+
+        let mut runtime = self._rgb_runtime()?;
         // add asset info to transfer if missing
         let contract_iface = if asset_transfer.asset_id.is_none() {
-            // check if asset is known
-            let exists_check = self.database.check_asset_exists(asset_id.clone());
-            let contract_iface = if exists_check.is_err() {
-                // unknown asset
+            let contract_iface = if let Some(minimal_contract_validated) = unknown_contract {
                 debug!(self.logger, "Registering contract...");
-                let mut minimal_contract = consignment.clone().into_contract();
-                minimal_contract.bundles = none!();
-                minimal_contract.terminals = none!();
-                let minimal_contract_validated =
-                    match minimal_contract.validate(&mut self._blockchain_resolver()?) {
-                        Ok(consignment) => consignment,
-                        Err(consignment) => consignment,
-                    };
+                // a contract without bundles has no witness heights to resolve
                 runtime
-                    .import_contract(
-                        minimal_contract_validated,
-                        &mut self._blockchain_resolver()?,
-                    )
+                    .import_contract(minimal_contract_validated, &mut ResolvedHeights::default())
                     .expect("failure importing issued contract");
+                runtime.persist()?;
                 debug!(self.logger, "Contract registered");
 
                 self.save_new_asset(&mut runtime, &asset_schema, contract_id)?
@@ -3470,12 +5374,13 @@ impl Wallet {
                 iface_cfa.contract_data()
             }
         };
+        // release the runtime before fetching the media from the proxy
+        drop(runtime);
         if let Some(media) = contract_data.media {
             let attachment_id = hex::encode(media.digest);
-            let media_res = self
-                .rest_client
-                .clone()
-                .get_media(&proxy_url, attachment_id.clone())?;
+            let media_res = self._call_proxy(&proxy_url, |c| {
+                c.get_media(&proxy_url, attachment_id.clone())
+            })?;
             debug!(self.logger, "Media GET response: {:?}", media_res);
             if let Some(media_res) = media_res.result {
                 let file_bytes = general_purpose::STANDARD
@@ -3548,10 +5453,9 @@ impl Wallet {
             "Consignment is valid. Received '{}' of contract '{}'", amount, asset_id
         );
 
-        let ack_res = self
-            .rest_client
-            .clone()
-            .post_ack(&proxy_url, recipient_id, true)?;
+        let ack_res = self._call_proxy(&proxy_url, |c| {
+            c.post_ack(&proxy_url, recipient_id.clone(), true)
+        })?;
         debug!(self.logger, "Consignment ACK response: {:?}", ack_res);
 
         let mut updated_transfer: DbTransferActMod = transfer.clone().into();
@@ -3594,39 +5498,78 @@ impl Wallet {
     ) -> Result<Option<DbBatchTransfer>, Error> {
         debug!(self.logger, "Waiting ACK...");
 
+        let txid = batch_transfer
+            .txid
+            .clone()
+            .expect("batch transfer should have a TXID");
+        let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(&txid);
+        let redundant_posting = self
+            ._get_batch_transfer_info(&transfer_dir)?
+            .redundant_posting;
         let mut batch_transfer_data =
             batch_transfer.get_transfers(&db_data.asset_transfers, &db_data.transfers)?;
         for asset_transfer_data in batch_transfer_data.asset_transfers_data.iter_mut() {
+            let asset_id = asset_transfer_data
+                .asset_transfer
+                .asset_id
+                .clone()
+                .expect("asset transfer should have an asset ID");
             for transfer in asset_transfer_data.transfers.iter_mut() {
                 if transfer.ack.is_some() {
                     continue;
                 }
-                let tte_data = self
+                let mut tte_data = self
                     .database
                     .get_transfer_transport_endpoints_data(transfer.idx)?;
                 if self._fail_batch_transfer_if_no_endpoints(batch_transfer, &tte_data)? {
                     return Ok(None);
                 }
-                let (_, transport_endpoint) = tte_data
+                if redundant_posting {
+                    self._repost_transfer_data(&txid, &asset_id, transfer, &tte_data)?;
+                    tte_data = self
+                        .database
+                        .get_transfer_transport_endpoints_data(transfer.idx)?;
+                }
+                let recipient_id = transfer
+                    .recipient_id
                     .clone()
-                    .into_iter()
-                    .find(|(tte, _ce)| tte.used)
-                    .expect("there should be 1 used TTE");
-                let proxy_url = transport_endpoint.endpoint.clone();
-                let ack_res = self.rest_client.clone().get_ack(
-                    &proxy_url,
-                    transfer
-                        .recipient_id
-                        .clone()
-                        .expect("transfer should have a recipient ID"),
-                )?;
-                debug!(self.logger, "Consignment ACK/NACK response: {:?}", ack_res);
-
-                if ack_res.result.is_some() {
+                    .expect("transfer should have a recipient ID");
+
+                // the receiver could ACK on any of the endpoints the consignment has been posted
+                // to, starting from the used one
+                tte_data.sort_by_key(|(tte, _)| !tte.used);
+                let mut ack = None;
+                let mut answered = false;
+                let mut proxy_err = None;
+                for (_, transport_endpoint) in
+                    tte_data.iter().filter(|(tte, _)| tte.used || tte.posted)
+                {
+                    let proxy_url = transport_endpoint.endpoint.clone();
+                    match self
+                        ._call_proxy(&proxy_url, |c| c.get_ack(&proxy_url, recipient_id.clone()))
+                    {
+                        Ok(ack_res) => {
+                            debug!(self.logger, "Consignment ACK/NACK response: {:?}", ack_res);
+                            answered = true;
+                            if ack_res.result.is_some() {
+                                ack = ack_res.result;
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            proxy_err.get_or_insert(e);
+                        }
+                    }
+                }
+                if let (false, Some(e)) = (answered, proxy_err) {
+                    return Err(e);
+                }
+
+                if ack.is_some() {
                     let mut updated_transfer: DbTransferActMod = transfer.clone().into();
-                    updated_transfer.ack = ActiveValue::Set(ack_res.result);
+                    updated_transfer.ack = ActiveValue::Set(ack);
                     self.database.update_transfer(&mut updated_transfer)?;
-                    transfer.ack = ack_res.result;
+                    transfer.ack = ack;
                 }
             }
         }
@@ -3643,12 +5586,6 @@ impl Wallet {
         {
             updated_batch_transfer.status = ActiveValue::Set(TransferStatus::Failed);
         } else if batch_transfer_transfers.iter().all(|t| t.ack == Some(true)) {
-            let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(
-                batch_transfer
-                    .txid
-                    .as_ref()
-                    .expect("batch transfer should have a TXID"),
-            );
             let signed_psbt = self._get_signed_psbt(transfer_dir)?;
             self._broadcast_psbt(signed_psbt)?;
             updated_batch_transfer.status = ActiveValue::Set(TransferStatus::WaitingConfirmations);
@@ -3663,7 +5600,7 @@ impl Wallet {
     }
 
     fn _wait_confirmations(
-        &mut self,
+        &self,
         batch_transfer: &DbBatchTransfer,
         db_data: &DbData,
         incoming: bool,
@@ -3745,14 +5682,15 @@ impl Wallet {
             let consignment = consignment
                 .validate(&mut self._blockchain_resolver()?)
                 .unwrap_or_else(|c| c);
+            let mut heights = self._resolve_witness_heights(&consignment)?;
             let mut runtime = self._rgb_runtime()?;
             let force = false;
-            let validation_status =
-                runtime.accept_transfer(consignment, &mut self._blockchain_resolver()?, force)?;
+            let validation_status = runtime.accept_transfer(consignment, &mut heights, force)?;
             let validity = validation_status.validity();
             if !matches!(validity, Validity::Valid) {
                 return Err(InternalError::Unexpected)?;
             }
+            runtime.persist()?;
         }
 
         let mut updated_batch_transfer: DbBatchTransferActMod = batch_transfer.clone().into();
@@ -3765,7 +5703,7 @@ impl Wallet {
     }
 
     fn _wait_counterparty(
-        &mut self,
+        &self,
         transfer: &DbBatchTransfer,
         db_data: &mut DbData,
         incoming: bool,
@@ -3778,7 +5716,7 @@ impl Wallet {
     }
 
     fn _refresh_transfer(
-        &mut self,
+        &self,
         transfer: &DbBatchTransfer,
         db_data: &mut DbData,
         filter: &Vec<RefreshFilter>,
@@ -3815,11 +5753,12 @@ impl Wallet {
     /// Changes to each transfer depend on its status and whether the wallet is on the receiving or
     /// sending side.
     pub fn refresh(
-        &mut self,
+        &self,
         online: Online,
         asset_id: Option<String>,
         filter: Vec<RefreshFilter>,
     ) -> Result<bool, Error> {
+        let _write_lock = self._write_lock();
         if let Some(aid) = asset_id.clone() {
             info!(self.logger, "Refreshing asset {}...", aid);
             self.database.check_asset_exists(aid)?;
@@ -3828,20 +5767,18 @@ impl Wallet {
         }
         self._check_online(online)?;
 
-        let mut db_data = self.database.get_db_data(false)?;
-
-        if let Some(aid) = asset_id {
-            let batch_transfers_ids: Vec<i32> = self
-                .database
-                .iter_asset_asset_transfers(aid, db_data.asset_transfers.clone())
-                .into_iter()
-                .map(|t| t.batch_transfer_idx)
-                .collect();
-            db_data
-                .batch_transfers
-                .retain(|t| batch_transfers_ids.contains(&t.idx));
+        let batch_transfers = self.database.iter_pending_batch_transfers(asset_id)?;
+        if batch_transfers.is_empty() {
+            info!(self.logger, "Refresh completed");
+            return Ok(false);
+        }
+        let mut db_data = DbData {
+            batch_transfers,
+            asset_transfers: self.database.iter_asset_transfers()?,
+            transfers: self.database.iter_transfers()?,
+            colorings: self.database.iter_colorings()?,
+            txos: self.database.iter_txos()?,
         };
-        db_data.batch_transfers.retain(|t| t.pending());
 
         let mut transfers_changed = false;
         for transfer in db_data.batch_transfers.clone().into_iter() {
@@ -3853,6 +5790,10 @@ impl Wallet {
             }
         }
 
+        if transfers_changed {
+            self._state_changed()?;
+        }
+
         info!(self.logger, "Refresh completed");
         Ok(transfers_changed)
     }
@@ -3862,9 +5803,6 @@ impl Wallet {
         asset_id: String,
         amount_needed: u64,
         unspents: Vec<LocalUnspent>,
-        asset_transfers: Option<Vec<DbAssetTransfer>>,
-        batch_transfers: Option<Vec<DbBatchTransfer>>,
-        colorings: Option<Vec<DbColoring>>,
     ) -> Result<AssetSpend, Error> {
         debug!(self.logger, "Selecting inputs for asset '{}'...", asset_id);
         let mut input_allocations: HashMap<DbTxo, u64> = HashMap::new();
@@ -3887,13 +5825,7 @@ impl Wallet {
             }
         }
         if amount_input_asset < amount_needed {
-            let ass_balance = self.database.get_asset_balance(
-                asset_id.clone(),
-                asset_transfers,
-                batch_transfers,
-                colorings,
-                None,
-            )?;
+            let ass_balance = self.database.get_asset_balance(asset_id.clone())?;
             if ass_balance.future < amount_needed {
                 return Err(Error::InsufficientTotalAssets { asset_id });
             }
@@ -3924,7 +5856,9 @@ impl Wallet {
         witness_recipients: &HashMap<ScriptBuf, u64>,
         fee_rate: f32,
     ) -> Result<BdkPsbt, Error> {
-        let mut builder = self.bdk_wallet.build_tx();
+        let change_address = self._get_new_address();
+        let bdk_wallet = self._bdk_wallet();
+        let mut builder = bdk_wallet.build_tx();
         builder
             .add_utxos(&input_outpoints)
             .map_err(InternalError::from)?
@@ -3936,7 +5870,7 @@ impl Wallet {
             builder.add_recipient(bdk_script, *amount_sat);
         }
         builder
-            .drain_to(self._get_new_address().script_pubkey())
+            .drain_to(change_address.script_pubkey())
             .add_data(&[1]);
 
         Ok(builder
@@ -3993,6 +5927,7 @@ impl Wallet {
         unspents: Vec<LocalUnspent>,
         runtime: &mut RgbRuntime,
         min_confirmations: u8,
+        redundant_posting: bool,
     ) -> Result<(), Error> {
         let change_utxo = self._get_utxo(
             input_outpoints.into_iter().map(|t| t.into()).collect(),
@@ -4206,6 +6141,7 @@ impl Wallet {
             blank_allocations,
             donation,
             min_confirmations,
+            redundant_posting,
         };
         let serialized_info = serde_json::to_string(&info_contents).map_err(InternalError::from)?;
         let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
@@ -4214,16 +6150,17 @@ impl Wallet {
         Ok(())
     }
 
-    fn _post_transfer_data(
-        &self,
-        recipients: &mut Vec<LocalRecipient>,
-        asset_transfer_dir: PathBuf,
-        asset_dir: Option<PathBuf>,
-        txid: String,
-    ) -> Result<(), Error> {
+    fn _get_batch_transfer_info(&self, transfer_dir: &Path) -> Result<InfoBatchTransfer, Error> {
+        let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
+        let serialized_info = fs::read_to_string(info_file)?;
+        Ok(serde_json::from_str(&serialized_info).map_err(InternalError::from)?)
+    }
+
+    fn _get_attachments(&self, asset_id: &str) -> Result<Vec<(String, PathBuf)>, Error> {
         let mut attachments = vec![];
-        if let Some(ass_dir) = &asset_dir {
-            for fp in fs::read_dir(ass_dir)? {
+        let asset_dir = self.wallet_dir.join(ASSETS_DIR).join(asset_id);
+        if asset_dir.is_dir() {
+            for fp in fs::read_dir(asset_dir)? {
                 let fpath = fp?.path();
                 let file_path = fpath.join(MEDIA_FNAME);
                 let file_bytes = std::fs::read(file_path.clone())?;
@@ -4232,12 +6169,90 @@ impl Wallet {
                 attachments.push((attachment_id, file_path))
             }
         }
+        Ok(attachments)
+    }
+
+    /// Post the consignment and the optional media to the provided proxy, returning whether they
+    /// have been accepted
+    fn _post_to_transport_endpoint(
+        &self,
+        proxy_url: &str,
+        recipient_id: &str,
+        consignment_path: &Path,
+        txid: &str,
+        vout: Option<u32>,
+        attachments: &[(String, PathBuf)],
+    ) -> Result<bool, Error> {
+        let consignment_res = match self._call_proxy(proxy_url, |c| {
+            c.post_consignment(
+                proxy_url,
+                recipient_id.to_string(),
+                consignment_path.to_path_buf(),
+                txid.to_string(),
+                vout,
+            )
+        }) {
+            Ok(consignment_res) => consignment_res,
+            Err(e @ Error::ProxyUnauthorized { .. }) => return Err(e),
+            Err(e) => {
+                info!(
+                    self.logger,
+                    "Cannot post consignment to {}: {}", proxy_url, e
+                );
+                return Ok(false);
+            }
+        };
+        debug!(
+            self.logger,
+            "Consignment POST response: {:?}", consignment_res
+        );
+
+        if let Some(err) = consignment_res.error {
+            if err.code == -101 {
+                return Err(Error::RecipientIDAlreadyUsed)?;
+            }
+            return Ok(false);
+        } else if consignment_res.result.is_none() {
+            return Ok(false);
+        }
+        for (attachment_id, file_path) in attachments {
+            let media_res = self._call_proxy(proxy_url, |c| {
+                c.post_media(proxy_url, attachment_id.clone(), file_path.clone())
+            });
+            debug!(self.logger, "Attachment POST response: {:?}", media_res);
+            match media_res {
+                Ok(res) if res.error.is_none() => {}
+                Err(e @ Error::ProxyUnauthorized { .. }) => return Err(e),
+                _ => {
+                    info!(self.logger, "Cannot post media to {}", proxy_url);
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
 
+    fn _post_transfer_data(
+        &self,
+        recipients: &mut Vec<LocalRecipient>,
+        asset_transfer_dir: PathBuf,
+        asset_id: &str,
+        txid: String,
+        redundant_posting: bool,
+    ) -> Result<(), Error> {
+        let attachments = self._get_attachments(asset_id)?;
         let consignment_path = asset_transfer_dir.join(CONSIGNMENT_FILE);
         for recipient in recipients {
             let recipient_id = recipient.recipient_id();
+            let mut endpoint_order = vec![];
+            for (i, transport_endpoint) in recipient.transport_endpoints.iter().enumerate() {
+                let priority = self._endpoint_priority(&transport_endpoint.endpoint)?;
+                endpoint_order.push((priority, i));
+            }
+            endpoint_order.sort();
             let mut found_valid = false;
-            for transport_endpoint in recipient.transport_endpoints.iter_mut() {
+            for (_, i) in endpoint_order {
+                let transport_endpoint = &mut recipient.transport_endpoints[i];
                 if transport_endpoint.transport_type != TransportType::JsonRpc
                     || !transport_endpoint.usable
                 {
@@ -4247,40 +6262,35 @@ impl Wallet {
                     );
                     continue;
                 }
-                let proxy_url = transport_endpoint.endpoint.clone();
-                let consignment_res = self.rest_client.clone().post_consignment(
-                    &proxy_url,
-                    recipient_id.clone(),
-                    consignment_path.clone(),
-                    txid.clone(),
+                let posted = match self._post_to_transport_endpoint(
+                    &transport_endpoint.endpoint,
+                    &recipient_id,
+                    &consignment_path,
+                    &txid,
                     recipient.vout,
-                )?;
-                debug!(
-                    self.logger,
-                    "Consignment POST response: {:?}", consignment_res
-                );
-
-                if let Some(err) = consignment_res.error {
-                    if err.code == -101 {
-                        return Err(Error::RecipientIDAlreadyUsed)?;
+                    &attachments,
+                ) {
+                    Ok(posted) => posted,
+                    // the consignment is already on a proxy, failed redundant posts are retried
+                    // as part of refresh
+                    Err(e) if found_valid => {
+                        info!(
+                            self.logger,
+                            "Cannot post consignment to {}: {}", transport_endpoint.endpoint, e
+                        );
+                        false
                     }
+                    Err(e) => return Err(e),
+                };
+                if !posted {
                     continue;
-                } else if consignment_res.result.is_none() {
-                    continue;
-                } else {
-                    for attachment in attachments.clone() {
-                        let media_res = self.rest_client.clone().post_media(
-                            &proxy_url,
-                            attachment.0,
-                            attachment.1,
-                        )?;
-                        debug!(self.logger, "Attachment POST response: {:?}", media_res);
-                        if let Some(_err) = media_res.error {
-                            return Err(InternalError::Unexpected)?;
-                        }
-                    }
+                }
+                transport_endpoint.posted = true;
+                if !found_valid {
                     transport_endpoint.used = true;
                     found_valid = true;
+                }
+                if !redundant_posting {
                     break;
                 }
             }
@@ -4292,6 +6302,73 @@ impl Wallet {
         Ok(())
     }
 
+    /// Post the consignment of an outgoing transfer to its transport endpoints that have not
+    /// accepted it yet
+    fn _repost_transfer_data(
+        &self,
+        txid: &str,
+        asset_id: &str,
+        transfer: &DbTransfer,
+        tte_data: &[(DbTransferTransportEndpoint, DbTransportEndpoint)],
+    ) -> Result<(), Error> {
+        let pending: Vec<_> = tte_data.iter().filter(|(tte, _)| !tte.posted).collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let asset_transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid).join(asset_id);
+        let info_file = asset_transfer_dir.join(TRANSFER_DATA_FILE);
+        let serialized_info = fs::read_to_string(info_file)?;
+        let info_contents: InfoAssetTransfer =
+            serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+        let recipient_id = transfer
+            .recipient_id
+            .clone()
+            .expect("transfer should have a recipient ID");
+        let vout = info_contents
+            .recipients
+            .iter()
+            .find(|r| r.recipient_id() == recipient_id)
+            .expect("transfer should have a recipient")
+            .vout;
+        let attachments = self._get_attachments(asset_id)?;
+        let consignment_path = asset_transfer_dir.join(CONSIGNMENT_FILE);
+        for (transfer_transport_endpoint, transport_endpoint) in pending {
+            let endpoint = TransportEndpoint {
+                endpoint: transport_endpoint.endpoint.clone(),
+                transport_type: transport_endpoint.transport_type,
+            };
+            if let Some(reason) = self._check_proxy_protocol(&endpoint, vout.is_some())? {
+                debug!(
+                    self.logger,
+                    "Skipping transport endpoint {:?}: {}", transport_endpoint, reason
+                );
+                continue;
+            }
+            match self._post_to_transport_endpoint(
+                &endpoint.endpoint,
+                &recipient_id,
+                &consignment_path,
+                txid,
+                vout,
+                &attachments,
+            ) {
+                Ok(true) => {
+                    let mut updated_tte: DbTransferTransportEndpointActMod =
+                        transfer_transport_endpoint.clone().into();
+                    updated_tte.posted = ActiveValue::Set(true);
+                    self.database
+                        .update_transfer_transport_endpoint(&mut updated_tte)?;
+                }
+                Ok(false) => {}
+                Err(e) => info!(
+                    self.logger,
+                    "Cannot post consignment to {}: {}", endpoint.endpoint, e
+                ),
+            }
+        }
+        Ok(())
+    }
+
     fn _save_transfers(
         &self,
         txid: String,
@@ -4302,7 +6379,7 @@ impl Wallet {
         min_confirmations: u8,
     ) -> Result<(), Error> {
         let created_at = now().unix_timestamp();
-        let expiration = Some(created_at + DURATION_SEND_TRANSFER);
+        let expiration = Some(created_at + self._config().duration_send_transfer);
 
         let batch_transfer = DbBatchTransferActMod {
             txid: ActiveValue::Set(Some(txid)),
@@ -4387,25 +6464,49 @@ impl Wallet {
     ///
     /// This is the full version, requiring a wallet with private keys
     pub fn send(
-        &mut self,
+        &self,
+        online: Online,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        donation: bool,
+        fee_rate: f32,
+        min_confirmations: u8,
+    ) -> Result<String, Error> {
+        self.send_with_options(
+            online,
+            recipient_map,
+            donation,
+            fee_rate,
+            min_confirmations,
+            SendOptions::default(),
+        )
+    }
+
+    /// Send tokens with the provided [`SendOptions`]. See the
+    /// [`send_begin_with_options`](Wallet::send_begin_with_options) function for details.
+    ///
+    /// This is the full version, requiring a wallet with private keys
+    pub fn send_with_options(
+        &self,
         online: Online,
         recipient_map: HashMap<String, Vec<Recipient>>,
         donation: bool,
         fee_rate: f32,
         min_confirmations: u8,
+        options: SendOptions,
     ) -> Result<String, Error> {
         info!(self.logger, "Sending to: {:?}...", recipient_map);
-        self._check_xprv()?;
+        self._check_signer()?;
 
-        let unsigned_psbt = self.send_begin(
+        let unsigned_psbt = self.send_begin_with_options(
             online.clone(),
             recipient_map,
             donation,
             fee_rate,
             min_confirmations,
+            options,
         )?;
 
-        let psbt = self.sign_psbt(unsigned_psbt)?;
+        let psbt = self._sign_with_signer(unsigned_psbt)?;
 
         self.send_end(online, psbt)
     }
@@ -4427,13 +6528,43 @@ impl Wallet {
     ///
     /// Returns a PSBT ready to be signed
     pub fn send_begin(
-        &mut self,
+        &self,
+        online: Online,
+        recipient_map: HashMap<String, Vec<Recipient>>,
+        donation: bool,
+        fee_rate: f32,
+        min_confirmations: u8,
+    ) -> Result<String, Error> {
+        self.send_begin_with_options(
+            online,
+            recipient_map,
+            donation,
+            fee_rate,
+            min_confirmations,
+            SendOptions::default(),
+        )
+    }
+
+    /// Prepare the PSBT to send tokens with the provided [`SendOptions`]. See the
+    /// [`send_begin`](Wallet::send_begin) function for details.
+    ///
+    /// If `redundant_posting` is set, the consignment (and optional media) will be posted (by
+    /// [`send_end`](Wallet::send_end)) to all the usable transport endpoints of each recipient,
+    /// instead of stopping at the first one accepting it. Endpoints that fail will be retried as
+    /// part of [`refresh`](Wallet::refresh), as long as the transfer is waiting for the
+    /// counterparty.
+    ///
+    /// Returns a PSBT ready to be signed
+    pub fn send_begin_with_options(
+        &self,
         online: Online,
         recipient_map: HashMap<String, Vec<Recipient>>,
         donation: bool,
         fee_rate: f32,
         min_confirmations: u8,
+        options: SendOptions,
     ) -> Result<String, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Sending (begin) to: {:?}...", recipient_map);
         self._check_online(online)?;
         self._check_fee_rate(fee_rate)?;
@@ -4458,12 +6589,7 @@ impl Wallet {
         // input selection
         let utxos = self.database.get_unspent_txos(db_data.txos.clone())?;
 
-        let unspents = self.database.get_rgb_allocations(
-            utxos,
-            Some(db_data.colorings.clone()),
-            Some(db_data.batch_transfers.clone()),
-            Some(db_data.asset_transfers.clone()),
-        )?;
+        let unspents = self.database.get_rgb_allocations(utxos)?;
 
         let mut input_unspents = unspents.clone();
         input_unspents.retain(|u| {
@@ -4487,34 +6613,32 @@ impl Wallet {
             for recipient in recipients.clone() {
                 self._check_transport_endpoints(&recipient.transport_endpoints)?;
 
+                let needs_vout =
+                    matches!(recipient.recipient_data, RecipientData::WitnessData { .. });
                 let mut transport_endpoints: Vec<LocalTransportEndpoint> = vec![];
-                let mut found_valid = false;
+                let mut unsupported = vec![];
                 for endpoint_str in &recipient.transport_endpoints {
                     let transport_endpoint = TransportEndpoint::new(endpoint_str.clone())?;
-                    let mut local_transport_endpoint = LocalTransportEndpoint {
+                    let unsupported_reason =
+                        self._check_proxy_protocol(&transport_endpoint, needs_vout)?;
+                    if let Some(reason) = &unsupported_reason {
+                        unsupported.push(format!("{} ({reason})", transport_endpoint.endpoint));
+                    }
+                    transport_endpoints.push(LocalTransportEndpoint {
                         transport_type: transport_endpoint.transport_type,
                         endpoint: transport_endpoint.endpoint.clone(),
                         used: false,
-                        usable: false,
-                    };
-                    if let Ok(server_info) = self
-                        .rest_client
-                        .clone()
-                        .get_info(&transport_endpoint.endpoint)
-                    {
-                        if let Some(info) = server_info.result {
-                            if info.protocol_version == *PROXY_PROTOCOL_VERSION {
-                                local_transport_endpoint.usable = true;
-                                found_valid = true;
-                            }
-                        }
-                    };
-                    transport_endpoints.push(local_transport_endpoint);
+                        usable: unsupported_reason.is_none(),
+                        posted: false,
+                    });
                 }
 
-                if !found_valid {
+                if unsupported.len() == transport_endpoints.len() {
                     return Err(Error::InvalidTransportEndpoints {
-                        details: s!("no valid transport endpoints"),
+                        details: format!(
+                            "no valid transport endpoints: {}",
+                            unsupported.join(", ")
+                        ),
                     });
                 }
 
@@ -4543,14 +6667,8 @@ impl Wallet {
             let contract_id = ContractId::from_str(&asset_id).expect("invalid contract ID");
             let asset_iface = self._get_asset_iface(contract_id, &runtime)?;
             let amount: u64 = recipients.iter().map(|a| a.amount).sum();
-            let asset_spend = self._select_rgb_inputs(
-                asset_id.clone(),
-                amount,
-                input_unspents.clone(),
-                Some(db_data.asset_transfers.clone()),
-                Some(db_data.batch_transfers.clone()),
-                Some(db_data.colorings.clone()),
-            )?;
+            let asset_spend =
+                self._select_rgb_inputs(asset_id.clone(), amount, input_unspents.clone())?;
             let transfer_info = InfoAssetTransfer {
                 recipients: local_recipients.clone(),
                 asset_spend,
@@ -4601,12 +6719,15 @@ impl Wallet {
             unspents,
             &mut runtime,
             min_confirmations,
+            options.redundant_posting,
         )?;
+        runtime.persist()?;
 
         // rename transfer directory
         let txid = psbt.clone().extract_tx().txid().to_string();
-        let new_transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid);
+        let new_transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid.clone());
         fs::rename(transfer_dir, new_transfer_dir)?;
+        self._save_prepared_psbt(txid, PreparedPsbtType::Send)?;
 
         info!(self.logger, "Send (begin) completed");
         Ok(psbt.to_string())
@@ -4616,28 +6737,32 @@ impl Wallet {
     /// server, saving the transfer to DB and broadcasting the provided PSBT, if appropriate.
     ///
     /// This is the second half of the partial version. The provided PSBT, prepared with the
-    /// `send_begin` function, needs to have already been signed.
+    /// `send_begin` function, needs to have already been signed. If its transaction (including the
+    /// RGB commitment) differs from the prepared one a [`PsbtMismatch`](Error::PsbtMismatch) error
+    /// is returned, if it has been prepared longer ago than the configured
+    /// `duration_prepared_psbt` a [`PreparedPsbtExpired`](Error::PreparedPsbtExpired) one is, and
+    /// the transfer is not processed.
     ///
     /// Returns the TXID of the signed PSBT that's been saved and optionally broadcast
     pub fn send_end(&self, online: Online, signed_psbt: String) -> Result<String, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Sending (end)...");
         self._check_online(online)?;
 
         // save signed PSBT
         let psbt = BdkPsbt::from_str(&signed_psbt)?;
+        let prepared_psbt = self._get_prepared_psbt(&psbt, PreparedPsbtType::Send)?;
         let txid = psbt.clone().extract_tx().txid().to_string();
         let transfer_dir = self.wallet_dir.join(TRANSFER_DIR).join(txid.clone());
         let psbt_out = transfer_dir.join(SIGNED_PSBT_FILE);
         fs::write(psbt_out, psbt.to_string())?;
 
         // restore transfer data
-        let info_file = transfer_dir.join(TRANSFER_DATA_FILE);
-        let serialized_info = fs::read_to_string(info_file)?;
-        let info_contents: InfoBatchTransfer =
-            serde_json::from_str(&serialized_info).map_err(InternalError::from)?;
+        let info_contents = self._get_batch_transfer_info(&transfer_dir)?;
         let blank_allocations = info_contents.blank_allocations;
         let change_utxo_idx = info_contents.change_utxo_idx;
         let donation = info_contents.donation;
+        let info_contents_redundant_posting = info_contents.redundant_posting;
         let mut transfer_info_map: BTreeMap<String, InfoAssetTransfer> = BTreeMap::new();
         for ass_transf_dir in fs::read_dir(transfer_dir)? {
             let asset_transfer_dir = ass_transf_dir?.path();
@@ -4656,17 +6781,12 @@ impl Wallet {
                 .to_string();
 
             // post consignment(s) and optional media
-            let ass_dir = self.wallet_dir.join(ASSETS_DIR).join(asset_id.clone());
-            let asset_dir = if ass_dir.is_dir() {
-                Some(ass_dir)
-            } else {
-                None
-            };
             self._post_transfer_data(
                 &mut info_contents.recipients,
                 asset_transfer_dir,
-                asset_dir,
+                &asset_id,
                 txid.clone(),
+                info_contents_redundant_posting,
             )?;
 
             transfer_info_map.insert(asset_id, info_contents.clone());
@@ -4687,6 +6807,9 @@ impl Wallet {
             status,
             info_contents.min_confirmations,
         )?;
+        self.database.del_prepared_psbt(&prepared_psbt)?;
+
+        self._state_changed()?;
 
         info!(self.logger, "Send (end) completed");
         Ok(txid)
@@ -4702,7 +6825,9 @@ impl Wallet {
         amount: u64,
         fee_rate: f32,
     ) -> Result<String, Error> {
+        let _write_lock = self._write_lock();
         info!(self.logger, "Sending BTC...");
+        self._check_signer()?;
         self._check_online(online)?;
         self._check_fee_rate(fee_rate)?;
 
@@ -4717,13 +6842,14 @@ impl Wallet {
 
         let unspendable = self._get_unspendable_bdk_outpoints()?;
 
-        let mut tx_builder = self.bdk_wallet.build_tx();
+        let bdk_wallet = self._bdk_wallet();
+        let mut tx_builder = bdk_wallet.build_tx();
         tx_builder
             .unspendable(unspendable)
             .add_recipient(address.script_pubkey(), amount)
             .fee_rate(FeeRate::from_sat_per_vb(fee_rate));
 
-        let mut psbt = tx_builder
+        let psbt = tx_builder
             .finish()
             .map_err(|e| match e {
                 bdk::Error::InsufficientFunds { needed, available } => {
@@ -4733,8 +6859,10 @@ impl Wallet {
                 _ => Error::from(InternalError::from(e)),
             })?
             .0;
+        drop(bdk_wallet);
 
-        self._sign_psbt(&mut psbt)?;
+        let signed_psbt = self._sign_with_signer(psbt.to_string())?;
+        let psbt = BdkPsbt::from_str(&signed_psbt)?;
 
         let tx = self._broadcast_psbt(psbt)?;
 
@@ -4743,7 +6871,22 @@ impl Wallet {
     }
 }
 
+impl Drop for Wallet {
+    fn drop(&mut self) {
+        if self.shared.is_none() {
+            unregister_panic_logger(&self.wallet_dir);
+        }
+    }
+}
+
 pub(crate) mod backup;
+pub(crate) mod manager;
+pub mod signer;
+
+use backup::{AutoBackupConfig, AutoBackupWorker};
+pub use manager::WalletManager;
+use signer::sign_bdk_psbt;
+pub use signer::{Signer, SoftwareSigner};
 
 #[cfg(test)]
-mod test;
+pub(crate) mod test;
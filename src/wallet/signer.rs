@@ -0,0 +1,103 @@
+//! PSBT signing
+//!
+//! This module defines the [`Signer`] trait and a reference [`SoftwareSigner`] implementation.
+
+use amplify::s;
+use bdk::bitcoin::psbt::Psbt as BdkPsbt;
+use bdk::bitcoin::secp256k1::Secp256k1;
+use bdk::bitcoin::util::bip32::ExtendedPubKey;
+use bdk::bitcoin::Network as BdkNetwork;
+use bdk::database::{BatchDatabase, MemoryDatabase};
+use bdk::keys::bip39::Mnemonic;
+use bdk::keys::ExtendedKey;
+use bdk::{SignOptions, Wallet as BdkWallet};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::error::InternalError;
+use crate::keys::get_extended_key;
+use crate::utils::{calculate_descriptor_from_xprv, BitcoinNetwork};
+use crate::wallet::{KEYCHAIN_BTC, KEYCHAIN_RGB};
+use crate::Error;
+
+/// A signer for the PSBTs prepared by a [`Wallet`](crate::wallet::Wallet)
+///
+/// Once registered with [`set_signer`](crate::wallet::Wallet::set_signer), it is used by the
+/// full versions of the APIs that need to sign a transaction (e.g.
+/// [`send`](crate::wallet::Wallet::send)), allowing them to work with watch-only wallets.
+pub trait Signer: Send + Sync {
+    /// Sign the provided base64-encoded PSBT and return the signed PSBT, base64-encoded.
+    ///
+    /// The PSBT may include RGB data (as proprietary keys) and the signer is expected to leave it
+    /// untouched.
+    fn sign_psbt(&self, unsigned_psbt: String) -> Result<String, Error>;
+}
+
+pub(crate) fn sign_bdk_psbt<D: BatchDatabase>(
+    bdk_wallet: &BdkWallet<D>,
+    psbt: &mut BdkPsbt,
+) -> Result<(), Error> {
+    bdk_wallet
+        .sign(psbt, SignOptions::default())
+        .map_err(InternalError::from)?;
+    Ok(())
+}
+
+/// A [`Signer`] holding the wallet private keys in memory
+pub struct SoftwareSigner {
+    bdk_wallet: Mutex<BdkWallet<MemoryDatabase>>,
+}
+
+impl SoftwareSigner {
+    /// Create a new signer for the wallet with the provided xPub, on the given Bitcoin network,
+    /// from the provided mnemonic phrase and optional BIP39 passphrase
+    ///
+    /// An [`Error::InvalidMnemonic`] is returned if the mnemonic and passphrase don't derive the
+    /// wallet xPub.
+    pub fn new(
+        bitcoin_network: BitcoinNetwork,
+        pubkey: String,
+        mnemonic: String,
+        passphrase: Option<String>,
+    ) -> Result<Self, Error> {
+        let bdk_network = BdkNetwork::from(bitcoin_network);
+        let secp = Secp256k1::new();
+        let extended_key: ExtendedKey = ExtendedKey::from(ExtendedPubKey::from_str(&pubkey)?);
+        let xpub = extended_key.into_xpub(bdk_network, &secp);
+        let mnemonic = Mnemonic::parse(mnemonic)?;
+        let xpub_from_mnemonic =
+            get_extended_key(mnemonic.clone(), passphrase.clone()).into_xpub(bdk_network, &secp);
+        if xpub_from_mnemonic != xpub {
+            return Err(Error::InvalidMnemonic {
+                details: s!("mnemonic and passphrase don't match the wallet xPub"),
+            });
+        }
+        let xprv = get_extended_key(mnemonic, passphrase)
+            .into_xprv(bdk_network)
+            .expect("should be possible to get an extended private key");
+        let descriptor = calculate_descriptor_from_xprv(xprv, bitcoin_network, KEYCHAIN_RGB);
+        let change_descriptor = calculate_descriptor_from_xprv(xprv, bitcoin_network, KEYCHAIN_BTC);
+        let bdk_wallet = BdkWallet::new(
+            &descriptor,
+            Some(&change_descriptor),
+            bdk_network,
+            MemoryDatabase::default(),
+        )
+        .map_err(InternalError::from)?;
+        Ok(SoftwareSigner {
+            bdk_wallet: Mutex::new(bdk_wallet),
+        })
+    }
+}
+
+impl Signer for SoftwareSigner {
+    fn sign_psbt(&self, unsigned_psbt: String) -> Result<String, Error> {
+        let mut psbt = BdkPsbt::from_str(&unsigned_psbt).map_err(InternalError::from)?;
+        let bdk_wallet = self
+            .bdk_wallet
+            .lock()
+            .expect("signer wallet mutex should not be poisoned");
+        sign_bdk_psbt(&bdk_wallet, &mut psbt)?;
+        Ok(psbt.to_string())
+    }
+}
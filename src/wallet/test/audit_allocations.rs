@@ -0,0 +1,92 @@
+use super::*;
+use serial_test::parallel;
+
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+
+    // no allocations
+    let discrepancies = wallet.audit_allocations(None).unwrap();
+    assert!(discrepancies.is_empty());
+
+    // issued allocations
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let discrepancies = wallet.audit_allocations(Some(online.clone())).unwrap();
+    assert!(discrepancies.is_empty());
+
+    // pending send, the runtime already knows the change allocation
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    test_send_default(&wallet, &online, recipient_map);
+    let discrepancies = wallet.audit_allocations(Some(online.clone())).unwrap();
+    assert!(discrepancies.is_empty());
+
+    // DB allocation diverging from the runtime state
+    let issue_coloring = wallet
+        .database
+        .iter_colorings()
+        .unwrap()
+        .into_iter()
+        .find(|c| c.coloring_type == ColoringType::Issue)
+        .unwrap();
+    let issue_txo = get_test_txo(&wallet, issue_coloring.txo_idx);
+    let mut updated_coloring: DbColoringActMod = issue_coloring.into();
+    updated_coloring.amount = ActiveValue::Set((AMOUNT * 2).to_string());
+    wallet.database.update_coloring(updated_coloring).unwrap();
+    let discrepancies = wallet.audit_allocations(Some(online)).unwrap();
+    assert_eq!(
+        discrepancies,
+        vec![AllocationDiscrepancy {
+            outpoint: issue_txo.outpoint(),
+            asset_id: asset.asset_id,
+            settled_amount: AMOUNT * 2,
+            unsettled_amount: 0,
+            runtime_amount: AMOUNT,
+        }]
+    );
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let (wallet, _online) = get_empty_wallet!();
+    let (_other_wallet, other_online) = get_empty_wallet!();
+
+    // bad online object
+    let result = wallet.audit_allocations(Some(other_online));
+    assert!(matches!(result, Err(Error::CannotChangeOnline)));
+}
@@ -1,6 +1,15 @@
 use super::*;
-use crate::wallet::backup::restore_backup;
+use crate::restore_keys;
+use crate::wallet::backup::{
+    backup_info, restore_backup, restore_backup_chain, restore_from_reader, ScryptParams,
+};
 use serial_test::parallel;
+use std::fs::File;
+use std::io::Write;
+
+const BACKUP_V1_FILE: &str = "tests/backup_v1.rgb-lib_backup";
+const BACKUP_V1_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+    abandon abandon abandon about";
 
 #[test]
 #[parallel]
@@ -12,8 +21,8 @@ fn success() {
     let password = "password";
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
     let mut wallet_data = wallet.wallet_data.clone();
     let wallet_dir = wallet.wallet_dir.clone();
 
@@ -48,7 +57,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     // take transfers from WaitingCounterparty to Settled
     stop_mining();
@@ -67,7 +76,7 @@ fn success() {
         .unwrap();
 
     // pre-backup wallet data
-    check_test_wallet_data(&mut wallet, &asset, None, 1, amount);
+    check_test_wallet_data(&wallet, &asset, None, 1, amount);
 
     // backup
     println!("\nbacking up...");
@@ -91,9 +100,9 @@ fn success() {
 
     // post-restore wallet data
     wallet_data.data_dir = RESTORE_DIR.to_string();
-    let mut wallet = Wallet::new(wallet_data).unwrap();
+    let wallet = Wallet::new(wallet_data).unwrap();
     let online = wallet.go_online(true, ELECTRUM_URL.to_string()).unwrap();
-    check_test_wallet_data(&mut wallet, &asset, None, 1, amount);
+    check_test_wallet_data(&wallet, &asset, None, 1, amount);
 
     // spend asset once more and check wallet data again
     let receive_data = rcv_wallet
@@ -115,7 +124,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     // take transfers from WaitingCounterparty to Settled
     stop_mining();
@@ -132,7 +141,7 @@ fn success() {
     wallet
         .refresh(online.clone(), Some(asset.asset_id.clone()), vec![])
         .unwrap();
-    check_test_wallet_data(&mut wallet, &asset, None, 2, amount * 2);
+    check_test_wallet_data(&wallet, &asset, None, 2, amount * 2);
 
     // issue a second asset with the restored wallet
     let _asset = wallet
@@ -172,6 +181,507 @@ fn fail() {
     std::fs::remove_file(&backup_file).unwrap_or_default();
 }
 
+#[test]
+#[parallel]
+fn backup_needed_success() {
+    initialize();
+
+    let backup_file = format!("{TEST_DATA_DIR}/test_backup_needed_success.rgb-lib_backup");
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+
+    // UTXO creation changes the wallet state
+    let (wallet, online) = get_funded_wallet!();
+    let info = wallet.backup_info().unwrap();
+    assert!(info.backup_needed);
+    let state_version = info.state_version;
+    assert!(state_version > 0);
+
+    // backup
+    wallet.backup(&backup_file, "password").unwrap();
+    let info = wallet.backup_info().unwrap();
+    assert!(!info.backup_needed);
+    assert_eq!(info.last_backup_state_version, Some(state_version));
+    assert!(info.last_backup_timestamp.is_some());
+
+    // issuance changes the wallet state
+    wallet
+        .issue_asset_nia(
+            online,
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let info = wallet.backup_info().unwrap();
+    assert!(info.backup_needed);
+    assert_eq!(info.state_version, state_version + 1);
+
+    // cleanup
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+}
+
+#[test]
+#[parallel]
+fn stream_success() {
+    let password = "password";
+
+    let wallet = get_test_wallet(true, None);
+    let wallet_dir = wallet.get_wallet_dir();
+
+    // add a file spanning many encryption chunks, in a subdirectory
+    let media_dir = wallet_dir.join("media_files");
+    std::fs::create_dir_all(&media_dir).unwrap();
+    let big_file: Vec<u8> = (0..1_000_000).map(|_| rand::random::<u8>()).collect();
+    std::fs::write(media_dir.join("big_file"), &big_file).unwrap();
+
+    // backup to an in-memory buffer
+    let mut backup_data: Vec<u8> = vec![];
+    wallet.backup_to_writer(&mut backup_data, password).unwrap();
+    assert!(!backup_data.is_empty());
+
+    // restore with wrong password
+    let result = restore_from_reader(backup_data.as_slice(), "wrong password", RESTORE_DIR);
+    assert!(matches!(result, Err(Error::WrongPassword)));
+    let restore_wallet_dir = PathBuf::from_str(RESTORE_DIR)
+        .unwrap()
+        .join(wallet_dir.file_name().unwrap());
+    assert!(!restore_wallet_dir.exists());
+
+    // restore from the buffer
+    restore_from_reader(backup_data.as_slice(), password, RESTORE_DIR).unwrap();
+    let (same, _msg) = compare_test_directories(&wallet_dir, &restore_wallet_dir, vec!["log"]);
+    assert!(same);
+
+    // data written to a file can be restored as a regular backup file
+    let backup_file = format!("{TEST_DATA_DIR}/test_backup_stream.rgb-lib_backup");
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+    std::fs::write(&backup_file, &backup_data).unwrap();
+    std::fs::remove_dir_all(&restore_wallet_dir).unwrap();
+    restore_backup(&backup_file, password, RESTORE_DIR).unwrap();
+    let (same, _msg) = compare_test_directories(&wallet_dir, &restore_wallet_dir, vec!["log"]);
+    assert!(same);
+
+    // invalid backup data
+    let result = restore_from_reader(&b"invalid"[..], password, RESTORE_DIR);
+    assert!(matches!(result, Err(Error::Internal { details: _ })));
+
+    // cleanup
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+}
+
+#[test]
+#[parallel]
+fn info_success() {
+    let backup_file = format!("{TEST_DATA_DIR}/test_backup_info_success.rgb-lib_backup");
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+    let password = "password";
+    let scrypt_params = ScryptParams {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+
+    let wallet = get_test_wallet(true, None);
+    let wallet_dir = wallet.get_wallet_dir();
+
+    // backup with custom scrypt parameters
+    wallet
+        .backup_customize(&backup_file, password, Some(scrypt_params))
+        .unwrap();
+
+    // header can be read without the password
+    let info = backup_info(&backup_file).unwrap();
+    assert_eq!(info.version, 2);
+    assert_eq!(
+        info.fingerprint,
+        wallet_dir.file_name().unwrap().to_str().unwrap()
+    );
+    assert_eq!(info.bitcoin_network, BitcoinNetwork::Regtest);
+    assert!(info.created_at > 0 && info.created_at <= now().unix_timestamp());
+    assert_eq!(info.rgb_lib_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.scrypt_params, scrypt_params);
+
+    // restore
+    restore_backup(&backup_file, password, RESTORE_DIR).unwrap();
+    let restore_wallet_dir = PathBuf::from_str(RESTORE_DIR)
+        .unwrap()
+        .join(wallet_dir.file_name().unwrap());
+    let (same, _msg) = compare_test_directories(&wallet_dir, &restore_wallet_dir, vec!["log"]);
+    assert!(same);
+
+    // cleanup
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+}
+
+#[test]
+#[parallel]
+fn info_fail() {
+    let backup_file = format!("{TEST_DATA_DIR}/test_backup_info_fail.rgb-lib_backup");
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+
+    let wallet = get_test_wallet(true, None);
+
+    // invalid scrypt parameters
+    let scrypt_params = ScryptParams {
+        log_n: 10,
+        r: 0,
+        p: 1,
+    };
+    let result = wallet.backup_customize(&backup_file, "password", Some(scrypt_params));
+    assert!(matches!(
+        result,
+        Err(Error::InvalidScryptParams { details: _ })
+    ));
+    assert!(!PathBuf::from(&backup_file).exists());
+
+    // scrypt parameters above the maximum cost
+    for (scrypt_params, details) in [
+        (
+            ScryptParams {
+                log_n: 21,
+                r: 8,
+                p: 1,
+            },
+            "memory cost above the maximum of 1073741824 bytes",
+        ),
+        (
+            ScryptParams {
+                log_n: 64,
+                r: 8,
+                p: 1,
+            },
+            "memory cost above the maximum of 1073741824 bytes",
+        ),
+        (
+            ScryptParams {
+                log_n: 10,
+                r: 8,
+                p: 17,
+            },
+            "p above the maximum of 16",
+        ),
+    ] {
+        let result = wallet.backup_customize(&backup_file, "password", Some(scrypt_params));
+        assert!(matches!(result, Err(Error::InvalidScryptParams { details: m }) if m == details));
+        assert!(!PathBuf::from(&backup_file).exists());
+    }
+
+    // v1 backups have no header
+    let result = backup_info(BACKUP_V1_FILE);
+    assert!(matches!(result, Err(Error::UnsupportedBackupVersion { version: v }) if v == "1"));
+
+    // inexistent backup
+    let result = backup_info(&backup_file);
+    assert!(matches!(result, Err(Error::IO { details: _ })));
+}
+
+/// Copy the backup at `src` to `dst`, modifying its header with the provided function
+fn rewrite_backup_header(src: &str, dst: &str, modify: impl Fn(&mut serde_json::Value)) {
+    let mut archive = zip::ZipArchive::new(File::open(src).unwrap()).unwrap();
+    let mut writer = zip::ZipWriter::new(File::create(dst).unwrap());
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).unwrap();
+        if file.name() == "backup.header" {
+            let mut header: serde_json::Value = serde_json::from_reader(&mut file).unwrap();
+            modify(&mut header);
+            writer
+                .start_file("backup.header", zip::write::FileOptions::default())
+                .unwrap();
+            writer
+                .write_all(&serde_json::to_vec(&header).unwrap())
+                .unwrap();
+        } else {
+            writer.raw_copy_file(file).unwrap();
+        }
+    }
+    writer.finish().unwrap();
+}
+
+#[test]
+#[parallel]
+fn tampered_header_fail() {
+    let backup_file = format!("{TEST_DATA_DIR}/test_backup_tampered_header.rgb-lib_backup");
+    let tampered_file = format!("{TEST_DATA_DIR}/test_backup_tampered_header_mod.rgb-lib_backup");
+    let restore_dir = format!("{TEST_DATA_DIR}/restored_tampered_header");
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+    std::fs::remove_file(&tampered_file).unwrap_or_default();
+    let password = "password";
+    let scrypt_params = ScryptParams {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+
+    let wallet = get_test_wallet(true, None);
+    wallet
+        .backup_customize(&backup_file, password, Some(scrypt_params))
+        .unwrap();
+
+    // a rewritten but unchanged header is still valid
+    rewrite_backup_header(&backup_file, &tampered_file, |_| {});
+    restore_backup(&tampered_file, password, &restore_dir).unwrap();
+
+    // a modified header fails the decryption
+    std::fs::remove_dir_all(&restore_dir).unwrap_or_default();
+    rewrite_backup_header(&backup_file, &tampered_file, |h| {
+        h["created_at"] = serde_json::json!(0);
+    });
+    assert_eq!(backup_info(&tampered_file).unwrap().created_at, 0);
+    let result = restore_backup(&tampered_file, password, &restore_dir);
+    assert!(matches!(result, Err(Error::WrongPassword)));
+
+    // excessive scrypt parameters are rejected before deriving the key
+    rewrite_backup_header(&backup_file, &tampered_file, |h| {
+        h["scrypt_params"]["log_n"] = serde_json::json!(30);
+    });
+    let result = restore_backup(&tampered_file, password, &restore_dir);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidScryptParams { details: m })
+            if m == "memory cost above the maximum of 1073741824 bytes"
+    ));
+
+    // cleanup
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+    std::fs::remove_file(&tampered_file).unwrap_or_default();
+}
+
+#[test]
+#[parallel]
+fn restore_v1_success() {
+    let restore_dir = format!("{TEST_DATA_DIR}/restored_v1");
+    std::fs::remove_dir_all(&restore_dir).unwrap_or_default();
+
+    // restore a backup created with the v1 format
+    restore_backup(BACKUP_V1_FILE, "password", &restore_dir).unwrap();
+
+    // the restored wallet can be loaded
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let keys = restore_keys(bitcoin_network, BACKUP_V1_MNEMONIC.to_string(), None).unwrap();
+    let wallet = Wallet::new(WalletData {
+        data_dir: restore_dir.clone(),
+        bitcoin_network,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
+    })
+    .unwrap();
+    assert_eq!(
+        wallet.get_wallet_dir(),
+        fs::canonicalize(restore_dir)
+            .unwrap()
+            .join(keys.xpub_fingerprint)
+    );
+    assert!(wallet.list_assets(vec![]).unwrap().nia.unwrap().is_empty());
+}
+
+#[test]
+#[parallel]
+fn incremental_success() {
+    let password = "password";
+    let scrypt_params = ScryptParams {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+    let backup_base = format!("{TEST_DATA_DIR}/test_backup_incremental_base.rgb-lib_backup");
+    let backup_inc_1 = format!("{TEST_DATA_DIR}/test_backup_incremental_1.rgb-lib_backup");
+    let backup_inc_2 = format!("{TEST_DATA_DIR}/test_backup_incremental_2.rgb-lib_backup");
+    for backup_file in [&backup_base, &backup_inc_1, &backup_inc_2] {
+        std::fs::remove_file(backup_file).unwrap_or_default();
+    }
+    let restore_dir_1 = format!("{TEST_DATA_DIR}/restored_incremental_1");
+    let restore_dir_2 = format!("{TEST_DATA_DIR}/restored_incremental_2");
+    for restore_dir in [&restore_dir_1, &restore_dir_2] {
+        std::fs::remove_dir_all(restore_dir).unwrap_or_default();
+    }
+
+    let wallet = get_test_wallet(true, None);
+    let wallet_dir = wallet.get_wallet_dir();
+    let extra_file = wallet_dir.join("extra_file");
+
+    // full backup
+    wallet
+        .backup_customize(&backup_base, password, Some(scrypt_params))
+        .unwrap();
+    let info_base = backup_info(&backup_base).unwrap();
+    assert!(info_base.parent_backup_id.is_none());
+
+    // incremental backup after adding a file
+    wallet._state_changed().unwrap();
+    std::fs::write(&extra_file, "extra").unwrap();
+    wallet
+        .backup_incremental(&backup_base, &backup_inc_1, password)
+        .unwrap();
+    let info_inc_1 = backup_info(&backup_inc_1).unwrap();
+    assert_eq!(
+        info_inc_1.parent_backup_id,
+        Some(info_base.backup_id.clone())
+    );
+    assert_eq!(info_inc_1.scrypt_params, scrypt_params);
+    assert!(!wallet.backup_info().unwrap().backup_needed);
+
+    // incremental backup after removing the file
+    wallet._state_changed().unwrap();
+    std::fs::remove_file(&extra_file).unwrap();
+    wallet
+        .backup_incremental(&backup_inc_1, &backup_inc_2, password)
+        .unwrap();
+    let info_inc_2 = backup_info(&backup_inc_2).unwrap();
+    assert_eq!(info_inc_2.parent_backup_id, Some(info_inc_1.backup_id));
+
+    // restore the full chain
+    restore_backup_chain(
+        &backup_base,
+        vec![backup_inc_1.clone(), backup_inc_2.clone()],
+        password,
+        &restore_dir_2,
+    )
+    .unwrap();
+    let restore_wallet_dir_2 = PathBuf::from(&restore_dir_2).join(wallet_dir.file_name().unwrap());
+    let (same, _msg) = compare_test_directories(&wallet_dir, &restore_wallet_dir_2, vec!["log"]);
+    assert!(same);
+    assert!(!restore_wallet_dir_2.join("extra_file").exists());
+
+    // restore part of the chain
+    restore_backup_chain(
+        &backup_base,
+        vec![backup_inc_1.clone()],
+        password,
+        &restore_dir_1,
+    )
+    .unwrap();
+    let restore_wallet_dir_1 = PathBuf::from(&restore_dir_1).join(wallet_dir.file_name().unwrap());
+    assert_eq!(
+        std::fs::read_to_string(restore_wallet_dir_1.join("extra_file")).unwrap(),
+        "extra"
+    );
+
+    // cleanup
+    for backup_file in [&backup_base, &backup_inc_1, &backup_inc_2] {
+        std::fs::remove_file(backup_file).unwrap_or_default();
+    }
+}
+
+#[test]
+#[parallel]
+fn incremental_fail() {
+    let password = "password";
+    let scrypt_params = ScryptParams {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+    let backup_base = format!("{TEST_DATA_DIR}/test_backup_incremental_fail_base.rgb-lib_backup");
+    let backup_inc_1 = format!("{TEST_DATA_DIR}/test_backup_incremental_fail_1.rgb-lib_backup");
+    let backup_inc_2 = format!("{TEST_DATA_DIR}/test_backup_incremental_fail_2.rgb-lib_backup");
+    let backup_other = format!("{TEST_DATA_DIR}/test_backup_incremental_fail_other.rgb-lib_backup");
+    for backup_file in [&backup_base, &backup_inc_1, &backup_inc_2, &backup_other] {
+        std::fs::remove_file(backup_file).unwrap_or_default();
+    }
+    let restore_dir = format!("{TEST_DATA_DIR}/restored_incremental_fail");
+
+    let wallet = get_test_wallet(true, None);
+    let other_wallet = get_test_wallet(true, None);
+    wallet
+        .backup_customize(&backup_base, password, Some(scrypt_params))
+        .unwrap();
+    wallet
+        .backup_incremental(&backup_base, &backup_inc_1, password)
+        .unwrap();
+    wallet
+        .backup_incremental(&backup_inc_1, &backup_inc_2, password)
+        .unwrap();
+    other_wallet
+        .backup_customize(&backup_other, password, Some(scrypt_params))
+        .unwrap();
+
+    // wrong password
+    let backup_file = format!("{TEST_DATA_DIR}/test_backup_incremental_fail_new.rgb-lib_backup");
+    std::fs::remove_file(&backup_file).unwrap_or_default();
+    let result = wallet.backup_incremental(&backup_base, &backup_file, "wrong password");
+    assert!(matches!(result, Err(Error::WrongPassword)));
+    assert!(!PathBuf::from(&backup_file).exists());
+
+    // parent backup of another wallet
+    let result = wallet.backup_incremental(&backup_other, &backup_file, password);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // parent backup with format v1
+    let result = wallet.backup_incremental(BACKUP_V1_FILE, &backup_file, password);
+    assert!(matches!(result, Err(Error::UnsupportedBackupVersion { version: v }) if v == "1"));
+
+    // incremental backup restored without its base
+    let result = restore_backup(&backup_inc_1, password, &restore_dir);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // chain with a missing link
+    let result = restore_backup_chain(
+        &backup_base,
+        vec![backup_inc_2.clone()],
+        password,
+        &restore_dir,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // chain with backups in the wrong order
+    let result = restore_backup_chain(
+        &backup_base,
+        vec![backup_inc_2.clone(), backup_inc_1.clone()],
+        password,
+        &restore_dir,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // chain with a backup of another wallet
+    let result = restore_backup_chain(
+        &backup_other,
+        vec![backup_inc_1.clone()],
+        password,
+        &restore_dir,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // chain with a v1 base
+    let result = restore_backup_chain(
+        BACKUP_V1_FILE,
+        vec![backup_inc_1.clone()],
+        password,
+        &restore_dir,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidBackupChain { details: _ })
+    ));
+
+    // cleanup
+    for backup_file in [&backup_base, &backup_inc_1, &backup_inc_2, &backup_other] {
+        std::fs::remove_file(backup_file).unwrap_or_default();
+    }
+}
+
 #[test]
 #[parallel]
 fn double_restore() {
@@ -184,9 +694,9 @@ fn double_restore() {
     let password_2 = "password2";
 
     // wallets
-    let (mut wallet_1, online_1) = get_funded_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
     let mut wallet_1_data = wallet_1.wallet_data.clone();
     let mut wallet_2_data = wallet_2.wallet_data.clone();
     let wallet_1_dir = wallet_1.wallet_dir.clone();
@@ -252,8 +762,8 @@ fn double_restore() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet_1, &online_1, recipient_map_1);
-    let txid_2 = test_send_default(&mut wallet_2, &online_2, recipient_map_2);
+    let txid_1 = test_send_default(&wallet_1, &online_1, recipient_map_1);
+    let txid_2 = test_send_default(&wallet_2, &online_2, recipient_map_2);
     assert!(!txid_1.is_empty());
     assert!(!txid_2.is_empty());
     // take transfers from WaitingCounterparty to Settled
@@ -279,8 +789,8 @@ fn double_restore() {
         .unwrap();
 
     // pre-backup wallet data
-    check_test_wallet_data(&mut wallet_1, &asset_1, None, 1, amount);
-    check_test_wallet_data(&mut wallet_2, &asset_2, Some(asset_2_supply), 1, amount * 2);
+    check_test_wallet_data(&wallet_1, &asset_1, None, 1, amount);
+    check_test_wallet_data(&wallet_2, &asset_2, Some(asset_2_supply), 1, amount * 2);
 
     // backup
     println!("\nbacking up...");
@@ -314,12 +824,12 @@ fn double_restore() {
     // post-restore wallet data
     wallet_1_data.data_dir = RESTORE_DIR.to_string();
     wallet_2_data.data_dir = RESTORE_DIR.to_string();
-    let mut wallet_1 = Wallet::new(wallet_1_data).unwrap();
-    let mut wallet_2 = Wallet::new(wallet_2_data).unwrap();
+    let wallet_1 = Wallet::new(wallet_1_data).unwrap();
+    let wallet_2 = Wallet::new(wallet_2_data).unwrap();
     let online_1 = wallet_1.go_online(true, ELECTRUM_URL.to_string()).unwrap();
     let online_2 = wallet_2.go_online(true, ELECTRUM_URL.to_string()).unwrap();
-    check_test_wallet_data(&mut wallet_1, &asset_1, None, 1, amount);
-    check_test_wallet_data(&mut wallet_2, &asset_2, Some(asset_2_supply), 1, amount * 2);
+    check_test_wallet_data(&wallet_1, &asset_1, None, 1, amount);
+    check_test_wallet_data(&wallet_2, &asset_2, Some(asset_2_supply), 1, amount * 2);
 
     // issue a second asset with the restored wallets
     wallet_1
@@ -12,7 +12,7 @@ fn success() {
 
     let amount = 69;
     let expiration = 60;
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // default expiration + min confirmations
     let now_timestamp = now().unix_timestamp();
@@ -150,6 +150,19 @@ fn success() {
         .get_transfer_transport_endpoints_data(transfer.idx)
         .unwrap();
     assert_eq!(tte_data.len(), transport_endpoints.len());
+
+    // the proxy protocol version of the endpoints is cached
+    let cached_versions: Vec<Option<String>> = tte_data
+        .iter()
+        .map(|(_, te)| te.protocol_version.clone())
+        .collect();
+    assert_eq!(
+        cached_versions,
+        vec![Some(s!("0.2")), Some(s!("0.1")), Some(s!("0.2"))]
+    );
+    assert!(tte_data
+        .iter()
+        .all(|(_, te)| te.protocol_checked_at.is_some()));
 }
 
 #[test]
@@ -157,7 +170,7 @@ fn success() {
 fn respect_max_allocations() {
     initialize();
 
-    let (mut wallet, _online) = get_funded_wallet!();
+    let (wallet, _online) = get_funded_wallet!();
 
     let available_allocations = UTXO_NUM as u32 * MAX_ALLOCATIONS_PER_UTXO;
     let mut created_allocations = 0;
@@ -201,7 +214,7 @@ fn expire() {
     initialize();
 
     let expiration = 1;
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // check expiration
     let now_timestamp = now().unix_timestamp();
@@ -246,8 +259,8 @@ fn pending_outgoing_transfer_fail() {
 
     let amount = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -290,7 +303,7 @@ fn pending_outgoing_transfer_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     // check blind doesn't get allocated to UTXO being spent
@@ -352,7 +365,7 @@ fn pending_outgoing_transfer_fail() {
 fn fail() {
     initialize();
 
-    let mut wallet = get_test_wallet(true, Some(1)); // using 1 max allocation per utxo
+    let wallet = get_test_wallet(true, Some(1)); // using 1 max allocation per utxo
     let online = wallet.go_online(true, ELECTRUM_URL.to_string()).unwrap();
 
     // insufficient funds
@@ -384,7 +397,7 @@ fn fail() {
 
     fund_wallet(wallet.get_address());
     mine(false);
-    test_create_utxos(&mut wallet, online.clone(), true, Some(1), None, FEE_RATE);
+    test_create_utxos(&wallet, online.clone(), true, Some(1), None, FEE_RATE);
 
     // bad asset id
     let result = wallet.blind_receive(
@@ -417,7 +430,7 @@ fn fail() {
 
     // transport endpoints: malformed string
     fund_wallet(wallet.get_address());
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
     let transport_endpoints = vec!["malformed".to_string()];
     let result = wallet.blind_receive(None, None, Some(0), transport_endpoints, MIN_CONFIRMATIONS);
     assert!(matches!(
@@ -464,6 +477,16 @@ fn fail() {
         Err(Error::InvalidTransportEndpoints { details: m }) if m == msg
     ));
 
+    // transport endpoints: onion endpoint without routing proxy traffic through SOCKS5
+    let transport_endpoints = vec![format!(
+        "rpcs://rgbproxyexampleaddressxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx.onion/json-rpc"
+    )];
+    let result = wallet.blind_receive(None, None, Some(0), transport_endpoints, MIN_CONFIRMATIONS);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTransportEndpoint { details: _ })
+    ));
+
     // transport endpoints: no endpoints for transfer > Failed
     let transport_endpoints = vec![format!("rpc://{PROXY_HOST}")];
     let receive_data = wallet
@@ -509,8 +532,8 @@ fn wrong_asset_fail() {
 
     let amount: u64 = 66;
 
-    let (mut wallet_1, online_1) = get_funded_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!();
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
 
     // issue one asset per wallet
     let asset_a = wallet_1
@@ -552,7 +575,7 @@ fn wrong_asset_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet_2, &online_2, recipient_map);
+    let txid = test_send_default(&wallet_2, &online_2, recipient_map);
     assert!(!txid.is_empty());
 
     // transfer is pending
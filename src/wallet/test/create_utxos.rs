@@ -1,4 +1,5 @@
 use super::*;
+use crate::wallet::test::harness::RegtestHarness;
 use serial_test::parallel;
 
 #[test]
@@ -8,9 +9,8 @@ fn success() {
 
     // up_to version with 0 allocatable UTXOs
     println!("\n=== up_to true, 0 allocatable");
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), true, None, None, FEE_RATE);
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let num_utxos_created = test_create_utxos(&wallet, online.clone(), true, None, None, FEE_RATE);
     assert_eq!(num_utxos_created, UTXO_NUM);
     let unspents = wallet.list_unspents(None, false).unwrap();
     assert_eq!(unspents.len(), (UTXO_NUM + 1) as usize);
@@ -18,7 +18,7 @@ fn success() {
     // up_to version with allocatable UTXOs partially available (1 missing)
     println!("\n=== up_to true, need to create 1 more");
     let num_utxos_created = test_create_utxos(
-        &mut wallet,
+        &wallet,
         online.clone(),
         true,
         Some(UTXO_NUM + 1),
@@ -31,7 +31,7 @@ fn success() {
 
     // forced version always creates UTXOs
     println!("\n=== up_to false");
-    let num_utxos_created = test_create_utxos_default(&mut wallet, online);
+    let num_utxos_created = test_create_utxos_default(&wallet, online);
     assert_eq!(num_utxos_created, UTXO_NUM);
     let unspents = wallet.list_unspents(None, false).unwrap();
     assert_eq!(unspents.len(), (UTXO_NUM * 2 + 2) as usize);
@@ -45,13 +45,13 @@ fn up_to_allocation_checks() {
     let amount = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_empty_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, rcv_online) = get_empty_wallet!();
 
     // MAX_ALLOCATIONS_PER_UTXO failed allocations
     //  - check unspent counted as allocatable
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
     let mut blinded_utxos: Vec<String> = vec![];
     let mut txo_list: HashSet<DbTxo> = HashSet::new();
@@ -89,7 +89,7 @@ fn up_to_allocation_checks() {
 
     // MAX_ALLOCATIONS_PER_UTXO allocations
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), true, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), true, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
     // create MAX_ALLOCATIONS_PER_UTXO blinds on the same UTXO
     let mut txo_list: HashSet<DbTxo> = HashSet::new();
@@ -111,7 +111,7 @@ fn up_to_allocation_checks() {
     assert_eq!(txo_list.len(), 1);
     // request 1 new UTXO, expecting one is created
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), true, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), true, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
     let unspents = wallet.list_unspents(None, false).unwrap();
     assert_eq!(unspents.len(), 3);
@@ -122,10 +122,10 @@ fn up_to_allocation_checks() {
         fund_wallet(rcv_wallet.get_address());
 
         let num_utxos_created =
-            test_create_utxos(&mut wallet, online.clone(), true, Some(2), None, FEE_RATE);
+            test_create_utxos(&wallet, online.clone(), true, Some(2), None, FEE_RATE);
         assert_eq!(num_utxos_created, 2);
         let num_utxos_created = test_create_utxos(
-            &mut rcv_wallet,
+            &rcv_wallet,
             rcv_online.clone(),
             true,
             Some(1),
@@ -163,14 +163,14 @@ fn up_to_allocation_checks() {
                 transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
             }],
         )]);
-        let txid = test_send_default(&mut wallet, &online, recipient_map);
+        let txid = test_send_default(&wallet, &online, recipient_map);
         assert!(!txid.is_empty());
 
         // - wait counterparty
         // UTXO 1 (input) locked, UTXO 2 (change) has at least 1 free allocation
         show_unspent_colorings(&wallet, "sender after send - WaitingCounterparty");
         let num_utxos_created =
-            test_create_utxos(&mut wallet, online.clone(), true, Some(2), None, FEE_RATE);
+            test_create_utxos(&wallet, online.clone(), true, Some(2), None, FEE_RATE);
         assert_eq!(num_utxos_created, 1);
         // UTXO 1 (blind) has at least 1 free allocation
         show_unspent_colorings(&rcv_wallet, "receiver after send - WaitingCounterparty");
@@ -204,8 +204,7 @@ fn up_to_allocation_checks() {
             .unwrap();
         // UTXO 1 now spent, UTXO 2 (RGB change) has at least 1 free allocation, UTXOs 3-4 are empty
         show_unspent_colorings(&wallet, "sender after send - Settled");
-        let num_utxos_created =
-            test_create_utxos(&mut wallet, online, true, Some(4), None, FEE_RATE);
+        let num_utxos_created = test_create_utxos(&wallet, online, true, Some(4), None, FEE_RATE);
         assert_eq!(num_utxos_created, 1);
         // UTXO 1 (blind) has at least 1 free allocation
         show_unspent_colorings(&rcv_wallet, "receiver after send - Settled");
@@ -220,7 +219,7 @@ fn fail() {
     initialize();
 
     // cannot create UTXOs for an empty wallet
-    let (mut wallet, online) = get_empty_wallet!();
+    let (wallet, online) = get_empty_wallet!();
     let result = wallet.create_utxos(online.clone(), true, None, None, FEE_RATE);
     assert!(matches!(
         result,
@@ -231,15 +230,102 @@ fn fail() {
     ));
 
     fund_wallet(wallet.get_address());
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
 
     // don't create UTXOs if enough allocations are already available
     let result = wallet.create_utxos(online.clone(), true, None, None, FEE_RATE);
     assert!(matches!(result, Err(Error::AllocationsAlreadyAvailable)));
 
+    // tampered PSBT
+    let unsigned_psbt = wallet
+        .create_utxos_begin(online.clone(), false, Some(1), None, FEE_RATE)
+        .unwrap();
+    let mut psbt = BdkPsbt::from_str(&unsigned_psbt).unwrap();
+    psbt.unsigned_tx.output[0].value -= 1000;
+    let signed_psbt = wallet.sign_psbt(psbt.to_string()).unwrap();
+    let result = wallet.create_utxos_end(online.clone(), signed_psbt);
+    assert!(matches!(result, Err(Error::PsbtMismatch { txid: _ })));
+
+    // PSBT prepared for another operation
+    let rcv_wallet = get_test_wallet(true, None);
+    let unsigned_psbt = wallet
+        .drain_to_begin(online.clone(), rcv_wallet.get_address(), false, FEE_RATE)
+        .unwrap();
+    let signed_psbt = wallet.sign_psbt(unsigned_psbt).unwrap();
+    let result = wallet.create_utxos_end(online.clone(), signed_psbt);
+    assert!(matches!(result, Err(Error::PsbtMismatch { txid: _ })));
+
     // fee min/max
     let result = wallet.create_utxos_begin(online.clone(), false, Some(1), None, 0.9);
     assert!(matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_LOW));
     let result = wallet.create_utxos_begin(online, false, Some(1), None, 1000.1);
     assert!(matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_HIGH));
 }
+
+#[test]
+#[parallel]
+fn expired_prepared_psbt() {
+    let harness = RegtestHarness::start();
+    let wallet = get_test_wallet(true, None);
+    let online = wallet.go_online(true, harness.electrum_url()).unwrap();
+    harness.indexer.fund(&wallet.get_address(), 100_000_000);
+    harness.mine(1);
+    let expired_at = now().unix_timestamp() - DURATION_PREPARED_PSBT as i64 - 1;
+
+    // PSBT prepared by a begin call that has never been completed
+    let expired_txid = s!("expired");
+    wallet
+        .database
+        .set_prepared_psbt(DbPreparedPsbtActMod {
+            txid: ActiveValue::Set(expired_txid.clone()),
+            prepared_psbt_type: ActiveValue::Set(PreparedPsbtType::CreateUtxos),
+            created_at: ActiveValue::Set(expired_at),
+            ..Default::default()
+        })
+        .unwrap();
+
+    // preparing a new PSBT deletes the expired one and keeps the new one
+    let unsigned_psbt = wallet
+        .create_utxos_begin(online.clone(), false, Some(1), None, FEE_RATE)
+        .unwrap();
+    assert!(wallet
+        .database
+        .get_prepared_psbt(expired_txid, PreparedPsbtType::CreateUtxos)
+        .unwrap()
+        .is_none());
+    let txid = BdkPsbt::from_str(&unsigned_psbt)
+        .unwrap()
+        .unsigned_tx
+        .txid()
+        .to_string();
+    let prepared_psbt = wallet
+        .database
+        .get_prepared_psbt(txid.clone(), PreparedPsbtType::CreateUtxos)
+        .unwrap()
+        .unwrap();
+
+    // completing a PSBT prepared too long ago fails
+    wallet.database.del_prepared_psbt(&prepared_psbt).unwrap();
+    wallet
+        .database
+        .set_prepared_psbt(DbPreparedPsbtActMod {
+            txid: ActiveValue::Set(txid.clone()),
+            prepared_psbt_type: ActiveValue::Set(PreparedPsbtType::CreateUtxos),
+            created_at: ActiveValue::Set(expired_at),
+            ..Default::default()
+        })
+        .unwrap();
+    let signed_psbt = wallet.sign_psbt(unsigned_psbt).unwrap();
+    let result = wallet.create_utxos_end(online.clone(), signed_psbt.clone());
+    assert!(matches!(result, Err(Error::PreparedPsbtExpired { txid: t }) if t == txid));
+
+    // it can be completed when prepared PSBTs don't expire
+    wallet
+        .set_config(WalletConfig {
+            duration_prepared_psbt: None,
+            ..WalletConfig::default()
+        })
+        .unwrap();
+    let num_utxos_created = wallet.create_utxos_end(online, signed_psbt).unwrap();
+    assert_eq!(num_utxos_created, 1);
+}
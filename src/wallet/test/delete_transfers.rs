@@ -6,7 +6,7 @@ use serial_test::parallel;
 fn success() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // return false if no transfer has changed
     assert!(!wallet.delete_transfers(None, None, false).unwrap());
@@ -193,9 +193,9 @@ fn batch_success() {
 
     let amount = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet_1, _rcv_online_1) = get_funded_wallet!();
-    let (mut rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet_1, _rcv_online_1) = get_funded_wallet!();
+    let (rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -247,7 +247,7 @@ fn batch_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online.clone(), None, Some(txid.clone()), false)
@@ -294,7 +294,7 @@ fn batch_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online, None, Some(txid.clone()), false)
@@ -307,7 +307,7 @@ fn batch_success() {
 fn fail() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     let receive_data = wallet
         .blind_receive(
@@ -369,9 +369,9 @@ fn batch_fail() {
 
     let amount = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet_1, _rcv_online_1) = get_funded_wallet!();
-    let (mut rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet_1, _rcv_online_1) = get_funded_wallet!();
+    let (rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -423,7 +423,7 @@ fn batch_fail() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     wallet
         .fail_transfers(online.clone(), None, Some(txid.clone()), false)
         .unwrap();
@@ -473,7 +473,7 @@ fn batch_fail() {
             },
         ],
     )]);
-    let txid_1 = test_send_default(&mut wallet, &online, recipient_map_1);
+    let txid_1 = test_send_default(&wallet, &online, recipient_map_1);
     wallet
         .fail_transfers(online.clone(), None, Some(txid_1.clone()), false)
         .unwrap();
@@ -501,7 +501,7 @@ fn batch_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut wallet, &online, recipient_map_2);
+    let txid_2 = test_send_default(&wallet, &online, recipient_map_2);
     wallet
         .fail_transfers(online, None, Some(txid_2.clone()), false)
         .unwrap();
@@ -0,0 +1,76 @@
+use super::*;
+use serial_test::parallel;
+
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    // consistent wallet
+    let (wallet, online) = get_funded_wallet!();
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let report = wallet.diagnose(online.clone()).unwrap();
+    assert!(report.inconsistent_utxos.is_empty());
+    assert!(report.missing_assets.is_empty());
+
+    // copy the wallet, then spend all its UTXOs from the original instance
+    let wallet_copy = copy_test_wallet(&wallet, "test_diagnose.copy");
+    let rcv_wallet = get_test_wallet(true, None);
+    wallet
+        .drain_to(online, rcv_wallet.get_address(), true, FEE_RATE)
+        .unwrap();
+    mine(false);
+
+    // the copy reports the spent UTXOs, along with the lost allocations
+    let result = wallet_copy.go_online(false, ELECTRUM_URL.to_string());
+    assert!(matches!(result, Err(Error::Inconsistency { details: _ })));
+    let online_copy = wallet_copy
+        .go_online(true, ELECTRUM_URL.to_string())
+        .unwrap();
+    let report = wallet_copy.diagnose(online_copy).unwrap();
+    assert_eq!(report.inconsistent_utxos.len(), UTXO_NUM as usize);
+    let lost_allocations: Vec<&RgbAllocation> = report
+        .inconsistent_utxos
+        .iter()
+        .flat_map(|u| &u.rgb_allocations)
+        .collect();
+    assert_eq!(lost_allocations.len(), 1);
+    assert_eq!(lost_allocations[0].asset_id, Some(asset.asset_id));
+    assert_eq!(lost_allocations[0].amount, AMOUNT);
+    assert!(report
+        .inconsistent_utxos
+        .iter()
+        .all(|u| u.pending_transfer_idxs.is_empty()));
+    assert!(report.missing_assets.is_empty());
+
+    // a copy without RGB data reports the missing asset
+    let wallet_copy_no_rgb = copy_test_wallet(&wallet_copy, "test_diagnose.copy_no_rgb");
+    fs::remove_dir_all(wallet_copy_no_rgb.get_wallet_dir().join("regtest")).unwrap();
+    let online_copy_no_rgb = wallet_copy_no_rgb
+        .go_online(true, ELECTRUM_URL.to_string())
+        .unwrap();
+    let report = wallet_copy_no_rgb.diagnose(online_copy_no_rgb).unwrap();
+    assert_eq!(report.missing_assets.len(), 1);
+    assert!(!report.missing_assets[0].consignment_available);
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let (wallet, _online) = get_empty_wallet!();
+    let (_other_wallet, other_online) = get_empty_wallet!();
+
+    // bad online object
+    let result = wallet.diagnose(other_online);
+    assert!(matches!(result, Err(Error::CannotChangeOnline)));
+}
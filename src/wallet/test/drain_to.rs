@@ -10,7 +10,7 @@ fn success() {
     let rcv_wallet = get_test_wallet(true, None);
 
     // drain funded wallet with no allocation UTXOs
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
     wallet
         .drain_to(online.clone(), rcv_wallet.get_address(), false, FEE_RATE)
         .unwrap();
@@ -20,7 +20,7 @@ fn success() {
 
     // issue asset (to produce an RGB allocation)
     fund_wallet(wallet.get_address());
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
     wallet
         .issue_asset_nia(
             online.clone(),
@@ -74,8 +74,20 @@ fn fail() {
     let result = wallet.drain_to(online.clone(), s!("invalid address"), false, FEE_RATE);
     assert!(matches!(result, Err(Error::InvalidAddress { details: _ })));
 
-    // fee min/max
+    // tampered PSBT
     fund_wallet(wallet.get_address());
+    let unsigned_psbt = wallet
+        .drain_to_begin(online.clone(), rcv_wallet.get_address(), false, FEE_RATE)
+        .unwrap();
+    let mut psbt = BdkPsbt::from_str(&unsigned_psbt).unwrap();
+    psbt.unsigned_tx.output[0].script_pubkey = BdkAddress::from_str(&wallet.get_address())
+        .unwrap()
+        .script_pubkey();
+    let signed_psbt = wallet.sign_psbt(psbt.to_string()).unwrap();
+    let result = wallet.drain_to_end(online.clone(), signed_psbt);
+    assert!(matches!(result, Err(Error::PsbtMismatch { txid: _ })));
+
+    // fee min/max
     let result = wallet.drain_to_begin(online.clone(), rcv_wallet.get_address(), true, 0.9);
     assert!(matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == FEE_MSG_LOW));
     let result = wallet.drain_to_begin(online, rcv_wallet.get_address(), true, 1000.1);
@@ -9,8 +9,8 @@ fn success() {
     let amount = 66;
     let expiration = 1;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // return false if no transfer has changed
     assert!(!wallet
@@ -94,7 +94,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     stop_mining();
     rcv_wallet
@@ -198,7 +198,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     rcv_wallet
         .refresh(rcv_online.clone(), None, vec![])
@@ -260,9 +260,9 @@ fn batch_success() {
 
     let amount = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet_1, rcv_online_1) = get_funded_wallet!();
-    let (mut rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet_1, rcv_online_1) = get_funded_wallet!();
+    let (rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -314,7 +314,7 @@ fn batch_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     assert!(check_test_transfer_status_recipient(
         &rcv_wallet_1,
@@ -378,7 +378,7 @@ fn batch_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online.clone(), None, Some(txid), false)
@@ -422,7 +422,7 @@ fn batch_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     rcv_wallet_1.refresh(rcv_online_1, None, vec![]).unwrap();
     assert!(check_test_transfer_status_recipient(
@@ -451,8 +451,8 @@ fn fail() {
     initialize();
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -515,7 +515,7 @@ fn fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    test_send_default(&mut wallet, &online, recipient_map);
+    test_send_default(&wallet, &online, recipient_map);
 
     // check starting transfer status
     assert!(check_test_transfer_status_recipient(
@@ -609,9 +609,9 @@ fn batch_fail() {
 
     let amount = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet_1, _rcv_online_1) = get_funded_wallet!();
-    let (mut rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet_1, _rcv_online_1) = get_funded_wallet!();
+    let (rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -663,7 +663,7 @@ fn batch_fail() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     let result = wallet.fail_transfers(
         online.clone(),
         Some(receive_data_1.recipient_id),
@@ -713,7 +713,7 @@ fn batch_fail() {
             },
         ],
     )]);
-    let txid_1 = test_send_default(&mut wallet, &online, recipient_map_1);
+    let txid_1 = test_send_default(&wallet, &online, recipient_map_1);
     let receive_data_3 = rcv_wallet_2
         .blind_receive(
             None,
@@ -733,7 +733,7 @@ fn batch_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut wallet, &online, recipient_map_2);
+    let txid_2 = test_send_default(&wallet, &online, recipient_map_2);
     let result = wallet.fail_transfers(
         online.clone(),
         Some(receive_data_3.recipient_id),
@@ -1,5 +1,7 @@
 use super::*;
+use crate::wallet::test::harness::RegtestHarness;
 use serial_test::parallel;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[test]
 #[parallel]
@@ -8,3 +10,38 @@ fn success() {
     let address = wallet.get_address();
     assert!(!address.is_empty());
 }
+
+#[test]
+#[parallel]
+fn during_sync() {
+    let harness = RegtestHarness::start();
+    let wallet = get_test_wallet(true, None);
+    let online = wallet.go_online(true, harness.electrum_url()).unwrap();
+
+    // addresses derived while syncs are running are never handed out twice
+    let derived = AtomicBool::new(false);
+    let addresses: Vec<String> = std::thread::scope(|s| {
+        let sync = s.spawn(|| {
+            let mut syncs = 0;
+            while !derived.load(Ordering::SeqCst) || syncs == 0 {
+                wallet._sync_db_txos().unwrap();
+                syncs += 1;
+            }
+        });
+        let addresses = (0..30).map(|_| wallet.get_address()).collect();
+        derived.store(true, Ordering::SeqCst);
+        sync.join().unwrap();
+        addresses
+    });
+    let unique_addresses: HashSet<&String> = addresses.iter().collect();
+    assert_eq!(unique_addresses.len(), addresses.len());
+    wallet._sync_db_txos().unwrap();
+    assert!(!addresses.contains(&wallet.get_address()));
+
+    // funds sent to an address derived during a sync are found by the next one
+    let amount = 1000;
+    harness.indexer.fund(addresses.last().unwrap(), amount);
+    harness.mine(1);
+    let btc_balance = wallet.get_btc_balance(online).unwrap();
+    assert_eq!(btc_balance.vanilla.settled, amount);
+}
@@ -6,7 +6,7 @@ use serial_test::parallel;
 fn success() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // issue an RGB20 asset
     let asset = wallet
@@ -62,8 +62,8 @@ fn transfer_balances() {
     let amount_1: u64 = 66;
     let amount_2: u64 = 33;
 
-    let (mut wallet_send, online_send) = get_funded_wallet!();
-    let (mut wallet_recv, online_recv) = get_funded_wallet!();
+    let (wallet_send, online_send) = get_funded_wallet!();
+    let (wallet_recv, online_recv) = get_funded_wallet!();
 
     // issue
     let asset = wallet_send
@@ -128,7 +128,7 @@ fn transfer_balances() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet_send, &online_send, recipient_map);
+    let txid = test_send_default(&wallet_send, &online_send, recipient_map);
     wallet_send
         .fail_transfers(online_send.clone(), None, Some(txid), false)
         .unwrap();
@@ -153,7 +153,7 @@ fn transfer_balances() {
         }],
     )]);
     // actual send
-    test_send_default(&mut wallet_send, &online_send, recipient_map);
+    test_send_default(&wallet_send, &online_send, recipient_map);
 
     show_unspent_colorings(&wallet_send, "send after 1st send");
     show_unspent_colorings(&wallet_recv, "recv after 1st send");
@@ -288,7 +288,7 @@ fn transfer_balances() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    test_send_default(&mut wallet_send, &online_send, recipient_map);
+    test_send_default(&wallet_send, &online_send, recipient_map);
 
     show_unspent_colorings(&wallet_send, "send after 2nd send");
     show_unspent_colorings(&wallet_recv, "recv after 2nd send");
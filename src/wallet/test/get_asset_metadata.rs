@@ -6,8 +6,8 @@ use serial_test::parallel;
 fn success() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     let asset_nia = wallet
         .issue_asset_nia(
@@ -41,7 +41,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    test_send_default(&mut wallet, &online, recipient_map);
+    test_send_default(&wallet, &online, recipient_map);
     rcv_wallet.refresh(rcv_online, None, vec![]).unwrap();
     let nia_metadata = rcv_wallet.get_asset_metadata(asset_nia.asset_id).unwrap();
 
@@ -87,7 +87,7 @@ fn success() {
 fn fail() {
     initialize();
 
-    let (mut wallet, _online) = get_empty_wallet!();
+    let (wallet, _online) = get_empty_wallet!();
 
     let result = wallet.get_asset_metadata(s!(""));
     assert!(matches!(result, Err(Error::AssetNotFound { asset_id: _ })));
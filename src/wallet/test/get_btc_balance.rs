@@ -6,7 +6,7 @@ use serial_test::parallel;
 fn success() {
     initialize();
 
-    let (mut wallet, online) = get_empty_wallet!();
+    let (wallet, online) = get_empty_wallet!();
 
     // empty balances
     let balances = wallet.get_btc_balance(online.clone()).unwrap();
@@ -71,7 +71,7 @@ fn success() {
 
     // future vanilla change + colored UTXOs balance
     stop_mining();
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
     let balances = wallet.get_btc_balance(online.clone()).unwrap();
     assert!(matches!(
         balances.vanilla,
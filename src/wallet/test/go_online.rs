@@ -1,13 +1,50 @@
 use super::*;
 use serial_test::parallel;
 use std::ffi::OsString;
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+// start a mock SOCKS5 proxy accepting a single connection, returning its address and a channel
+// yielding the first byte received (the SOCKS protocol version)
+fn start_mock_socks5() -> (String, mpsc::Receiver<u8>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut version = [0u8; 1];
+        stream.read_exact(&mut version).unwrap();
+        sender.send(version[0]).unwrap();
+    });
+    (address, receiver)
+}
+
+fn get_test_wallet_with_network_config(network_config: NetworkConfig) -> Result<Wallet, Error> {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+
+    let keys = generate_keys(BitcoinNetwork::Regtest);
+    Wallet::new(WalletData {
+        data_dir: TEST_DATA_DIR.to_string(),
+        bitcoin_network: BitcoinNetwork::Regtest,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: Some(network_config),
+        proxy_retry_config: None,
+    })
+}
 
 #[test]
 #[parallel]
 fn success() {
     initialize();
 
-    let mut wallet = get_test_wallet(true, None);
+    let wallet = get_test_wallet(true, None);
 
     // go online
     let result_1 = wallet.go_online(false, ELECTRUM_URL.to_string());
@@ -28,7 +65,7 @@ fn success() {
 fn fail() {
     initialize();
 
-    let mut wallet = get_test_wallet(true, None);
+    let wallet = get_test_wallet(true, None);
 
     // cannot go online with a broken electrum URL
     let result = wallet.go_online(false, s!("other:50001"));
@@ -42,7 +79,7 @@ fn fail() {
     // bad online object
     let wrong_online = Online {
         id: 1,
-        electrum_url: wallet.online_data.as_ref().unwrap().electrum_url.clone(),
+        electrum_url: wallet._online_data().unwrap().electrum_url.clone(),
     };
     let result = wallet._check_online(wrong_online);
     assert!(matches!(result, Err(Error::CannotChangeOnline)));
@@ -54,7 +91,7 @@ fn consistency_check_fail_utxos() {
     initialize();
 
     // prepare test wallet with UTXOs + an asset
-    let (mut wallet_orig, online_orig) = get_funded_wallet!(true, true);
+    let (wallet_orig, online_orig) = get_funded_wallet!(true, true);
     let wallet_data_orig = wallet_orig.get_wallet_data();
     wallet_orig
         .issue_asset_nia(
@@ -99,6 +136,11 @@ fn consistency_check_fail_utxos() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        passphrase: wallet_data_orig.passphrase.clone(),
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     };
     let wallet_data_prefill = WalletData {
         data_dir: data_dir_prefill.into_os_string().into_string().unwrap(),
@@ -107,6 +149,11 @@ fn consistency_check_fail_utxos() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        passphrase: wallet_data_orig.passphrase.clone(),
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     };
     let wallet_data_prefill_2 = WalletData {
         data_dir: data_dir_prefill_2.into_os_string().into_string().unwrap(),
@@ -115,6 +162,11 @@ fn consistency_check_fail_utxos() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic,
+        passphrase: wallet_data_orig.passphrase.clone(),
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     };
     // copy original wallet's db data to prefilled wallet data dir
     let wallet_dir_entries = fs::read_dir(&wallet_dir_orig).unwrap();
@@ -139,7 +191,7 @@ fn consistency_check_fail_utxos() {
     // introduce asset inconsistency by spending UTXOs from other instance of the same wallet,
     // simulating a wallet used on multiple devices (which needs to be avoided to prevent asset
     // loss)
-    let mut wallet_empty = Wallet::new(wallet_data_empty).unwrap();
+    let wallet_empty = Wallet::new(wallet_data_empty).unwrap();
     let online_empty = wallet_empty
         .go_online(false, ELECTRUM_URL.to_string())
         .unwrap();
@@ -149,12 +201,12 @@ fn consistency_check_fail_utxos() {
         .unwrap();
 
     // detect asset inconsistency
-    let mut wallet_prefill = Wallet::new(wallet_data_prefill).unwrap();
+    let wallet_prefill = Wallet::new(wallet_data_prefill).unwrap();
     let result = wallet_prefill.go_online(false, ELECTRUM_URL.to_string());
     assert!(matches!(result, Err(Error::Inconsistency { details: _ })));
 
     // make sure detection works multiple times (doesn't get reset on first failed check)
-    let mut wallet_prefill_2 = Wallet::new(wallet_data_prefill_2).unwrap();
+    let wallet_prefill_2 = Wallet::new(wallet_data_prefill_2).unwrap();
     for file in &db_files {
         let src = PathBuf::from(&wallet_dir_prefill).join(file);
         let dst = PathBuf::from(&wallet_dir_prefill_2).join(file);
@@ -170,7 +222,7 @@ fn consistency_check_fail_asset_ids() {
     initialize();
 
     // prepare test wallet with UTXOs + an asset
-    let (mut wallet_orig, online_orig) = get_funded_wallet!();
+    let (wallet_orig, online_orig) = get_funded_wallet!();
     let wallet_data_orig = wallet_orig.get_wallet_data();
     let _asset = wallet_orig
         .issue_asset_nia(
@@ -215,6 +267,11 @@ fn consistency_check_fail_asset_ids() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        passphrase: wallet_data_orig.passphrase.clone(),
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     };
     let wallet_data_prefill_2 = WalletData {
         data_dir: data_dir_prefill_2.to_str().unwrap().to_string(),
@@ -223,6 +280,11 @@ fn consistency_check_fail_asset_ids() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic.clone(),
+        passphrase: wallet_data_orig.passphrase.clone(),
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     };
     let wallet_data_prefill_3 = WalletData {
         data_dir: data_dir_prefill_3.to_str().unwrap().to_string(),
@@ -231,6 +293,11 @@ fn consistency_check_fail_asset_ids() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: wallet_data_orig.pubkey.clone(),
         mnemonic: wallet_data_orig.mnemonic,
+        passphrase: wallet_data_orig.passphrase.clone(),
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     };
     // copy original wallet's data to prefilled wallets 1 + 2 data dir
     for destination in [&wallet_dir_prefill_1, &wallet_dir_prefill_2] {
@@ -246,7 +313,7 @@ fn consistency_check_fail_asset_ids() {
     }
 
     // check the first wallet copy works ok
-    let mut wallet_prefill_1 = Wallet::new(wallet_data_prefill_1).unwrap();
+    let wallet_prefill_1 = Wallet::new(wallet_data_prefill_1).unwrap();
     let result = wallet_prefill_1.go_online(false, ELECTRUM_URL.to_string());
     assert!(result.is_ok());
 
@@ -254,7 +321,7 @@ fn consistency_check_fail_asset_ids() {
     fs::remove_dir_all(wallet_dir_prefill_2.join("regtest")).unwrap();
 
     // detect inconsistency
-    let mut wallet_prefill_2 = Wallet::new(wallet_data_prefill_2).unwrap();
+    let wallet_prefill_2 = Wallet::new(wallet_data_prefill_2).unwrap();
     let result = wallet_prefill_2.go_online(false, ELECTRUM_URL.to_string());
     assert!(matches!(result, Err(Error::Inconsistency { details: _ })));
 
@@ -268,7 +335,7 @@ fn consistency_check_fail_asset_ids() {
         .arg(&wallet_dir_prefill_3)
         .status();
     assert!(result.is_ok());
-    let mut wallet_prefill_3 = Wallet::new(wallet_data_prefill_3).unwrap();
+    let wallet_prefill_3 = Wallet::new(wallet_data_prefill_3).unwrap();
     let result = wallet_prefill_3.go_online(false, ELECTRUM_URL.to_string());
     assert!(matches!(result, Err(Error::Inconsistency { details: _ })));
 }
@@ -279,7 +346,7 @@ fn on_off_online() {
     initialize();
 
     // create wallet and go online
-    let mut wallet = get_test_wallet(true, None);
+    let wallet = get_test_wallet(true, None);
     let wallet_data = wallet.wallet_data.clone();
     let online = wallet.go_online(false, ELECTRUM_URL.to_string()).unwrap();
 
@@ -288,6 +355,64 @@ fn on_off_online() {
     drop(wallet);
 
     // re-instantiate wallet and go back online
-    let mut wallet = Wallet::new(wallet_data).unwrap();
+    let wallet = Wallet::new(wallet_data).unwrap();
     wallet.go_online(false, ELECTRUM_URL.to_string()).unwrap();
 }
+
+#[test]
+#[parallel]
+fn network_config() {
+    let onion_electrum_url =
+        "tcp://electrumexampleaddressxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx.onion:50001";
+
+    // onion URLs are refused if indexer traffic is not routed through SOCKS5
+    let wallet = get_test_wallet(true, None);
+    let result = wallet.go_online(true, onion_electrum_url.to_string());
+    assert!(matches!(result, Err(Error::InvalidElectrum { details: _ })));
+    let wallet = get_test_wallet_with_network_config(NetworkConfig {
+        socks5_address: s!("127.0.0.1:9050"),
+        socks5_credentials: None,
+        route: NetworkRoute::Proxy,
+    })
+    .unwrap();
+    let result = wallet.go_online(true, onion_electrum_url.to_string());
+    assert!(matches!(result, Err(Error::InvalidElectrum { details: _ })));
+
+    // indexer traffic goes through the SOCKS5 proxy
+    let (socks5_address, versions) = start_mock_socks5();
+    let wallet = get_test_wallet_with_network_config(NetworkConfig {
+        socks5_address,
+        socks5_credentials: Some(Socks5Credentials {
+            username: s!("user"),
+            password: s!("password"),
+        }),
+        route: NetworkRoute::Indexer,
+    })
+    .unwrap();
+    let result = wallet.go_online(true, onion_electrum_url.to_string());
+    assert!(matches!(result, Err(Error::InvalidElectrum { details: _ })));
+    assert_eq!(versions.recv().unwrap(), 5);
+
+    // proxy traffic goes through the SOCKS5 proxy
+    let (socks5_address, versions) = start_mock_socks5();
+    let wallet = get_test_wallet_with_network_config(NetworkConfig {
+        socks5_address,
+        socks5_credentials: None,
+        route: NetworkRoute::All,
+    })
+    .unwrap();
+    let result = wallet._proxy_client(PROXY_URL).get_info(PROXY_URL);
+    assert!(matches!(result, Err(Error::Proxy { details: _ })));
+    assert_eq!(versions.recv().unwrap(), 5);
+
+    // invalid SOCKS5 address
+    let result = get_test_wallet_with_network_config(NetworkConfig {
+        socks5_address: s!("invalid address"),
+        socks5_credentials: None,
+        route: NetworkRoute::Proxy,
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InvalidNetworkConfig { details: _ })
+    ));
+}
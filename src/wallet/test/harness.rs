@@ -0,0 +1,704 @@
+//! Hermetic regtest harness
+//!
+//! Runs wallets against an in-process simulated chain, served by a mock electrum indexer, and an
+//! in-process RGB proxy, so that multi-wallet scenarios don't need the Docker services.
+
+use super::*;
+use serial_test::parallel;
+
+use crate::proxy_server::{ProxyServer, ProxyServerConfig};
+use bitcoin::absolute::LockTime;
+use bitcoin::block::{Header, Version as BlockVersion};
+use bitcoin::consensus::encode::{deserialize, serialize, serialize_hex};
+use bitcoin::hash_types::TxMerkleNode;
+use bitcoin::hashes::sha256d;
+use bitcoin::{BlockHash, CompactTarget, Sequence, Transaction, TxIn, TxOut, Witness};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use tempfile::TempDir;
+
+const GENESIS_TIME: u32 = 1_700_000_000;
+const BLOCK_INTERVAL: u32 = 600;
+const COINBASE_AMOUNT: u64 = 50 * 100_000_000;
+const FUNDING_AMOUNT: u64 = 100_000_000;
+const NO_SUCH_TX: &str =
+    "No such mempool or blockchain transaction. Use gettransaction for wallet transactions.";
+
+struct MockBlock {
+    header: Header,
+    txids: Vec<Txid>,
+}
+
+/// Simulated chain: blocks, mempool and every transaction ever seen
+#[derive(Default)]
+struct MockChain {
+    blocks: Vec<MockBlock>,
+    mempool: Vec<Txid>,
+    txs: HashMap<Txid, Transaction>,
+    faucet_txids: HashSet<Txid>,
+    nonce: i64,
+}
+
+impl MockChain {
+    fn new() -> Self {
+        let mut chain = MockChain::default();
+        chain.mine(1);
+        chain
+    }
+
+    fn tip_height(&self) -> usize {
+        self.blocks.len() - 1
+    }
+
+    fn _unique_script_sig(&mut self) -> ScriptBuf {
+        self.nonce += 1;
+        bitcoin::script::Builder::new()
+            .push_int(self.blocks.len() as i64)
+            .push_int(self.nonce)
+            .into_script()
+    }
+
+    fn _coinbase(&mut self) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: self._unique_script_sig(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: COINBASE_AMOUNT,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    fn mine(&mut self, blocks: u32) {
+        for _ in 0..blocks {
+            let coinbase = self._coinbase();
+            let mut txids = vec![coinbase.txid()];
+            self.txs.insert(coinbase.txid(), coinbase);
+            txids.append(&mut self.mempool);
+            let mut engine = sha256d::Hash::engine();
+            for txid in &txids {
+                std::io::Write::write_all(&mut engine, txid.as_ref()).unwrap();
+            }
+            let merkle_root = TxMerkleNode::from_raw_hash(sha256d::Hash::from_engine(engine));
+            let height = self.blocks.len() as u32;
+            let header = Header {
+                version: BlockVersion::TWO,
+                prev_blockhash: self
+                    .blocks
+                    .last()
+                    .map_or(BlockHash::all_zeros(), |b| b.header.block_hash()),
+                merkle_root,
+                time: GENESIS_TIME + height * BLOCK_INTERVAL,
+                bits: CompactTarget::from_consensus(0x207fffff),
+                nonce: self.nonce as u32,
+            };
+            self.blocks.push(MockBlock { header, txids });
+        }
+    }
+
+    fn invalidate(&mut self, blocks: u32) {
+        let mut reorged = vec![];
+        for _ in 0..blocks {
+            let block = self
+                .blocks
+                .pop()
+                .expect("cannot invalidate the genesis block");
+            assert!(
+                !self.blocks.is_empty(),
+                "cannot invalidate the genesis block"
+            );
+            reorged.splice(0..0, block.txids.into_iter().skip(1));
+        }
+        reorged.append(&mut self.mempool);
+        self.mempool = reorged;
+    }
+
+    fn evict(&mut self, txid: &Txid) {
+        let mut evicted = HashSet::from([*txid]);
+        self.mempool.retain(|mempool_txid| {
+            let spends_evicted = self.txs[mempool_txid]
+                .input
+                .iter()
+                .any(|i| evicted.contains(&i.previous_output.txid));
+            if mempool_txid == txid || spends_evicted {
+                evicted.insert(*mempool_txid);
+                return false;
+            }
+            true
+        });
+    }
+
+    fn fund(&mut self, script_pubkey: ScriptBuf, amount: u64) -> Txid {
+        let faucet = self._coinbase();
+        let faucet_txid = faucet.txid();
+        self.txs.insert(faucet_txid, faucet);
+        self.faucet_txids.insert(faucet_txid);
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(faucet_txid, 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: amount,
+                script_pubkey,
+            }],
+        };
+        self.broadcast(tx).expect("funding TX should be valid")
+    }
+
+    fn height(&self, txid: &Txid) -> Option<usize> {
+        self.blocks.iter().position(|b| b.txids.contains(txid))
+    }
+
+    fn _live_txids(&self) -> impl Iterator<Item = &Txid> {
+        self.blocks
+            .iter()
+            .flat_map(|b| b.txids.iter())
+            .chain(self.mempool.iter())
+    }
+
+    fn _spender(&self, outpoint: &OutPoint) -> Option<Txid> {
+        self._live_txids()
+            .find(|txid| {
+                self.txs[*txid]
+                    .input
+                    .iter()
+                    .any(|i| i.previous_output == *outpoint)
+            })
+            .cloned()
+    }
+
+    fn broadcast(&mut self, tx: Transaction) -> Result<Txid, String> {
+        let txid = tx.txid();
+        if self._live_txids().any(|t| *t == txid) {
+            return Ok(txid);
+        }
+        for input in &tx.input {
+            let prev_txid = input.previous_output.txid;
+            let known = self.faucet_txids.contains(&prev_txid)
+                || self._live_txids().any(|t| *t == prev_txid);
+            let prev_output_exists = self.txs.get(&prev_txid).map_or(false, |t| {
+                t.output.len() > input.previous_output.vout as usize
+            });
+            if !known || !prev_output_exists {
+                return Err(s!("bad-txns-inputs-missingorspent"));
+            }
+            if self._spender(&input.previous_output).is_some() {
+                return Err(s!("txn-mempool-conflict"));
+            }
+        }
+        self.txs.insert(txid, tx);
+        self.mempool.push(txid);
+        Ok(txid)
+    }
+
+    fn _script_hash(script: &ScriptBuf) -> String {
+        let script_hash: sha256::Hash = Sha256Hash::hash(script.as_bytes());
+        let mut hash = script_hash.to_byte_array();
+        hash.reverse();
+        hex::encode(hash)
+    }
+
+    fn _electrum_height(&self, txid: &Txid) -> i64 {
+        match self.height(txid) {
+            Some(height) => height as i64,
+            None => {
+                let unconfirmed_parent = self.txs[txid]
+                    .input
+                    .iter()
+                    .any(|i| self.mempool.contains(&i.previous_output.txid));
+                if unconfirmed_parent {
+                    -1
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    fn _outputs_of(&self, script_hash: &str) -> Vec<(OutPoint, u64)> {
+        let mut outputs = vec![];
+        for txid in self._live_txids() {
+            for (vout, output) in self.txs[txid].output.iter().enumerate() {
+                if MockChain::_script_hash(&output.script_pubkey) == script_hash {
+                    outputs.push((OutPoint::new(*txid, vout as u32), output.value));
+                }
+            }
+        }
+        outputs
+    }
+
+    fn history(&self, script_hash: &str) -> Value {
+        let outpoints: Vec<OutPoint> = self
+            ._outputs_of(script_hash)
+            .into_iter()
+            .map(|(o, _)| o)
+            .collect();
+        let mut txids: Vec<Txid> = vec![];
+        for txid in self._live_txids() {
+            let tx = &self.txs[txid];
+            let receives = outpoints.iter().any(|o| o.txid == *txid);
+            let spends = tx
+                .input
+                .iter()
+                .any(|i| outpoints.contains(&i.previous_output));
+            if (receives || spends) && !txids.contains(txid) {
+                txids.push(*txid);
+            }
+        }
+        Value::Array(
+            txids
+                .iter()
+                .map(|txid| json!({"tx_hash": txid.to_string(), "height": self._electrum_height(txid)}))
+                .collect(),
+        )
+    }
+
+    fn list_unspent(&self, script_hash: &str) -> Value {
+        Value::Array(
+            self._outputs_of(script_hash)
+                .into_iter()
+                .filter(|(outpoint, _)| self._spender(outpoint).is_none())
+                .map(|(outpoint, value)| {
+                    json!({
+                        "tx_hash": outpoint.txid.to_string(),
+                        "tx_pos": outpoint.vout,
+                        "height": self.height(&outpoint.txid).unwrap_or(0),
+                        "value": value,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn transaction(&self, txid: &str, verbose: bool) -> Result<Value, String> {
+        let txid = Txid::from_str(txid).map_err(|_| NO_SUCH_TX.to_string())?;
+        let tx = self.txs.get(&txid).ok_or_else(|| NO_SUCH_TX.to_string())?;
+        if !verbose {
+            return Ok(Value::String(serialize_hex(tx)));
+        }
+        let mut details = json!({
+            "txid": txid.to_string(),
+            "hash": tx.wtxid().to_string(),
+            "hex": serialize_hex(tx),
+            "version": tx.version,
+            "locktime": tx.lock_time.to_consensus_u32(),
+        });
+        if let Some(height) = self.height(&txid) {
+            let header = &self.blocks[height].header;
+            details["confirmations"] = json!(self.tip_height() - height + 1);
+            details["blockhash"] = json!(header.block_hash().to_string());
+            details["blocktime"] = json!(header.time);
+            details["time"] = json!(header.time);
+        }
+        Ok(details)
+    }
+
+    fn header_hex(&self, height: usize) -> Result<String, String> {
+        self.blocks
+            .get(height)
+            .map(|b| serialize_hex(&b.header))
+            .ok_or(format!("missing header at height {height}"))
+    }
+}
+
+/// Mock electrum server backed by a [`MockChain`]
+pub(crate) struct MockIndexer {
+    address: SocketAddr,
+    chain: Arc<Mutex<MockChain>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MockIndexer {
+    pub(crate) fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let chain = Arc::new(Mutex::new(MockChain::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (server_chain, server_stopped) = (chain.clone(), stopped.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let chain = server_chain.clone();
+                thread::spawn(move || MockIndexer::_serve(stream, chain));
+            }
+        });
+        MockIndexer {
+            address,
+            chain,
+            stopped,
+        }
+    }
+
+    pub(crate) fn electrum_url(&self) -> String {
+        self.address.to_string()
+    }
+
+    fn _chain(&self) -> MutexGuard<'_, MockChain> {
+        self.chain.lock().unwrap()
+    }
+
+    pub(crate) fn mine(&self, blocks: u32) {
+        self._chain().mine(blocks)
+    }
+
+    pub(crate) fn invalidate(&self, blocks: u32) {
+        self._chain().invalidate(blocks)
+    }
+
+    pub(crate) fn evict(&self, txid: &str) {
+        self._chain().evict(&Txid::from_str(txid).unwrap())
+    }
+
+    pub(crate) fn fund(&self, address: &str, amount: u64) -> String {
+        let script_pubkey = Address::from_str(address)
+            .unwrap()
+            .assume_checked()
+            .script_pubkey();
+        self._chain().fund(script_pubkey, amount).to_string()
+    }
+
+    pub(crate) fn tip_height(&self) -> usize {
+        self._chain().tip_height()
+    }
+
+    pub(crate) fn mempool(&self) -> Vec<String> {
+        self._chain()
+            .mempool
+            .iter()
+            .map(|t| t.to_string())
+            .collect()
+    }
+
+    fn _serve(stream: TcpStream, chain: Arc<Mutex<MockChain>>) {
+        let mut writer = stream.try_clone().unwrap();
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let Ok(request) = serde_json::from_str::<Value>(&line) else {
+                break;
+            };
+            let requests = match request {
+                Value::Array(requests) => requests,
+                request => vec![request],
+            };
+            for request in requests {
+                let method = request["method"].as_str().unwrap_or_default();
+                let params = request["params"].as_array().cloned().unwrap_or_default();
+                let result = MockIndexer::_call(&mut chain.lock().unwrap(), method, &params);
+                let response = match result {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+                    Err(message) => json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "error": {"code": 2, "message": message},
+                    }),
+                };
+                if writeln!(writer, "{response}").is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn _call(chain: &mut MockChain, method: &str, params: &[Value]) -> Result<Value, String> {
+        let str_param = |i: usize| params.get(i).and_then(Value::as_str).unwrap_or_default();
+        let usize_param = |i: usize| params.get(i).and_then(Value::as_u64).unwrap_or(0) as usize;
+        match method {
+            "server.version" => Ok(json!(["MockIndexer 0.1", "1.4"])),
+            "server.ping" | "blockchain.scripthash.subscribe" => Ok(Value::Null),
+            "server.banner" => Ok(json!("")),
+            "blockchain.estimatefee" | "blockchain.relayfee" => Ok(json!(0.00001)),
+            "blockchain.headers.subscribe" => Ok(json!({
+                "height": chain.tip_height(),
+                "hex": chain.header_hex(chain.tip_height())?,
+            })),
+            "blockchain.block.header" => Ok(json!(chain.header_hex(usize_param(0))?)),
+            "blockchain.block.headers" => {
+                let start = usize_param(0);
+                let end = (start + usize_param(1)).min(chain.blocks.len());
+                let hex: String = (start..end)
+                    .map(|h| chain.header_hex(h))
+                    .collect::<Result<_, _>>()?;
+                Ok(json!({"count": end.saturating_sub(start), "hex": hex, "max": 2016}))
+            }
+            "blockchain.scripthash.get_history" => Ok(chain.history(str_param(0))),
+            "blockchain.scripthash.listunspent" => Ok(chain.list_unspent(str_param(0))),
+            "blockchain.scripthash.get_balance" => {
+                let (mut confirmed, mut unconfirmed) = (0, 0);
+                for utxo in chain.list_unspent(str_param(0)).as_array().unwrap() {
+                    if utxo["height"].as_u64().unwrap() > 0 {
+                        confirmed += utxo["value"].as_u64().unwrap();
+                    } else {
+                        unconfirmed += utxo["value"].as_u64().unwrap();
+                    }
+                }
+                Ok(json!({"confirmed": confirmed, "unconfirmed": unconfirmed}))
+            }
+            "blockchain.transaction.get" => chain.transaction(
+                str_param(0),
+                params.get(1).and_then(Value::as_bool).unwrap_or(false),
+            ),
+            "blockchain.transaction.broadcast" => {
+                let bytes = hex::decode(str_param(0)).map_err(|e| e.to_string())?;
+                let tx: Transaction = deserialize(&bytes).map_err(|e| e.to_string())?;
+                debug_assert_eq!(serialize(&tx), bytes);
+                Ok(json!(chain.broadcast(tx)?.to_string()))
+            }
+            _ => Err(format!("unsupported method {method}")),
+        }
+    }
+}
+
+impl Drop for MockIndexer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// A mock indexer and an RGB proxy running in-process
+pub(crate) struct RegtestHarness {
+    pub(crate) indexer: MockIndexer,
+    proxy: ProxyServer,
+    _proxy_dir: TempDir,
+}
+
+impl RegtestHarness {
+    pub(crate) fn start() -> Self {
+        let proxy_dir = tempfile::tempdir().unwrap();
+        let proxy = ProxyServer::start(ProxyServerConfig {
+            listen_address: s!("127.0.0.1:0"),
+            db_path: proxy_dir.path().join("proxy.sqlite"),
+            bearer_token: None,
+        })
+        .unwrap();
+        RegtestHarness {
+            indexer: MockIndexer::start(),
+            proxy,
+            _proxy_dir: proxy_dir,
+        }
+    }
+
+    pub(crate) fn electrum_url(&self) -> String {
+        self.indexer.electrum_url()
+    }
+
+    pub(crate) fn transport_endpoints(&self) -> Vec<String> {
+        vec![format!("rpc://{}/json-rpc", self.proxy.address())]
+    }
+
+    pub(crate) fn mine(&self, blocks: u32) {
+        self.indexer.mine(blocks)
+    }
+
+    // return an online wallet, funded with 1 BTC and with UTXOs available for RGB allocations
+    pub(crate) fn get_funded_wallet(&self) -> (Wallet, Online) {
+        let wallet = get_test_wallet(true, None);
+        let online = wallet.go_online(true, self.electrum_url()).unwrap();
+        self.indexer.fund(&wallet.get_address(), FUNDING_AMOUNT);
+        self.mine(1);
+        wallet
+            .create_utxos(online.clone(), false, None, None, FEE_RATE)
+            .unwrap();
+        self.mine(1);
+        (wallet, online)
+    }
+}
+
+#[test]
+#[parallel]
+fn indexer() {
+    let harness = RegtestHarness::start();
+    let indexer = &harness.indexer;
+    let client = electrum_client::Client::new(&harness.electrum_url()).unwrap();
+    assert_eq!(client.block_headers_subscribe().unwrap().height, 0);
+
+    // funding TXs enter the mempool and get confirmed when mining
+    let address = get_test_wallet(false, None).get_address();
+    let txid = indexer.fund(&address, FUNDING_AMOUNT);
+    assert_eq!(indexer.mempool(), vec![txid.clone()]);
+    let script = Address::from_str(&address)
+        .unwrap()
+        .assume_checked()
+        .script_pubkey();
+    let history = client.script_get_history(&script).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].height, 0);
+    indexer.mine(2);
+    assert_eq!(indexer.tip_height(), 2);
+    assert!(indexer.mempool().is_empty());
+    let history = client.script_get_history(&script).unwrap();
+    assert_eq!(history[0].height, 1);
+    let unspent = client.script_list_unspent(&script).unwrap();
+    assert_eq!(unspent[0].value, FUNDING_AMOUNT);
+    let details: Value = client
+        .raw_call(
+            "blockchain.transaction.get",
+            vec![Param::String(txid.clone()), Param::Bool(true)],
+        )
+        .unwrap();
+    assert_eq!(details["confirmations"], 2);
+    let header = client.block_header(2).unwrap();
+    assert_eq!(
+        header.prev_blockhash,
+        client.block_header(1).unwrap().block_hash()
+    );
+
+    // reorgs send TXs back to the mempool
+    indexer.invalidate(2);
+    assert_eq!(indexer.tip_height(), 0);
+    assert_eq!(indexer.mempool(), vec![txid.clone()]);
+    let details: Value = client
+        .raw_call(
+            "blockchain.transaction.get",
+            vec![Param::String(txid.clone()), Param::Bool(true)],
+        )
+        .unwrap();
+    assert!(details.get("confirmations").is_none());
+
+    // evicted TXs are forgotten
+    indexer.evict(&txid);
+    assert!(indexer.mempool().is_empty());
+    assert!(client.script_get_history(&script).unwrap().is_empty());
+
+    // unknown TXs
+    let result = client.raw_call(
+        "blockchain.transaction.get",
+        vec![Param::String(s!("_")), Param::Bool(true)],
+    );
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("No such mempool or blockchain transaction"));
+}
+
+#[test]
+#[parallel]
+fn send_receive() {
+    let harness = RegtestHarness::start();
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = harness.get_funded_wallet();
+    let (rcv_wallet, rcv_online) = harness.get_funded_wallet();
+
+    // issue
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            harness.transport_endpoints(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: harness.transport_endpoints(),
+        }],
+    )]);
+    let txid = test_send_default(&wallet, &online, recipient_map);
+    assert!(!txid.is_empty());
+
+    // take transfers from WaitingCounterparty to Settled
+    rcv_wallet
+        .refresh(rcv_online.clone(), None, vec![])
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &receive_data.recipient_id,
+        TransferStatus::WaitingConfirmations
+    ));
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    assert_eq!(harness.indexer.mempool(), vec![txid.clone()]);
+    harness.mine(1);
+    rcv_wallet.refresh(rcv_online, None, vec![]).unwrap();
+    wallet
+        .refresh(online, Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    assert!(check_test_transfer_status_recipient(
+        &rcv_wallet,
+        &receive_data.recipient_id,
+        TransferStatus::Settled
+    ));
+    let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
+    let (transfer_data, _) = get_test_transfer_data(&wallet, &transfer);
+    assert_eq!(transfer_data.status, TransferStatus::Settled);
+
+    // check balances
+    let balance = wallet.get_asset_balance(asset.asset_id.clone()).unwrap();
+    assert_eq!(balance.settled, AMOUNT - amount);
+    let rcv_balance = rcv_wallet.get_asset_balance(asset.asset_id).unwrap();
+    assert_eq!(rcv_balance.settled, amount);
+}
+
+#[test]
+#[parallel]
+fn reorg() {
+    let harness = RegtestHarness::start();
+    let amount: u64 = 10_000;
+
+    let (wallet, online) = harness.get_funded_wallet();
+    let rcv_wallet = get_test_wallet(true, None);
+    let rcv_online = rcv_wallet.go_online(true, harness.electrum_url()).unwrap();
+
+    // confirm a BTC send
+    let txid = wallet
+        .send_btc(online, rcv_wallet.get_address(), amount, FEE_RATE)
+        .unwrap();
+    harness.mine(1);
+    let balance = rcv_wallet.get_btc_balance(rcv_online.clone()).unwrap();
+    assert_eq!(balance.vanilla.settled, amount);
+
+    // the block including the TX gets reorged out, sending the TX back to the mempool
+    harness.indexer.invalidate(1);
+    assert_eq!(harness.indexer.mempool(), vec![txid.clone()]);
+    let balance = rcv_wallet.get_btc_balance(rcv_online.clone()).unwrap();
+    assert_eq!(balance.vanilla.settled, 0);
+    assert_eq!(balance.vanilla.future, amount);
+
+    // the TX gets dropped from the mempool
+    harness.indexer.evict(&txid);
+    let balance = rcv_wallet.get_btc_balance(rcv_online.clone()).unwrap();
+    assert_eq!(balance.vanilla.future, 0);
+
+    // the wallet keeps following the chain after the TX is gone
+    harness.indexer.fund(&rcv_wallet.get_address(), amount);
+    harness.mine(1);
+    let balance = rcv_wallet.get_btc_balance(rcv_online).unwrap();
+    assert_eq!(balance.vanilla.settled, amount);
+}
@@ -0,0 +1,208 @@
+use super::*;
+use bdk::bitcoin::{PackedLockTime, Sequence, TxIn, TxOut, Witness};
+use rgb_core::OpId;
+use serial_test::parallel;
+
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+
+    // create 1 UTXO and issue 2 assets on it
+    let num_utxos_created =
+        test_create_utxos(&wallet, online.clone(), true, Some(1), None, FEE_RATE);
+    assert_eq!(num_utxos_created, 1);
+    let asset_a = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let asset_b = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT * 2],
+        )
+        .unwrap();
+
+    // create 1 more UTXO for change
+    let num_utxos_created =
+        test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
+    assert_eq!(num_utxos_created, 1);
+
+    // prepare the send PSBT
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset_a.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let unsigned_psbt = wallet
+        .send_begin(
+            online.clone(),
+            recipient_map,
+            false,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+
+    // inspect it
+    let inspection = wallet.inspect_psbt(unsigned_psbt.clone()).unwrap();
+    assert!(!inspection.inputs.is_empty());
+    assert!(inspection.inputs.iter().all(|i| i.is_mine));
+    assert!(inspection.inputs.iter().all(|i| i.btc_amount.is_some()));
+    let input_sum: u64 = inspection
+        .inputs
+        .iter()
+        .map(|i| i.btc_amount.unwrap())
+        .sum();
+    let output_sum: u64 = inspection.outputs.iter().map(|o| o.btc_amount).sum();
+    assert_eq!(inspection.fee, Some(input_sum - output_sum));
+    let op_returns: Vec<&PsbtOutput> = inspection
+        .outputs
+        .iter()
+        .filter(|o| o.is_op_return)
+        .collect();
+    assert_eq!(op_returns.len(), 1);
+    let opret_commitment = inspection.opret_commitment.unwrap();
+    assert!(op_returns[0].script.ends_with(&opret_commitment));
+    assert_eq!(inspection.transitions.len(), 2);
+
+    // the sent asset moves to the recipient and the change
+    let transition_a = inspection
+        .transitions
+        .iter()
+        .find(|t| t.asset_id == asset_a.asset_id)
+        .unwrap();
+    assert!(!transition_a.blank);
+    assert_eq!(transition_a.spent_allocations.len(), 1);
+    assert_eq!(transition_a.spent_allocations[0].amount, AMOUNT);
+    assert_eq!(transition_a.new_allocations.len(), 2);
+    let recipient_allocation = transition_a
+        .new_allocations
+        .iter()
+        .find(|a| a.recipient_id == Some(receive_data.recipient_id.clone()))
+        .unwrap();
+    assert_eq!(recipient_allocation.amount, amount);
+    assert!(!recipient_allocation.is_mine);
+    let change_allocation = transition_a
+        .new_allocations
+        .iter()
+        .find(|a| a.recipient_id.is_none())
+        .unwrap();
+    assert_eq!(change_allocation.amount, AMOUNT - amount);
+    assert!(change_allocation.is_mine);
+
+    // the other asset on the same UTXO moves to the change with a blank transition
+    let transition_b = inspection
+        .transitions
+        .iter()
+        .find(|t| t.asset_id == asset_b.asset_id)
+        .unwrap();
+    assert!(transition_b.blank);
+    assert_eq!(transition_b.spent_allocations.len(), 1);
+    assert_eq!(transition_b.spent_allocations[0].amount, AMOUNT * 2);
+    assert_eq!(transition_b.new_allocations.len(), 1);
+    assert_eq!(transition_b.new_allocations[0].amount, AMOUNT * 2);
+    assert_eq!(
+        transition_b.new_allocations[0].outpoint,
+        change_allocation.outpoint
+    );
+    assert!(transition_b.new_allocations[0].is_mine);
+
+    // signing doesn't change the inspected content
+    let signed_psbt = wallet.sign_psbt(unsigned_psbt).unwrap();
+    let signed_inspection = wallet.inspect_psbt(signed_psbt).unwrap();
+    assert_eq!(signed_inspection.txid, inspection.txid);
+    assert_eq!(signed_inspection.transitions, inspection.transitions);
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let wallet = get_test_wallet(true, None);
+
+    // invalid PSBT
+    let result = wallet.inspect_psbt(s!("invalid"));
+    assert!(matches!(result, Err(Error::InvalidPsbt { details: _ })));
+
+    let txout = |value| TxOut {
+        value,
+        script_pubkey: BdkScript::new(),
+    };
+    let tx = |previous_output, value| BdkTransaction {
+        version: 2,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![TxIn {
+            previous_output,
+            script_sig: BdkScript::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        }],
+        output: vec![txout(value)],
+    };
+    let prev_tx = tx(BdkOutPoint::default(), 1000);
+
+    // outputs exceeding inputs
+    let mut psbt =
+        BdkPsbt::from_unsigned_tx(tx(BdkOutPoint::new(prev_tx.txid(), 0), 2000)).unwrap();
+    psbt.inputs[0].witness_utxo = Some(txout(1000));
+    let result = wallet.inspect_psbt(psbt.to_string());
+    assert!(
+        matches!(result, Err(Error::InvalidPsbt { details: m }) if m == "outputs exceed inputs")
+    );
+
+    // input spending an out-of-range output of its previous transaction
+    let mut psbt = BdkPsbt::from_unsigned_tx(tx(BdkOutPoint::new(prev_tx.txid(), 5), 500)).unwrap();
+    psbt.inputs[0].non_witness_utxo = Some(prev_tx.clone());
+    let result = wallet.inspect_psbt(psbt.to_string());
+    assert!(
+        matches!(result, Err(Error::InvalidPsbt { details: m }) if m.contains("inexistent output"))
+    );
+
+    // a valid previous output gives the fee
+    let mut psbt = BdkPsbt::from_unsigned_tx(tx(BdkOutPoint::new(prev_tx.txid(), 0), 500)).unwrap();
+    psbt.inputs[0].non_witness_utxo = Some(prev_tx.clone());
+    let inspection = wallet.inspect_psbt(psbt.to_string()).unwrap();
+    assert_eq!(inspection.fee, Some(500));
+
+    // input consuming RGB state from a UTXO unknown to the wallet
+    let mut rgb_psbt = PartiallySignedTransaction::from_str(&psbt.to_string()).unwrap();
+    rgb_psbt.inputs[0]
+        .set_rgb_consumer(
+            ContractId::from_raw_array([1; 32]),
+            OpId::from_raw_array([2; 32]),
+        )
+        .unwrap();
+    let result = wallet.inspect_psbt(rgb_psbt.to_string());
+    let outpoint = format!("{}:0", prev_tx.txid());
+    assert!(
+        matches!(result, Err(Error::InvalidPsbt { details: m }) if m == format!("input {outpoint} spends RGB state from an unknown UTXO"))
+    );
+}
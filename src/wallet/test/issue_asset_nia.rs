@@ -6,7 +6,7 @@ use serial_test::parallel;
 fn success() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // add a pending operation to an UTXO so spendable balance will be != settled / future
     let _receive_data = wallet.blind_receive(
@@ -50,7 +50,7 @@ fn multi_success() {
     let amounts: Vec<u64> = vec![111, 222, 333, 444, 555];
     let sum: u64 = amounts.iter().sum();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     let asset = wallet
         .issue_asset_nia(
@@ -97,8 +97,8 @@ fn no_issue_on_pending_send() {
 
     let amount: u64 = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue 1st asset
     let asset_1 = wallet
@@ -140,7 +140,7 @@ fn no_issue_on_pending_send() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     // issue 2nd asset
@@ -202,7 +202,7 @@ fn no_issue_on_pending_send() {
 fn fail() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // supply overflow
     let result = wallet.issue_asset_nia(
@@ -217,7 +217,7 @@ fn fail() {
     // bad online object
     let other_online = Online {
         id: 1,
-        electrum_url: wallet.online_data.as_ref().unwrap().electrum_url.clone(),
+        electrum_url: wallet._online_data().unwrap().electrum_url.clone(),
     };
     let result = wallet.issue_asset_nia(
         other_online,
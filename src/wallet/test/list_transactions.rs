@@ -8,8 +8,8 @@ fn success() {
 
     let amount: u64 = 66;
 
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     mine(false);
     // don't sync wallet without online
@@ -73,7 +73,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    test_send_default(&mut wallet, &online, recipient_map);
+    test_send_default(&wallet, &online, recipient_map);
     // settle the transfer so the tx gets broadcasted and receiver sees the new UTXO
     rcv_wallet
         .refresh(rcv_online.clone(), None, vec![])
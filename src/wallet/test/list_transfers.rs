@@ -6,7 +6,7 @@ use serial_test::parallel;
 fn success() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // issue RGB20 asset
     let asset = wallet
@@ -28,7 +28,7 @@ fn success() {
 
     drain_wallet(&wallet, online.clone());
     fund_wallet(wallet.get_address());
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
 
     // issue RGB25 asset
     let asset = wallet
@@ -9,8 +9,8 @@ fn success() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_empty_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_empty_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // no unspents
     let unspent_list_settled = wallet.list_unspents(None, true).unwrap();
@@ -28,7 +28,7 @@ fn success() {
     assert_eq!(unspent_list_all.len(), 1);
     assert!(unspent_list_all.iter().all(|u| !u.utxo.colorable));
 
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
 
     // multiple unspents, one settled RGB allocation
     let asset = wallet
@@ -109,7 +109,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     wallet
         .fail_transfers(online.clone(), None, Some(txid), false)
@@ -139,11 +139,11 @@ fn success() {
     drain_wallet(&wallet, online.clone());
     fund_wallet(wallet.get_address());
     mine(false);
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
     drain_wallet(&rcv_wallet, rcv_online.clone());
     fund_wallet(rcv_wallet.get_address());
     mine(false);
-    test_create_utxos_default(&mut rcv_wallet, rcv_online.clone());
+    test_create_utxos_default(&rcv_wallet, rcv_online.clone());
 
     // issue + send some asset
     let asset = wallet
@@ -174,7 +174,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     show_unspent_colorings(&rcv_wallet, "receiver after send - WaitingCounterparty");
     show_unspent_colorings(&wallet, "sender after send - WaitingCounterparty");
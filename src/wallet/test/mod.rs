@@ -8,6 +8,8 @@ use time::OffsetDateTime;
 use walkdir::WalkDir;
 
 use crate::generate_keys;
+use crate::keys::{generate_keys_with_options, MnemonicLanguage, MnemonicWordCount};
+use crate::utils::RGB_RUNTIME_LOCK_FILE;
 
 use super::*;
 
@@ -218,6 +220,11 @@ fn get_test_wallet_with_net(
         max_allocations_per_utxo: max_allocations_per_utxo.unwrap_or(MAX_ALLOCATIONS_PER_UTXO),
         pubkey: keys.xpub,
         mnemonic,
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     })
     .unwrap()
 }
@@ -231,9 +238,32 @@ fn get_test_wallet(private_keys: bool, max_allocations_per_utxo: Option<u32>) ->
     )
 }
 
+// return a new instance of the provided wallet, using a copy of its data in the given directory
+fn copy_test_wallet(wallet: &Wallet, data_dir_name: &str) -> Wallet {
+    let data_dir = PathBuf::from(TEST_DATA_DIR).join(data_dir_name);
+    if data_dir.is_dir() {
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+    fs::create_dir_all(&data_dir).unwrap();
+    let wallet_dir = wallet.get_wallet_dir();
+    let status = Command::new("cp")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .arg("-r")
+        .arg(&wallet_dir)
+        .arg(data_dir.join(wallet_dir.file_name().unwrap()))
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let mut wallet_data = wallet.get_wallet_data();
+    wallet_data.data_dir = data_dir.to_str().unwrap().to_string();
+    Wallet::new(wallet_data).unwrap()
+}
+
 // the get_*_wallet! macros can be called with no arguments to use defaults
 fn get_empty_wallet(print_log: bool, private_keys: bool) -> (Wallet, Online) {
-    let mut wallet = get_test_wallet(private_keys, None);
+    let wallet = get_test_wallet(private_keys, None);
     if print_log {
         println!("wallet directory: {:?}", wallet.get_wallet_dir());
     }
@@ -264,8 +294,8 @@ macro_rules! get_funded_noutxo_wallet {
 }
 
 fn get_funded_wallet(print_log: bool, private_keys: bool) -> (Wallet, Online) {
-    let (mut wallet, online) = get_funded_noutxo_wallet(print_log, private_keys);
-    test_create_utxos_default(&mut wallet, online.clone());
+    let (wallet, online) = get_funded_noutxo_wallet(print_log, private_keys);
+    test_create_utxos_default(&wallet, online.clone());
     (wallet, online)
 }
 macro_rules! get_funded_wallet {
@@ -277,12 +307,12 @@ macro_rules! get_funded_wallet {
     };
 }
 
-fn test_create_utxos_default(wallet: &mut Wallet, online: Online) -> u8 {
+fn test_create_utxos_default(wallet: &Wallet, online: Online) -> u8 {
     _test_create_utxos(wallet, online, false, None, None, FEE_RATE)
 }
 
 fn test_create_utxos(
-    wallet: &mut Wallet,
+    wallet: &Wallet,
     online: Online,
     up_to: bool,
     num: Option<u8>,
@@ -293,7 +323,7 @@ fn test_create_utxos(
 }
 
 fn _test_create_utxos(
-    wallet: &mut Wallet,
+    wallet: &Wallet,
     online: Online,
     up_to: bool,
     num: Option<u8>,
@@ -330,7 +360,7 @@ fn _test_create_utxos(
 }
 
 fn test_send_default(
-    wallet: &mut Wallet,
+    wallet: &Wallet,
     online: &Online,
     recipient_map: HashMap<String, Vec<Recipient>>,
 ) -> String {
@@ -379,7 +409,7 @@ fn check_test_transfer_status_sender(
 }
 
 fn check_test_wallet_data(
-    wallet: &mut Wallet,
+    wallet: &Wallet,
     asset: &AssetNIA,
     custom_issued_supply: Option<u64>,
     transfer_num: usize,
@@ -428,12 +458,15 @@ fn check_test_wallet_data(
 
 fn compare_test_directories(src: &Path, dst: &Path, skip_src: Vec<&str>) -> (bool, String) {
     const BUF_SIZE: usize = 4096;
+    // the runtime lock file is not part of the wallet state
     let mut walk_src = WalkDir::new(src)
         .sort_by(|a, b| a.path().cmp(b.path()))
-        .into_iter();
+        .into_iter()
+        .filter_entry(|e| e.file_name() != RGB_RUNTIME_LOCK_FILE);
     let mut walk_dst = WalkDir::new(dst)
         .sort_by(|a, b| a.path().cmp(b.path()))
-        .into_iter();
+        .into_iter()
+        .filter_entry(|e| e.file_name() != RGB_RUNTIME_LOCK_FILE);
     let (same, msg) = loop {
         let path_src = walk_src.next();
         if path_src.is_some() {
@@ -746,10 +779,12 @@ pub fn mock_contract_data(terms: RicardianContract, media: Option<Attachment>) -
     }
 }
 
+mod audit_allocations;
 mod backup;
 mod blind_receive;
 mod create_utxos;
 mod delete_transfers;
+mod diagnose;
 mod drain_to;
 mod fail_transfers;
 mod get_address;
@@ -757,6 +792,8 @@ mod get_asset_balance;
 mod get_asset_metadata;
 mod get_btc_balance;
 mod go_online;
+pub(crate) mod harness;
+mod inspect_psbt;
 mod issue_asset_cfa;
 mod issue_asset_nia;
 mod list_assets;
@@ -764,7 +801,14 @@ mod list_transactions;
 mod list_transfers;
 mod list_unspents;
 mod new;
+mod proxy_auth;
+mod proxy_retry;
 mod refresh;
+mod repair;
 mod send;
 mod send_btc;
+mod set_auto_backup;
+mod set_config;
+mod set_signer;
+mod wallet_manager;
 mod witness_receive;
@@ -2,11 +2,12 @@ use super::*;
 use bdk::miniscript::descriptor::DescriptorType;
 use serial_test::parallel;
 
+use crate::utils::{load_rgb_runtime, LogFormat, LogLevel, RgbRuntimeLock};
+
 fn check_wallet(wallet: &Wallet, desc_type: DescriptorType, network: BitcoinNetwork) {
     let coin_type = i32::from(network != BitcoinNetwork::Mainnet);
-    let descriptor = &wallet
-        .bdk_wallet
-        .get_descriptor_for_keychain(KeychainKind::External);
+    let bdk_wallet = wallet._bdk_wallet();
+    let descriptor = bdk_wallet.get_descriptor_for_keychain(KeychainKind::External);
     let descriptor_type = &descriptor.desc_type();
     assert_eq!(descriptor_type, &desc_type);
     let mut descriptor_string = descriptor.to_string();
@@ -32,8 +33,7 @@ fn testnet_success() {
     fs::create_dir_all(TEST_DATA_DIR).unwrap();
 
     let bitcoin_network = BitcoinNetwork::Testnet;
-    let mut wallet =
-        get_test_wallet_with_net(true, Some(MAX_ALLOCATIONS_PER_UTXO), bitcoin_network);
+    let wallet = get_test_wallet_with_net(true, Some(MAX_ALLOCATIONS_PER_UTXO), bitcoin_network);
     check_wallet(&wallet, DescriptorType::Wpkh, bitcoin_network);
     wallet
         .go_online(false, s!("ssl://electrum.iriswallet.com:50013"))
@@ -60,6 +60,11 @@ fn mainnet_success() {
         max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
         pubkey: keys.xpub.clone(),
         mnemonic: Some(keys.mnemonic.clone()),
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
     })
     .unwrap();
     check_wallet(&wallet, DescriptorType::Wpkh, bitcoin_network);
@@ -68,6 +73,50 @@ fn mainnet_success() {
     assert_eq!(wallet.wallet_data.mnemonic, Some(keys.mnemonic));
 }
 
+#[test]
+#[parallel]
+fn passphrase_success() {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let passphrase = Some(s!("passphrase"));
+    let keys = generate_keys_with_options(
+        bitcoin_network,
+        MnemonicWordCount::Words24,
+        MnemonicLanguage::Spanish,
+        passphrase.clone(),
+    );
+    let wallet_data = WalletData {
+        data_dir: TEST_DATA_DIR.to_string(),
+        bitcoin_network,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub.clone(),
+        mnemonic: Some(keys.mnemonic.clone()),
+        passphrase,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
+    };
+    let wallet = Wallet::new(wallet_data.clone()).unwrap();
+    check_wallet(&wallet, DescriptorType::Wpkh, bitcoin_network);
+    assert!(!wallet.watch_only);
+    drop(wallet);
+
+    // missing passphrase
+    let mut wallet_data_bad = wallet_data.clone();
+    wallet_data_bad.passphrase = None;
+    let result = Wallet::new(wallet_data_bad);
+    assert!(matches!(result, Err(Error::InvalidBitcoinKeys)));
+
+    // wrong passphrase
+    let mut wallet_data_bad = wallet_data;
+    wallet_data_bad.passphrase = Some(s!("wrong"));
+    let result = Wallet::new(wallet_data_bad);
+    assert!(matches!(result, Err(Error::InvalidBitcoinKeys)));
+}
+
 #[test]
 #[parallel]
 fn fail() {
@@ -112,8 +161,8 @@ fn re_instantiate_wallet() {
     let amount: u64 = 66;
 
     // create wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
     let wallet_data = wallet.wallet_data.clone();
 
     // issue
@@ -147,7 +196,7 @@ fn re_instantiate_wallet() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     // take transfers from WaitingCounterparty to Settled
     stop_mining();
@@ -170,9 +219,274 @@ fn re_instantiate_wallet() {
     drop(wallet);
 
     // re-instantiate wallet
-    let mut wallet = Wallet::new(wallet_data).unwrap();
+    let wallet = Wallet::new(wallet_data).unwrap();
     let _online = wallet.go_online(true, ELECTRUM_URL.to_string()).unwrap();
 
     // check wallet asset
-    check_test_wallet_data(&mut wallet, &asset, None, 1, amount);
+    check_test_wallet_data(&wallet, &asset, None, 1, amount);
+}
+
+#[test]
+#[parallel]
+fn rgb_runtime_lock_success() {
+    let wallet = get_test_wallet(true, None);
+    let wallet_dir = wallet.get_wallet_dir();
+    let lock_path = wallet_dir.join(RGB_RUNTIME_LOCK_FILE);
+
+    // a lock file left behind by a crashed process doesn't block the wallet
+    fs::write(&lock_path, "").unwrap();
+    let runtime = wallet._rgb_runtime().unwrap();
+
+    // the runtime lock is exclusive while the runtime is in use
+    let result = RgbRuntimeLock::acquire(&wallet_dir, Duration::ZERO);
+    assert!(matches!(result, Err(Error::RgbRuntimeLocked { details: m })
+            if m.contains(&std::process::id().to_string())));
+    drop(runtime);
+    assert!(RgbRuntimeLock::acquire(&wallet_dir, Duration::ZERO).is_ok());
+
+    // unpersisted changes are discarded and leave the generation untouched
+    let mut runtime = wallet._rgb_runtime().unwrap();
+    runtime.import_schema(nia_schema()).unwrap();
+    drop(runtime);
+    assert!(fs::read_to_string(&lock_path).unwrap().starts_with("0 "));
+
+    // persisted changes bump the lock generation
+    let mut runtime = wallet._rgb_runtime().unwrap();
+    runtime.import_schema(nia_schema()).unwrap();
+    runtime.persist().unwrap();
+    drop(runtime);
+    let lock_content = fs::read_to_string(&lock_path).unwrap();
+    assert!(lock_content.starts_with("1 "));
+
+    // reads reuse the cached runtime and leave the generation untouched
+    let runtime = wallet._rgb_runtime().unwrap();
+    assert_eq!(runtime.schema_ids().unwrap().len(), NUM_KNOWN_SCHEMAS);
+    drop(runtime);
+    assert_eq!(fs::read_to_string(&lock_path).unwrap(), lock_content);
+
+    // a runtime changed by another process is reloaded instead of being overwritten
+    let mut other_runtime = load_rgb_runtime(wallet_dir.clone(), BitcoinNetwork::Regtest).unwrap();
+    other_runtime.import_schema(nia_schema()).unwrap();
+    other_runtime.persist().unwrap();
+    drop(other_runtime);
+    let lock_content = fs::read_to_string(&lock_path).unwrap();
+    assert!(lock_content.starts_with("2 "));
+    let stock_path = wallet_dir.join("regtest").join("stock.dat");
+    let stock = fs::read(&stock_path).unwrap();
+    let runtime = wallet._rgb_runtime().unwrap();
+    assert_eq!(runtime.schema_ids().unwrap().len(), NUM_KNOWN_SCHEMAS);
+    drop(runtime);
+
+    // dropping the wallet leaves the persisted runtime unchanged and releases the lock
+    drop(wallet);
+    assert_eq!(fs::read_to_string(&lock_path).unwrap(), lock_content);
+    assert_eq!(fs::read(&stock_path).unwrap(), stock);
+    assert!(!stock_path.with_extension("dat.tmp").exists());
+    assert!(RgbRuntimeLock::acquire(&wallet_dir, Duration::ZERO).is_ok());
+}
+
+#[test]
+#[parallel]
+fn concurrent_success() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Wallet>();
+
+    let wallet = get_test_wallet(true, None);
+
+    // read-only calls don't wait for a mutating operation in progress
+    let write_lock = wallet._write_lock();
+    let addresses: Vec<String> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    assert!(wallet.list_assets(vec![]).unwrap().nia.unwrap().is_empty());
+                    assert!(wallet.list_unspents(None, false).unwrap().is_empty());
+                    wallet.get_address()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    drop(write_lock);
+
+    // each thread got its own address
+    let unique: HashSet<&String> = HashSet::from_iter(&addresses);
+    assert_eq!(unique.len(), addresses.len());
+}
+
+#[test]
+#[parallel]
+fn log_config_success() {
+    let keys = generate_keys(BitcoinNetwork::Regtest);
+    let data_dir = PathBuf::from(TEST_DATA_DIR).join("log_config_success");
+    fs::create_dir_all(&data_dir).unwrap();
+    let log_config = LogConfig {
+        level: LogLevel::Info,
+        format: LogFormat::Json,
+        max_file_size: Some(500),
+        max_rotated_files: 2,
+        ..LogConfig::default()
+    };
+    let wallet_data = WalletData {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        bitcoin_network: BitcoinNetwork::Regtest,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: Some(log_config),
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
+    };
+
+    // logs of previous sessions are kept, rotating the log file
+    let mut wallet_dir = PathBuf::new();
+    for _ in 0..5 {
+        let wallet = Wallet::new(wallet_data.clone()).unwrap();
+        wallet_dir = wallet.get_wallet_dir();
+    }
+    let log_files: Vec<PathBuf> = ["log", "log.1", "log.2"]
+        .iter()
+        .map(|f| wallet_dir.join(f))
+        .collect();
+    assert!(log_files.iter().all(|f| f.exists()));
+    assert!(!wallet_dir.join("log.3").exists());
+
+    // messages are JSON objects and respect the configured level
+    let mut num_new_wallet = 0;
+    for log_file in log_files {
+        for line in fs::read_to_string(log_file).unwrap().lines() {
+            let message: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_ne!(message["level"], "DEBG");
+            if message["msg"]
+                .as_str()
+                .unwrap()
+                .starts_with("New wallet in")
+            {
+                num_new_wallet += 1;
+            }
+        }
+    }
+    assert!(num_new_wallet > 1);
+}
+
+struct CapturingDrain {
+    messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl slog::Drain for CapturingDrain {
+    type Ok = ();
+    type Err = slog::Never;
+
+    fn log(&self, record: &slog::Record, _values: &slog::OwnedKVList) -> Result<(), slog::Never> {
+        let mut kvs = CapturingSerializer(vec![]);
+        slog::KV::serialize(&record.kv(), record, &mut kvs).unwrap();
+        let mut messages = self.messages.lock().unwrap();
+        messages.push(record.msg().to_string());
+        messages.extend(kvs.0);
+        Ok(())
+    }
+}
+
+struct CapturingSerializer(Vec<String>);
+
+impl slog::Serializer for CapturingSerializer {
+    fn emit_arguments(&mut self, key: slog::Key, val: &std::fmt::Arguments) -> slog::Result {
+        self.0.push(format!("{key}={val}"));
+        Ok(())
+    }
+}
+
+#[test]
+#[parallel]
+fn custom_logger_success() {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+
+    let passphrase = s!("secret passphrase");
+    let bearer_token = s!("secret bearer token");
+    let keys = generate_keys_with_options(
+        BitcoinNetwork::Regtest,
+        MnemonicWordCount::Words12,
+        MnemonicLanguage::English,
+        Some(passphrase.clone()),
+    );
+    let messages = Arc::new(Mutex::new(vec![]));
+    let custom_logger = slog::Logger::root(
+        CapturingDrain {
+            messages: messages.clone(),
+        },
+        o!(),
+    );
+    let wallet = Wallet::new_with_logger(
+        WalletData {
+            data_dir: TEST_DATA_DIR.to_string(),
+            bitcoin_network: BitcoinNetwork::Regtest,
+            database_type: DatabaseType::Sqlite,
+            max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+            pubkey: keys.xpub,
+            mnemonic: Some(keys.mnemonic.clone()),
+            passphrase: Some(passphrase.clone()),
+            log_config: Some(LogConfig {
+                level: LogLevel::Info,
+                ..LogConfig::default()
+            }),
+            proxy_auth: HashMap::from([(
+                s!("127.0.0.1"),
+                ProxyAuth {
+                    bearer_token: Some(bearer_token.clone()),
+                    headers: HashMap::new(),
+                },
+            )]),
+            network_config: None,
+            proxy_retry_config: None,
+        },
+        CustomLogger::Slog(custom_logger),
+    )
+    .unwrap();
+
+    // messages go to the custom logger, at the configured level, instead of the log file
+    assert!(!wallet.get_wallet_dir().join("log").exists());
+    assert!(messages
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|m| m.starts_with("New wallet in")));
+    debug!(wallet.logger, "debug message");
+    assert!(!messages
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|m| m == "debug message"));
+
+    // secrets are redacted
+    info!(wallet.logger, "mnemonic: {}", keys.mnemonic);
+    info!(wallet.logger, "passphrase: {}", passphrase);
+    info!(wallet.logger, "bearer token: {}", bearer_token);
+    let recipient_data = RecipientData::WitnessData {
+        script_buf: ScriptBuf::new(),
+        amount_sat: 1000,
+        blinding: Some(777777),
+    };
+    info!(wallet.logger, "recipient data: {:?}", recipient_data);
+    info!(wallet.logger, "key-value"; "mnemonic" => &keys.mnemonic, "amount" => 66);
+    let wallet_data = format!("{:?}", wallet.get_wallet_data());
+    assert!(!wallet_data.contains(&keys.mnemonic) && !wallet_data.contains(&passphrase));
+    let auto_backup_config = AutoBackupConfig {
+        backup_dir: s!("backups"),
+        password: s!("backup password"),
+        retention: 1,
+        scrypt_params: None,
+    };
+    assert!(!format!("{auto_backup_config:?}").contains("backup password"));
+    let messages = messages.lock().unwrap();
+    assert!(messages.contains(&s!("mnemonic=<redacted>")));
+    assert!(messages.contains(&s!("amount=66")));
+    assert!(messages.contains(&s!("mnemonic: <redacted>")));
+    assert!(messages.contains(&s!("passphrase: <redacted>")));
+    assert!(messages.contains(&s!("bearer token: <redacted>")));
+    assert!(messages
+        .iter()
+        .any(|m| m.starts_with("recipient data:") && !m.contains("777777")));
 }
@@ -0,0 +1,124 @@
+use super::*;
+use serial_test::parallel;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+// start a mock proxy answering a single request with the given HTTP status, returning its URL
+// and a channel yielding the headers of the received request
+fn start_mock_proxy(status: &'static str) -> (String, mpsc::Receiver<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/json-rpc", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_string();
+            if line.is_empty() {
+                break;
+            }
+            headers.push(line.to_lowercase());
+        }
+        let body = r#"{"jsonrpc":"2.0","id":null,"result":true}"#;
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        sender.send(headers).unwrap();
+    });
+    (url, receiver)
+}
+
+fn get_test_wallet_with_proxy_auth(proxy_auth: HashMap<String, ProxyAuth>) -> Wallet {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+
+    let keys = generate_keys(BitcoinNetwork::Regtest);
+    Wallet::new(WalletData {
+        data_dir: TEST_DATA_DIR.to_string(),
+        bitcoin_network: BitcoinNetwork::Regtest,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: None,
+        proxy_auth,
+        network_config: None,
+        proxy_retry_config: None,
+    })
+    .unwrap()
+}
+
+#[test]
+#[parallel]
+fn success() {
+    let (proxy_url, headers) = start_mock_proxy("200 OK");
+    let port = RestUrl::parse(&proxy_url).unwrap().port().unwrap();
+
+    // credentials for host:port take precedence over the ones for host only
+    let wallet = get_test_wallet_with_proxy_auth(HashMap::from([
+        (
+            s!("127.0.0.1"),
+            ProxyAuth {
+                bearer_token: Some(s!("host-token")),
+                headers: HashMap::new(),
+            },
+        ),
+        (
+            format!("127.0.0.1:{port}"),
+            ProxyAuth {
+                bearer_token: Some(s!("port-token")),
+                headers: HashMap::from([(s!("X-Api-Key"), s!("api-key"))]),
+            },
+        ),
+    ]));
+    let ack = wallet
+        ._proxy_client(&proxy_url)
+        .get_ack(&proxy_url, s!("recipient"))
+        .unwrap();
+    assert_eq!(ack.result, Some(true));
+    let headers = headers.recv().unwrap();
+    assert!(headers.contains(&s!("authorization: bearer port-token")));
+    assert!(headers.contains(&s!("x-api-key: api-key")));
+
+    // no credentials configured for the endpoint host
+    let (proxy_url, headers) = start_mock_proxy("200 OK");
+    let wallet = get_test_wallet_with_proxy_auth(HashMap::from([(
+        s!("proxy.example.com"),
+        ProxyAuth {
+            bearer_token: Some(s!("token")),
+            headers: HashMap::new(),
+        },
+    )]));
+    wallet
+        ._proxy_client(&proxy_url)
+        .get_ack(&proxy_url, s!("recipient"))
+        .unwrap();
+    let headers = headers.recv().unwrap();
+    assert!(!headers.iter().any(|h| h.starts_with("authorization:")));
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let wallet = get_test_wallet_with_proxy_auth(HashMap::new());
+
+    // unauthorized
+    let (proxy_url, _headers) = start_mock_proxy("401 Unauthorized");
+    let result = wallet
+        ._proxy_client(&proxy_url)
+        .get_ack(&proxy_url, s!("recipient"));
+    assert!(matches!(result, Err(Error::ProxyUnauthorized { endpoint: e }) if e == proxy_url));
+
+    // forbidden
+    let (proxy_url, _headers) = start_mock_proxy("403 Forbidden");
+    let result = wallet._proxy_client(&proxy_url).get_info(&proxy_url);
+    assert!(matches!(result, Err(Error::ProxyUnauthorized { endpoint: e }) if e == proxy_url));
+}
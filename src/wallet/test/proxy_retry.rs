@@ -0,0 +1,185 @@
+use super::*;
+use serial_test::parallel;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+// start a mock proxy failing the first `failures` connections and answering the following ones,
+// returning the transport endpoint pointing to it; failures answer with `failure_status`, if
+// provided, or drop the connection otherwise
+fn start_mock_proxy(failures: usize, failure_status: Option<&'static str>) -> TransportEndpoint {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for (i, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            let status = match (i < failures, failure_status) {
+                (true, None) => continue,
+                (true, Some(failure_status)) => failure_status,
+                (false, _) => "200 OK",
+            };
+            let body = r#"{"jsonrpc":"2.0","id":null,"result":true}"#;
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    TransportEndpoint::new(format!("rpc://{address}/json-rpc")).unwrap()
+}
+
+fn get_test_wallet_with_proxy_retry_config(proxy_retry_config: ProxyRetryConfig) -> Wallet {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+
+    let keys = generate_keys(BitcoinNetwork::Regtest);
+    Wallet::new(WalletData {
+        data_dir: TEST_DATA_DIR.to_string(),
+        bitcoin_network: BitcoinNetwork::Regtest,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: Some(proxy_retry_config),
+    })
+    .unwrap()
+}
+
+#[test]
+#[parallel]
+fn backoff() {
+    let proxy_retry_config = ProxyRetryConfig {
+        max_retries: 10,
+        initial_backoff_ms: 100,
+        max_backoff_ms: 1000,
+    };
+    assert_eq!(proxy_retry_config.backoff(0), Duration::from_millis(100));
+    assert_eq!(proxy_retry_config.backoff(1), Duration::from_millis(200));
+    assert_eq!(proxy_retry_config.backoff(3), Duration::from_millis(800));
+    assert_eq!(proxy_retry_config.backoff(4), Duration::from_millis(1000));
+    assert_eq!(
+        proxy_retry_config.backoff(u8::MAX),
+        Duration::from_millis(1000)
+    );
+}
+
+#[test]
+#[parallel]
+fn success() {
+    let wallet = get_test_wallet_with_proxy_retry_config(ProxyRetryConfig {
+        max_retries: 2,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 20,
+    });
+
+    // transient outages are retried and the endpoint health is recorded
+    let transport_endpoint = start_mock_proxy(2, None);
+    let endpoint = transport_endpoint.endpoint.clone();
+    wallet
+        ._get_or_create_transport_endpoint(&transport_endpoint)
+        .unwrap();
+    let ack = wallet
+        ._call_proxy(&endpoint, |c| c.get_ack(&endpoint, s!("recipient")))
+        .unwrap();
+    assert_eq!(ack.result, Some(true));
+    let db_transport_endpoint = wallet
+        .database
+        .get_transport_endpoint(endpoint.clone())
+        .unwrap()
+        .unwrap();
+    assert!(db_transport_endpoint.last_success_at.is_some());
+    assert_eq!(db_transport_endpoint.consecutive_failures, 0);
+    assert!(db_transport_endpoint.latency_ms.is_some());
+
+    // temporarily unavailable proxies are retried
+    let transport_endpoint = start_mock_proxy(1, Some("503 Service Unavailable"));
+    let endpoint = transport_endpoint.endpoint.clone();
+    wallet
+        ._get_or_create_transport_endpoint(&transport_endpoint)
+        .unwrap();
+    let ack = wallet
+        ._call_proxy(&endpoint, |c| c.get_ack(&endpoint, s!("recipient")))
+        .unwrap();
+    assert_eq!(ack.result, Some(true));
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let wallet = get_test_wallet_with_proxy_retry_config(ProxyRetryConfig {
+        max_retries: 1,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 20,
+    });
+
+    // outage lasting longer than the retries
+    let transport_endpoint = start_mock_proxy(4, None);
+    let endpoint = transport_endpoint.endpoint.clone();
+    wallet
+        ._get_or_create_transport_endpoint(&transport_endpoint)
+        .unwrap();
+    for expected_failures in 1..=2 {
+        let result = wallet._call_proxy(&endpoint, |c| c.get_ack(&endpoint, s!("recipient")));
+        assert!(matches!(result, Err(Error::Proxy { .. })));
+        let db_transport_endpoint = wallet
+            .database
+            .get_transport_endpoint(endpoint.clone())
+            .unwrap()
+            .unwrap();
+        assert!(db_transport_endpoint.last_success_at.is_none());
+        assert_eq!(
+            db_transport_endpoint.consecutive_failures,
+            expected_failures
+        );
+    }
+
+    // rate limiting counts as a failure
+    let limited_endpoint = start_mock_proxy(4, Some("429 Too Many Requests"));
+    wallet
+        ._get_or_create_transport_endpoint(&limited_endpoint)
+        .unwrap();
+    let endpoint_url = limited_endpoint.endpoint.clone();
+    let result = wallet._call_proxy(&endpoint_url, |c| c.get_ack(&endpoint_url, s!("recipient")));
+    assert!(matches!(result, Err(Error::Proxy { .. })));
+    let db_transport_endpoint = wallet
+        .database
+        .get_transport_endpoint(endpoint_url)
+        .unwrap()
+        .unwrap();
+    assert_eq!(db_transport_endpoint.consecutive_failures, 1);
+
+    // failing endpoints are sorted after healthy ones
+    let healthy_endpoint = start_mock_proxy(0, None);
+    wallet
+        ._get_or_create_transport_endpoint(&healthy_endpoint)
+        .unwrap();
+    assert!(
+        wallet
+            ._endpoint_priority(&healthy_endpoint.endpoint)
+            .unwrap()
+            < wallet._endpoint_priority(&endpoint).unwrap()
+    );
+
+    // the endpoint recovers
+    let ack = wallet
+        ._call_proxy(&endpoint, |c| c.get_ack(&endpoint, s!("recipient")))
+        .unwrap();
+    assert_eq!(ack.result, Some(true));
+    let db_transport_endpoint = wallet
+        .database
+        .get_transport_endpoint(endpoint)
+        .unwrap()
+        .unwrap();
+    assert_eq!(db_transport_endpoint.consecutive_failures, 0);
+}
@@ -26,8 +26,8 @@ fn success() {
         incoming: false,
     };
 
-    let (mut wallet_1, online_1) = get_funded_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!();
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
 
     // issue
     let asset_1 = wallet_1
@@ -77,7 +77,7 @@ fn success() {
     )]);
     // return false if no transfer has changed
     assert!(!wallet_2.refresh(online_2.clone(), None, vec![]).unwrap());
-    let txid_1a = test_send_default(&mut wallet_1, &online_1, recipient_map_1a);
+    let txid_1a = test_send_default(&wallet_1, &online_1, recipient_map_1a);
     assert!(!txid_1a.is_empty());
     let receive_data_1a = wallet_1
         .blind_receive(
@@ -98,7 +98,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2a = test_send_default(&mut wallet_2, &online_2, recipient_map_2a);
+    let txid_2a = test_send_default(&wallet_2, &online_2, recipient_map_2a);
     assert!(!txid_2a.is_empty());
     assert!(wallet_1.refresh(online_1.clone(), None, vec![]).unwrap());
     assert!(wallet_2.refresh(online_2.clone(), None, vec![]).unwrap());
@@ -129,7 +129,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1b = test_send_default(&mut wallet_1, &online_1, recipient_map_1b);
+    let txid_1b = test_send_default(&wallet_1, &online_1, recipient_map_1b);
     assert!(!txid_1b.is_empty());
     // wallet 2 > 1, WaitingCounterparty
     let receive_data_1b = wallet_1
@@ -152,7 +152,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2b = test_send_default(&mut wallet_2, &online_2, recipient_map_2b);
+    let txid_2b = test_send_default(&wallet_2, &online_2, recipient_map_2b);
     assert!(!txid_2b.is_empty());
     show_unspent_colorings(&wallet_2, "wallet 2 after send 2b");
     assert!(check_test_transfer_status_sender(
@@ -325,7 +325,7 @@ fn success() {
 fn fail() {
     initialize();
 
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // asset not found
     let result = wallet.refresh(online, Some(s!("rgb1inexistent")), vec![]);
@@ -339,9 +339,9 @@ fn nia_with_media() {
 
     let amount: u64 = 66;
 
-    let (mut wallet_1, online_1) = get_funded_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!();
-    let (mut wallet_3, online_3) = get_funded_wallet!();
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
+    let (wallet_3, online_3) = get_funded_wallet!();
 
     let fp = "tests/qrcode.png".to_string();
     let fpath = std::path::Path::new(&fp);
@@ -395,7 +395,7 @@ fn nia_with_media() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet_1, &online_1, recipient_map);
+    let txid = test_send_default(&wallet_1, &online_1, recipient_map);
     assert!(!txid.is_empty());
 
     wallet_2.refresh(online_2.clone(), None, vec![]).unwrap();
@@ -425,7 +425,7 @@ fn nia_with_media() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet_2, &online_2, recipient_map);
+    let txid = test_send_default(&wallet_2, &online_2, recipient_map);
     assert!(!txid.is_empty());
 
     wallet_3.refresh(online_3.clone(), None, vec![]).unwrap();
@@ -442,3 +442,64 @@ fn nia_with_media() {
     assert_eq!(rcv_transfer_data.status, TransferStatus::Settled);
     assert_eq!(transfer_data.status, TransferStatus::Settled);
 }
+
+#[test]
+#[parallel]
+fn read_during_refresh() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
+
+    let asset = wallet_1
+        .issue_asset_nia(
+            online_1.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send, leaving the transfer waiting for the sender to refresh it
+    let receive_data = wallet_2
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    test_send_default(&wallet_1, &online_1, recipient_map);
+    assert!(wallet_2.refresh(online_2, None, vec![]).unwrap());
+
+    // keep the refresh of the sender stuck in its post-broadcast sync
+    let bdk_sync_wallet = wallet_1.bdk_sync_wallet.lock().unwrap();
+    std::thread::scope(|s| {
+        let refresh = s.spawn(|| wallet_1.refresh(online_1.clone(), None, vec![]));
+
+        // reads complete while the refresh is still running
+        assert!(!wallet_1.get_address().is_empty());
+        assert!(!wallet_1.list_unspents(None, false).unwrap().is_empty());
+        assert!(!wallet_1.list_transactions(None).unwrap().is_empty());
+        wallet_1.get_asset_balance(asset.asset_id.clone()).unwrap();
+        wallet_1.get_asset_metadata(asset.asset_id.clone()).unwrap();
+        assert!(!refresh.is_finished());
+
+        drop(bdk_sync_wallet);
+        assert!(refresh.join().unwrap().unwrap());
+    });
+}
@@ -0,0 +1,207 @@
+use super::*;
+use serial_test::parallel;
+
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+
+    // issue an asset and send it, leaving the transfer pending
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT, AMOUNT],
+        )
+        .unwrap();
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    test_send_default(&wallet, &online, recipient_map);
+
+    // copy the wallet, then spend all its UTXOs from the original instance
+    let wallet_copy = copy_test_wallet(&wallet, "test_repair.copy");
+    wallet
+        .drain_to(online, rcv_wallet.get_address(), true, FEE_RATE)
+        .unwrap();
+    mine(false);
+
+    // the copy reports the spent UTXOs and the affected pending transfer
+    let result = wallet_copy.go_online(false, ELECTRUM_URL.to_string());
+    assert!(matches!(result, Err(Error::Inconsistency { details: _ })));
+    let online_copy = wallet_copy
+        .go_online(true, ELECTRUM_URL.to_string())
+        .unwrap();
+    let report = wallet_copy.diagnose(online_copy.clone()).unwrap();
+    assert!(!report.inconsistent_utxos.is_empty());
+    let pending_transfer_idxs: Vec<i32> = report
+        .inconsistent_utxos
+        .iter()
+        .flat_map(|u| u.pending_transfer_idxs.clone())
+        .collect();
+    assert!(!pending_transfer_idxs.is_empty());
+
+    // repair marking the UTXOs spent and failing the affected transfers
+    let report = wallet_copy
+        .repair(
+            online_copy.clone(),
+            vec![
+                RepairAction::FailAffectedTransfers,
+                RepairAction::MarkUtxosSpent,
+            ],
+        )
+        .unwrap();
+    assert!(report.inconsistent_utxos.is_empty());
+    assert!(report.missing_assets.is_empty());
+    let transfers = wallet_copy.list_transfers(asset.asset_id).unwrap();
+    assert!(transfers
+        .iter()
+        .filter(|t| pending_transfer_idxs.contains(&t.idx))
+        .all(|t| t.status == TransferStatus::Failed));
+    assert!(wallet_copy.backup_info().unwrap().backup_needed);
+
+    // the repaired wallet passes the consistency check
+    wallet_copy
+        .go_online(false, ELECTRUM_URL.to_string())
+        .unwrap();
+}
+
+#[test]
+#[parallel]
+fn reimport_contracts_success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
+
+    // receive an asset, the receiver only has the incoming consignment for it
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    test_send_default(&wallet, &online, recipient_map);
+    rcv_wallet
+        .refresh(rcv_online.clone(), None, vec![])
+        .unwrap();
+    wallet.refresh(online.clone(), None, vec![]).unwrap();
+    mine(false);
+    rcv_wallet
+        .refresh(rcv_online, Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+
+    // remove the RGB data from a copy of the receiver wallet
+    let rcv_wallet_copy = copy_test_wallet(&rcv_wallet, "test_repair.rcv_copy");
+    fs::remove_dir_all(rcv_wallet_copy.get_wallet_dir().join("regtest")).unwrap();
+    let online_copy = rcv_wallet_copy
+        .go_online(true, ELECTRUM_URL.to_string())
+        .unwrap();
+    let report = rcv_wallet_copy.diagnose(online_copy.clone()).unwrap();
+    assert_eq!(
+        report.missing_assets,
+        vec![MissingAsset {
+            asset_id: asset.asset_id.clone(),
+            consignment_available: true,
+        }]
+    );
+
+    // re-import the contract from the stored consignment
+    let report = rcv_wallet_copy
+        .repair(online_copy, vec![RepairAction::ReimportContracts])
+        .unwrap();
+    assert!(report.missing_assets.is_empty());
+    let online_copy = rcv_wallet_copy
+        .go_online(false, ELECTRUM_URL.to_string())
+        .unwrap();
+    let balance = rcv_wallet_copy
+        .get_asset_balance(asset.asset_id.clone())
+        .unwrap();
+    assert_eq!(balance.settled, amount);
+
+    // the re-imported asset can be spent
+    let (rcv_wallet_2, _rcv_online_2) = get_funded_wallet!();
+    let receive_data = rcv_wallet_2
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id,
+        vec![Recipient {
+            amount: amount / 2,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = test_send_default(&rcv_wallet_copy, &online_copy, recipient_map);
+    assert!(!txid.is_empty());
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    let (wallet, _online) = get_empty_wallet!();
+    let (_other_wallet, other_online) = get_empty_wallet!();
+
+    // bad online object
+    let result = wallet.repair(other_online, vec![RepairAction::MarkUtxosSpent]);
+    assert!(matches!(result, Err(Error::CannotChangeOnline)));
+}
@@ -3,6 +3,50 @@ const TINY_BTC_AMOUNT: u32 = 294;
 use super::*;
 use serial_test::parallel;
 use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+
+// start a mock proxy, supporting protocol version 0.2 on the given address, that accepts every
+// request or, if unauthorized, rejects all but the server info ones
+fn start_mock_proxy(address: &str, unauthorized: bool) {
+    let listener = TcpListener::bind(address).unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("content-length: ") {
+                    content_length = length.parse().unwrap();
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader.read_exact(&mut request).unwrap();
+            let (status, body) = if String::from_utf8_lossy(&request).contains("server.info") {
+                (
+                    "200 OK",
+                    r#"{"jsonrpc":"2.0","id":null,"result":{"protocol_version":"0.2","version":"0.2.0","uptime":1}}"#,
+                )
+            } else if unauthorized {
+                ("401 Unauthorized", "")
+            } else {
+                ("200 OK", r#"{"jsonrpc":"2.0","id":null,"result":true}"#)
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    });
+}
 
 #[test]
 #[parallel]
@@ -12,8 +56,8 @@ fn success() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -49,7 +93,7 @@ fn success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
     let tte_data = wallet
@@ -204,8 +248,8 @@ fn success() {
         .find(|u| Some(u.utxo.outpoint.clone()) == transfer_data.change_utxo);
     assert!(change_unspent.is_some());
 
-    // send, ignoring rgbhttpjsonrpc transport endpoints with non-compliant APIs or unsupported
-    // protocol version
+    // send, ignoring rgbhttpjsonrpc transport endpoints with non-compliant APIs and preferring
+    // the ones supporting the current protocol version
     let transport_endpoints = vec![
         format!("rpc://{PROXY_HOST_MOD_API}"),
         format!("rpc://{PROXY_HOST_MOD_PROTO}"),
@@ -232,7 +276,7 @@ fn success() {
     )]);
     let unspents = wallet.list_unspents(None, false).unwrap();
     let unspents_color_count_before = unspents.iter().filter(|u| u.utxo.colorable).count();
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
     let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
     let tte_data = wallet
@@ -253,8 +297,7 @@ fn success() {
     assert!(!ce_1.0.used);
     assert!(ce_2.0.used);
     let consignment = wallet
-        .rest_client
-        .clone()
+        ._proxy_client(PROXY_URL_MOD_API)
         .get_consignment(
             PROXY_URL_MOD_API,
             receive_data_api_proto.recipient_id.clone(),
@@ -262,8 +305,7 @@ fn success() {
         .unwrap();
     assert!(consignment.error.is_some());
     let consignment = wallet
-        .rest_client
-        .clone()
+        ._proxy_client(PROXY_URL_MOD_PROTO)
         .get_consignment(
             PROXY_URL_MOD_PROTO,
             receive_data_api_proto.recipient_id.clone(),
@@ -271,8 +313,7 @@ fn success() {
         .unwrap();
     assert!(consignment.error.is_some());
     let consignment = wallet
-        .rest_client
-        .clone()
+        ._proxy_client(PROXY_URL)
         .get_consignment(PROXY_URL, receive_data_api_proto.recipient_id.clone())
         .unwrap();
     assert!(consignment.result.is_some());
@@ -352,8 +393,7 @@ fn success() {
     assert!(!ce_1.0.used);
     assert!(ce_2.0.used);
     let consignment = wallet
-        .rest_client
-        .clone()
+        ._proxy_client(PROXY_URL_MOD_PROTO)
         .get_consignment(
             PROXY_URL_MOD_PROTO,
             receive_data_invalid_unreachable.recipient_id.clone(),
@@ -361,8 +401,7 @@ fn success() {
         .unwrap();
     assert!(consignment.error.is_some());
     let consignment = wallet
-        .rest_client
-        .clone()
+        ._proxy_client(PROXY_URL)
         .get_consignment(PROXY_URL, receive_data_invalid_unreachable.recipient_id)
         .unwrap();
     assert!(consignment.result.is_some());
@@ -386,6 +425,213 @@ fn success() {
     assert_eq!(unspents_color_count_after, unspents_color_count_before - 1);
 }
 
+#[test]
+#[parallel]
+fn redundant_posting_success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
+
+    // issue
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // reserve an address for a proxy that is down at send time
+    let mock_address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let mock_url = format!("http://{mock_address}/json-rpc");
+
+    // send, posting the consignment to all usable endpoints
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            vec![
+                PROXY_ENDPOINT.clone(),
+                format!("rpc://{PROXY_HOST_MOD_PROTO}"),
+            ],
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: vec![
+                PROXY_ENDPOINT.clone(),
+                format!("rpc://{PROXY_HOST_MOD_PROTO}"),
+                format!("rpc://{mock_address}/json-rpc"),
+            ],
+        }],
+    )]);
+    let txid = wallet
+        .send_with_options(
+            online.clone(),
+            recipient_map,
+            false,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+            SendOptions {
+                redundant_posting: true,
+            },
+        )
+        .unwrap();
+    assert!(!txid.is_empty());
+    let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
+    let tte_data = wallet
+        .database
+        .get_transfer_transport_endpoints_data(transfer.idx)
+        .unwrap();
+    assert_eq!(tte_data.len(), 3);
+    let posted: Vec<(&str, bool, bool)> = tte_data
+        .iter()
+        .map(|(tte, te)| (te.endpoint.as_str(), tte.used, tte.posted))
+        .collect();
+    assert_eq!(
+        posted,
+        vec![
+            (PROXY_URL, true, true),
+            (PROXY_URL_MOD_PROTO, false, true),
+            (mock_url.as_str(), false, false),
+        ]
+    );
+    for proxy_url in [PROXY_URL, PROXY_URL_MOD_PROTO] {
+        let consignment = wallet
+            ._proxy_client(proxy_url)
+            .get_consignment(proxy_url, receive_data.recipient_id.clone())
+            .unwrap();
+        assert!(consignment.result.is_some());
+    }
+
+    // the proxy comes back up and the consignment is posted to it on refresh
+    start_mock_proxy(&mock_address, false);
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    let tte_data = wallet
+        .database
+        .get_transfer_transport_endpoints_data(transfer.idx)
+        .unwrap();
+    assert!(tte_data.iter().all(|(tte, _)| tte.posted));
+    let transfer_data = wallet.list_transfers(asset.asset_id.clone()).unwrap();
+    let transfer_transport_endpoints = &transfer_data.last().unwrap().transport_endpoints;
+    assert!(transfer_transport_endpoints.iter().all(|tte| tte.posted));
+
+    // settle transfer
+    rcv_wallet
+        .refresh(rcv_online.clone(), None, vec![])
+        .unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    mine(false);
+    rcv_wallet.refresh(rcv_online, None, vec![]).unwrap();
+    wallet
+        .refresh(online, Some(asset.asset_id), vec![])
+        .unwrap();
+    let (transfer_data, _) = get_test_transfer_data(&wallet, &transfer);
+    assert_eq!(transfer_data.status, TransferStatus::Settled);
+}
+
+#[test]
+#[parallel]
+fn redundant_posting_unauthorized() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+
+    // issue
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // a secondary proxy refusing the consignment
+    let mock_address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let mock_url = format!("http://{mock_address}/json-rpc");
+    start_mock_proxy(&mock_address, true);
+
+    // send succeeds as the consignment has been posted to the primary proxy
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: vec![
+                PROXY_ENDPOINT.clone(),
+                format!("rpc://{mock_address}/json-rpc"),
+            ],
+        }],
+    )]);
+    let txid = wallet
+        .send_with_options(
+            online,
+            recipient_map,
+            false,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+            SendOptions {
+                redundant_posting: true,
+            },
+        )
+        .unwrap();
+    let (transfer, _, _) = get_test_transfer_sender(&wallet, &txid);
+    let tte_data = wallet
+        .database
+        .get_transfer_transport_endpoints_data(transfer.idx)
+        .unwrap();
+    let posted: Vec<(&str, bool, bool)> = tte_data
+        .iter()
+        .map(|(tte, te)| (te.endpoint.as_str(), tte.used, tte.posted))
+        .collect();
+    assert_eq!(
+        posted,
+        vec![(PROXY_URL, true, true), (mock_url.as_str(), false, false)]
+    );
+}
+
 #[test]
 #[parallel]
 fn spend_all() {
@@ -394,9 +640,9 @@ fn spend_all() {
     let file_str = "README.md";
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
-    test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
+    test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
 
     // issue
     let asset = wallet
@@ -438,7 +684,7 @@ fn spend_all() {
     assert!(allocation_asset_ids.contains(&asset_blank.asset_id));
 
     // send
-    test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+    test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
     let receive_data = rcv_wallet
         .blind_receive(
             None,
@@ -458,7 +704,7 @@ fn spend_all() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &receive_data.recipient_id);
@@ -562,8 +808,8 @@ fn send_twice_success() {
     let amount_2: u64 = 33;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -600,7 +846,7 @@ fn send_twice_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_1 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -664,7 +910,7 @@ fn send_twice_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_2 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -722,16 +968,9 @@ fn send_blank_success() {
     let file_str = "README.md";
 
     // wallets
-    let (mut wallet_1, online_1) = get_funded_noutxo_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!();
-    test_create_utxos(
-        &mut wallet_1,
-        online_1.clone(),
-        false,
-        Some(1),
-        None,
-        FEE_RATE,
-    );
+    let (wallet_1, online_1) = get_funded_noutxo_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
+    test_create_utxos(&wallet_1, online_1.clone(), false, Some(1), None, FEE_RATE);
 
     // issue
     let asset_nia = wallet_1
@@ -781,14 +1020,7 @@ fn send_blank_success() {
 
     // send
     println!("\n=== send 1");
-    test_create_utxos(
-        &mut wallet_1,
-        online_1.clone(),
-        false,
-        Some(1),
-        None,
-        FEE_RATE,
-    );
+    test_create_utxos(&wallet_1, online_1.clone(), false, Some(1), None, FEE_RATE);
     let receive_data_1 = wallet_2
         .blind_receive(
             None,
@@ -808,7 +1040,7 @@ fn send_blank_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet_1, &online_1, recipient_map);
+    let txid_1 = test_send_default(&wallet_1, &online_1, recipient_map);
     assert!(!txid_1.is_empty());
     show_unspent_colorings(&wallet_1, "wallet 1 after send 1, WaitingCounterparty");
 
@@ -899,7 +1131,7 @@ fn send_blank_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut wallet_1, &online_1, recipient_map);
+    let txid_2 = test_send_default(&wallet_1, &online_1, recipient_map);
     assert!(!txid_2.is_empty());
     show_unspent_colorings(&wallet_1, "wallet 1 after send 2, WaitingCounterparty");
 
@@ -978,9 +1210,9 @@ fn send_received_success() {
     let file_str = "README.md";
 
     // wallets
-    let (mut wallet_1, online_1) = get_funded_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!(true, true);
-    let (mut wallet_3, online_3) = get_funded_wallet!();
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!(true, true);
+    let (wallet_3, online_3) = get_funded_wallet!();
 
     // issue
     let asset_nia = wallet_1
@@ -1048,7 +1280,7 @@ fn send_received_success() {
             }],
         ),
     ]);
-    let txid_1 = test_send_default(&mut wallet_1, &online_1, recipient_map);
+    let txid_1 = test_send_default(&wallet_1, &online_1, recipient_map);
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -1144,7 +1376,7 @@ fn send_received_success() {
             }],
         ),
     ]);
-    let txid_2 = test_send_default(&mut wallet_2, &online_2, recipient_map);
+    let txid_2 = test_send_default(&wallet_2, &online_2, recipient_map);
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -1228,9 +1460,9 @@ fn send_received_cfa_success() {
     let file_str = "README.md";
 
     // wallets
-    let (mut wallet_1, online_1) = get_funded_wallet!();
-    let (mut wallet_2, online_2) = get_funded_wallet!();
-    let (mut wallet_3, online_3) = get_funded_wallet!();
+    let (wallet_1, online_1) = get_funded_wallet!();
+    let (wallet_2, online_2) = get_funded_wallet!();
+    let (wallet_3, online_3) = get_funded_wallet!();
 
     // issue
     let asset = wallet_1
@@ -1268,7 +1500,7 @@ fn send_received_cfa_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet_1, &online_1, recipient_map);
+    let txid_1 = test_send_default(&wallet_1, &online_1, recipient_map);
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -1328,7 +1560,7 @@ fn send_received_cfa_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut wallet_2, &online_2, recipient_map);
+    let txid_2 = test_send_default(&wallet_2, &online_2, recipient_map);
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -1412,8 +1644,8 @@ fn receive_multiple_same_asset_success() {
     let amount_2: u64 = 33;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -1464,7 +1696,7 @@ fn receive_multiple_same_asset_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     let rcv_transfer_1 = get_test_transfer_recipient(&rcv_wallet, &receive_data_1.recipient_id);
@@ -1705,8 +1937,8 @@ fn receive_multiple_different_assets_success() {
     let amount_2: u64 = 33;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset_1 = wallet
@@ -1770,7 +2002,7 @@ fn receive_multiple_different_assets_success() {
             }],
         ),
     ]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     let rcv_transfer_1 = get_test_transfer_recipient(&rcv_wallet, &receive_data_1.recipient_id);
@@ -2051,9 +2283,9 @@ fn batch_donation_success() {
     let amount_b2 = 22;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet_1, rcv_online_1) = get_funded_wallet!();
-    let (mut rcv_wallet_2, rcv_online_2) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet_1, rcv_online_1) = get_funded_wallet!();
+    let (rcv_wallet_2, rcv_online_2) = get_funded_wallet!();
 
     // issue
     let asset_a = wallet
@@ -2246,8 +2478,8 @@ fn reuse_failed_blinded_success() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // issue asset
     let asset = wallet
@@ -2280,7 +2512,7 @@ fn reuse_failed_blinded_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map.clone());
+    let txid = test_send_default(&wallet, &online, recipient_map.clone());
     assert!(!txid.is_empty());
 
     // try to send again and check the asset is not spendable
@@ -2313,9 +2545,9 @@ fn ack() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet_1, rcv_online_1) = get_funded_wallet!();
-    let (mut rcv_wallet_2, rcv_online_2) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet_1, rcv_online_1) = get_funded_wallet!();
+    let (rcv_wallet_2, rcv_online_2) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -2366,7 +2598,7 @@ fn ack() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     // all transfers are in WaitingCounterparty status
@@ -2426,8 +2658,8 @@ fn nack() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -2460,7 +2692,7 @@ fn nack() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     // transfers are in WaitingCounterparty status
@@ -2477,7 +2709,7 @@ fn nack() {
 
     // manually NACK the transfer (consignment is valid so refreshing receiver would yield an ACK)
     rcv_wallet
-        .rest_client
+        ._proxy_client(PROXY_URL)
         .post_ack(PROXY_URL, receive_data.recipient_id, false)
         .unwrap();
 
@@ -2500,8 +2732,8 @@ fn expire() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -2534,7 +2766,7 @@ fn expire() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     // check expiration is set correctly
@@ -2571,10 +2803,10 @@ fn no_change_on_pending_send() {
     let amount_2: u64 = 32;
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), true, Some(3), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), true, Some(3), None, FEE_RATE);
     assert_eq!(num_utxos_created, 3);
 
     // issue 1 + get its UTXO
@@ -2628,7 +2860,7 @@ fn no_change_on_pending_send() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_1 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_1.is_empty());
 
     // send asset_2 (send_1 in WaitingCounterparty)
@@ -2652,7 +2884,7 @@ fn no_change_on_pending_send() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_2 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_2.is_empty());
     // check change was not allocated on issue 1 UTXO (pending Input coloring)
     assert!(!unspent_1.rgb_allocations.iter().any(|a| !a.settled));
@@ -2691,7 +2923,7 @@ fn no_change_on_pending_send() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_3 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_3 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_3.is_empty());
     show_unspent_colorings(&wallet, "after 3rd send");
     // check change was not allocated on issue 1 UTXO (pending Input coloring)
@@ -2706,9 +2938,9 @@ fn fail() {
     initialize();
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
-    let mut wallet_1_alloc = get_test_wallet(true, Some(1));
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let wallet_1_alloc = get_test_wallet(true, Some(1));
     let online_1_alloc = wallet_1_alloc
         .go_online(true, ELECTRUM_URL.to_string())
         .unwrap();
@@ -2717,7 +2949,7 @@ fn fail() {
     fund_wallet(wallet_1_alloc.get_address());
     mine(false);
     test_create_utxos(
-        &mut wallet_1_alloc,
+        &wallet_1_alloc,
         online_1_alloc.clone(),
         true,
         Some(1),
@@ -2897,7 +3129,16 @@ fn fail() {
         Err(Error::InvalidTransportEndpoint { details: _ })
     ));
 
-    // transport endpoints: no valid endpoints (down, modified)
+    // transport endpoints: no valid endpoints (down, previous protocol version without vout)
+    let witness_receive_data = rcv_wallet
+        .witness_receive(
+            None,
+            None,
+            Some(60),
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
     let transport_endpoints = vec![
         format!("rpc://127.6.6.6:7777/json-rpc"),
         format!("rpc://{PROXY_HOST_MOD_PROTO}"),
@@ -2905,9 +3146,11 @@ fn fail() {
     let recipient_map = HashMap::from([(
         asset.asset_id.clone(),
         vec![Recipient {
-            recipient_data: RecipientData::BlindedUTXO(
-                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
-            ),
+            recipient_data: RecipientData::WitnessData {
+                script_buf: ScriptBuf::from_hex(&witness_receive_data.recipient_id).unwrap(),
+                amount_sat: 1000,
+                blinding: None,
+            },
             amount: AMOUNT / 2,
             transport_endpoints,
         }],
@@ -2919,7 +3162,10 @@ fn fail() {
         FEE_RATE,
         MIN_CONFIRMATIONS,
     );
-    let msg = s!("no valid transport endpoints");
+    let msg = format!(
+        "no valid transport endpoints: http://127.6.6.6:7777/json-rpc (unreachable), \
+        {PROXY_URL_MOD_PROTO} (protocol version 0.1 doesn't support witness recipients)"
+    );
     assert!(matches!(
         result,
         Err(Error::InvalidTransportEndpoints { details: m }) if m == msg
@@ -2987,10 +3233,10 @@ fn pending_incoming_transfer_fail() {
     let amount_2: u64 = 33;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_noutxo_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_noutxo_wallet!();
     test_create_utxos(
-        &mut rcv_wallet,
+        &rcv_wallet,
         rcv_online.clone(),
         false,
         Some(1),
@@ -3033,7 +3279,7 @@ fn pending_incoming_transfer_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_1 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -3132,8 +3378,8 @@ fn pending_outgoing_transfer_fail() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue asset
     let asset = wallet
@@ -3166,7 +3412,7 @@ fn pending_outgoing_transfer_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     // 2nd send (1st still pending)
@@ -3221,9 +3467,9 @@ fn pending_transfer_input_fail() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
-    test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+    test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
 
     // issue
     let asset = wallet
@@ -3282,8 +3528,8 @@ fn already_used_fail() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // issue asset to 3 UTXOs
     let asset = wallet
@@ -3316,7 +3562,7 @@ fn already_used_fail() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map.clone());
+    let txid = test_send_default(&wallet, &online, recipient_map.clone());
     assert!(!txid.is_empty());
 
     // 2nd transfer using the same blinded UTXO
@@ -3324,6 +3570,86 @@ fn already_used_fail() {
     assert!(matches!(result, Err(Error::RecipientIDAlreadyUsed)));
 }
 
+#[test]
+#[parallel]
+fn psbt_mismatch_fail() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // wallets
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
+
+    // issue
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // prepare the send PSBT
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id,
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let unsigned_psbt = wallet
+        .send_begin(
+            online.clone(),
+            recipient_map,
+            false,
+            FEE_RATE,
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+
+    // tampered PSBT is rejected and the transfer is not saved
+    let mut psbt = BdkPsbt::from_str(&unsigned_psbt).unwrap();
+    let output = psbt
+        .unsigned_tx
+        .output
+        .iter_mut()
+        .find(|o| !o.script_pubkey.is_op_return())
+        .unwrap();
+    output.value -= 1000;
+    let signed_psbt = wallet.sign_psbt(psbt.to_string()).unwrap();
+    let result = wallet.send_end(online.clone(), signed_psbt);
+    assert!(matches!(result, Err(Error::PsbtMismatch { txid: _ })));
+    let transfers = wallet.database.iter_transfers().unwrap();
+    assert!(!transfers
+        .iter()
+        .any(|t| t.recipient_id == Some(receive_data.recipient_id.clone())));
+
+    // untampered PSBT is accepted
+    let signed_psbt = wallet.sign_psbt(unsigned_psbt).unwrap();
+    wallet
+        .send_end(online.clone(), signed_psbt.clone())
+        .unwrap();
+
+    // the same PSBT cannot be completed twice
+    let result = wallet.send_end(online, signed_psbt);
+    assert!(matches!(result, Err(Error::PsbtMismatch { txid: _ })));
+}
+
 #[test]
 #[parallel]
 fn cfa_blank_success() {
@@ -3333,8 +3659,8 @@ fn cfa_blank_success() {
     let amount_issue_nft = 1;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // issue RGB20
     let asset_nia = wallet
@@ -3392,13 +3718,13 @@ fn psbt_rgb_consumer_success() {
     let amount_issue_ft = 10000;
 
     // create wallet with funds and no UTXOs
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // create 1 UTXO
     println!("utxo 1");
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), true, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), true, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
 
     // issue an RGB20 asset
@@ -3416,7 +3742,7 @@ fn psbt_rgb_consumer_success() {
     // create 1 more UTXO for change, up_to false or AllocationsAlreadyAvailable is returned
     println!("utxo 2");
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
 
     // try to send it
@@ -3523,7 +3849,7 @@ fn psbt_rgb_consumer_success() {
     // create 1 more UTXO for change, up_to false or AllocationsAlreadyAvailable is returned
     println!("utxo 3");
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
 
     // try to send the second asset to a recipient and the third to different one
@@ -3578,12 +3904,12 @@ fn insufficient_bitcoins() {
     initialize();
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // create 1 UTXO with not enough bitcoins for a send and drain the rest
     let num_utxos_created = test_create_utxos(
-        &mut wallet,
+        &wallet,
         online.clone(),
         false,
         Some(1),
@@ -3646,7 +3972,7 @@ fn insufficient_bitcoins() {
     // create 1 UTXO for change (add funds, create UTXO, drain the rest)
     fund_wallet(wallet.get_address());
     let num_utxos_created = test_create_utxos(
-        &mut wallet,
+        &wallet,
         online.clone(),
         false,
         Some(1),
@@ -3673,12 +3999,12 @@ fn insufficient_allocations_fail() {
     initialize();
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // create 1 UTXO with not enough bitcoins for a send
     let num_utxos_created = test_create_utxos(
-        &mut wallet,
+        &wallet,
         online.clone(),
         false,
         Some(1),
@@ -3732,7 +4058,7 @@ fn insufficient_allocations_fail() {
     // create 1 more UTXO for change, up_to false or AllocationsAlreadyAvailable is returned
     println!("utxo 2");
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), false, Some(1), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), false, Some(1), None, FEE_RATE);
     assert_eq!(num_utxos_created, 1);
 
     // send works with no colorable UTXOs available as additional bitcoin inputs
@@ -3750,18 +4076,12 @@ fn insufficient_allocations_success() {
     initialize();
 
     // wallets
-    let (mut wallet, online) = get_funded_noutxo_wallet!();
-    let (mut rcv_wallet, _rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_noutxo_wallet!();
+    let (rcv_wallet, _rcv_online) = get_funded_wallet!();
 
     // create 1 UTXO with not enough bitcoins for a send
-    let num_utxos_created = test_create_utxos(
-        &mut wallet,
-        online.clone(),
-        false,
-        Some(1),
-        Some(300),
-        FEE_RATE,
-    );
+    let num_utxos_created =
+        test_create_utxos(&wallet, online.clone(), false, Some(1), Some(300), FEE_RATE);
     assert_eq!(num_utxos_created, 1);
 
     // issue an RGB20 asset on the unspendable UTXO
@@ -3777,7 +4097,7 @@ fn insufficient_allocations_success() {
 
     // create 2 more UTXOs, 1 for change + 1 as additional bitcoin input
     let num_utxos_created =
-        test_create_utxos(&mut wallet, online.clone(), false, Some(2), None, FEE_RATE);
+        test_create_utxos(&wallet, online.clone(), false, Some(2), None, FEE_RATE);
     assert_eq!(num_utxos_created, 2);
 
     // send with 1 colorable UTXOs available as additional bitcoin input
@@ -3812,7 +4132,7 @@ fn send_to_oneself() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -3845,7 +4165,7 @@ fn send_to_oneself() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     stop_mining();
@@ -3872,8 +4192,8 @@ fn send_received_back_success() {
     let amount_2: u64 = 33;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -3910,7 +4230,7 @@ fn send_received_back_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_1 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_1 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_1.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -3974,7 +4294,7 @@ fn send_received_back_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_2 = test_send_default(&mut rcv_wallet, &rcv_online, recipient_map);
+    let txid_2 = test_send_default(&rcv_wallet, &rcv_online, recipient_map);
     assert!(!txid_2.is_empty());
 
     // take transfers from WaitingCounterparty to Settled
@@ -4042,7 +4362,7 @@ fn send_received_back_success() {
             transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
         }],
     )]);
-    let txid_3 = test_send_default(&mut wallet, &online, recipient_map);
+    let txid_3 = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid_3.is_empty());
     show_unspent_colorings(&wallet, "wallet after 3rd transfer");
     show_unspent_colorings(&rcv_wallet, "rcv_wallet after 3rd transfer");
@@ -4088,8 +4408,8 @@ fn witness_success() {
     let amount: u64 = 66;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
@@ -4144,7 +4464,7 @@ fn witness_success() {
             },
         ],
     )]);
-    let txid = test_send_default(&mut wallet, &online, recipient_map);
+    let txid = test_send_default(&wallet, &online, recipient_map);
     assert!(!txid.is_empty());
 
     stop_mining();
@@ -4245,8 +4565,8 @@ fn min_confirmations() {
     let min_confirmations = 2;
 
     // wallets
-    let (mut wallet, online) = get_funded_wallet!();
-    let (mut rcv_wallet, rcv_online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
 
     // issue
     let asset = wallet
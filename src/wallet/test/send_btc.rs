@@ -9,13 +9,13 @@ fn success() {
     let amount: u64 = 1000;
 
     // wallets
-    let (mut wallet, online) = get_empty_wallet!();
+    let (wallet, online) = get_empty_wallet!();
     let (rcv_wallet, rcv_online) = get_empty_wallet!();
 
     // initial balance
     stop_mining();
     fund_wallet(wallet.get_address());
-    test_create_utxos_default(&mut wallet, online.clone());
+    test_create_utxos_default(&wallet, online.clone());
     let balances = wallet.get_btc_balance(online.clone()).unwrap();
     assert!(matches!(
         balances.vanilla,
@@ -131,7 +131,7 @@ fn fail() {
     // bad online
     let wrong_online = Online {
         id: 1,
-        electrum_url: wallet.online_data.as_ref().unwrap().electrum_url.clone(),
+        electrum_url: wallet._online_data().unwrap().electrum_url.clone(),
     };
     let result = wallet.send_btc(wrong_online, rcv_wallet.get_address(), amount, FEE_RATE);
     assert!(matches!(result, Err(Error::CannotChangeOnline)));
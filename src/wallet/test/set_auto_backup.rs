@@ -0,0 +1,147 @@
+use super::*;
+use crate::wallet::backup::{backup_info, AutoBackupConfig, ScryptParams};
+use serial_test::parallel;
+
+const SCRYPT_PARAMS: ScryptParams = ScryptParams {
+    log_n: 10,
+    r: 8,
+    p: 1,
+};
+
+fn list_backups(backup_dir: &str) -> Vec<String> {
+    let mut backups: Vec<String> = fs::read_dir(backup_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_str().unwrap().to_string())
+        .collect();
+    backups.sort();
+    backups
+}
+
+#[test]
+#[parallel]
+fn success() {
+    let wallet = get_test_wallet(true, None);
+    let fingerprint = wallet.get_wallet_dir().file_name().unwrap().to_owned();
+    let fingerprint = fingerprint.to_str().unwrap();
+    let backup_dir = format!("{TEST_DATA_DIR}/auto_backup_{fingerprint}");
+
+    // no backup needed for a new wallet
+    let info = wallet.backup_info().unwrap();
+    assert!(!info.backup_needed);
+    assert_eq!(info.state_version, 0);
+    assert_eq!(info.last_backup_state_version, None);
+    assert_eq!(info.last_backup_timestamp, None);
+
+    // a state change requires a backup
+    wallet._state_changed().unwrap();
+    let info = wallet.backup_info().unwrap();
+    assert!(info.backup_needed);
+    assert_eq!(info.state_version, 1);
+
+    // enable automatic backups, keeping the last 2
+    let config = AutoBackupConfig {
+        backup_dir: backup_dir.clone(),
+        password: s!("password"),
+        retention: 2,
+        scrypt_params: Some(SCRYPT_PARAMS),
+    };
+    wallet.set_auto_backup(Some(config.clone())).unwrap();
+    assert!(list_backups(&backup_dir).is_empty());
+
+    // state changes don't wait for the backup and close ones are coalesced into a single backup
+    for _ in 0..3 {
+        wallet._state_changed().unwrap();
+    }
+    assert!(list_backups(&backup_dir).is_empty());
+    assert!(wallet.backup_info().unwrap().backup_needed);
+    wallet._flush_auto_backup();
+    assert_eq!(
+        list_backups(&backup_dir),
+        vec![format!("{fingerprint}_{:020}.rgb-lib_backup", 4)]
+    );
+
+    // each backup reflects the latest state, old ones are removed
+    for _ in 0..2 {
+        wallet._state_changed().unwrap();
+        wallet._flush_auto_backup();
+    }
+    let backups = list_backups(&backup_dir);
+    assert_eq!(
+        backups,
+        vec![
+            format!("{fingerprint}_{:020}.rgb-lib_backup", 5),
+            format!("{fingerprint}_{:020}.rgb-lib_backup", 6),
+        ]
+    );
+    let info = wallet.backup_info().unwrap();
+    assert!(!info.backup_needed);
+    assert_eq!(info.state_version, 6);
+    assert_eq!(info.last_backup_state_version, Some(6));
+    assert!(info.last_backup_timestamp.is_some());
+    let header = backup_info(&format!("{backup_dir}/{}", backups[1])).unwrap();
+    assert_eq!(header.fingerprint, fingerprint);
+    assert_eq!(header.scrypt_params, SCRYPT_PARAMS);
+
+    // disable automatic backups
+    wallet.set_auto_backup(None).unwrap();
+    wallet._state_changed().unwrap();
+    wallet._flush_auto_backup();
+    assert_eq!(list_backups(&backup_dir).len(), 2);
+    assert!(wallet.backup_info().unwrap().backup_needed);
+
+    // a pending backup is completed when the wallet is dropped
+    wallet.set_auto_backup(Some(config)).unwrap();
+    wallet._state_changed().unwrap();
+    drop(wallet);
+    assert_eq!(
+        list_backups(&backup_dir)[1],
+        format!("{fingerprint}_{:020}.rgb-lib_backup", 8)
+    );
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let wallet = get_test_wallet(true, None);
+    let fingerprint = wallet.get_wallet_dir().file_name().unwrap().to_owned();
+    let fingerprint = fingerprint.to_str().unwrap();
+    let backup_dir = format!("{TEST_DATA_DIR}/auto_backup_fail_{fingerprint}");
+    let config = AutoBackupConfig {
+        backup_dir: backup_dir.clone(),
+        password: s!("password"),
+        retention: 1,
+        scrypt_params: Some(SCRYPT_PARAMS),
+    };
+
+    // invalid retention
+    let result = wallet.set_auto_backup(Some(AutoBackupConfig {
+        retention: 0,
+        ..config.clone()
+    }));
+    assert!(matches!(
+        result,
+        Err(Error::InvalidAutoBackupConfig { details: m }) if m == "retention must be greater than 0"
+    ));
+
+    // invalid scrypt parameters
+    let result = wallet.set_auto_backup(Some(AutoBackupConfig {
+        scrypt_params: Some(ScryptParams {
+            r: 0,
+            ..SCRYPT_PARAMS
+        }),
+        ..config.clone()
+    }));
+    assert!(matches!(
+        result,
+        Err(Error::InvalidScryptParams { details: _ })
+    ));
+
+    // a failed automatic backup doesn't fail the operation and a backup is still needed
+    wallet.set_auto_backup(Some(config)).unwrap();
+    fs::remove_dir_all(&backup_dir).unwrap();
+    wallet._state_changed().unwrap();
+    wallet._flush_auto_backup();
+    let info = wallet.backup_info().unwrap();
+    assert!(info.backup_needed);
+    assert_eq!(info.last_backup_state_version, None);
+}
@@ -0,0 +1,255 @@
+use super::*;
+use crate::wallet::test::harness::RegtestHarness;
+use serial_test::parallel;
+
+fn custom_config() -> WalletConfig {
+    WalletConfig {
+        min_btc_required: 3000,
+        utxo_size: 2000,
+        utxo_num: 2,
+        max_transport_endpoints: 1,
+        min_fee_rate: 2.0,
+        max_fee_rate: 50.0,
+        duration_send_transfer: 1800,
+        duration_rcv_transfer: 600,
+        electrum_timeout: 8,
+        proxy_timeout: 30,
+        electrum_stop_gap: 30,
+        duration_proxy_protocol_cache: 60,
+        duration_prepared_psbt: None,
+    }
+}
+
+#[test]
+#[parallel]
+fn success() {
+    let wallet = get_test_wallet(true, None);
+
+    // a new wallet uses the default config
+    let default_config = WalletConfig::default();
+    assert_eq!(wallet.get_config(), default_config);
+    assert_eq!(default_config.utxo_num, UTXO_NUM);
+    assert_eq!(default_config.utxo_size, UTXO_SIZE);
+    assert_eq!(
+        default_config.max_transport_endpoints,
+        MAX_TRANSPORT_ENDPOINTS
+    );
+    assert_eq!(default_config.duration_rcv_transfer, DURATION_RCV_TRANSFER);
+    assert_eq!(
+        default_config.duration_send_transfer,
+        DURATION_SEND_TRANSFER
+    );
+
+    // set a custom config
+    let config = custom_config();
+    wallet.set_config(config.clone()).unwrap();
+    assert_eq!(wallet.get_config(), config);
+
+    // fee rates are checked against the configured bounds
+    assert!(wallet._check_fee_rate(FEE_RATE).is_err());
+    assert!(wallet._check_fee_rate(config.min_fee_rate).is_ok());
+    let result = wallet._check_fee_rate(config.max_fee_rate + 1.0);
+    assert!(
+        matches!(result, Err(Error::InvalidFeeRate { details: m }) if m == "value above maximum 50")
+    );
+
+    // the config is persisted
+    let wallet_data = wallet.get_wallet_data();
+    drop(wallet);
+    let wallet = Wallet::new(wallet_data).unwrap();
+    assert_eq!(wallet.get_config(), config);
+
+    // the persisted config is updated
+    let config = WalletConfig {
+        utxo_num: 3,
+        ..config
+    };
+    wallet.set_config(config.clone()).unwrap();
+    let wallet_data = wallet.get_wallet_data();
+    drop(wallet);
+    let wallet = Wallet::new(wallet_data).unwrap();
+    assert_eq!(wallet.get_config(), config);
+
+    // restore the default config
+    wallet.set_config(WalletConfig::default()).unwrap();
+    assert_eq!(wallet.get_config(), default_config);
+}
+
+#[test]
+#[parallel]
+fn online() {
+    let harness = RegtestHarness::start();
+    let wallet = get_test_wallet(true, None);
+    let online = wallet.go_online(true, harness.electrum_url()).unwrap();
+    harness.indexer.fund(&wallet.get_address(), 100_000_000);
+    harness.mine(1);
+
+    // changing the electrum parameters while online keeps the Online data valid
+    let config = custom_config();
+    wallet.set_config(config.clone()).unwrap();
+    assert!(wallet._check_online(online.clone()).is_ok());
+
+    // the configured number and size of UTXOs are used by default
+    let num_utxos_created = wallet
+        .create_utxos(online.clone(), false, None, None, config.min_fee_rate)
+        .unwrap();
+    assert_eq!(num_utxos_created, config.utxo_num);
+    harness.mine(1);
+    let unspents = wallet.list_unspents(Some(online.clone()), false).unwrap();
+    let colorable: Vec<Unspent> = unspents.into_iter().filter(|u| u.utxo.colorable).collect();
+    assert_eq!(colorable.len(), config.utxo_num as usize);
+    assert!(colorable
+        .iter()
+        .all(|u| u.utxo.btc_amount == config.utxo_size as u64));
+
+    // the configured fee rate bounds are enforced
+    let result = wallet.create_utxos(online.clone(), false, None, None, FEE_RATE);
+    assert!(matches!(result, Err(Error::InvalidFeeRate { details: _ })));
+
+    // the configured receive duration is used by default
+    let now_timestamp = now().unix_timestamp();
+    let receive_data = wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            harness.transport_endpoints(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let expiration = receive_data.expiration_timestamp.unwrap();
+    assert!(expiration - now_timestamp >= config.duration_rcv_transfer as i64);
+    assert!(expiration - now_timestamp < DURATION_RCV_TRANSFER as i64);
+
+    // the configured max number of transport endpoints is enforced
+    let transport_endpoints =
+        [harness.transport_endpoints(), harness.transport_endpoints()].concat();
+    let result = wallet.blind_receive(None, None, None, transport_endpoints, MIN_CONFIRMATIONS);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTransportEndpoints { details: m })
+            if m == "library supports at max 1 transport endpoints"
+    ));
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    let wallet = get_test_wallet(true, None);
+
+    let invalid_configs = [
+        (
+            WalletConfig {
+                utxo_size: 0,
+                ..WalletConfig::default()
+            },
+            "utxo_size must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                utxo_num: 0,
+                ..WalletConfig::default()
+            },
+            "utxo_num must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                max_transport_endpoints: 0,
+                ..WalletConfig::default()
+            },
+            "max_transport_endpoints must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                min_fee_rate: 0.0,
+                ..WalletConfig::default()
+            },
+            "min_fee_rate must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                min_fee_rate: f32::NAN,
+                ..WalletConfig::default()
+            },
+            "min_fee_rate must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                min_fee_rate: 10.0,
+                max_fee_rate: 5.0,
+                ..WalletConfig::default()
+            },
+            "max_fee_rate must not be lower than min_fee_rate",
+        ),
+        (
+            WalletConfig {
+                duration_send_transfer: 0,
+                ..WalletConfig::default()
+            },
+            "duration_send_transfer must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                electrum_timeout: 0,
+                ..WalletConfig::default()
+            },
+            "electrum_timeout must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                proxy_timeout: 0,
+                ..WalletConfig::default()
+            },
+            "proxy_timeout must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                electrum_stop_gap: 0,
+                ..WalletConfig::default()
+            },
+            "electrum_stop_gap must be greater than 0",
+        ),
+        (
+            WalletConfig {
+                duration_prepared_psbt: Some(0),
+                ..WalletConfig::default()
+            },
+            "duration_prepared_psbt must be greater than 0",
+        ),
+    ];
+    for (config, details) in invalid_configs {
+        let result = wallet.set_config(config);
+        assert!(matches!(result, Err(Error::InvalidWalletConfig { details: m }) if m == details));
+    }
+    // a rejected config is not applied
+    assert_eq!(wallet.get_config(), WalletConfig::default());
+
+    // custom stop gap with SOCKS5 credentials for the indexer traffic
+    let mut wallet_data = wallet.get_wallet_data();
+    drop(wallet);
+    wallet_data.network_config = Some(NetworkConfig {
+        socks5_address: s!("127.0.0.1:9050"),
+        socks5_credentials: Some(Socks5Credentials {
+            username: s!("user"),
+            password: s!("password"),
+        }),
+        route: NetworkRoute::Indexer,
+    });
+    let wallet = Wallet::new(wallet_data).unwrap();
+    let result = wallet.set_config(WalletConfig {
+        electrum_stop_gap: 30,
+        ..WalletConfig::default()
+    });
+    assert!(matches!(
+        result,
+        Err(Error::InvalidWalletConfig { details: m })
+            if m == "a custom electrum_stop_gap is not supported with SOCKS5 credentials"
+    ));
+    // other settings are accepted
+    wallet
+        .set_config(WalletConfig {
+            electrum_timeout: 10,
+            ..WalletConfig::default()
+        })
+        .unwrap();
+}
@@ -0,0 +1,169 @@
+use super::*;
+use serial_test::parallel;
+
+struct FailingSigner {}
+
+impl Signer for FailingSigner {
+    fn sign_psbt(&self, _unsigned_psbt: String) -> Result<String, Error> {
+        Err(Error::Signer {
+            details: s!("refused"),
+        })
+    }
+}
+
+fn get_watch_only_wallet_with_signer() -> (Wallet, Online) {
+    fs::create_dir_all(TEST_DATA_DIR).unwrap();
+
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let keys = generate_keys(bitcoin_network);
+    let wallet = Wallet::new(WalletData {
+        data_dir: TEST_DATA_DIR.to_string(),
+        bitcoin_network,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub.clone(),
+        mnemonic: None,
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
+    })
+    .unwrap();
+    let signer =
+        SoftwareSigner::new(bitcoin_network, keys.xpub, keys.mnemonic, keys.passphrase).unwrap();
+    wallet.set_signer(Some(Box::new(signer)));
+    let online = wallet.go_online(true, ELECTRUM_URL.to_string()).unwrap();
+    (wallet, online)
+}
+
+#[test]
+#[parallel]
+fn success() {
+    initialize();
+
+    let amount: u64 = 66;
+
+    // watch-only wallet with a software signer
+    let (wallet, online) = get_watch_only_wallet_with_signer();
+    assert!(wallet.watch_only);
+    let (rcv_wallet, rcv_online) = get_funded_wallet!();
+
+    // create UTXOs
+    fund_wallet(wallet.get_address());
+    let num_utxos_created = test_create_utxos_default(&wallet, online.clone());
+    assert_eq!(num_utxos_created, UTXO_NUM);
+
+    // issue
+    let asset = wallet
+        .issue_asset_nia(
+            online.clone(),
+            TICKER.to_string(),
+            NAME.to_string(),
+            PRECISION,
+            vec![AMOUNT],
+        )
+        .unwrap();
+
+    // send
+    let receive_data = rcv_wallet
+        .blind_receive(
+            None,
+            None,
+            None,
+            TRANSPORT_ENDPOINTS.clone(),
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let recipient_map = HashMap::from([(
+        asset.asset_id.clone(),
+        vec![Recipient {
+            amount,
+            recipient_data: RecipientData::BlindedUTXO(
+                SecretSeal::from_str(&receive_data.recipient_id).unwrap(),
+            ),
+            transport_endpoints: TRANSPORT_ENDPOINTS.clone(),
+        }],
+    )]);
+    let txid = test_send_default(&wallet, &online, recipient_map);
+    assert!(!txid.is_empty());
+    rcv_wallet
+        .refresh(rcv_online.clone(), None, vec![])
+        .unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    mine(false);
+    rcv_wallet
+        .refresh(rcv_online, Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    wallet
+        .refresh(online.clone(), Some(asset.asset_id.clone()), vec![])
+        .unwrap();
+    let rcv_transfer = get_test_transfer_recipient(&rcv_wallet, &receive_data.recipient_id);
+    let (rcv_transfer_data, _) = get_test_transfer_data(&rcv_wallet, &rcv_transfer);
+    assert_eq!(rcv_transfer_data.status, TransferStatus::Settled);
+
+    // send BTC
+    wallet
+        .send_btc(online.clone(), rcv_wallet.get_address(), 1000, FEE_RATE)
+        .unwrap();
+
+    // drain
+    wallet
+        .drain_to(online, rcv_wallet.get_address(), true, FEE_RATE)
+        .unwrap();
+    mine(false);
+    let unspents = list_test_unspents(&wallet, "after draining");
+    assert_eq!(unspents.len(), 0);
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    initialize();
+
+    // watch-only wallet without a signer
+    let (wallet, online) = get_funded_noutxo_wallet!(false, false);
+    let result = wallet.create_utxos(online.clone(), false, None, None, FEE_RATE);
+    assert!(matches!(result, Err(Error::WatchOnly)));
+
+    // signer error is returned
+    wallet.set_signer(Some(Box::new(FailingSigner {})));
+    let result = wallet.create_utxos(online.clone(), false, None, None, FEE_RATE);
+    assert!(matches!(result, Err(Error::Signer { details: m }) if m == "refused"));
+
+    // unregistered signer
+    wallet.set_signer(None);
+    let result = wallet.create_utxos(online, false, None, None, FEE_RATE);
+    assert!(matches!(result, Err(Error::WatchOnly)));
+}
+
+#[test]
+#[parallel]
+fn software_signer_fail() {
+    let bitcoin_network = BitcoinNetwork::Regtest;
+    let keys = generate_keys(bitcoin_network);
+
+    // mnemonic of another wallet
+    let other_keys = generate_keys(bitcoin_network);
+    let result = SoftwareSigner::new(
+        bitcoin_network,
+        keys.xpub.clone(),
+        other_keys.mnemonic,
+        None,
+    );
+    assert!(matches!(result, Err(Error::InvalidMnemonic { details: _ })));
+
+    // wrong passphrase
+    let result = SoftwareSigner::new(
+        bitcoin_network,
+        keys.xpub.clone(),
+        keys.mnemonic.clone(),
+        Some(s!("wrong")),
+    );
+    assert!(matches!(result, Err(Error::InvalidMnemonic { details: _ })));
+
+    // matching keys
+    SoftwareSigner::new(bitcoin_network, keys.xpub, keys.mnemonic, keys.passphrase).unwrap();
+}
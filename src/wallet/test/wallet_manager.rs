@@ -0,0 +1,184 @@
+use super::*;
+use crate::wallet::test::harness::RegtestHarness;
+use serial_test::parallel;
+
+fn get_test_manager(name: &str, max_open_wallets: usize) -> WalletManager {
+    let data_dir = PathBuf::from(TEST_DATA_DIR).join(name);
+    if data_dir.is_dir() {
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+    fs::create_dir_all(&data_dir).unwrap();
+    WalletManager::new(data_dir.to_string_lossy().to_string(), max_open_wallets).unwrap()
+}
+
+fn get_test_wallet_data() -> WalletData {
+    let keys = generate_keys(BitcoinNetwork::Regtest);
+    WalletData {
+        data_dir: s!("ignored"),
+        bitcoin_network: BitcoinNetwork::Regtest,
+        database_type: DatabaseType::Sqlite,
+        max_allocations_per_utxo: MAX_ALLOCATIONS_PER_UTXO,
+        pubkey: keys.xpub,
+        mnemonic: Some(keys.mnemonic),
+        passphrase: None,
+        log_config: None,
+        proxy_auth: HashMap::new(),
+        network_config: None,
+        proxy_retry_config: None,
+    }
+}
+
+fn fingerprint(wallet: &Wallet) -> String {
+    wallet
+        .get_wallet_dir()
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+#[parallel]
+fn success() {
+    let manager = get_test_manager("wallet_manager_success", 2);
+    let wallet_data_1 = get_test_wallet_data();
+    let wallet_data_2 = get_test_wallet_data();
+    let wallet_data_3 = get_test_wallet_data();
+
+    // wallets are opened in the manager data directory
+    let wallet_1 = manager.open_wallet(wallet_data_1.clone()).unwrap();
+    let fingerprint_1 = fingerprint(&wallet_1);
+    assert!(wallet_1
+        .get_wallet_dir()
+        .starts_with(fs::canonicalize(TEST_DATA_DIR).unwrap()));
+    assert_eq!(manager.list_open_wallets(), vec![fingerprint_1.clone()]);
+
+    // opening an open wallet returns the open instance
+    let wallet_1_again = manager.open_wallet(wallet_data_1.clone()).unwrap();
+    assert!(Arc::ptr_eq(&wallet_1, &wallet_1_again));
+    drop(wallet_1_again);
+
+    // the least recently used wallet is closed when the limit is reached
+    let fingerprint_2 = fingerprint(&manager.open_wallet(wallet_data_2.clone()).unwrap());
+    let fingerprint_3 = fingerprint(&manager.open_wallet(wallet_data_3).unwrap());
+    assert_eq!(
+        manager.list_open_wallets(),
+        vec![fingerprint_3.clone(), fingerprint_2.clone()]
+    );
+    assert!(manager.get_wallet(&fingerprint_1).is_none());
+
+    // a closed wallet still in use is handed out again when reopened
+    let wallet_1_reopened = manager.open_wallet(wallet_data_1).unwrap();
+    assert!(Arc::ptr_eq(&wallet_1, &wallet_1_reopened));
+    assert_eq!(
+        manager.list_open_wallets(),
+        vec![fingerprint_1.clone(), fingerprint_3.clone()]
+    );
+
+    // a closed wallet no longer in use is opened again from disk
+    let wallet_2 = manager.open_wallet(wallet_data_2).unwrap();
+    assert_eq!(fingerprint(&wallet_2), fingerprint_2);
+    assert!(wallet_2
+        .list_assets(vec![])
+        .unwrap()
+        .nia
+        .unwrap()
+        .is_empty());
+
+    // get bumps the wallet usage
+    let wallet = manager.get_wallet(&fingerprint_1).unwrap();
+    assert!(Arc::ptr_eq(&wallet, &wallet_1));
+    assert_eq!(
+        manager.list_open_wallets(),
+        vec![fingerprint_1.clone(), fingerprint_2.clone()]
+    );
+
+    // close
+    assert!(manager.close_wallet(&fingerprint_1));
+    assert!(!manager.close_wallet(&fingerprint_1));
+    assert_eq!(manager.list_open_wallets(), vec![fingerprint_2]);
+}
+
+#[test]
+#[parallel]
+fn shared_online_success() {
+    let harness = RegtestHarness::start();
+    let harness_2 = RegtestHarness::start();
+
+    let manager = get_test_manager("wallet_manager_shared_online_success", 2);
+    let wallet_1 = manager.open_wallet(get_test_wallet_data()).unwrap();
+    let wallet_2 = manager.open_wallet(get_test_wallet_data()).unwrap();
+
+    // wallets going online with the same electrum URL share the connection
+    let online_1 = wallet_1.go_online(true, harness.electrum_url()).unwrap();
+    let online_2 = wallet_2.go_online(true, harness.electrum_url()).unwrap();
+    assert!(Arc::ptr_eq(
+        &wallet_1._online_data().unwrap(),
+        &wallet_2._online_data().unwrap()
+    ));
+
+    // but each wallet only accepts its own Online data
+    assert_ne!(online_1.id, online_2.id);
+    let result = wallet_2.create_utxos(online_1.clone(), false, None, None, FEE_RATE);
+    assert!(matches!(result, Err(Error::CannotChangeOnline)));
+
+    let online_3 = wallet_2.go_online(true, harness_2.electrum_url()).unwrap();
+    assert_ne!(online_2.id, online_3.id);
+
+    // shared connections are usable by every wallet
+    harness.indexer.fund(&wallet_1.get_address(), 100_000_000);
+    harness.mine(1);
+    harness_2.indexer.fund(&wallet_2.get_address(), 100_000_000);
+    harness_2.mine(1);
+    test_create_utxos_default(&wallet_1, online_1);
+    test_create_utxos_default(&wallet_2, online_3);
+
+    // connections no longer used by any wallet are closed
+    manager.close_wallet(&fingerprint(&wallet_1));
+    manager.close_wallet(&fingerprint(&wallet_2));
+    drop((wallet_1, wallet_2));
+    assert!(manager
+        .shared
+        .online_data
+        .lock()
+        .unwrap()
+        .values()
+        .all(|online_data| online_data.upgrade().is_none()));
+}
+
+#[test]
+#[parallel]
+fn concurrent_open() {
+    let manager = Arc::new(get_test_manager("wallet_manager_concurrent_open", 2));
+    let wallet_data = get_test_wallet_data();
+
+    // concurrent openings of the same wallet return the same instance
+    let openers: Vec<_> = (0..4)
+        .map(|_| {
+            let (manager, wallet_data) = (manager.clone(), wallet_data.clone());
+            std::thread::spawn(move || manager.open_wallet(wallet_data).unwrap())
+        })
+        .collect();
+    let wallets: Vec<Arc<Wallet>> = openers.into_iter().map(|o| o.join().unwrap()).collect();
+    assert!(wallets.iter().all(|w| Arc::ptr_eq(w, &wallets[0])));
+    assert_eq!(manager.list_open_wallets(), vec![fingerprint(&wallets[0])]);
+    assert!(manager.opening_wallets.lock().unwrap().is_empty());
+}
+
+#[test]
+#[parallel]
+fn fail() {
+    // inexistent data directory
+    let result = WalletManager::new(s!("inexistent"), 1);
+    assert!(matches!(result, Err(Error::InexistentDataDir)));
+
+    // invalid pubkey
+    let manager = get_test_manager("wallet_manager_fail", 1);
+    let wallet_data = WalletData {
+        pubkey: s!("invalid"),
+        ..get_test_wallet_data()
+    };
+    let result = manager.open_wallet(wallet_data);
+    assert!(matches!(result, Err(Error::InvalidPubkey { details: _ })));
+    assert!(manager.list_open_wallets().is_empty());
+}
@@ -8,7 +8,7 @@ fn success() {
 
     let amount = 69;
     let expiration = 60;
-    let (mut wallet, online) = get_funded_wallet!();
+    let (wallet, online) = get_funded_wallet!();
 
     // default expiration + min confirmations
     let now_timestamp = now().unix_timestamp();
@@ -147,12 +147,43 @@ fn success() {
     assert_eq!(tte_data.len(), transport_endpoints.len());
 }
 
+#[test]
+#[parallel]
+fn unreachable_endpoint() {
+    let wallet = get_test_wallet(false, None);
+
+    // an unreachable proxy doesn't prevent receiving, as its protocol version is unknown
+    let endpoint = "http://127.0.0.1:1/json-rpc";
+    let receive_data = wallet
+        .witness_receive(
+            None,
+            None,
+            None,
+            vec![s!("rpc://127.0.0.1:1/json-rpc")],
+            MIN_CONFIRMATIONS,
+        )
+        .unwrap();
+    let transfer = get_test_transfer_recipient(&wallet, &receive_data.recipient_id);
+    let tte_data = wallet
+        .database
+        .get_transfer_transport_endpoints_data(transfer.idx)
+        .unwrap();
+    assert_eq!(tte_data.len(), 1);
+    assert_eq!(tte_data[0].1.endpoint, endpoint);
+    let db_transport_endpoint = wallet
+        .database
+        .get_transport_endpoint(endpoint.to_string())
+        .unwrap()
+        .unwrap();
+    assert!(db_transport_endpoint.protocol_version.is_none());
+}
+
 #[test]
 #[parallel]
 fn fail() {
     initialize();
 
-    let (mut wallet, _) = get_empty_wallet!();
+    let (wallet, _) = get_empty_wallet!();
 
     // invalid invoice (missing network)
     let receive_data = wallet
@@ -172,4 +203,16 @@ fn fail() {
         result,
         Err(Error::InvalidInvoiceData { details: _ })
     ));
+
+    // transport endpoints: only endpoints with a protocol version not supporting witness recipients
+    let transport_endpoints = vec![format!("rpc://{PROXY_HOST_MOD_PROTO}")];
+    let result = wallet.witness_receive(None, None, None, transport_endpoints, MIN_CONFIRMATIONS);
+    let msg = format!(
+        "no valid transport endpoints: {PROXY_URL_MOD_PROTO} \
+        (protocol version 0.1 doesn't support witness recipients)"
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTransportEndpoints { details: m }) if m == msg
+    ));
 }